@@ -0,0 +1,56 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_async_pool::{async_message_id_to_string, AsyncMessage};
+use massa_execution_exports::PendingAsyncMessage;
+use serde::{Deserialize, Serialize};
+
+/// Asynchronous message waiting in the candidate pool
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AsyncMessageInfo {
+    /// id of the message
+    pub id: String,
+    /// the message itself
+    pub message: AsyncMessage,
+    /// whether the trigger of the message was matched, always false for messages without trigger
+    pub trigger_fired: bool,
+    /// whether the message can be executed at the next slot
+    pub executable: bool,
+}
+
+impl From<PendingAsyncMessage> for AsyncMessageInfo {
+    fn from(value: PendingAsyncMessage) -> Self {
+        AsyncMessageInfo {
+            id: async_message_id_to_string(&value.id),
+            message: value.message,
+            trigger_fired: value.trigger_fired,
+            executable: value.executable,
+        }
+    }
+}
+
+impl std::fmt::Display for AsyncMessageInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Async message {}{}{}",
+            self.id,
+            crate::display_if_true(self.trigger_fired, "trigger fired"),
+            crate::display_if_true(self.executable, "executable")
+        )?;
+        writeln!(
+            f,
+            "\tFrom {} to {}, handler {}",
+            self.message.sender, self.message.destination, self.message.handler
+        )?;
+        writeln!(
+            f,
+            "\tValid from slot {} until slot {}",
+            self.message.validity_start, self.message.validity_end
+        )?;
+        writeln!(
+            f,
+            "\tMax gas: {}, fee: {}, coins: {}",
+            self.message.max_gas, self.message.fee, self.message.coins
+        )
+    }
+}
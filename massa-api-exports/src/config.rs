@@ -69,4 +69,14 @@ pub struct APIConfig {
     pub periods_per_cycle: u64,
     /// keypair file
     pub keypair: KeyPair,
+    /// safety margin added to the estimated gas of smart contract operations, in percent
+    pub gas_estimation_margin_percent: u64,
+    /// max gas per block
+    pub max_gas_per_block: u64,
+    /// max gas of a read-only execution
+    pub max_read_only_gas: u64,
+    /// maximum time to wait for a slot to be finalized and its determinism checked
+    pub slot_determinism_check_timeout: MassaTime,
+    /// gas cost of the compilation of the bytecode of an `ExecuteSC` operation
+    pub sp_compilation_cost: u64,
 }
@@ -1,7 +1,12 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_execution_exports::{ExecutionTraceFrame, SlotDeterminismCheckOutput};
 use massa_final_state::StateChanges;
-use massa_models::{address::Address, output_event::SCOutputEvent, slot::Slot};
+use massa_hash::Hash;
+use massa_models::{
+    address::Address, amount::Amount, block_id::BlockId, operation::OperationId,
+    output_event::SCOutputEvent, slot::Slot,
+};
 use serde::{Deserialize, Serialize};
 use std::{collections::VecDeque, fmt::Display};
 
@@ -27,6 +32,8 @@ pub struct ExecuteReadOnlyResponse {
     pub gas_cost: u64,
     /// state changes caused by the execution step
     pub state_changes: StateChanges,
+    /// trace of the ABI calls made during the execution, if requested
+    pub trace: Option<ExecutionTraceFrame>,
 }
 
 impl Display for ExecuteReadOnlyResponse {
@@ -66,6 +73,13 @@ pub struct ReadOnlyBytecodeExecution {
     /// whether to start execution from final or active state. Default false
     #[serde(default)]
     pub is_final: bool,
+    /// past final slot on top of which to execute, on nodes in archive mode. Takes precedence over `is_final`.
+    /// Only the ledger is read at that slot, the rest of the state is the current final state
+    #[serde(default)]
+    pub at_slot: Option<Slot>,
+    /// whether to return the trace of the ABI calls made during the execution. Default false
+    #[serde(default)]
+    pub trace: bool,
 }
 
 /// read SC call request
@@ -84,4 +98,124 @@ pub struct ReadOnlyCall {
     /// whether to start execution from final or active state. Default false
     #[serde(default)]
     pub is_final: bool,
+    /// past final slot on top of which to execute, on nodes in archive mode. Takes precedence over `is_final`.
+    /// Only the ledger is read at that slot, the rest of the state is the current final state
+    #[serde(default)]
+    pub at_slot: Option<Slot>,
+    /// whether to return the trace of the ABI calls made during the execution. Default false
+    #[serde(default)]
+    pub trace: bool,
+}
+
+/// smart contract operation whose `max_gas` is estimated
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub enum GasEstimationTarget {
+    /// `ExecuteSC` operation
+    ExecuteSC {
+        /// byte code
+        bytecode: Vec<u8>,
+        /// Operation datastore, optional
+        operation_datastore: Option<Vec<u8>>,
+    },
+    /// `CallSC` operation
+    CallSC {
+        /// target address
+        target_address: Address,
+        /// target function
+        target_function: String,
+        /// function parameter
+        parameter: Vec<u8>,
+        /// coins sent to the target address
+        coins: Amount,
+    },
+}
+
+/// gas estimation request
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct GasEstimationRequest {
+    /// address of the operation sender
+    pub caller_address: Address,
+    /// operation to estimate
+    pub target: GasEstimationTarget,
+}
+
+/// The response to a gas estimation request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GasEstimationResponse {
+    /// The slot at which the read-only execution occurred, if it succeeded.
+    pub executed_at: Option<Slot>,
+    /// The gas used by the read-only execution, compilation included.
+    pub gas_used: u64,
+    /// The recommended `max_gas` of the operation, safety margin included.
+    pub max_gas: u64,
+    /// The error that occurred during the execution, if any.
+    pub error: Option<String>,
+}
+
+impl Display for GasEstimationResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(e) = &self.error {
+            return writeln!(f, "Gas estimation failed: {}", e);
+        }
+        if let Some(slot) = &self.executed_at {
+            writeln!(f, "Executed at slot: {}", slot)?;
+        }
+        writeln!(f, "Gas used: {}", self.gas_used)?;
+        writeln!(f, "Recommended max gas: {}", self.max_gas)
+    }
+}
+
+/// The result of the determinism check of a final slot.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SlotDeterminismCheckInfo {
+    /// The checked slot.
+    pub slot: Slot,
+    /// The block executed at the slot, if any.
+    pub block_id: Option<BlockId>,
+    /// Whether the state changes of the re-execution match the finalized ones.
+    pub matches: bool,
+    /// The hash of the state changes finalized at the slot.
+    pub final_state_changes_hash: Hash,
+    /// The hash of the state changes of the re-execution.
+    pub reexecution_state_changes_hash: Hash,
+    /// The traces of the operations executed during the re-execution, if requested.
+    pub operation_traces: Vec<(OperationId, ExecutionTraceFrame)>,
+}
+
+impl From<SlotDeterminismCheckOutput> for SlotDeterminismCheckInfo {
+    fn from(output: SlotDeterminismCheckOutput) -> Self {
+        SlotDeterminismCheckInfo {
+            slot: output.slot,
+            block_id: output.block_id,
+            matches: output.final_state_changes_hash == output.reexecution_state_changes_hash,
+            final_state_changes_hash: output.final_state_changes_hash,
+            reexecution_state_changes_hash: output.reexecution_state_changes_hash,
+            operation_traces: output.operation_traces,
+        }
+    }
+}
+
+impl Display for SlotDeterminismCheckInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Checked slot: {}", self.slot)?;
+        if let Some(block_id) = &self.block_id {
+            writeln!(f, "Block: {}", block_id)?;
+        }
+        if self.matches {
+            writeln!(f, "State changes match: {}", self.final_state_changes_hash)?;
+        } else {
+            writeln!(f, "State changes MISMATCH:")?;
+            writeln!(f, "\tfinalized: {}", self.final_state_changes_hash)?;
+            writeln!(f, "\tre-execution: {}", self.reexecution_state_changes_hash)?;
+        }
+        for (operation_id, trace) in &self.operation_traces {
+            writeln!(
+                f,
+                "Operation {}: {} trace steps",
+                operation_id,
+                trace.steps.len()
+            )?;
+        }
+        Ok(())
+    }
 }
@@ -13,6 +13,8 @@ use serde::{Deserialize, Serialize};
 
 /// address related structures
 pub mod address;
+/// asynchronous messages
+pub mod async_message;
 /// block-related structures
 pub mod block;
 /// node configuration
@@ -37,6 +39,8 @@ pub mod page;
 pub mod rolls;
 /// slots
 pub mod slot;
+/// final state proofs
+pub mod state_proof;
 
 /// Dumb utils function to display nicely boolean value
 fn display_if_true(value: bool, text: &str) -> String {
@@ -1,8 +1,12 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
+    amount::Amount,
     block_id::BlockId,
     operation::{OperationId, SecureShareOperation},
+    output_event::SCOutputEvent,
+    slot::Slot,
 };
 
 use massa_signature::{PublicKey, Signature};
@@ -65,6 +69,185 @@ impl std::fmt::Display for OperationInfo {
     }
 }
 
+/// Balance changed by an operation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BalanceChange {
+    /// address whose balance changed
+    pub address: Address,
+    /// balance before the execution of the operation
+    pub before: Amount,
+    /// balance after the execution of the operation
+    pub after: Amount,
+}
+
+/// Datastore entry written or deleted by an operation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DatastoreKeyChange {
+    /// address owning the datastore
+    pub address: Address,
+    /// datastore key
+    pub key: Vec<u8>,
+}
+
+/// Outcome of the execution of an operation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationReceipt {
+    /// id of the executed operation
+    pub id: OperationId,
+    /// slot at which the operation was executed
+    pub slot: Slot,
+    /// block in which the operation was included
+    pub block_id: Option<BlockId>,
+    /// true if the slot of the execution is final
+    pub is_final: bool,
+    /// error message if the execution failed, `None` if it succeeded
+    pub error: Option<String>,
+    /// gas consumed by the execution
+    pub gas_used: u64,
+    /// fee paid by the operation sender
+    pub fee: Amount,
+    /// balances changed by the execution, fee included
+    pub balance_changes: Vec<BalanceChange>,
+    /// datastore entries written or deleted by the execution
+    pub datastore_keys: Vec<DatastoreKeyChange>,
+    /// events emitted by the execution
+    pub events: Vec<SCOutputEvent>,
+}
+
+impl OperationReceipt {
+    /// Builds the receipt of an operation from its execution receipt,
+    /// picking the events of the operation among the given events.
+    /// Events already pruned from the event store are missing.
+    pub fn new<'a>(
+        receipt: massa_execution_exports::OperationReceipt,
+        events: impl IntoIterator<Item = &'a SCOutputEvent>,
+    ) -> Self {
+        let events = events
+            .into_iter()
+            .filter(|event| {
+                event.context.slot == receipt.slot
+                    && event.context.origin_operation_id == Some(receipt.operation_id)
+                    && receipt.event_indexes.contains(&event.context.index_in_slot)
+            })
+            .cloned()
+            .collect();
+        OperationReceipt {
+            id: receipt.operation_id,
+            slot: receipt.slot,
+            block_id: receipt.block_id,
+            is_final: receipt.is_final,
+            error: receipt.error,
+            gas_used: receipt.gas_used,
+            fee: receipt.fee,
+            balance_changes: receipt
+                .balance_changes
+                .into_iter()
+                .map(|(address, (before, after))| BalanceChange {
+                    address,
+                    before,
+                    after,
+                })
+                .collect(),
+            datastore_keys: receipt
+                .datastore_keys
+                .into_iter()
+                .map(|(address, key)| DatastoreKeyChange { address, key })
+                .collect(),
+            events,
+        }
+    }
+}
+
+/// Outcome of the simulation of a signed operation on top of the candidate state
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationSimulation {
+    /// id of the simulated operation
+    pub id: OperationId,
+    /// would-be receipt of the operation, `None` if it could not be included in a block
+    pub receipt: Option<OperationReceipt>,
+    /// error preventing the inclusion of the operation in a block
+    /// (validity period, fee or vesting checks, already executed ...)
+    pub error: Option<String>,
+    /// gas consumed by the simulated execution
+    pub gas_estimate: u64,
+    /// fee of the operation
+    pub fee: Amount,
+    /// candidate balance of the operation sender
+    pub sender_balance: Amount,
+    /// maximal amount of coins the operation may spend, fee included
+    pub max_spending: Amount,
+    /// true if the candidate balance of the sender covers the maximal spending of the operation
+    pub balance_sufficient: bool,
+}
+
+impl std::fmt::Display for OperationSimulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Simulation of operation {}", self.id)?;
+        writeln!(
+            f,
+            "Sender balance: {}{}",
+            self.sender_balance,
+            display_option_bool(
+                Some(self.balance_sufficient),
+                "covers max spending",
+                "does not cover max spending",
+                ""
+            )
+        )?;
+        writeln!(f, "Max spending: {} (fee: {})", self.max_spending, self.fee)?;
+        writeln!(f, "Gas estimate: {}", self.gas_estimate)?;
+        if let Some(error) = &self.error {
+            writeln!(f, "Would not be included: {}", error)?;
+        }
+        if let Some(receipt) = &self.receipt {
+            write!(f, "{}", receipt)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for OperationReceipt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Operation {} executed at slot {}{}{}",
+            self.id,
+            self.slot,
+            display_if_true(self.is_final, "final"),
+            if self.error.is_none() {
+                "[success]"
+            } else {
+                "[failed]"
+            }
+        )?;
+        if let Some(block_id) = self.block_id {
+            writeln!(f, "In block: {}", block_id)?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "Error: {}", error)?;
+        }
+        writeln!(f, "Gas used: {}", self.gas_used)?;
+        writeln!(f, "Fee: {}", self.fee)?;
+        writeln!(f, "Balance changes:")?;
+        for change in &self.balance_changes {
+            writeln!(
+                f,
+                "\t- {}: {} -> {}",
+                change.address, change.before, change.after
+            )?;
+        }
+        writeln!(f, "Datastore keys:")?;
+        for change in &self.datastore_keys {
+            writeln!(f, "\t- {}: {:?}", change.address, change.key)?;
+        }
+        writeln!(f, "Events:")?;
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use jsonrpsee::core::__reexports::serde_json::{self, Value};
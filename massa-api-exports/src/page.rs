@@ -1,5 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_models::{
+    execution::{AddressHistoryCursor, AddressHistoryEntry, EventCursor},
+    output_event::SCOutputEvent,
+};
 use paginate::Pages;
 use serde::{Deserialize, Serialize, Serializer};
 
@@ -67,3 +71,39 @@ impl<T> From<PagedVec<T>> for PagedVecV2<T> {
         }
     }
 }
+
+/// Represents the request inputs for a cursor-paginated list of events
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EventPageRequest {
+    /// The maximum number of events in the page
+    pub limit: usize,
+    /// The position of the last event of the previous page, None for the first page
+    pub cursor: Option<EventCursor>,
+}
+
+/// A page of events, along with the cursor of the next page
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EventPage {
+    /// The events of the page, ordered by slot and index in slot
+    pub content: Vec<SCOutputEvent>,
+    /// The cursor to request the next page with, None if there are no more events
+    pub next_cursor: Option<EventCursor>,
+}
+
+/// Represents the request inputs for a cursor-paginated address history
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AddressHistoryPageRequest {
+    /// The maximum number of entries in the page
+    pub limit: usize,
+    /// The position of the last entry of the previous page, None for the first page
+    pub cursor: Option<AddressHistoryCursor>,
+}
+
+/// A page of the history of an address, along with the cursor of the next page
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AddressHistoryPage {
+    /// The entries of the page, from the most recent slot
+    pub content: Vec<AddressHistoryEntry>,
+    /// The cursor to request the next page with, None if there are no more entries
+    pub next_cursor: Option<AddressHistoryCursor>,
+}
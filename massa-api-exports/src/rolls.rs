@@ -1,5 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_hash::Hash;
+use massa_models::address::Address;
 use serde::{Deserialize, Serialize};
 
 /// Roll counts
@@ -21,3 +23,24 @@ impl std::fmt::Display for RollsInfo {
         Ok(())
     }
 }
+
+/// Inputs of the selector draws of a cycle.
+/// Feeding them to a selector reproduces the block and endorsement draws of the cycle.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct DrawInputs {
+    /// cycle of the draws
+    pub cycle: u64,
+    /// roll counts at the end of `cycle - 3`
+    pub rolls: Vec<(Address, u64)>,
+    /// seed derived from the randomness of `cycle - 2`
+    pub seed: Hash,
+}
+
+impl std::fmt::Display for DrawInputs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Cycle: {}", self.cycle)?;
+        writeln!(f, "\tSeed: {}", self.seed)?;
+        writeln!(f, "\tStakers: {}", self.rolls.len())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_db::{verify_state_proof, StateProof};
+use massa_hash::Hash;
+use massa_ledger_exports::{Key, KeySerializer, KeyType};
+use massa_models::{address::Address, slot::Slot};
+use massa_serialization::Serializer;
+use serde::{Deserialize, Serialize};
+
+/// Final state entry for which a proof is requested
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub enum StateProofInput {
+    /// balance of an address
+    Balance(Address),
+    /// bytecode of an address
+    Bytecode(Address),
+    /// datastore entry of an address
+    Datastore {
+        /// associated address of the entry
+        address: Address,
+        /// datastore key
+        key: Vec<u8>,
+    },
+}
+
+impl StateProofInput {
+    /// Serialized final state key of the requested entry, as stored in the database
+    pub fn to_db_key(&self) -> Vec<u8> {
+        let key = match self {
+            StateProofInput::Balance(address) => Key::new(address, KeyType::BALANCE),
+            StateProofInput::Bytecode(address) => Key::new(address, KeyType::BYTECODE),
+            StateProofInput::Datastore { address, key } => {
+                Key::new(address, KeyType::DATASTORE(key.clone()))
+            }
+        };
+        let mut serialized_key = Vec::new();
+        KeySerializer::new(false)
+            .serialize(&key, &mut serialized_key)
+            .expect("critical: ledger key serialization failed");
+        serialized_key
+    }
+}
+
+/// Final state entry with its proof
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct StateProofEntry {
+    /// serialized final state key of the entry
+    pub key: Vec<u8>,
+    /// serialized value of the entry, `None` if the entry is absent from the final state
+    pub value: Option<Vec<u8>>,
+    /// sibling nodes on the path from the leaf of the entry to the root
+    pub side_nodes: Vec<Vec<u8>>,
+    /// unrelated leaf found on the path of the key, for some proofs of absence
+    pub non_membership_leaf_data: Option<Vec<u8>>,
+}
+
+impl StateProofEntry {
+    /// Checks the proof of the entry against a final state hash
+    pub fn verify(&self, state_hash: &Hash) -> bool {
+        verify_state_proof(
+            state_hash,
+            &self.key,
+            self.value.as_deref(),
+            &StateProof {
+                side_nodes: self.side_nodes.clone(),
+                non_membership_leaf_data: self.non_membership_leaf_data.clone(),
+            },
+        )
+    }
+}
+
+/// Final state entries and their proofs, all against the same final state hash
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct StateProofOutput {
+    /// final slot at the output of which the proofs were computed
+    pub slot: Slot,
+    /// final state hash at that slot
+    pub state_hash: Hash,
+    /// proven entries, in the order of the request
+    pub entries: Vec<StateProofEntry>,
+}
+
+impl StateProofOutput {
+    /// Checks that the output was computed against `trusted_state_hash`, and every entry against it.
+    ///
+    /// The `state_hash` field comes from the node that computed the proofs: checking the entries
+    /// against it only shows that the answer is consistent, not that it is true.
+    /// The caller must get `trusted_state_hash` from a source it trusts.
+    pub fn verify(&self, trusted_state_hash: &Hash) -> bool {
+        &self.state_hash == trusted_state_hash
+            && self
+                .entries
+                .iter()
+                .all(|entry| entry.verify(trusted_state_hash))
+    }
+}
+
+impl std::fmt::Display for StateProofOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Final slot: {}", self.slot)?;
+        writeln!(f, "Final state hash: {}", self.state_hash)?;
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "\tKey: {:?} => value: {:?} ({} side nodes)",
+                entry.key,
+                entry.value,
+                entry.side_nodes.len()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -11,15 +11,24 @@ use jsonrpsee::server::{AllowHosts, BatchRequestConfig, ServerBuilder, ServerHan
 use jsonrpsee::RpcModule;
 use massa_api_exports::{
     address::AddressInfo,
+    async_message::AsyncMessageInfo,
     block::{BlockInfo, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError::WrongAPI,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        ExecuteReadOnlyResponse, GasEstimationRequest, GasEstimationResponse,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, SlotDeterminismCheckInfo,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
-    page::{PageRequest, PagedVec},
+    operation::{OperationInfo, OperationInput, OperationReceipt, OperationSimulation},
+    page::{
+        AddressHistoryPage, AddressHistoryPageRequest, EventPage, EventPageRequest, PageRequest,
+        PagedVec,
+    },
+    rolls::DrawInputs,
+    state_proof::{StateProofInput, StateProofOutput},
     TimeInterval,
 };
 use massa_consensus_exports::{ConsensusChannels, ConsensusController};
@@ -31,17 +40,23 @@ use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::{
-    address::Address, block::Block, block_id::BlockId, endorsement::EndorsementId,
-    execution::EventFilter, slot::Slot, version::Version,
+    address::Address,
+    block::Block,
+    block_id::BlockId,
+    endorsement::EndorsementId,
+    execution::{AsyncMessageFilter, EventFilter},
+    slot::Slot,
+    version::Version,
 };
 use massa_pool_exports::{PoolChannels, PoolController};
 use massa_pos_exports::SelectorController;
-use massa_protocol_exports::{ProtocolConfig, ProtocolController};
+use massa_protocol_exports::{ConnectedPeerInfo, PeerScore, ProtocolConfig, ProtocolController};
 use massa_storage::Storage;
 use massa_versioning::keypair_factory::KeyPairFactory;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -241,6 +256,14 @@ pub trait MassaRpc {
         arg: Vec<ReadOnlyCall>,
     ) -> RpcResult<Vec<ExecuteReadOnlyResponse>>;
 
+    /// Estimate the `max_gas` of smart contract operations
+    /// by running them in read-only mode on top of the candidate state.
+    #[method(name = "estimate_gas")]
+    async fn estimate_gas(
+        &self,
+        arg: Vec<GasEstimationRequest>,
+    ) -> RpcResult<Vec<GasEstimationResponse>>;
+
     /// Remove a vector of addresses used to stake.
     /// No confirmation to expect.
     #[method(name = "remove_staking_addresses")]
@@ -315,6 +338,35 @@ pub trait MassaRpc {
     #[method(name = "node_unban_by_id")]
     async fn node_unban_by_id(&self, arg: Vec<NodeId>) -> RpcResult<()>;
 
+    /// Returns the reputation scores of the peers known by the node.
+    #[method(name = "node_peers_scores")]
+    async fn node_peers_scores(&self) -> RpcResult<BTreeMap<NodeId, PeerScore>>;
+
+    /// Returns the peers the node is connected to, with the live state of their connection.
+    #[method(name = "node_get_peers")]
+    async fn node_get_peers(&self) -> RpcResult<BTreeMap<NodeId, ConnectedPeerInfo>>;
+
+    /// Try to connect to the peer listening at the given address.
+    /// No confirmation to expect.
+    #[method(name = "node_connect_peer")]
+    async fn node_connect_peer(&self, arg: SocketAddr) -> RpcResult<()>;
+
+    /// Close the connection with the given node id, without banning it.
+    /// No confirmation to expect.
+    #[method(name = "node_disconnect_peer")]
+    async fn node_disconnect_peer(&self, arg: NodeId) -> RpcResult<()>;
+
+    /// Check the determinism of an upcoming final slot: when it is finalized, re-execute it against
+    /// the final state it starts from and compare the resulting state changes with the finalized ones.
+    /// Past final slots can't be checked. Returns once the slot is finalized,
+    /// optionally with the traces of its operations, or fails after `slot_determinism_check_timeout`.
+    #[method(name = "execution_check_slot_determinism")]
+    async fn execution_check_slot_determinism(
+        &self,
+        arg: Slot,
+        trace: bool,
+    ) -> RpcResult<SlotDeterminismCheckInfo>;
+
     /// Summary of the current state: time, last final blocks (hash, thread, slot, timestamp), clique count, connected nodes count.
     #[method(name = "get_status")]
     async fn get_status(&self) -> RpcResult<NodeStatus>;
@@ -330,10 +382,22 @@ pub trait MassaRpc {
         page_request: Option<PageRequest>,
     ) -> RpcResult<PagedVec<(Address, u64)>>;
 
+    /// Returns the inputs of the selector draws (look back rolls and seed) of the given cycles.
+    #[method(name = "get_draw_inputs")]
+    async fn get_draw_inputs(&self, arg: Vec<u64>) -> RpcResult<Vec<DrawInputs>>;
+
     /// Returns operation(s) information associated to a given list of operation(s) ID(s).
     #[method(name = "get_operations")]
     async fn get_operations(&self, arg: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>>;
 
+    /// Returns the execution receipts of a given list of operation(s) ID(s).
+    /// Operations without a known execution are omitted.
+    #[method(name = "get_operation_receipts")]
+    async fn get_operation_receipts(
+        &self,
+        arg: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationReceipt>>;
+
     /// Returns endorsement(s) information associated to a given list of endorsement(s) ID(s)
     #[method(name = "get_endorsements")]
     async fn get_endorsements(&self, arg: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>>;
@@ -353,20 +417,41 @@ pub trait MassaRpc {
     async fn get_graph_interval(&self, arg: TimeInterval) -> RpcResult<Vec<BlockSummary>>;
 
     /// Get multiple datastore entries.
+    /// With `at_slot`, read their final values right after that past final slot (archive mode only).
     #[method(name = "get_datastore_entries")]
     async fn get_datastore_entries(
         &self,
         arg: Vec<DatastoreEntryInput>,
+        at_slot: Option<Slot>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>>;
 
+    /// Get final state entries (balances, bytecodes, datastore entries)
+    /// along with sparse merkle proofs against the final state hash.
+    #[method(name = "get_state_proof")]
+    async fn get_state_proof(&self, arg: Vec<StateProofInput>) -> RpcResult<StateProofOutput>;
+
     /// Get addresses.
+    /// With `at_slot`, only read their balances and datastore keys right after that past final slot (archive mode only).
     #[method(name = "get_addresses")]
-    async fn get_addresses(&self, arg: Vec<Address>) -> RpcResult<Vec<AddressInfo>>;
+    async fn get_addresses(
+        &self,
+        arg: Vec<Address>,
+        at_slot: Option<Slot>,
+    ) -> RpcResult<Vec<AddressInfo>>;
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
     #[method(name = "send_operations")]
     async fn send_operations(&self, arg: Vec<OperationInput>) -> RpcResult<Vec<OperationId>>;
 
+    /// Simulates the execution of signed operations on top of the candidate state,
+    /// without adding them to the pool nor propagating them.
+    /// Each operation is simulated independently.
+    #[method(name = "simulate_operations")]
+    async fn simulate_operations(
+        &self,
+        arg: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationSimulation>>;
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -377,6 +462,31 @@ pub trait MassaRpc {
     async fn get_filtered_sc_output_event(&self, arg: EventFilter)
         -> RpcResult<Vec<SCOutputEvent>>;
 
+    /// Get a page of the events matching a filter.
+    /// The next page is requested with the `next_cursor` of the returned page.
+    #[method(name = "get_filtered_sc_output_event_page")]
+    async fn get_filtered_sc_output_event_page(
+        &self,
+        arg: EventFilter,
+        page_request: EventPageRequest,
+    ) -> RpcResult<EventPage>;
+
+    /// Get the pending asynchronous messages matching a filter,
+    /// with whether their trigger fired and whether they can be executed at the next slot.
+    #[method(name = "get_async_messages")]
+    async fn get_async_messages(&self, arg: AsyncMessageFilter)
+        -> RpcResult<Vec<AsyncMessageInfo>>;
+
+    /// Get a page of the final operations sent or received by an address, most recent first.
+    /// The next page is requested with the `next_cursor` of the returned page.
+    /// Only available on nodes maintaining the address history index.
+    #[method(name = "get_address_history")]
+    async fn get_address_history(
+        &self,
+        arg: Address,
+        page_request: AddressHistoryPageRequest,
+    ) -> RpcResult<AddressHistoryPage>;
+
     /// Get OpenRPC specification.
     #[method(name = "rpc.discover")]
     async fn get_openrpc_spec(&self) -> RpcResult<Value>;
@@ -6,29 +6,47 @@ use async_trait::async_trait;
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
     address::AddressInfo,
+    async_message::AsyncMessageInfo,
     block::{BlockInfo, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        ExecuteReadOnlyResponse, GasEstimationRequest, GasEstimationResponse,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, SlotDeterminismCheckInfo,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
-    page::{PageRequest, PagedVec},
+    operation::{OperationInfo, OperationInput, OperationReceipt, OperationSimulation},
+    page::{
+        AddressHistoryPage, AddressHistoryPageRequest, EventPage, EventPageRequest, PageRequest,
+        PagedVec,
+    },
+    rolls::DrawInputs,
+    state_proof::{StateProofInput, StateProofOutput},
     ListType, ScrudOperation, TimeInterval,
 };
 use massa_execution_exports::ExecutionController;
 use massa_hash::Hash;
 use massa_models::{
-    address::Address, block::Block, block_id::BlockId, clique::Clique, composite::PubkeySig,
-    endorsement::EndorsementId, execution::EventFilter, node::NodeId, operation::OperationId,
-    output_event::SCOutputEvent, prehash::PreHashSet, slot::Slot,
+    address::Address,
+    block::Block,
+    block_id::BlockId,
+    clique::Clique,
+    composite::PubkeySig,
+    endorsement::EndorsementId,
+    execution::{AsyncMessageFilter, EventFilter},
+    node::NodeId,
+    operation::OperationId,
+    output_event::SCOutputEvent,
+    prehash::PreHashSet,
+    slot::Slot,
 };
-use massa_protocol_exports::{PeerId, ProtocolController};
+use massa_protocol_exports::{ConnectedPeerInfo, PeerId, PeerScore, ProtocolController};
 use massa_signature::KeyPair;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{remove_file, OpenOptions};
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
@@ -128,6 +146,13 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<_>()
     }
 
+    async fn estimate_gas(
+        &self,
+        _reqs: Vec<GasEstimationRequest>,
+    ) -> RpcResult<Vec<GasEstimationResponse>> {
+        crate::wrong_api::<_>()
+    }
+
     async fn remove_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
         let node_wallet = self.0.node_wallet.clone();
         let mut w_wallet = node_wallet.write();
@@ -178,6 +203,62 @@ impl MassaRpcServer for API<Private> {
             .map_err(|e| ApiError::ProtocolError(e).into())
     }
 
+    async fn node_peers_scores(&self) -> RpcResult<BTreeMap<NodeId, PeerScore>> {
+        let protocol_controller = self.0.protocol_controller.clone();
+        let scores = protocol_controller
+            .get_peer_scores()
+            .map_err(ApiError::ProtocolError)?;
+        //TODO: Change when unify node id and peer id
+        Ok(scores
+            .into_iter()
+            .map(|(peer_id, score)| (NodeId::new(peer_id.get_public_key()), score))
+            .collect())
+    }
+
+    async fn node_get_peers(&self) -> RpcResult<BTreeMap<NodeId, ConnectedPeerInfo>> {
+        let protocol_controller = self.0.protocol_controller.clone();
+        let peers = protocol_controller
+            .get_connected_peers()
+            .map_err(ApiError::ProtocolError)?;
+        //TODO: Change when unify node id and peer id
+        Ok(peers
+            .into_iter()
+            .map(|(peer_id, info)| (NodeId::new(peer_id.get_public_key()), info))
+            .collect())
+    }
+
+    async fn node_connect_peer(&self, addr: SocketAddr) -> RpcResult<()> {
+        let protocol_controller = self.0.protocol_controller.clone();
+        protocol_controller
+            .connect_peer(addr)
+            .map_err(|e| ApiError::ProtocolError(e).into())
+    }
+
+    async fn node_disconnect_peer(&self, id: NodeId) -> RpcResult<()> {
+        let protocol_controller = self.0.protocol_controller.clone();
+        //TODO: Change when unify node id and peer id
+        protocol_controller
+            .disconnect_peer(PeerId::from_public_key(id.get_public_key()))
+            .map_err(|e| ApiError::ProtocolError(e).into())
+    }
+
+    async fn execution_check_slot_determinism(
+        &self,
+        slot: Slot,
+        trace: bool,
+    ) -> RpcResult<SlotDeterminismCheckInfo> {
+        let execution_controller = self.0.execution_controller.clone();
+        let timeout = self.0.api_settings.slot_determinism_check_timeout;
+        // the check only completes once the slot is final: wait for it outside of the async runtime
+        let output = tokio::task::spawn_blocking(move || {
+            execution_controller.check_slot_determinism(slot, trace, timeout)
+        })
+        .await
+        .map_err(|err| ApiError::InternalServerError(err.to_string()))?
+        .map_err(ApiError::ExecutionError)?;
+        Ok(output.into())
+    }
+
     async fn node_unban_by_ip(&self, _ips: Vec<IpAddr>) -> RpcResult<()> {
         //TODO: Reinvoke
         // let network_command_sender = self.0.network_command_sender.clone();
@@ -202,10 +283,21 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<PagedVec<(Address, u64)>>()
     }
 
+    async fn get_draw_inputs(&self, _: Vec<u64>) -> RpcResult<Vec<DrawInputs>> {
+        crate::wrong_api::<Vec<DrawInputs>>()
+    }
+
     async fn get_operations(&self, _: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>> {
         crate::wrong_api::<Vec<OperationInfo>>()
     }
 
+    async fn get_operation_receipts(
+        &self,
+        _: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationReceipt>> {
+        crate::wrong_api::<Vec<OperationReceipt>>()
+    }
+
     async fn get_endorsements(&self, _: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>> {
         crate::wrong_api::<Vec<EndorsementInfo>>()
     }
@@ -225,11 +317,16 @@ impl MassaRpcServer for API<Private> {
     async fn get_datastore_entries(
         &self,
         _: Vec<DatastoreEntryInput>,
+        _: Option<Slot>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>> {
         crate::wrong_api()
     }
 
-    async fn get_addresses(&self, _: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
+    async fn get_state_proof(&self, _: Vec<StateProofInput>) -> RpcResult<StateProofOutput> {
+        crate::wrong_api::<StateProofOutput>()
+    }
+
+    async fn get_addresses(&self, _: Vec<Address>, _: Option<Slot>) -> RpcResult<Vec<AddressInfo>> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
 
@@ -237,10 +334,37 @@ impl MassaRpcServer for API<Private> {
         crate::wrong_api::<Vec<OperationId>>()
     }
 
+    async fn simulate_operations(
+        &self,
+        _: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationSimulation>> {
+        crate::wrong_api::<Vec<OperationSimulation>>()
+    }
+
     async fn get_filtered_sc_output_event(&self, _: EventFilter) -> RpcResult<Vec<SCOutputEvent>> {
         crate::wrong_api::<Vec<SCOutputEvent>>()
     }
 
+    async fn get_filtered_sc_output_event_page(
+        &self,
+        _: EventFilter,
+        _: EventPageRequest,
+    ) -> RpcResult<EventPage> {
+        crate::wrong_api::<EventPage>()
+    }
+
+    async fn get_async_messages(&self, _: AsyncMessageFilter) -> RpcResult<Vec<AsyncMessageInfo>> {
+        crate::wrong_api::<Vec<AsyncMessageInfo>>()
+    }
+
+    async fn get_address_history(
+        &self,
+        _: Address,
+        _: AddressHistoryPageRequest,
+    ) -> RpcResult<AddressHistoryPage> {
+        crate::wrong_api::<AddressHistoryPage>()
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         //TODO: Reinvoke
         // let network_command_sender = self.0.network_command_sender.clone();
@@ -7,22 +7,32 @@ use itertools::{izip, Itertools};
 use jsonrpsee::core::{Error as JsonRpseeError, RpcResult};
 use massa_api_exports::{
     address::AddressInfo,
+    async_message::AsyncMessageInfo,
     block::{BlockInfo, BlockInfoContent, BlockSummary},
     config::APIConfig,
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
     error::ApiError,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult},
+    execution::{
+        ExecuteReadOnlyResponse, GasEstimationRequest, GasEstimationResponse, GasEstimationTarget,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, ReadOnlyResult, SlotDeterminismCheckInfo,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
-    page::{PageRequest, PagedVec},
+    operation::{OperationInfo, OperationInput, OperationReceipt, OperationSimulation},
+    page::{
+        AddressHistoryPage, AddressHistoryPageRequest, EventPage, EventPageRequest, PageRequest,
+        PagedVec,
+    },
+    rolls::DrawInputs,
     slot::SlotAmount,
+    state_proof::{StateProofEntry, StateProofInput, StateProofOutput},
     TimeInterval,
 };
 use massa_consensus_exports::block_status::DiscardReason;
 use massa_consensus_exports::ConsensusController;
 use massa_execution_exports::{
-    ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionController, ExecutionError, ExecutionStackElement, GasEstimationConfig,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
 use massa_models::{
     address::Address,
@@ -35,7 +45,7 @@ use massa_models::{
     endorsement::EndorsementId,
     endorsement::SecureShareEndorsement,
     error::ModelsError,
-    execution::EventFilter,
+    execution::{AddressHistoryCursor, AsyncMessageFilter, EventCursor, EventFilter},
     node::NodeId,
     operation::OperationDeserializer,
     operation::OperationId,
@@ -50,7 +60,9 @@ use massa_models::{
 };
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
-use massa_protocol_exports::{PeerConnectionType, ProtocolConfig, ProtocolController};
+use massa_protocol_exports::{
+    ConnectedPeerInfo, PeerConnectionType, PeerScore, ProtocolConfig, ProtocolController,
+};
 use massa_serialization::{DeserializeError, Deserializer};
 use massa_storage::Storage;
 use massa_time::MassaTime;
@@ -90,6 +102,53 @@ impl API<Public> {
             keypair_factory: KeyPairFactory { mip_store },
         })
     }
+
+    /// Deserializes signed operations and verifies their signature
+    fn deserialize_operations(
+        &self,
+        ops: Vec<OperationInput>,
+    ) -> RpcResult<Vec<SecureShareOperation>> {
+        let api_cfg = &self.0.api_settings;
+        let operation_deserializer = SecureShareDeserializer::new(OperationDeserializer::new(
+            api_cfg.max_datastore_value_length,
+            api_cfg.max_function_name_length,
+            api_cfg.max_parameter_size,
+            api_cfg.max_op_datastore_entry_count,
+            api_cfg.max_op_datastore_key_length,
+            api_cfg.max_op_datastore_value_length,
+        ));
+        ops.into_iter()
+            .map(|op_input| {
+                let mut op_serialized = Vec::new();
+                op_serialized.extend(op_input.signature.to_bytes());
+                op_serialized.extend(op_input.creator_public_key.to_bytes());
+                op_serialized.extend(op_input.serialized_content);
+                let (rest, op): (&[u8], SecureShareOperation) = operation_deserializer
+                    .deserialize::<DeserializeError>(&op_serialized)
+                    .map_err(|err| {
+                        ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
+                    })?;
+                if rest.is_empty() {
+                    Ok(op)
+                } else {
+                    Err(ApiError::ModelsError(ModelsError::DeserializeError(
+                        "There is data left after operation deserialization".to_owned(),
+                    ))
+                    .into())
+                }
+            })
+            .map(|op| match op {
+                Ok(operation) => {
+                    let _verify_signature = match operation.verify_signature() {
+                        Ok(()) => (),
+                        Err(e) => return Err(ApiError::ModelsError(e).into()),
+                    };
+                    Ok(operation)
+                }
+                Err(e) => Err(e),
+            })
+            .collect::<RpcResult<Vec<SecureShareOperation>>>()
+    }
 }
 
 #[async_trait]
@@ -133,6 +192,8 @@ impl MassaRpcServer for API<Public> {
             bytecode,
             operation_datastore,
             is_final,
+            at_slot,
+            trace,
         } in reqs
         {
             let address = if let Some(addr) = address {
@@ -186,6 +247,8 @@ impl MassaRpcServer for API<Public> {
                     operation_datastore: op_datastore,
                 }],
                 is_final,
+                at_slot,
+                trace,
             };
 
             // run
@@ -204,6 +267,12 @@ impl MassaRpcServer for API<Public> {
                 output_events: result
                     .as_ref()
                     .map_or_else(|_| Default::default(), |v| v.out.events.clone().0),
+                // the trace is also returned when the execution failed
+                trace: match &result {
+                    Ok(output) => output.trace.clone(),
+                    Err(ExecutionError::TracedError { trace, .. }) => Some(*trace.clone()),
+                    Err(_) => None,
+                },
                 state_changes: result.map_or_else(|_| Default::default(), |v| v.out.state_changes),
             };
 
@@ -230,6 +299,8 @@ impl MassaRpcServer for API<Public> {
             parameter,
             caller_address,
             is_final,
+            at_slot,
+            trace,
         } in reqs
         {
             let caller_address = if let Some(addr) = caller_address {
@@ -274,6 +345,8 @@ impl MassaRpcServer for API<Public> {
                     },
                 ],
                 is_final,
+                at_slot,
+                trace,
             };
 
             // run
@@ -292,6 +365,12 @@ impl MassaRpcServer for API<Public> {
                 output_events: result
                     .as_ref()
                     .map_or_else(|_| Default::default(), |v| v.out.events.clone().0),
+                // the trace is also returned when the execution failed
+                trace: match &result {
+                    Ok(output) => output.trace.clone(),
+                    Err(ExecutionError::TracedError { trace, .. }) => Some(*trace.clone()),
+                    Err(_) => None,
+                },
                 state_changes: result.map_or_else(|_| Default::default(), |v| v.out.state_changes),
             };
 
@@ -302,6 +381,96 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn estimate_gas(
+        &self,
+        reqs: Vec<GasEstimationRequest>,
+    ) -> RpcResult<Vec<GasEstimationResponse>> {
+        if reqs.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let config = GasEstimationConfig {
+            margin_percent: self.0.api_settings.gas_estimation_margin_percent,
+            max_gas_per_block: self.0.api_settings.max_gas_per_block,
+            max_read_only_gas: self.0.api_settings.max_read_only_gas,
+            sp_compilation_cost: self.0.api_settings.sp_compilation_cost,
+        };
+
+        let mut res: Vec<GasEstimationResponse> = Vec::with_capacity(reqs.len());
+        for GasEstimationRequest {
+            caller_address,
+            target,
+        } in reqs
+        {
+            let target = match target {
+                GasEstimationTarget::ExecuteSC {
+                    bytecode,
+                    operation_datastore,
+                } => {
+                    let datastore = match operation_datastore {
+                        Some(v) => {
+                            let deserializer = DatastoreDeserializer::new(
+                                self.0.api_settings.max_op_datastore_entry_count,
+                                self.0.api_settings.max_op_datastore_key_length,
+                                self.0.api_settings.max_op_datastore_value_length,
+                            );
+                            match deserializer.deserialize::<DeserializeError>(&v) {
+                                Ok((_, deserialized)) => deserialized,
+                                Err(e) => {
+                                    return Err(ApiError::InconsistencyError(format!(
+                                        "Operation datastore error: {}",
+                                        e
+                                    ))
+                                    .into())
+                                }
+                            }
+                        }
+                        None => Default::default(),
+                    };
+                    massa_execution_exports::GasEstimationTarget::ExecuteSC {
+                        bytecode,
+                        datastore,
+                    }
+                }
+                GasEstimationTarget::CallSC {
+                    target_address,
+                    target_function,
+                    parameter,
+                    coins,
+                } => massa_execution_exports::GasEstimationTarget::CallSC {
+                    target_addr: target_address,
+                    target_func: target_function,
+                    parameter,
+                    coins,
+                },
+            };
+
+            let result = massa_execution_exports::estimate_gas(
+                self.0.execution_controller.as_ref(),
+                &config,
+                caller_address,
+                target,
+            );
+
+            res.push(match result {
+                Ok(estimation) => GasEstimationResponse {
+                    executed_at: Some(estimation.slot),
+                    gas_used: estimation.gas_used,
+                    max_gas: estimation.max_gas,
+                    error: None,
+                },
+                Err(err) => GasEstimationResponse {
+                    executed_at: None,
+                    gas_used: 0,
+                    max_gas: 0,
+                    error: Some(format!("gas estimation failed: {}", err)),
+                },
+            });
+        }
+
+        Ok(res)
+    }
+
     async fn remove_staking_addresses(&self, _: Vec<Address>) -> RpcResult<()> {
         crate::wrong_api::<()>()
     }
@@ -326,6 +495,30 @@ impl MassaRpcServer for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    async fn node_peers_scores(&self) -> RpcResult<BTreeMap<NodeId, PeerScore>> {
+        crate::wrong_api::<BTreeMap<NodeId, PeerScore>>()
+    }
+
+    async fn node_get_peers(&self) -> RpcResult<BTreeMap<NodeId, ConnectedPeerInfo>> {
+        crate::wrong_api::<BTreeMap<NodeId, ConnectedPeerInfo>>()
+    }
+
+    async fn node_connect_peer(&self, _: SocketAddr) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn node_disconnect_peer(&self, _: NodeId) -> RpcResult<()> {
+        crate::wrong_api::<()>()
+    }
+
+    async fn execution_check_slot_determinism(
+        &self,
+        _: Slot,
+        _: bool,
+    ) -> RpcResult<SlotDeterminismCheckInfo> {
+        crate::wrong_api::<SlotDeterminismCheckInfo>()
+    }
+
     async fn get_status(&self) -> RpcResult<NodeStatus> {
         let execution_controller = self.0.execution_controller.clone();
         let consensus_controller = self.0.consensus_controller.clone();
@@ -483,6 +676,27 @@ impl MassaRpcServer for API<Public> {
         Ok(paged_vec)
     }
 
+    async fn get_draw_inputs(&self, cycles: Vec<u64>) -> RpcResult<Vec<DrawInputs>> {
+        if cycles.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let mut res = Vec::with_capacity(cycles.len());
+        for cycle in cycles {
+            let (rolls, seed) = self
+                .0
+                .execution_controller
+                .get_selector_draw_inputs(cycle)
+                .map_err(ApiError::from)?;
+            res.push(DrawInputs {
+                cycle,
+                rolls: rolls.into_iter().collect(),
+                seed,
+            });
+        }
+        Ok(res)
+    }
+
     async fn get_operations(&self, ops: Vec<OperationId>) -> RpcResult<Vec<OperationInfo>> {
         // get the operations and the list of blocks that contain them from storage
         let storage_info: Vec<(SecureShareOperation, PreHashSet<BlockId>)> = {
@@ -560,6 +774,33 @@ impl MassaRpcServer for API<Public> {
         Ok(res)
     }
 
+    async fn get_operation_receipts(
+        &self,
+        ops: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationReceipt>> {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let execution_controller = &self.0.execution_controller;
+        let thread_count = self.0.api_settings.thread_count;
+        Ok(execution_controller
+            .get_operation_receipts(&ops)
+            .into_iter()
+            .flatten()
+            .map(|receipt| {
+                // receipts only keep the indexes of their events: read them from the event store
+                let events = execution_controller.get_filtered_sc_output_event(EventFilter {
+                    start: Some(receipt.slot),
+                    end: receipt.slot.get_next_slot(thread_count).ok(),
+                    original_operation_id: Some(receipt.operation_id),
+                    ..Default::default()
+                });
+                OperationReceipt::new(receipt, &events)
+            })
+            .collect())
+    }
+
     async fn get_endorsements(&self, eds: Vec<EndorsementId>) -> RpcResult<Vec<EndorsementInfo>> {
         // get the endorsements and the list of blocks that contain them from storage
         let storage_info: Vec<(SecureShareEndorsement, PreHashSet<BlockId>)> = {
@@ -762,8 +1003,32 @@ impl MassaRpcServer for API<Public> {
     async fn get_datastore_entries(
         &self,
         entries: Vec<DatastoreEntryInput>,
+        at_slot: Option<Slot>,
     ) -> RpcResult<Vec<DatastoreEntryOutput>> {
         let execution_controller = self.0.execution_controller.clone();
+
+        // past final values, read from the archive
+        if let Some(slot) = at_slot {
+            if entries.len() as u64 > self.0.api_settings.max_arguments {
+                return Err(ApiError::BadRequest("too many arguments".into()).into());
+            }
+            return Ok(execution_controller
+                .get_archived_data_entries(
+                    entries
+                        .into_iter()
+                        .map(|input| (input.address, input.key))
+                        .collect::<Vec<_>>(),
+                    slot,
+                )
+                .map_err(ApiError::from)?
+                .into_iter()
+                .map(|value| DatastoreEntryOutput {
+                    final_value: value.clone(),
+                    candidate_value: value,
+                })
+                .collect());
+        }
+
         Ok(execution_controller
             .get_final_and_active_data_entry(
                 entries
@@ -779,7 +1044,83 @@ impl MassaRpcServer for API<Public> {
             .collect())
     }
 
-    async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
+    async fn get_state_proof(&self, inputs: Vec<StateProofInput>) -> RpcResult<StateProofOutput> {
+        if inputs.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+
+        let keys: Vec<Vec<u8>> = inputs.iter().map(|input| input.to_db_key()).collect();
+        let proofs = self
+            .0
+            .execution_controller
+            .get_final_state_proofs(&keys)
+            .map_err(ApiError::from)?;
+
+        Ok(StateProofOutput {
+            slot: proofs.slot,
+            state_hash: proofs.state_hash,
+            entries: keys
+                .into_iter()
+                .zip(proofs.entries)
+                .map(|(key, (value, proof))| StateProofEntry {
+                    key,
+                    value,
+                    side_nodes: proof.side_nodes,
+                    non_membership_leaf_data: proof.non_membership_leaf_data,
+                })
+                .collect(),
+        })
+    }
+
+    async fn get_addresses(
+        &self,
+        addresses: Vec<Address>,
+        at_slot: Option<Slot>,
+    ) -> RpcResult<Vec<AddressInfo>> {
+        // past final balances and datastore keys, read from the archive.
+        // Rolls, deferred credits, draws and created objects are not archived and left empty.
+        if let Some(slot) = at_slot {
+            if addresses.len() as u64 > self.0.api_settings.max_arguments {
+                return Err(ApiError::BadRequest("too many arguments".into()).into());
+            }
+            let balances = self
+                .0
+                .execution_controller
+                .get_archived_balances(&addresses, slot)
+                .map_err(ApiError::from)?;
+            let datastore_keys = self
+                .0
+                .execution_controller
+                .get_archived_datastore_keys(&addresses, slot)
+                .map_err(ApiError::from)?;
+            return Ok(izip!(addresses, balances, datastore_keys)
+                .map(|(address, balance, datastore_keys)| {
+                    let balance = balance.unwrap_or_default();
+                    let datastore_keys = datastore_keys
+                        .unwrap_or_default()
+                        .into_iter()
+                        .collect::<Vec<_>>();
+                    AddressInfo {
+                        address,
+                        thread: address.get_thread(self.0.api_settings.thread_count),
+                        final_balance: balance,
+                        final_roll_count: 0,
+                        final_datastore_keys: datastore_keys.clone(),
+                        candidate_balance: balance,
+                        candidate_roll_count: 0,
+                        candidate_datastore_keys: datastore_keys,
+                        deferred_credits: Vec::new(),
+                        next_block_draws: Vec::new(),
+                        next_endorsement_draws: Vec::new(),
+                        created_blocks: Vec::new(),
+                        created_operations: Vec::new(),
+                        created_endorsements: Vec::new(),
+                        cycle_infos: Vec::new(),
+                    }
+                })
+                .collect());
+        }
+
         // get info from storage about which blocks the addresses have created
         let created_blocks: Vec<PreHashSet<BlockId>> = {
             let lck = self.0.storage.read_blocks();
@@ -921,46 +1262,7 @@ impl MassaRpcServer for API<Public> {
         if ops.len() as u64 > api_cfg.max_arguments {
             return Err(ApiError::BadRequest("too many arguments".into()).into());
         }
-        let operation_deserializer = SecureShareDeserializer::new(OperationDeserializer::new(
-            api_cfg.max_datastore_value_length,
-            api_cfg.max_function_name_length,
-            api_cfg.max_parameter_size,
-            api_cfg.max_op_datastore_entry_count,
-            api_cfg.max_op_datastore_key_length,
-            api_cfg.max_op_datastore_value_length,
-        ));
-        let verified_ops = ops
-            .into_iter()
-            .map(|op_input| {
-                let mut op_serialized = Vec::new();
-                op_serialized.extend(op_input.signature.to_bytes());
-                op_serialized.extend(op_input.creator_public_key.to_bytes());
-                op_serialized.extend(op_input.serialized_content);
-                let (rest, op): (&[u8], SecureShareOperation) = operation_deserializer
-                    .deserialize::<DeserializeError>(&op_serialized)
-                    .map_err(|err| {
-                        ApiError::ModelsError(ModelsError::DeserializeError(err.to_string()))
-                    })?;
-                if rest.is_empty() {
-                    Ok(op)
-                } else {
-                    Err(ApiError::ModelsError(ModelsError::DeserializeError(
-                        "There is data left after operation deserialization".to_owned(),
-                    ))
-                    .into())
-                }
-            })
-            .map(|op| match op {
-                Ok(operation) => {
-                    let _verify_signature = match operation.verify_signature() {
-                        Ok(()) => (),
-                        Err(e) => return Err(ApiError::ModelsError(e).into()),
-                    };
-                    Ok(operation)
-                }
-                Err(e) => Err(e),
-            })
-            .collect::<RpcResult<Vec<SecureShareOperation>>>()?;
+        let verified_ops = self.deserialize_operations(ops)?;
         to_send.store_operations(verified_ops.clone());
         let ids: Vec<OperationId> = verified_ops.iter().map(|op| op.id).collect();
         cmd_sender.add_operations(to_send.clone());
@@ -974,6 +1276,67 @@ impl MassaRpcServer for API<Public> {
         Ok(ids)
     }
 
+    async fn simulate_operations(
+        &self,
+        ops: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationSimulation>> {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()).into());
+        }
+        let operations = self.deserialize_operations(ops)?;
+        let roll_price = CompactConfig::default().roll_price;
+
+        let mut res: Vec<OperationSimulation> = Vec::with_capacity(operations.len());
+        for operation in operations {
+            let id = operation.id;
+            let fee = operation.content.fee;
+            let max_spending = operation.get_max_spending(roll_price);
+            let sender_balance = self
+                .0
+                .execution_controller
+                .get_final_and_candidate_balance(&[operation.content_creator_address])[0]
+                .1
+                .unwrap_or_default();
+
+            // run the operation on top of the candidate state
+            let req = ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                target: ReadOnlyExecutionTarget::Operation(operation),
+                call_stack: Vec::new(),
+                is_final: false,
+                at_slot: None,
+                trace: false,
+            };
+            let result = self.0.execution_controller.execute_readonly_request(req);
+
+            // map result
+            let (receipt, error, gas_estimate) = match result {
+                Ok(mut output) => (
+                    output
+                        .out
+                        .receipts
+                        .remove(&id)
+                        .map(|receipt| OperationReceipt::new(receipt, &output.out.events.0)),
+                    None,
+                    output.gas_cost,
+                ),
+                Err(err) => (None, Some(err.to_string()), 0),
+            };
+            res.push(OperationSimulation {
+                id,
+                receipt,
+                error,
+                gas_estimate,
+                fee,
+                sender_balance,
+                max_spending,
+                balance_sufficient: sender_balance >= max_spending,
+            });
+        }
+
+        Ok(res)
+    }
+
     /// Get events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -993,6 +1356,83 @@ impl MassaRpcServer for API<Public> {
         Ok(events)
     }
 
+    async fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+        page_request: EventPageRequest,
+    ) -> RpcResult<EventPage> {
+        if page_request.limit as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many events requested".into()).into());
+        }
+
+        let content = self
+            .0
+            .execution_controller
+            .get_filtered_sc_output_event_page(filter, page_request.cursor, page_request.limit);
+
+        // a full page may be followed by more events
+        let next_cursor = match content.last() {
+            Some(last) if content.len() == page_request.limit => Some(EventCursor {
+                slot: last.context.slot,
+                index_in_slot: last.context.index_in_slot,
+            }),
+            _ => None,
+        };
+
+        Ok(EventPage {
+            content,
+            next_cursor,
+        })
+    }
+
+    async fn get_async_messages(
+        &self,
+        filter: AsyncMessageFilter,
+    ) -> RpcResult<Vec<AsyncMessageInfo>> {
+        Ok(self
+            .0
+            .execution_controller
+            .get_async_messages(&filter)
+            .into_iter()
+            .map(AsyncMessageInfo::from)
+            .collect())
+    }
+
+    async fn get_address_history(
+        &self,
+        address: Address,
+        page_request: AddressHistoryPageRequest,
+    ) -> RpcResult<AddressHistoryPage> {
+        if page_request.limit as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many entries requested".into()).into());
+        }
+
+        let Some(content) = self.0.execution_controller.get_address_history(
+            &address,
+            page_request.cursor,
+            page_request.limit,
+        ) else {
+            return Err(ApiError::BadRequest(
+                "address history index is disabled on this node".into(),
+            )
+            .into());
+        };
+
+        // a full page may be followed by more entries
+        let next_cursor = match content.last() {
+            Some(last) if content.len() == page_request.limit => Some(AddressHistoryCursor {
+                slot: last.slot,
+                operation_id: last.operation_id,
+            }),
+            _ => None,
+        };
+
+        Ok(AddressHistoryPage {
+            content,
+            next_cursor,
+        })
+    }
+
     async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         crate::wrong_api::<Vec<IpAddr>>()
     }
@@ -98,9 +98,9 @@ mod pool;
 pub use changes::{AsyncPoolChanges, AsyncPoolChangesDeserializer, AsyncPoolChangesSerializer};
 pub use config::AsyncPoolConfig;
 pub use message::{
-    AsyncMessage, AsyncMessageDeserializer, AsyncMessageId, AsyncMessageIdDeserializer,
-    AsyncMessageIdSerializer, AsyncMessageInfo, AsyncMessageSerializer, AsyncMessageTrigger,
-    AsyncMessageTriggerSerializer, AsyncMessageUpdate,
+    async_message_id_to_string, AsyncMessage, AsyncMessageDeserializer, AsyncMessageId,
+    AsyncMessageIdDeserializer, AsyncMessageIdSerializer, AsyncMessageInfo, AsyncMessageSerializer,
+    AsyncMessageTrigger, AsyncMessageTriggerSerializer, AsyncMessageUpdate,
 };
 pub use pool::{AsyncPool, AsyncPoolDeserializer, AsyncPoolSerializer};
 
@@ -94,6 +94,16 @@ impl Serializer<AsyncMessageId> for AsyncMessageIdSerializer {
     }
 }
 
+/// Encodes a message id into the base58check string identifying the message in the APIs.
+/// The decoded bytes are the serialized message id.
+pub fn async_message_id_to_string(id: &AsyncMessageId) -> String {
+    let mut serialized = Vec::new();
+    AsyncMessageIdSerializer::new()
+        .serialize(id, &mut serialized)
+        .expect("critical: asynchronous message id serialization should never fail");
+    bs58::encode(serialized).with_check().into_string()
+}
+
 #[derive(Clone)]
 pub struct AsyncMessageIdDeserializer {
     slot_deserializer: SlotDeserializer,
@@ -1,13 +1,83 @@
 mod client;
 mod server;
+use crate::{error::BootstrapError, settings::BootstrapCompression};
+use massa_models::config::MAX_BOOTSTRAP_MESSAGE_SIZE;
 use std::{
-    io::{self, ErrorKind},
+    borrow::Cow,
+    io::{self, ErrorKind, Read},
     time::{Duration, Instant},
 };
 
 pub(crate) use client::*;
 pub(crate) use server::*;
 
+/// zstd level of the compressed bootstrap messages
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// Compresses a serialized message with `codec`, and prefixes it with the codec id
+fn compress_message(
+    codec: BootstrapCompression,
+    msg_bytes: Vec<u8>,
+) -> Result<Vec<u8>, BootstrapError> {
+    match codec {
+        BootstrapCompression::None => Ok([&[u8::from(codec)][..], &msg_bytes].concat()),
+        BootstrapCompression::Zstd => {
+            let compressed =
+                zstd::bulk::compress(&msg_bytes, ZSTD_COMPRESSION_LEVEL).map_err(|e| {
+                    BootstrapError::GeneralError(format!(
+                        "could not compress bootstrap message: {}",
+                        e
+                    ))
+                })?;
+            Ok([&[u8::from(codec)][..], &compressed].concat())
+        }
+    }
+}
+
+/// Reads the codec id prefixing a received message and decompresses it.
+///
+/// Decompressed messages are bounded by `MAX_BOOTSTRAP_MESSAGE_SIZE` like uncompressed ones,
+/// so that the bounds of the message deserializer still apply to what was actually sent.
+fn decompress_message(
+    payload: &[u8],
+    accepted_codec: BootstrapCompression,
+) -> Result<Cow<'_, [u8]>, BootstrapError> {
+    let (codec, data) = payload
+        .split_first()
+        .ok_or_else(|| BootstrapError::DeserializeError("empty bootstrap message".to_string()))?;
+    let codec = BootstrapCompression::try_from(*codec).map_err(|_| {
+        BootstrapError::DeserializeError(format!("unknown compression codec {}", codec))
+    })?;
+    match codec {
+        BootstrapCompression::None => Ok(Cow::Borrowed(data)),
+        BootstrapCompression::Zstd if accepted_codec == BootstrapCompression::Zstd => {
+            let mut decompressed = Vec::new();
+            zstd::stream::read::Decoder::new(data)
+                .and_then(|decoder| {
+                    decoder
+                        .take(u64::from(MAX_BOOTSTRAP_MESSAGE_SIZE) + 1)
+                        .read_to_end(&mut decompressed)
+                })
+                .map_err(|e| {
+                    BootstrapError::DeserializeError(format!(
+                        "could not decompress bootstrap message: {}",
+                        e
+                    ))
+                })?;
+            if decompressed.len() > MAX_BOOTSTRAP_MESSAGE_SIZE as usize {
+                return Err(BootstrapError::DeserializeError(
+                    "decompressed bootstrap message is too large".to_string(),
+                ));
+            }
+            Ok(Cow::Owned(decompressed))
+        }
+        _ => Err(BootstrapError::GeneralError(format!(
+            "bootstrap message compressed with {:?}, which was not negotiated",
+            codec
+        ))),
+    }
+}
+
 trait BindingReadExact: io::Read {
     /// similar to std::io::Read::read_exact, but with a timeout that is function-global instead of per-individual-read
     fn read_exact_timeout(
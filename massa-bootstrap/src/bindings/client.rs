@@ -1,12 +1,12 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::bindings::BindingReadExact;
+use crate::bindings::{decompress_message, BindingReadExact};
 use crate::error::BootstrapError;
 use crate::messages::{
     BootstrapClientMessage, BootstrapClientMessageSerializer, BootstrapServerMessage,
     BootstrapServerMessageDeserializer,
 };
-use crate::settings::BootstrapClientConfig;
+use crate::settings::{BootstrapClientConfig, BootstrapCompression};
 use massa_hash::Hash;
 use massa_models::config::{
     MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES, SIGNATURE_DESER_SIZE,
@@ -16,6 +16,7 @@ use massa_models::version::{Version, VersionSerializer};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::{PublicKey, Signature};
 use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::borrow::Cow;
 use std::time::Instant;
 use std::{io::Write, net::TcpStream, time::Duration};
 
@@ -26,6 +27,9 @@ pub struct BootstrapClientBinder {
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
     cfg: BootstrapClientConfig,
+    /// Compression asked for with `BootstrapClientMessage::AskCompression`, if it was.
+    /// Once asked, every server message is prefixed by the codec it is compressed with.
+    compression: Option<BootstrapCompression>,
 }
 
 const KNOWN_PREFIX_LEN: usize = SIGNATURE_DESER_SIZE + MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES;
@@ -49,6 +53,7 @@ impl BootstrapClientBinder {
             prev_message: None,
             version_serializer: VersionSerializer::new(),
             cfg,
+            compression: None,
         }
     }
 
@@ -110,9 +115,15 @@ impl BootstrapClientBinder {
                 let msg_hash = Hash::compute_from(rehash_seed);
                 self.remote_pubkey.verify_signature(&msg_hash, &sig)?;
 
+                // ...Decompress (only once the signature is verified)...
+                let msg_bytes = match self.compression {
+                    Some(codec) => decompress_message(msg_bytes, codec)?,
+                    None => Cow::Borrowed(&msg_bytes[..]),
+                };
+
                 // ...And deserialize
                 let (_, msg) = message_deserializer
-                    .deserialize::<DeserializeError>(msg_bytes)
+                    .deserialize::<DeserializeError>(&msg_bytes)
                     .map_err(|err| BootstrapError::DeserializeError(format!("{}", err)))?;
                 msg
             } else {
@@ -128,9 +139,15 @@ impl BootstrapClientBinder {
                 let msg_hash = Hash::compute_from(sig_msg_bytes);
                 self.remote_pubkey.verify_signature(&msg_hash, &sig)?;
 
+                // ...Decompress (only once the signature is verified)...
+                let sig_msg_bytes = match self.compression {
+                    Some(codec) => decompress_message(sig_msg_bytes, codec)?,
+                    None => Cow::Borrowed(&sig_msg_bytes[..]),
+                };
+
                 // ...And deserialize
                 let (_, msg) = message_deserializer
-                    .deserialize::<DeserializeError>(sig_msg_bytes)
+                    .deserialize::<DeserializeError>(&sig_msg_bytes)
                     .map_err(|err| BootstrapError::DeserializeError(format!("{}", err)))?;
                 msg
             }
@@ -148,6 +165,10 @@ impl BootstrapClientBinder {
         let mut msg_bytes = Vec::new();
         let message_serializer = BootstrapClientMessageSerializer::new();
         message_serializer.serialize(msg, &mut msg_bytes)?;
+        if let BootstrapClientMessage::AskCompression { codec } = msg {
+            // the server answers the messages that follow with a codec prefix
+            self.compression = Some(*codec);
+        }
         let msg_len: u32 = msg_bytes.len().try_into().map_err(|e| {
             BootstrapError::GeneralError(format!("bootstrap message too large to encode: {}", e))
         })?;
@@ -1,12 +1,13 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::bindings::BindingReadExact;
+use crate::bindings::{compress_message, BindingReadExact};
 use crate::error::BootstrapError;
 use crate::messages::{
     BootstrapClientMessage, BootstrapClientMessageDeserializer, BootstrapServerMessage,
     BootstrapServerMessageSerializer,
 };
-use crate::settings::BootstrapSrvBindCfg;
+use crate::server::SessionBandwidth;
+use crate::settings::{BootstrapCompression, BootstrapSrvBindCfg};
 use massa_hash::Hash;
 use massa_hash::HASH_SIZE_BYTES;
 use massa_models::config::{MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE_BYTES};
@@ -40,12 +41,18 @@ pub struct BootstrapServerBinder {
     max_datastore_key_length: u8,
     randomness_size_bytes: usize,
     local_keypair: KeyPair,
-    // TODO: Reintroduce bandwidth limits
+    /// Paces the sent messages to the bandwidth of the session, once it started
+    bandwidth: Option<SessionBandwidth>,
     duplex: TcpStream,
     prev_message: Option<Hash>,
     version_serializer: VersionSerializer,
     version_deserializer: VersionDeserializer,
     write_error_timeout: MassaTime,
+    /// Compression allowed by the server config
+    compression: BootstrapCompression,
+    /// Compression of the state parts negotiated with `BootstrapClientMessage::AskCompression`,
+    /// if the client asked for it. Once negotiated, every message is prefixed by its codec.
+    negotiated_compression: Option<BootstrapCompression>,
 }
 
 impl BootstrapServerBinder {
@@ -54,12 +61,12 @@ impl BootstrapServerBinder {
     /// # Argument
     /// * `duplex`: duplex stream.
     /// * `local_keypair`: local node user keypair
-    /// * `limit`: limit max bytes per second (up and down)
+    /// * `cfg`: binder config. The bandwidth is limited by the bootstrap server scheduler, see `set_bandwidth_limiter`
     #[allow(clippy::too_many_arguments)]
     pub fn new(duplex: TcpStream, local_keypair: KeyPair, cfg: BootstrapSrvBindCfg) -> Self {
         let BootstrapSrvBindCfg {
-            // TODO: Reintroduce bandwidth limits
-            max_bytes_read_write: _limit,
+            max_bytes_read_write: _,
+            compression,
             thread_count,
             max_datastore_key_length,
             randomness_size_bytes,
@@ -69,6 +76,7 @@ impl BootstrapServerBinder {
         BootstrapServerBinder {
             max_consensus_block_ids: consensus_bootstrap_part_size,
             local_keypair,
+            bandwidth: None,
             duplex,
             prev_message: None,
             thread_count,
@@ -77,8 +85,15 @@ impl BootstrapServerBinder {
             version_serializer: VersionSerializer::new(),
             version_deserializer: VersionDeserializer::new(),
             write_error_timeout,
+            compression,
+            negotiated_compression: None,
         }
     }
+    /// Paces the messages sent from now on to the bandwidth of a session
+    pub(crate) fn set_bandwidth_limiter(&mut self, bandwidth: SessionBandwidth) {
+        self.bandwidth = Some(bandwidth);
+    }
+
     /// Performs a handshake. Should be called after connection
     /// MUST always be followed by a send of the `BootstrapMessage::BootstrapTime`
     pub fn handshake_timeout(
@@ -185,6 +200,14 @@ impl BootstrapServerBinder {
         // serialize the message to bytes
         let mut msg_bytes = Vec::new();
         BootstrapServerMessageSerializer::new().serialize(&msg, &mut msg_bytes)?;
+        if let Some(codec) = self.negotiated_compression {
+            // only the state parts are worth compressing
+            let codec = match msg {
+                BootstrapServerMessage::BootstrapPart { .. } => codec,
+                _ => BootstrapCompression::None,
+            };
+            msg_bytes = compress_message(codec, msg_bytes)?;
+        }
         let msg_len: u32 = msg_bytes.len().try_into().map_err(|e| {
             BootstrapError::GeneralError(format!("bootstrap message too large to encode: {}", e))
         })?;
@@ -211,6 +234,9 @@ impl BootstrapServerBinder {
         let stream_data = [sig.to_bytes().as_slice(), &msg_len_bytes, &msg_bytes].concat();
 
         // send the data
+        if let Some(bandwidth) = &self.bandwidth {
+            bandwidth.wait_to_send(stream_data.len());
+        }
         self.duplex.set_write_timeout(duration)?;
         self.duplex.write_all(&stream_data)?;
 
@@ -272,6 +298,19 @@ impl BootstrapServerBinder {
         .deserialize::<DeserializeError>(&msg_bytes)
         .map_err(|err| BootstrapError::GeneralError(format!("{}", err)))?;
 
+        if let BootstrapClientMessage::AskCompression { codec } = msg {
+            // from now on, the client expects our messages to be prefixed by their codec
+            self.negotiated_compression = Some(
+                if codec == BootstrapCompression::Zstd
+                    && self.compression == BootstrapCompression::Zstd
+                {
+                    BootstrapCompression::Zstd
+                } else {
+                    BootstrapCompression::None
+                },
+            );
+        }
+
         Ok(msg)
     }
 
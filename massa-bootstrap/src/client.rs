@@ -1,8 +1,12 @@
 use humantime::format_duration;
-use massa_db::{DBBatch, CHANGE_ID_DESER_ERROR};
+use massa_consensus_exports::bootstrapable_graph::BootstrapableGraph;
+use massa_db::{DBBatch, StreamBatch, CHANGE_ID_DESER_ERROR};
 use massa_final_state::{FinalState, FinalStateError};
 use massa_logging::massa_trace;
-use massa_models::{node::NodeId, slot::Slot, streaming_step::StreamingStep, version::Version};
+use massa_models::{
+    block_id::BlockId, node::NodeId, prehash::PreHashSet, slot::Slot,
+    streaming_step::StreamingStep, version::Version,
+};
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use massa_versioning::versioning::{ComponentStateTypeId, MipInfo, MipState, StateAtError};
@@ -25,7 +29,8 @@ use crate::{
     bindings::BootstrapClientBinder,
     error::BootstrapError,
     messages::{BootstrapClientMessage, BootstrapServerMessage},
-    settings::IpType,
+    parallel::get_state_ranges,
+    settings::{BootstrapCompression, IpType},
     BootstrapConfig, GlobalBootstrapState,
 };
 
@@ -87,59 +92,17 @@ fn stream_final_state_and_consensus(
                     last_start_period,
                     last_slot_before_downtime,
                 } => {
-                    // Set final state
-                    let mut write_final_state = global_bootstrap_state.final_state.write();
-
-                    // We only need to receive the initial_state once
-                    if let Some(last_start_period) = last_start_period {
-                        write_final_state.last_start_period = last_start_period;
-                    }
-                    if let Some(last_slot_before_downtime) = last_slot_before_downtime {
-                        write_final_state.last_slot_before_downtime = last_slot_before_downtime;
-                    }
-
-                    let (last_state_step, last_versioning_step) = write_final_state
-                        .db
-                        .write()
-                        .write_batch_bootstrap_client(state_part, versioning_part)
-                        .map_err(|e| {
-                            BootstrapError::GeneralError(format!(
-                                "Cannot write received stream batch to disk: {}",
-                                e
-                            ))
-                        })?;
-
-                    // Set consensus blocks
-                    if let Some(graph) = global_bootstrap_state.graph.as_mut() {
-                        // Extend the final blocks with the received part
-                        graph.final_blocks.extend(consensus_part.final_blocks);
-                        // Remove every outdated block
-                        graph.final_blocks.retain(|block_export| {
-                            !consensus_outdated_ids.contains(&block_export.block.id)
-                        });
-                    } else {
-                        global_bootstrap_state.graph = Some(consensus_part);
-                    }
-                    let last_consensus_step = StreamingStep::Ongoing(
-                        // Note that this unwrap call is safe because of the above conditional statement
-                        global_bootstrap_state
-                            .graph
-                            .as_ref()
-                            .unwrap()
-                            .final_blocks
-                            .iter()
-                            .map(|b_export| b_export.block.id)
-                            .collect(),
-                    );
-
                     // Set new message in case of disconnection
-                    *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
-                        last_slot: Some(slot),
-                        last_state_step,
-                        last_versioning_step,
-                        last_consensus_step,
-                        send_last_start_period: false,
-                    };
+                    *next_bootstrap_message = apply_bootstrap_part(
+                        global_bootstrap_state,
+                        slot,
+                        state_part,
+                        versioning_part,
+                        consensus_part,
+                        consensus_outdated_ids,
+                        last_start_period,
+                        last_slot_before_downtime,
+                    )?;
 
                     // Logs for an easier diagnostic if needed
                     debug!(
@@ -152,34 +115,7 @@ fn stream_final_state_and_consensus(
                     // Set next bootstrap message
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPeers;
 
-                    // Update MIP store by reading from the disk
-                    let mut guard = global_bootstrap_state.final_state.write();
-                    let db = guard.db.clone();
-                    let (updated, added) = guard
-                        .mip_store
-                        .extend_from_db(db)
-                        .map_err(|e| BootstrapError::from(FinalStateError::from(e)))?;
-
-                    warn_user_about_versioning_updates(updated, added);
-
-                    // Compute the db hash
-                    info!("Computing the db hash");
-                    let slot = guard
-                        .db
-                        .read()
-                        .get_change_id()
-                        .expect(CHANGE_ID_DESER_ERROR);
-                    let only_use_xor = guard.get_only_use_xor(&slot);
-                    guard
-                        .db
-                        .write()
-                        .recompute_db_hash(only_use_xor)
-                        .map_err(|e| {
-                            BootstrapError::from(FinalStateError::LedgerError(format!(
-                                "Can't recompute hashes: {}",
-                                e
-                            )))
-                        })?;
+                    finish_state_bootstrap(global_bootstrap_state)?;
 
                     return Ok(());
                 }
@@ -215,6 +151,113 @@ fn stream_final_state_and_consensus(
     }
 }
 
+/// Writes a part of the final state and of the consensus graph,
+/// received from a bootstrap server or read from a snapshot.
+///
+/// Returns the message asking for the next part.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_bootstrap_part(
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    slot: Slot,
+    state_part: StreamBatch<Slot>,
+    versioning_part: StreamBatch<Slot>,
+    consensus_part: BootstrapableGraph,
+    consensus_outdated_ids: PreHashSet<BlockId>,
+    last_start_period: Option<u64>,
+    last_slot_before_downtime: Option<Option<Slot>>,
+) -> Result<BootstrapClientMessage, BootstrapError> {
+    // Set final state
+    let mut write_final_state = global_bootstrap_state.final_state.write();
+
+    // We only need to receive the initial_state once
+    if let Some(last_start_period) = last_start_period {
+        write_final_state.last_start_period = last_start_period;
+    }
+    if let Some(last_slot_before_downtime) = last_slot_before_downtime {
+        write_final_state.last_slot_before_downtime = last_slot_before_downtime;
+    }
+
+    let (last_state_step, last_versioning_step) = write_final_state
+        .db
+        .write()
+        .write_batch_bootstrap_client(state_part, versioning_part)
+        .map_err(|e| {
+            BootstrapError::GeneralError(format!(
+                "Cannot write received stream batch to disk: {}",
+                e
+            ))
+        })?;
+
+    // Set consensus blocks
+    if let Some(graph) = global_bootstrap_state.graph.as_mut() {
+        // Extend the final blocks with the received part
+        graph.final_blocks.extend(consensus_part.final_blocks);
+        // Remove every outdated block
+        graph
+            .final_blocks
+            .retain(|block_export| !consensus_outdated_ids.contains(&block_export.block.id));
+    } else {
+        global_bootstrap_state.graph = Some(consensus_part);
+    }
+    let last_consensus_step = StreamingStep::Ongoing(
+        // Note that this unwrap call is safe because of the above conditional statement
+        global_bootstrap_state
+            .graph
+            .as_ref()
+            .unwrap()
+            .final_blocks
+            .iter()
+            .map(|b_export| b_export.block.id)
+            .collect(),
+    );
+
+    Ok(BootstrapClientMessage::AskBootstrapPart {
+        last_slot: Some(slot),
+        last_state_step,
+        last_versioning_step,
+        last_consensus_step,
+        send_last_start_period: false,
+    })
+}
+
+/// Once the whole final state is written, loads the MIP store from it and recomputes the db hash
+pub(crate) fn finish_state_bootstrap(
+    global_bootstrap_state: &mut GlobalBootstrapState,
+) -> Result<(), BootstrapError> {
+    // Update MIP store by reading from the disk
+    let mut guard = global_bootstrap_state.final_state.write();
+    let db = guard.db.clone();
+    let (updated, added) = guard
+        .mip_store
+        .extend_from_db(db)
+        .map_err(|e| BootstrapError::from(FinalStateError::from(e)))?;
+
+    warn_user_about_versioning_updates(updated, added);
+
+    // Compute the db hash
+    info!("Computing the db hash");
+    let slot = guard
+        .db
+        .read()
+        .get_change_id()
+        .expect(CHANGE_ID_DESER_ERROR);
+    let only_use_xor = guard.get_only_use_xor(&slot);
+    guard
+        .db
+        .write()
+        .recompute_db_hash(only_use_xor)
+        .map_err(|e| {
+            BootstrapError::from(FinalStateError::LedgerError(format!(
+                "Can't recompute hashes: {}",
+                e
+            )))
+        })?;
+
+    // the state is complete, there is nothing left to resume
+    guard.db.read().clear_bootstrap_cursors();
+    Ok(())
+}
+
 /// Gets the state from a bootstrap server (internal private function)
 /// needs to be CANCELLABLE
 fn bootstrap_from_server(
@@ -225,22 +268,61 @@ fn bootstrap_from_server(
     our_version: Version,
 ) -> Result<(), BootstrapError> {
     massa_trace!("bootstrap.lib.bootstrap_from_server", {});
+    open_bootstrap_session(cfg, client, our_version)?;
+    ask_bootstrap_data(cfg, client, next_bootstrap_message, global_bootstrap_state)
+}
+
+/// First version (major, minor) of the bootstrap server handling `BootstrapClientMessage::AskCompression`
+const COMPRESSION_MIN_VERSION: (u32, u32) = (23, 3);
 
-    // read error (if sent by the server)
+/// Starts a bootstrap session: reads the error the server may send at connection,
+/// then does the handshake, checks the server clock and version and negotiates the compression
+pub(crate) fn open_bootstrap_session(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    our_version: Version,
+) -> Result<(), BootstrapError> {
+    // read error (if sent by the server), or wait in the server queue until our session starts
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
-    match client.next_timeout(Some(cfg.read_error_timeout.to_duration())) {
-        Err(BootstrapError::TimedOut(_)) => {
-            massa_trace!(
-                "bootstrap.lib.bootstrap_from_server: No error sent at connection",
-                {}
-            );
-        }
-        Err(e) => return Err(e),
-        Ok(BootstrapServerMessage::BootstrapError { error: err }) => {
-            return Err(BootstrapError::ReceivedError(err))
-        }
-        Ok(msg) => return Err(BootstrapError::UnexpectedServerMessage(msg)),
-    };
+    let mut queued = false;
+    loop {
+        let read_timeout = match queued {
+            true => cfg.read_timeout,
+            false => cfg.read_error_timeout,
+        };
+        match client.next_timeout(Some(read_timeout.to_duration())) {
+            Err(BootstrapError::TimedOut(_)) if !queued => {
+                massa_trace!(
+                    "bootstrap.lib.bootstrap_from_server: No error sent at connection",
+                    {}
+                );
+                break;
+            }
+            Err(e) => return Err(e),
+            Ok(BootstrapServerMessage::BootstrapError { error: err }) => {
+                return Err(BootstrapError::ReceivedError(err))
+            }
+            Ok(BootstrapServerMessage::BootstrapQueued { position: 0, .. }) => break,
+            Ok(BootstrapServerMessage::BootstrapQueued {
+                position,
+                estimated_wait,
+            }) => {
+                if estimated_wait > cfg.max_queue_wait {
+                    return Err(BootstrapError::GeneralError(format!(
+                        "estimated wait in the bootstrap server queue is too long: {}",
+                        format_duration(estimated_wait.to_duration())
+                    )));
+                }
+                info!(
+                    "Waiting for a bootstrap session at position {} in the server queue (estimated wait: {})",
+                    position,
+                    format_duration(estimated_wait.to_duration())
+                );
+                queued = true;
+            }
+            Ok(msg) => return Err(BootstrapError::UnexpectedServerMessage(msg)),
+        };
+    }
 
     // handshake
     let send_time_uncompensated = MassaTime::now()?;
@@ -257,7 +339,7 @@ fn bootstrap_from_server(
 
     // First, clock and version.
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
-    let server_time = match client.next_timeout(Some(cfg.read_timeout.into())) {
+    let (server_time, server_version) = match client.next_timeout(Some(cfg.read_timeout.into())) {
         Err(e) => return Err(e),
         Ok(BootstrapServerMessage::BootstrapTime {
             server_time,
@@ -269,7 +351,7 @@ fn bootstrap_from_server(
                     version, our_version
                 )));
             }
-            server_time
+            (server_time, version)
         }
         Ok(BootstrapServerMessage::BootstrapError { error }) => {
             return Err(BootstrapError::ReceivedError(error))
@@ -304,6 +386,29 @@ fn bootstrap_from_server(
         return Err(BootstrapError::ClockError(message));
     }
 
+    // ask for compressed state parts, only to the servers that know the message
+    let (min_major, min_minor) = COMPRESSION_MIN_VERSION;
+    if cfg.compression != BootstrapCompression::None
+        && server_version.is_at_least(min_major, min_minor)
+    {
+        client.send_timeout(
+            &BootstrapClientMessage::AskCompression {
+                codec: cfg.compression,
+            },
+            Some(cfg.write_timeout.into()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Asks the server of an open session for the bootstrap data, from `next_bootstrap_message`
+/// until the bootstrap succeeds
+fn ask_bootstrap_data(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    next_bootstrap_message: &mut BootstrapClientMessage,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+) -> Result<(), BootstrapError> {
     let write_timeout: std::time::Duration = cfg.write_timeout.into();
     // Loop to ask data to the server depending on the last message we sent
     loop {
@@ -340,6 +445,13 @@ fn bootstrap_from_server(
             BootstrapClientMessage::BootstrapError { error: _ } => {
                 panic!("The next message to send shouldn't be BootstrapError");
             }
+            BootstrapClientMessage::AskBootstrapRange { .. }
+            | BootstrapClientMessage::AskStateRoot { .. } => {
+                panic!("Key ranges are only asked for by the parallel bootstrap");
+            }
+            BootstrapClientMessage::AskCompression { .. } => {
+                panic!("The compression is only asked for when opening the session");
+            }
         };
     }
     info!("Successful bootstrap");
@@ -365,7 +477,7 @@ fn send_client_message(
         })
 }
 
-fn connect_to_server(
+pub(crate) fn connect_to_server(
     connector: &mut impl BSConnector,
     bootstrap_config: &BootstrapConfig,
     addr: &SocketAddr,
@@ -474,16 +586,48 @@ pub fn get_state(
     // we filter the bootstrap list to keep only the ip addresses we are compatible with
     let filtered_bootstrap_list = get_bootstrap_list_iter(bootstrap_config)?;
 
+    let resume_message = get_resume_message(&final_state);
+    let bootstrap_in_parallel = resume_message.is_none() && bootstrap_config.parallel_servers > 1;
     let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskBootstrapPart {
+        resume_message.unwrap_or(BootstrapClientMessage::AskBootstrapPart {
             last_slot: None,
             last_state_step: StreamingStep::Started,
             last_versioning_step: StreamingStep::Started,
             last_consensus_step: StreamingStep::Started,
             send_last_start_period: true,
-        };
+        });
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
 
+    if bootstrap_in_parallel {
+        match get_state_ranges(
+            bootstrap_config,
+            &global_bootstrap_state.final_state,
+            &mut connector,
+            &filtered_bootstrap_list,
+            version,
+            &interupted,
+        ) {
+            Ok(slot) => {
+                // the rest of the bootstrap, from the changes made since the state root was checked
+                next_bootstrap_message = BootstrapClientMessage::AskBootstrapPart {
+                    last_slot: Some(slot),
+                    last_state_step: StreamingStep::Finished(None),
+                    last_versioning_step: StreamingStep::Started,
+                    last_consensus_step: StreamingStep::Started,
+                    send_last_start_period: true,
+                };
+            }
+            Err(e @ BootstrapError::Interupted(_)) => return Err(e),
+            Err(e) => {
+                warn!(
+                    "Error while bootstrapping from several servers at once: {}. Your node will bootstrap from one server at a time.",
+                    e
+                );
+                global_bootstrap_state.final_state.write().reset();
+            }
+        }
+    }
+
     loop {
         // check for interuption
         if *interupted.0.lock().expect("double-lock on interupt-mutex") {
@@ -560,6 +704,24 @@ pub fn get_state(
     }
 }
 
+/// Gets the message resuming an interrupted bootstrap from the cursors saved in the db, if any.
+///
+/// The consensus graph is not saved: it is streamed again from the start.
+fn get_resume_message(final_state: &Arc<RwLock<FinalState>>) -> Option<BootstrapClientMessage> {
+    let final_state = final_state.read();
+    let db = final_state.db.read();
+    let (last_state_step, last_versioning_step) = db.get_bootstrap_cursors()?;
+    let last_slot = db.get_change_id().expect(CHANGE_ID_DESER_ERROR);
+    info!("Resuming the interrupted bootstrap from slot {}", last_slot);
+    Some(BootstrapClientMessage::AskBootstrapPart {
+        last_slot: Some(last_slot),
+        last_state_step,
+        last_versioning_step,
+        last_consensus_step: StreamingStep::Started,
+        send_last_start_period: true,
+    })
+}
+
 fn get_bootstrap_list_iter(
     bootstrap_config: &BootstrapConfig,
 ) -> Result<Vec<(SocketAddr, NodeId)>, BootstrapError> {
@@ -27,8 +27,10 @@ mod error;
 pub use error::BootstrapError;
 mod listener;
 mod messages;
+mod parallel;
 mod server;
 mod settings;
+mod snapshot;
 mod tools;
 
 pub use client::{get_state, DefaultConnector};
@@ -38,8 +40,9 @@ pub use messages::{
     BootstrapServerMessage, BootstrapServerMessageDeserializer, BootstrapServerMessageSerializer,
 };
 pub use server::{start_bootstrap_server, BootstrapManager};
-pub use settings::IpType;
+pub use settings::{BootstrapCompression, IpType};
 pub use settings::{BootstrapConfig, BootstrapServerMessageDeserializerArgs};
+pub use snapshot::{export_snapshot, get_state_from_snapshot};
 
 #[cfg(test)]
 pub(crate) mod tests;
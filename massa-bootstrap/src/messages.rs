@@ -1,10 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::settings::BootstrapServerMessageDeserializerArgs;
+use crate::settings::{BootstrapCompression, BootstrapServerMessageDeserializerArgs};
 use massa_consensus_exports::bootstrapable_graph::{
     BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
 };
 use massa_db::StreamBatch;
+use massa_hash::{Hash, HashDeserializer, HashSerializer};
 use massa_models::block_id::{BlockId, BlockIdDeserializer, BlockIdSerializer};
 use massa_models::prehash::PreHashSet;
 use massa_models::serialization::{
@@ -69,7 +70,8 @@ pub enum BootstrapServerMessage {
         /// Last Slot before downtime for network restart management
         last_slot_before_downtime: Option<Option<Slot>>,
     },
-    /// Message sent when the final state and consensus bootstrap are finished
+    /// Message sent when the final state and consensus bootstrap are finished,
+    /// or when a key range of the state is complete
     BootstrapFinished,
     /// Slot sent to get state changes is too old
     SlotTooOld,
@@ -78,6 +80,23 @@ pub enum BootstrapServerMessage {
         /// Error message
         error: String,
     },
+    /// State root of the server, to check a state assembled from several servers
+    StateRoot {
+        /// Slot of the state the root was computed on
+        slot: Slot,
+        /// Changes of the state since the slot sent by the client
+        state_part: StreamBatch<Slot>,
+        /// Root of the sparse merkle tree of the state
+        root: Hash,
+    },
+    /// The server has no session available: the client waits in its queue.
+    /// Sent again until the session starts, which is announced with a position of 0.
+    BootstrapQueued {
+        /// Position of the client in the queue (0 when its session starts)
+        position: u32,
+        /// Estimated time before the session starts
+        estimated_wait: MassaTime,
+    },
 }
 
 impl ToString for BootstrapServerMessage {
@@ -91,6 +110,8 @@ impl ToString for BootstrapServerMessage {
             BootstrapServerMessage::BootstrapError { error } => {
                 format!("BootstrapError {{ error: {} }}", error)
             }
+            BootstrapServerMessage::StateRoot { .. } => "StateRoot".to_string(),
+            BootstrapServerMessage::BootstrapQueued { .. } => "BootstrapQueued".to_string(),
         }
     }
 }
@@ -104,6 +125,8 @@ enum MessageServerTypeId {
     FinalStateFinished = 3u32,
     SlotTooOld = 4u32,
     BootstrapError = 5u32,
+    StateRoot = 6u32,
+    BootstrapQueued = 7u32,
 }
 
 /// Serializer for `BootstrapServerMessage`
@@ -118,6 +141,7 @@ pub struct BootstrapServerMessageSerializer {
     vec_u8_serializer: VecU8Serializer,
     opt_vec_u8_serializer: OptionSerializer<Vec<u8>, VecU8Serializer>,
     slot_serializer: SlotSerializer,
+    hash_serializer: HashSerializer,
     opt_last_start_period_serializer: OptionSerializer<u64, U64VarIntSerializer>,
     opt_last_slot_before_downtime_serializer:
         OptionSerializer<Option<Slot>, OptionSerializer<Slot, SlotSerializer>>,
@@ -143,6 +167,7 @@ impl BootstrapServerMessageSerializer {
             vec_u8_serializer: VecU8Serializer::new(),
             opt_vec_u8_serializer: OptionSerializer::new(VecU8Serializer::new()),
             slot_serializer: SlotSerializer::new(),
+            hash_serializer: HashSerializer::new(),
             opt_last_start_period_serializer: OptionSerializer::new(U64VarIntSerializer::new()),
             opt_last_slot_before_downtime_serializer: OptionSerializer::new(OptionSerializer::new(
                 SlotSerializer::new(),
@@ -268,6 +293,35 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                 )?;
                 buffer.extend(error.as_bytes())
             }
+            BootstrapServerMessage::StateRoot {
+                slot,
+                state_part,
+                root,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::StateRoot), buffer)?;
+                self.slot_serializer.serialize(slot, buffer)?;
+                self.u64_serializer.serialize(
+                    &(state_part.updates_on_previous_elements.len() as u64),
+                    buffer,
+                )?;
+                for (key, value) in state_part.updates_on_previous_elements.iter() {
+                    self.vec_u8_serializer.serialize(key, buffer)?;
+                    self.opt_vec_u8_serializer.serialize(value, buffer)?;
+                }
+                self.slot_serializer
+                    .serialize(&state_part.change_id, buffer)?;
+                self.hash_serializer.serialize(root, buffer)?;
+            }
+            BootstrapServerMessage::BootstrapQueued {
+                position,
+                estimated_wait,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageServerTypeId::BootstrapQueued), buffer)?;
+                self.u32_serializer.serialize(position, buffer)?;
+                self.time_serializer.serialize(estimated_wait, buffer)?;
+            }
         }
         Ok(())
     }
@@ -277,6 +331,7 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
 pub struct BootstrapServerMessageDeserializer {
     message_id_deserializer: U32VarIntDeserializer,
     time_deserializer: MassaTimeDeserializer,
+    queue_position_deserializer: U32VarIntDeserializer,
     version_deserializer: VersionDeserializer,
     peers_deserializer: BootstrapPeersDeserializer,
     state_new_elements_length_deserializer: U64VarIntDeserializer,
@@ -287,6 +342,7 @@ pub struct BootstrapServerMessageDeserializer {
     block_id_set_deserializer: PreHashSetDeserializer<BlockId, BlockIdDeserializer>,
     length_bootstrap_error: U64VarIntDeserializer,
     slot_deserializer: SlotDeserializer,
+    hash_deserializer: HashDeserializer,
     opt_last_start_period_deserializer: OptionDeserializer<u64, U64VarIntDeserializer>,
     opt_last_slot_before_downtime_deserializer:
         OptionDeserializer<Option<Slot>, OptionDeserializer<Slot, SlotDeserializer>>,
@@ -302,6 +358,10 @@ impl BootstrapServerMessageDeserializer {
                 Included(MassaTime::from_millis(0)),
                 Included(MassaTime::from_millis(u64::MAX)),
             )),
+            queue_position_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(u32::MAX),
+            ),
             version_deserializer: VersionDeserializer::new(),
             peers_deserializer: BootstrapPeersDeserializer::new(
                 args.max_advertise_length,
@@ -340,6 +400,7 @@ impl BootstrapServerMessageDeserializer {
                 (Included(0), Included(u64::MAX)),
                 (Included(0), Excluded(args.thread_count)),
             ),
+            hash_deserializer: HashDeserializer::new(),
             opt_last_start_period_deserializer: OptionDeserializer::new(
                 U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX)),
             ),
@@ -575,6 +636,56 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     error: String::from_utf8_lossy(error).into_owned(),
                 })
                 .parse(input),
+                MessageServerTypeId::StateRoot => tuple((
+                    context("Failed slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context(
+                        "Failed updates deserialization",
+                        length_count(
+                            context("Failed length deserialization", |input| {
+                                self.state_updates_length_deserializer.deserialize(input)
+                            }),
+                            tuple((
+                                |input| self.vec_u8_deserializer.deserialize(input),
+                                |input| self.opt_vec_u8_deserializer.deserialize(input),
+                            )),
+                        ),
+                    ),
+                    context("Failed change_id deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed root deserialization", |input| {
+                        self.hash_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(slot, updates, change_id, root)| BootstrapServerMessage::StateRoot {
+                        slot,
+                        state_part: StreamBatch::<Slot> {
+                            new_elements: Default::default(),
+                            updates_on_previous_elements: updates.into_iter().collect(),
+                            change_id,
+                        },
+                        root,
+                    },
+                )
+                .parse(input),
+                MessageServerTypeId::BootstrapQueued => tuple((
+                    context("Failed position deserialization", |input| {
+                        self.queue_position_deserializer.deserialize(input)
+                    }),
+                    context("Failed estimated_wait deserialization", |input| {
+                        self.time_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(position, estimated_wait)| BootstrapServerMessage::BootstrapQueued {
+                        position,
+                        estimated_wait,
+                    },
+                )
+                .parse(input),
             }
         })
         .parse(buffer)
@@ -607,6 +718,28 @@ pub enum BootstrapClientMessage {
     },
     /// Bootstrap succeed
     BootstrapSuccess,
+    /// Ask for the parts of a key range of the final state only
+    AskBootstrapRange {
+        /// First key of the range
+        range_start: Vec<u8>,
+        /// Key following the range, None if the range goes to the last key
+        range_end: Option<Vec<u8>>,
+        /// Slot we are attached to for changes
+        last_slot: Option<Slot>,
+        /// Last received state key of the range
+        last_state_step: StreamingStep<Vec<u8>>,
+    },
+    /// Ask for the state root of the server
+    AskStateRoot {
+        /// Slot of our copy of the state, to receive its changes since then
+        last_slot: Slot,
+    },
+    /// Ask for the state parts to be compressed, not answered.
+    /// Every server message that follows is prefixed by the codec it is compressed with.
+    AskCompression {
+        /// Codec asked for
+        codec: BootstrapCompression,
+    },
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -616,12 +749,18 @@ enum MessageClientTypeId {
     AskFinalStatePart = 1u32,
     BootstrapError = 2u32,
     BootstrapSuccess = 3u32,
+    AskBootstrapRange = 4u32,
+    AskStateRoot = 5u32,
+    AskCompression = 6u32,
 }
 
 /// Serializer for `BootstrapClientMessage`
 pub struct BootstrapClientMessageSerializer {
     u32_serializer: U32VarIntSerializer,
     slot_serializer: SlotSerializer,
+    opt_slot_serializer: OptionSerializer<Slot, SlotSerializer>,
+    key_serializer: VecU8Serializer,
+    opt_key_serializer: OptionSerializer<Vec<u8>, VecU8Serializer>,
     state_step_serializer: StreamingStepSerializer<Vec<u8>, VecU8Serializer>,
     block_ids_step_serializer: StreamingStepSerializer<
         PreHashSet<BlockId>,
@@ -636,6 +775,9 @@ impl BootstrapClientMessageSerializer {
         Self {
             u32_serializer: U32VarIntSerializer::new(),
             slot_serializer: SlotSerializer::new(),
+            opt_slot_serializer: OptionSerializer::new(SlotSerializer::new()),
+            key_serializer: VecU8Serializer::new(),
+            opt_key_serializer: OptionSerializer::new(VecU8Serializer::new()),
             state_step_serializer: StreamingStepSerializer::new(VecU8Serializer::new()),
             block_ids_step_serializer: StreamingStepSerializer::new(PreHashSetSerializer::new(
                 BlockIdSerializer::new(),
@@ -711,6 +853,31 @@ impl Serializer<BootstrapClientMessage> for BootstrapClientMessageSerializer {
                 self.u32_serializer
                     .serialize(&u32::from(MessageClientTypeId::BootstrapSuccess), buffer)?;
             }
+            BootstrapClientMessage::AskBootstrapRange {
+                range_start,
+                range_end,
+                last_slot,
+                last_state_step,
+            } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskBootstrapRange), buffer)?;
+                self.key_serializer.serialize(range_start, buffer)?;
+                self.opt_key_serializer.serialize(range_end, buffer)?;
+                self.opt_slot_serializer.serialize(last_slot, buffer)?;
+                self.state_step_serializer
+                    .serialize(last_state_step, buffer)?;
+            }
+            BootstrapClientMessage::AskStateRoot { last_slot } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskStateRoot), buffer)?;
+                self.slot_serializer.serialize(last_slot, buffer)?;
+            }
+            BootstrapClientMessage::AskCompression { codec } => {
+                self.u32_serializer
+                    .serialize(&u32::from(MessageClientTypeId::AskCompression), buffer)?;
+                self.u32_serializer
+                    .serialize(&u32::from(u8::from(*codec)), buffer)?;
+            }
         }
         Ok(())
     }
@@ -721,6 +888,9 @@ pub struct BootstrapClientMessageDeserializer {
     id_deserializer: U32VarIntDeserializer,
     length_error_deserializer: U32VarIntDeserializer,
     slot_deserializer: SlotDeserializer,
+    opt_slot_deserializer: OptionDeserializer<Slot, SlotDeserializer>,
+    key_deserializer: VecU8Deserializer,
+    opt_key_deserializer: OptionDeserializer<Vec<u8>, VecU8Deserializer>,
     state_step_deserializer: StreamingStepDeserializer<Vec<u8>, VecU8Deserializer>,
     block_ids_step_deserializer: StreamingStepDeserializer<
         PreHashSet<BlockId>,
@@ -743,6 +913,18 @@ impl BootstrapClientMessageDeserializer {
                 (Included(0), Included(u64::MAX)),
                 (Included(0), Excluded(thread_count)),
             ),
+            opt_slot_deserializer: OptionDeserializer::new(SlotDeserializer::new(
+                (Included(0), Included(u64::MAX)),
+                (Included(0), Excluded(thread_count)),
+            )),
+            key_deserializer: VecU8Deserializer::new(
+                Included(0),
+                Included(max_datastore_value_length as u64),
+            ),
+            opt_key_deserializer: OptionDeserializer::new(VecU8Deserializer::new(
+                Included(0),
+                Included(max_datastore_value_length as u64),
+            )),
             state_step_deserializer: StreamingStepDeserializer::new(VecU8Deserializer::new(
                 Included(0),
                 Included(max_datastore_value_length as u64),
@@ -864,6 +1046,49 @@ impl Deserializer<BootstrapClientMessage> for BootstrapClientMessageDeserializer
                 MessageClientTypeId::BootstrapSuccess => {
                     Ok((input, BootstrapClientMessage::BootstrapSuccess))
                 }
+                MessageClientTypeId::AskBootstrapRange => tuple((
+                    context("Failed range_start deserialization", |input| {
+                        self.key_deserializer.deserialize(input)
+                    }),
+                    context("Failed range_end deserialization", |input| {
+                        self.opt_key_deserializer.deserialize(input)
+                    }),
+                    context("Failed last_slot deserialization", |input| {
+                        self.opt_slot_deserializer.deserialize(input)
+                    }),
+                    context("Failed last_state_step deserialization", |input| {
+                        self.state_step_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(|(range_start, range_end, last_slot, last_state_step)| {
+                    BootstrapClientMessage::AskBootstrapRange {
+                        range_start,
+                        range_end,
+                        last_slot,
+                        last_state_step,
+                    }
+                })
+                .parse(input),
+                MessageClientTypeId::AskStateRoot => {
+                    context("Failed last_slot deserialization", |input| {
+                        self.slot_deserializer.deserialize(input)
+                    })
+                    .map(|last_slot| BootstrapClientMessage::AskStateRoot { last_slot })
+                    .parse(input)
+                }
+                MessageClientTypeId::AskCompression => {
+                    context("Failed codec deserialization", |input| {
+                        self.id_deserializer.deserialize(input)
+                    })
+                    .map(|codec| BootstrapClientMessage::AskCompression {
+                        // the codecs we don't know are answered uncompressed
+                        codec: u8::try_from(codec)
+                            .ok()
+                            .and_then(|codec| BootstrapCompression::try_from(codec).ok())
+                            .unwrap_or(BootstrapCompression::None),
+                    })
+                    .parse(input)
+                }
             }
         })
         .parse(buffer)
@@ -0,0 +1,545 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Bootstrap of the final state from several servers at once.
+//!
+//! The final state is split into disjoint key ranges, streamed from up to `parallel_servers` servers
+//! at the same time, with one session per server.
+//!
+//! A server whose session fails or times out is replaced by another server of the bootstrap list,
+//! and its ranges resume from their cursors. A range whose changes are too old for the server
+//! is streamed again from scratch.
+//!
+//! Once every range is streamed, the state assembled from the ranges is brought to the slot of the last server,
+//! and checked against its state root: the root of the sparse merkle tree of the state
+//! (see `massa_db::compute_state_root_from_snapshot`). As in a regular bootstrap, that server is trusted
+//! for the whole state. Every range is brought to the same slot before the check, so ranges streamed at
+//! different slots do not cause a mismatch.
+//!
+//! On a mismatch, the ranges streamed by one of the other servers are streamed again from the trusted server,
+//! and the state is checked again, one server at a time: the server whose ranges fix the mismatch is the one that lied.
+//! Only when the ranges of the trusted server itself do not match its state root
+//! is the whole state bootstrapped again from one server at a time.
+//!
+//! The rest of the bootstrap (the changes made since then, the MIP store, the consensus graph and the peers)
+//! is then asked as in a regular bootstrap.
+
+use crate::{
+    bindings::BootstrapClientBinder,
+    client::{connect_to_server, open_bootstrap_session, BSConnector},
+    error::BootstrapError,
+    messages::{BootstrapClientMessage, BootstrapServerMessage},
+    BootstrapConfig,
+};
+use massa_db::{compute_state_root_from_snapshot, is_key_in_range, StreamBatch, LEDGER_PREFIX};
+use massa_final_state::FinalState;
+use massa_hash::Hash;
+use massa_ledger_exports::{Key, KeySerializer, KeyType};
+use massa_models::{
+    address::{Address, UserAddress, UserAddressV0},
+    node::NodeId,
+    slot::Slot,
+    streaming_step::StreamingStep,
+    version::Version,
+};
+use massa_serialization::Serializer;
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{HashSet, VecDeque},
+    net::SocketAddr,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Condvar,
+    },
+    thread,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// Number of key ranges per parallel server, so that the faster servers end up streaming more ranges
+const RANGES_PER_SERVER: usize = 4;
+
+/// Interval at which the interruption of the bootstrap is checked while waiting for the sessions
+const INTERRUPTION_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Splits the state into `count` key ranges (at least 2), given by their first key.
+///
+/// The first range holds every key before the ledger (async pool, PoS state, executed operations...).
+/// The others split the ledger on the first byte of the hash of the user addresses, which is evenly spread:
+/// a range starts at the balance key of the lowest user address with a given first byte.
+/// The other ledger entries, and the keys after the ledger, end up in the last range.
+pub(crate) fn split_state(count: usize) -> Vec<Vec<u8>> {
+    let ledger_parts = count.clamp(2, 257) - 1;
+    let key_serializer = KeySerializer::new(false);
+    let mut range_starts = vec![Vec::new(), LEDGER_PREFIX.as_bytes().to_vec()];
+    for part in 1..ledger_parts {
+        let mut hash_bytes = [0u8; 32];
+        hash_bytes[0] = (part * 256 / ledger_parts) as u8;
+        let address = Address::User(UserAddress::UserAddressV0(UserAddressV0(Hash::from_bytes(
+            &hash_bytes,
+        ))));
+        let mut range_start = Vec::new();
+        key_serializer
+            .serialize(&Key::new(&address, KeyType::BALANCE), &mut range_start)
+            .expect("ledger key serialization should not fail");
+        range_starts.push(range_start);
+    }
+    range_starts
+}
+
+/// Progress of a key range of the state
+pub(crate) struct StateRange {
+    /// First key of the range
+    pub(crate) start: Vec<u8>,
+    /// Key following the range, None for the last range
+    pub(crate) end: Option<Vec<u8>>,
+    /// Streaming cursor of the range
+    pub(crate) step: StreamingStep<Vec<u8>>,
+    /// Slot of the last received part of the range
+    pub(crate) slot: Option<Slot>,
+    /// Servers that sent parts of the range
+    pub(crate) servers: HashSet<SocketAddr>,
+}
+
+/// Progress of the parallel bootstrap, shared by the sessions
+pub(crate) struct ParallelProgress {
+    pub(crate) ranges: Vec<StateRange>,
+    /// Indexes of the ranges waiting for a session to be streamed
+    pub(crate) tasks: VecDeque<usize>,
+    /// Set to stop the sessions after their current task
+    aborted: bool,
+}
+
+impl ParallelProgress {
+    /// Progress of a bootstrap of the ranges starting at `range_starts`, none of them being streamed yet
+    pub(crate) fn new(range_starts: &[Vec<u8>]) -> Self {
+        ParallelProgress {
+            ranges: range_starts
+                .iter()
+                .enumerate()
+                .map(|(index, start)| StateRange {
+                    start: start.clone(),
+                    end: range_starts.get(index + 1).cloned(),
+                    step: StreamingStep::Started,
+                    slot: None,
+                    servers: HashSet::new(),
+                })
+                .collect(),
+            tasks: (0..range_starts.len()).collect(),
+            aborted: false,
+        }
+    }
+
+    /// Whether there are tasks waiting for a session
+    fn needs_sessions(&self) -> bool {
+        !self.aborted && !self.tasks.is_empty()
+    }
+
+    /// Whether every range is completely streamed
+    pub(crate) fn is_complete(&self) -> bool {
+        self.ranges.iter().all(|range| range.step.finished())
+    }
+
+    /// Slot the state is attached to: the slot of the least recent range
+    fn slot(&self) -> Option<Slot> {
+        self.ranges.iter().filter_map(|range| range.slot).min()
+    }
+
+    /// Deletes our copy of a range, and queues it to be streamed again from scratch
+    pub(crate) fn restart_range(
+        &mut self,
+        index: usize,
+        final_state: &Arc<RwLock<FinalState>>,
+    ) -> Result<(), BootstrapError> {
+        let range = &mut self.ranges[index];
+        final_state
+            .read()
+            .db
+            .write()
+            .delete_range(&range.start, range.end.as_deref())
+            .map_err(|e| {
+                BootstrapError::GeneralError(format!("Cannot delete a range of the state: {}", e))
+            })?;
+        range.step = StreamingStep::Started;
+        range.slot = None;
+        range.servers.clear();
+        self.tasks.push_back(index);
+        Ok(())
+    }
+}
+
+/// Checks that a received part only holds keys of the asked range
+fn check_part_keys(
+    state_part: &StreamBatch<Slot>,
+    range_start: &[u8],
+    range_end: Option<&[u8]>,
+) -> Result<(), BootstrapError> {
+    let in_range = |key: &Vec<u8>| is_key_in_range(key, range_start, range_end);
+    if state_part.new_elements.keys().all(in_range)
+        && state_part.updates_on_previous_elements.keys().all(in_range)
+    {
+        Ok(())
+    } else {
+        Err(BootstrapError::GeneralError(
+            "received state keys out of the asked range".to_string(),
+        ))
+    }
+}
+
+/// Streams a key range of the state from the server of a session, from its cursor until it is complete
+fn stream_range(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    final_state: &Arc<RwLock<FinalState>>,
+    progress: &Mutex<ParallelProgress>,
+    addr: SocketAddr,
+    index: usize,
+) -> Result<(), BootstrapError> {
+    let (range_start, range_end, last_slot, last_state_step) = {
+        let progress = progress.lock();
+        let range = &progress.ranges[index];
+        (
+            range.start.clone(),
+            range.end.clone(),
+            range.slot,
+            range.step.clone(),
+        )
+    };
+    client.send_timeout(
+        &BootstrapClientMessage::AskBootstrapRange {
+            range_start: range_start.clone(),
+            range_end: range_end.clone(),
+            last_slot,
+            last_state_step,
+        },
+        Some(cfg.write_timeout.into()),
+    )?;
+
+    loop {
+        match client.next_timeout(Some(cfg.read_timeout.into()))? {
+            BootstrapServerMessage::BootstrapPart {
+                slot, state_part, ..
+            } => {
+                check_part_keys(&state_part, &range_start, range_end.as_deref())?;
+                let step = final_state
+                    .read()
+                    .db
+                    .write()
+                    .write_range_batch_bootstrap_client(state_part)
+                    .map_err(|e| {
+                        BootstrapError::GeneralError(format!(
+                            "Cannot write received stream batch to disk: {}",
+                            e
+                        ))
+                    })?;
+                let mut progress = progress.lock();
+                let range = &mut progress.ranges[index];
+                range.slot = Some(slot);
+                range.step = step;
+                range.servers.insert(addr);
+            }
+            BootstrapServerMessage::BootstrapFinished => {
+                progress.lock().ranges[index].step = StreamingStep::Finished(None);
+                return Ok(());
+            }
+            BootstrapServerMessage::SlotTooOld => {
+                // the server no longer has the changes of the range since its last slot
+                return progress.lock().restart_range(index, final_state);
+            }
+            BootstrapServerMessage::BootstrapError { error } => {
+                return Err(BootstrapError::ReceivedError(error))
+            }
+            other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+        }
+    }
+}
+
+/// Brings the state assembled from the ranges to the slot of the server of a session,
+/// and checks it against the state root of the server.
+///
+/// The session is kept open to stream ranges again on a mismatch:
+/// computing the root of our copy has to fit in the read timeout of the server.
+/// Returns the slot the state is attached to, and whether it matches the state root.
+fn check_state_root(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    final_state: &Arc<RwLock<FinalState>>,
+    last_slot: Slot,
+) -> Result<(Slot, bool), BootstrapError> {
+    client.send_timeout(
+        &BootstrapClientMessage::AskStateRoot { last_slot },
+        Some(cfg.write_timeout.into()),
+    )?;
+    let (slot, root) = match client.next_timeout(Some(cfg.read_timeout.into()))? {
+        BootstrapServerMessage::StateRoot {
+            slot,
+            state_part,
+            root,
+        } => {
+            let final_state = final_state.read();
+            let mut db = final_state.db.write();
+            db.write_range_batch_bootstrap_client(state_part)
+                .map_err(|e| {
+                    BootstrapError::GeneralError(format!(
+                        "Cannot write received stream batch to disk: {}",
+                        e
+                    ))
+                })?;
+            db.set_initial_change_id(slot);
+            (slot, root)
+        }
+        BootstrapServerMessage::SlotTooOld => return Err(BootstrapError::GeneralError(
+            "the changes of the state since the ranges were streamed are too old for the server"
+                .to_string(),
+        )),
+        BootstrapServerMessage::BootstrapError { error } => {
+            return Err(BootstrapError::ReceivedError(error))
+        }
+        other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+    };
+
+    let rocks_db = final_state.read().db.read().db.clone();
+    let local_root = compute_state_root_from_snapshot(&rocks_db, &rocks_db.snapshot());
+    Ok((slot, local_root == root))
+}
+
+/// Streams the ranges waiting for a session with the server of a session, until there are none left.
+///
+/// Returns the session if it is still open, to check the state root.
+fn run_session(
+    cfg: &BootstrapConfig,
+    final_state: &Arc<RwLock<FinalState>>,
+    progress: &Mutex<ParallelProgress>,
+    addr: SocketAddr,
+    mut client: BootstrapClientBinder,
+) -> Option<BootstrapClientBinder> {
+    loop {
+        let index = {
+            let mut progress = progress.lock();
+            if progress.aborted {
+                return None;
+            }
+            progress.tasks.pop_front()
+        };
+        let Some(index) = index else {
+            return Some(client);
+        };
+        if let Err(err) = stream_range(cfg, &mut client, final_state, progress, addr, index) {
+            warn!(
+                "Error while bootstrapping a range of the state from {}: {}",
+                addr, err
+            );
+            // another server takes over the range, from its cursor
+            progress.lock().tasks.push_front(index);
+            // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
+            let _ = client.send_timeout(
+                &BootstrapClientMessage::BootstrapError {
+                    error: err.to_string(),
+                },
+                Some(cfg.write_error_timeout.into()),
+            );
+            return None;
+        }
+    }
+}
+
+/// Connects to a server and opens a bootstrap session, None if it fails
+fn open_session(
+    cfg: &BootstrapConfig,
+    connector: &mut impl BSConnector,
+    addr: &SocketAddr,
+    node_id: &NodeId,
+    version: Version,
+) -> Option<BootstrapClientBinder> {
+    let mut client = match connect_to_server(connector, cfg, addr, &node_id.get_public_key()) {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Error while connecting to bootstrap server: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = open_bootstrap_session(cfg, &mut client, version) {
+        warn!("Error while bootstrapping from {}: {}", addr, e);
+        return None;
+    }
+    Some(client)
+}
+
+/// Streams the ranges of the final state from several servers at once, and checks the assembled state
+/// against the state root of a server, see the module documentation.
+///
+/// Returns the slot the checked state is attached to, to ask for the rest of the bootstrap.
+pub(crate) fn get_state_ranges(
+    cfg: &BootstrapConfig,
+    final_state: &Arc<RwLock<FinalState>>,
+    connector: &mut impl BSConnector,
+    bootstrap_list: &[(SocketAddr, NodeId)],
+    version: Version,
+    interupted: &(std::sync::Mutex<bool>, Condvar),
+) -> Result<Slot, BootstrapError> {
+    let range_starts = split_state(cfg.parallel_servers * RANGES_PER_SERVER);
+    let progress = Mutex::new(ParallelProgress::new(&range_starts));
+    info!(
+        "Bootstrapping the {} ranges of the state from {} servers at once",
+        range_starts.len(),
+        cfg.parallel_servers
+    );
+
+    let mut servers = bootstrap_list.iter();
+    let mut last_session: Option<(SocketAddr, BootstrapClientBinder)> = None;
+    thread::scope(|scope| {
+        let (session_end_sender, session_end_receiver) = mpsc::channel();
+        let mut active_sessions = 0;
+        loop {
+            // open sessions with new servers while there are ranges waiting for them
+            while active_sessions < cfg.parallel_servers && progress.lock().needs_sessions() {
+                let Some((addr, node_id)) = servers.next() else {
+                    break;
+                };
+                info!("Start bootstrapping ranges of the state from {}", addr);
+                let Some(client) = open_session(cfg, &mut *connector, addr, node_id, version) else {
+                    continue;
+                };
+                let addr = *addr;
+                let session_end_sender = session_end_sender.clone();
+                let progress = &progress;
+                scope.spawn(move || {
+                    let session = run_session(cfg, final_state, progress, addr, client)
+                        .map(|client| (addr, client));
+                    // the receiver lives until every session has ended
+                    let _ = session_end_sender.send(session);
+                });
+                active_sessions += 1;
+            }
+
+            if active_sessions == 0 {
+                let progress = progress.lock();
+                return if progress.aborted {
+                    Err(BootstrapError::Interupted(
+                        "Sig INT received while getting state".to_string(),
+                    ))
+                } else if progress.is_complete() {
+                    Ok(())
+                } else {
+                    Err(BootstrapError::GeneralError(
+                        "no bootstrap server left to stream the state from".to_string(),
+                    ))
+                };
+            }
+
+            // wait for a session to end
+            match session_end_receiver.recv_timeout(INTERRUPTION_CHECK_INTERVAL) {
+                Ok(session) => {
+                    active_sessions -= 1;
+                    if let Some(session) = session {
+                        // keep the most recent session open, it is the least likely to time out
+                        if let Some((_, mut previous)) = last_session.replace(session) {
+                            let _ = previous.send_timeout(
+                                &BootstrapClientMessage::BootstrapSuccess,
+                                Some(cfg.write_error_timeout.into()),
+                            );
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if *interupted.0.lock().expect("double-lock on interupt-mutex") {
+                        progress.lock().aborted = true;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    unreachable!("a sender is kept by the scope")
+                }
+            }
+        }
+    })?;
+
+    let mut last_slot = progress
+        .lock()
+        .slot()
+        .ok_or_else(|| BootstrapError::GeneralError("no range of the state".to_string()))?;
+    // attach the state to the least recent range: the changes since then are streamed with the state root
+    final_state
+        .read()
+        .db
+        .read()
+        .set_initial_change_id(last_slot);
+
+    let (checker, mut client) = match last_session {
+        Some(session) => session,
+        None => servers
+            .find_map(|(addr, node_id)| {
+                open_session(cfg, &mut *connector, addr, node_id, version)
+                    .map(|client| (*addr, client))
+            })
+            .ok_or_else(|| {
+                BootstrapError::GeneralError(
+                    "no bootstrap server left to check the state root with".to_string(),
+                )
+            })?,
+    };
+    let mut last_suspect = None;
+    let slot = loop {
+        let (slot, matches) = check_state_root(cfg, &mut client, final_state, last_slot)?;
+        if matches {
+            if let Some(suspect) = last_suspect {
+                warn!(
+                    "Bootstrap server {} sent ranges of the state that do not match the state root of {}",
+                    suspect, checker
+                );
+            }
+            break slot;
+        }
+        // the whole state is now at `slot`, the ranges streamed again are brought to the same slot by the next check
+        last_slot = slot;
+        let suspect = progress
+            .lock()
+            .ranges
+            .iter()
+            .flat_map(|range| range.servers.iter())
+            .find(|server| **server != checker)
+            .copied();
+        let Some(suspect) = suspect else {
+            return Err(BootstrapError::GeneralError(format!(
+                "the state streamed in ranges does not match the state root of {} at slot {}",
+                checker, slot
+            )));
+        };
+        last_suspect = Some(suspect);
+        warn!(
+            "The state streamed in ranges does not match the state root of {} at slot {}: streaming the ranges sent by {} again from {}",
+            checker, slot, suspect, checker
+        );
+        {
+            let mut progress = progress.lock();
+            for index in 0..progress.ranges.len() {
+                if progress.ranges[index].servers.contains(&suspect) {
+                    progress.restart_range(index, final_state)?;
+                }
+            }
+        }
+        client = run_session(cfg, final_state, &progress, checker, client).ok_or_else(|| {
+            BootstrapError::GeneralError(format!(
+                "cannot stream the ranges sent by {} again from {}",
+                suspect, checker
+            ))
+        })?;
+    };
+    // We allow unused result because we don't care if an error is thrown when closing the session, we will close the socket anyway.
+    let _ = client.send_timeout(
+        &BootstrapClientMessage::BootstrapSuccess,
+        Some(cfg.write_error_timeout.into()),
+    );
+    drop(client);
+    final_state
+        .read()
+        .db
+        .read()
+        .save_bootstrap_cursors(&StreamingStep::Finished(None), &StreamingStep::Started)
+        .map_err(|e| {
+            BootstrapError::GeneralError(format!("Cannot save bootstrap cursors: {}", e))
+        })?;
+    info!(
+        "The ranges of the state are streamed and checked against the state root, at slot {}",
+        slot
+    );
+    Ok(slot)
+}
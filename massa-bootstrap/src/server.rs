@@ -20,18 +20,26 @@
 //!
 //! 1. Checks if the stopper has been invoked.
 //! 2. Checks if the client is permited under the white/black list rules
-//! 3. Checks if there are not too many active sessions already
+//! 3. Checks if a session is available, or if the client can wait for one in the queue
 //! 4. Checks if the client has attempted too recently
 //! 5. All checks have passed: spawn a thread on which to run the bootstrap session
-//!    This thread creates a new tokio runtime, and runs it with `block_on`
+//!    This thread creates a new tokio runtime, and runs it with `block_on`.
+//!    A queued client is kept informed of its position on this thread until its session starts.
+//!
+//! # Scheduler
+//!
+//! Limits the number of sessions, queues the clients that wait for one, and shares the bandwidth
+//! budget of the server between the running sessions.
+pub(crate) mod scheduler;
 mod white_black_list;
 
 use crossbeam::channel::tick;
 use humantime::format_duration;
 use massa_consensus_exports::{bootstrapable_graph::BootstrapableGraph, ConsensusController};
-use massa_db::CHANGE_ID_DESER_ERROR;
+use massa_db::{StreamBatch, CHANGE_ID_DESER_ERROR};
 use massa_final_state::FinalState;
 use massa_logging::massa_trace;
+use massa_metrics::MassaMetrics;
 use massa_models::{
     block_id::BlockId, prehash::PreHashSet, slot::Slot, streaming_step::StreamingStep,
     version::Version,
@@ -42,6 +50,7 @@ use massa_signature::KeyPair;
 use massa_time::MassaTime;
 
 use parking_lot::RwLock;
+use scheduler::{Admission, BootstrapScheduler, QueueStatus, QueueTicket, SessionToken};
 use std::{
     collections::HashMap,
     net::{IpAddr, SocketAddr},
@@ -52,6 +61,11 @@ use std::{
 use tracing::{debug, error, info, warn};
 use white_black_list::*;
 
+pub(crate) use scheduler::SessionBandwidth;
+
+/// Interval between two updates of the position of a client waiting in the queue
+const QUEUE_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
 use crate::{
     bindings::BootstrapServerBinder,
     error::BootstrapError,
@@ -136,6 +150,7 @@ pub fn start_bootstrap_server<L: BSEventPoller + Send + 'static>(
     config: BootstrapConfig,
     keypair: KeyPair,
     version: Version,
+    massa_metrics: MassaMetrics,
 ) -> Result<BootstrapManager, BootstrapError> {
     massa_trace!("bootstrap.lib.start_bootstrap_server", {});
 
@@ -145,6 +160,19 @@ pub fn start_bootstrap_server<L: BSEventPoller + Send + 'static>(
     let Ok(max_bootstraps) = config.max_simultaneous_bootstraps.try_into() else {
         return Err(BootstrapError::GeneralError("Fail to convert u32 to usize".to_string()));
     };
+    if !(config.max_bytes_read_write > 0.0 && config.max_bootstrap_bandwidth > 0.0) {
+        return Err(BootstrapError::GeneralError(
+            "bootstrap bandwidth limits should be positive".to_string(),
+        ));
+    }
+    let scheduler = BootstrapScheduler::new(
+        max_bootstraps,
+        config.max_bootstrap_queue_length,
+        config.max_bootstrap_bandwidth,
+        config.max_bytes_read_write,
+        config.bootstrap_timeout.to_duration(),
+        massa_metrics,
+    );
 
     let white_black_list = SharedWhiteBlackList::new(
         config.bootstrap_whitelist_path.clone(),
@@ -180,9 +208,10 @@ pub fn start_bootstrap_server<L: BSEventPoller + Send + 'static>(
                 keypair,
                 version,
                 ip_hist_map: HashMap::with_capacity(config.ip_list_max_size),
+                scheduler,
                 bootstrap_config: config,
             }
-            .event_loop(max_bootstraps)
+            .event_loop()
         })
         .expect("in `start_bootstrap_server`, OS failed to spawn main-loop thread");
     // Give the runtime to the bootstrap manager, otherwise it will be dropped, forcibly aborting the spawned tasks.
@@ -204,6 +233,7 @@ struct BootstrapServer<'a, L: BSEventPoller> {
     bootstrap_config: BootstrapConfig,
     version: Version,
     ip_hist_map: HashMap<IpAddr, Instant>,
+    scheduler: BootstrapScheduler,
 }
 
 impl<L: BSEventPoller> BootstrapServer<'_, L> {
@@ -227,9 +257,7 @@ impl<L: BSEventPoller> BootstrapServer<'_, L> {
         }
     }
 
-    fn event_loop(mut self, max_bootstraps: usize) -> Result<(), BootstrapError> {
-        // Use the strong-count of this variable to track the session count
-        let bootstrap_sessions_counter: Arc<()> = Arc::new(());
+    fn event_loop(mut self) -> Result<(), BootstrapError> {
         let per_ip_min_interval = self.bootstrap_config.per_ip_min_interval.to_duration();
         // TODO: Work out how to integration-test this
         loop {
@@ -245,7 +273,6 @@ impl<L: BSEventPoller> BootstrapServer<'_, L> {
             };
 
             for (dplx, remote_addr) in connections {
-                // claim a slot in the max_bootstrap_sessions
                 let server_binding = BootstrapServerBinder::new(
                     dplx,
                     self.keypair.clone(),
@@ -262,88 +289,103 @@ impl<L: BSEventPoller> BootstrapServer<'_, L> {
                     continue;
                 };
 
-                // the `- 1` is to account for the top-level Arc that is created at the top
-                // of this method. subsequent counts correspond to each `clone` that is passed
-                // into a thread
-                // TODO: If we don't find a way to handle the counting automagically, make
-                //       a dedicated wrapper-type with doc-comments, manual drop impl that
-                //       integrates logging, etc...
-                if Arc::strong_count(&bootstrap_sessions_counter) - 1 < max_bootstraps {
-                    massa_trace!("bootstrap.lib.run.select.accept", {
-                        "remote_addr": remote_addr
-                    });
-                    let now = Instant::now();
-
-                    // clear IP history if necessary
+                // claim a session, or a place in the queue.
+                // Dropping the admission gives it back if a later check fails
+                let admission = match self.scheduler.admit(remote_addr) {
+                    Ok(admission) => admission,
+                    Err(estimated_wait) => {
+                        server_binding.close_and_send_error(
+                            format!(
+                                "Bootstrap failed because the bootstrap server currently has no slots available and its queue is full. A place should free up in about {}.",
+                                format_duration(Duration::from_secs(estimated_wait.as_secs()))
+                            ),
+                            remote_addr,
+                            move || debug!("did not bootstrap {}: no available slots", remote_addr),
+                        );
+                        continue;
+                    }
+                };
+                massa_trace!("bootstrap.lib.run.select.accept", {
+                    "remote_addr": remote_addr
+                });
+                let now = Instant::now();
+
+                // clear IP history if necessary
+                if self.ip_hist_map.len() > self.bootstrap_config.ip_list_max_size {
+                    self.ip_hist_map
+                        .retain(|_k, v| now.duration_since(*v) <= per_ip_min_interval);
                     if self.ip_hist_map.len() > self.bootstrap_config.ip_list_max_size {
-                        self.ip_hist_map
-                            .retain(|_k, v| now.duration_since(*v) <= per_ip_min_interval);
-                        if self.ip_hist_map.len() > self.bootstrap_config.ip_list_max_size {
-                            // too many IPs are spamming us: clear cache
-                            warn!("high bootstrap load: at least {} different IPs attempted bootstrap in the last {}", self.ip_hist_map.len(),format_duration(self.bootstrap_config.per_ip_min_interval.to_duration()).to_string());
-                            self.ip_hist_map.clear();
-                        }
+                        // too many IPs are spamming us: clear cache
+                        warn!("high bootstrap load: at least {} different IPs attempted bootstrap in the last {}", self.ip_hist_map.len(),format_duration(self.bootstrap_config.per_ip_min_interval.to_duration()).to_string());
+                        self.ip_hist_map.clear();
                     }
+                }
 
-                    // check IP's bootstrap attempt history
-                    if let Err(msg) = BootstrapServer::<L>::greedy_client_check(
-                        &mut self.ip_hist_map,
-                        remote_addr,
-                        now,
-                        per_ip_min_interval,
-                    ) {
-                        // Client has been too greedy: send out the bad-news :(
-                        let msg = format!(
-                            "Your last bootstrap on this server was {} ago and you have to wait {} before retrying.",
-                            format_duration(msg),
-                            format_duration(per_ip_min_interval.saturating_sub(msg))
-                        );
-                        let tracer = move || {
-                            massa_trace!("bootstrap.lib.run.select.accept.refuse_limit", {
-                                "remote_addr": remote_addr
-                            })
-                        };
-                        server_binding.close_and_send_error(msg, remote_addr, tracer);
-                        continue;
+                // check IP's bootstrap attempt history
+                if let Err(msg) = BootstrapServer::<L>::greedy_client_check(
+                    &mut self.ip_hist_map,
+                    remote_addr,
+                    now,
+                    per_ip_min_interval,
+                ) {
+                    // Client has been too greedy: send out the bad-news :(
+                    let msg = format!(
+                        "Your last bootstrap on this server was {} ago and you have to wait {} before retrying.",
+                        format_duration(msg),
+                        format_duration(per_ip_min_interval.saturating_sub(msg))
+                    );
+                    let tracer = move || {
+                        massa_trace!("bootstrap.lib.run.select.accept.refuse_limit", {
+                            "remote_addr": remote_addr
+                        })
                     };
+                    server_binding.close_and_send_error(msg, remote_addr, tracer);
+                    continue;
+                };
 
-                    // Clients Option<last-attempt> is good, and has been updated
-                    massa_trace!("bootstrap.lib.run.select.accept.cache_available", {});
-
-                    // launch bootstrap
-                    let version = self.version;
-                    let data_execution = self.final_state.clone();
-                    let consensus_command_sender = self.consensus_controller.clone();
-                    let protocol_controller = self.protocol_controller.clone();
-                    let config = self.bootstrap_config.clone();
-
-                    let bootstrap_count_token = bootstrap_sessions_counter.clone();
-
-                    let _ = thread::Builder::new()
-                        .name(format!("bootstrap thread, peer: {}", remote_addr))
-                        .spawn(move || {
-                            run_bootstrap_session(
-                                server_binding,
-                                bootstrap_count_token,
-                                config,
-                                remote_addr,
-                                data_execution,
-                                version,
-                                consensus_command_sender,
-                                protocol_controller,
-                            )
-                        });
-
-                    massa_trace!("bootstrap.session.started", {
-                        "active_count": Arc::strong_count(&bootstrap_sessions_counter) - 1
+                // Clients Option<last-attempt> is good, and has been updated
+                massa_trace!("bootstrap.lib.run.select.accept.cache_available", {});
+
+                // launch bootstrap
+                let version = self.version;
+                let data_execution = self.final_state.clone();
+                let consensus_command_sender = self.consensus_controller.clone();
+                let protocol_controller = self.protocol_controller.clone();
+                let config = self.bootstrap_config.clone();
+
+                let _ = thread::Builder::new()
+                    .name(format!("bootstrap thread, peer: {}", remote_addr))
+                    .spawn(move || {
+                        let mut server_binding = server_binding;
+                        let session = match admission {
+                            Admission::Session(session) => session,
+                            Admission::Queued(ticket) => {
+                                let Some(session) = wait_in_queue(
+                                    &mut server_binding,
+                                    ticket,
+                                    config.write_error_timeout.to_duration(),
+                                    remote_addr,
+                                ) else {
+                                    return;
+                                };
+                                session
+                            }
+                        };
+                        run_bootstrap_session(
+                            server_binding,
+                            session,
+                            config,
+                            remote_addr,
+                            data_execution,
+                            version,
+                            consensus_command_sender,
+                            protocol_controller,
+                        )
                     });
-                } else {
-                    server_binding.close_and_send_error(
-                        "Bootstrap failed because the bootstrap server currently has no slots available.".to_string(),
-                        remote_addr,
-                        move || debug!("did not bootstrap {}: no available slots", remote_addr),
-                    );
-                }
+
+                massa_trace!("bootstrap.session.started", {
+                    "active_count": self.scheduler.session_count()
+                });
             }
         }
     }
@@ -382,12 +424,12 @@ impl<L: BSEventPoller> BootstrapServer<'_, L> {
 /// a multi-thread-aware tokio runtime (the bs-main-loop runtime, to be exact). When this
 /// function blocks in the `block_on`, it should thread-block, and switch to another session
 ///
-/// The arc_counter variable is used as a proxy to keep track the number of active bootstrap
-/// sessions.
+/// The session token holds the place of the session in the scheduler until it is dropped,
+/// and paces the messages sent to the bandwidth of the session.
 #[allow(clippy::too_many_arguments)]
 fn run_bootstrap_session(
     mut server: BootstrapServerBinder,
-    arc_counter: Arc<()>,
+    session: SessionToken,
     config: BootstrapConfig,
     remote_addr: SocketAddr,
     data_execution: Arc<RwLock<FinalState>>,
@@ -396,6 +438,7 @@ fn run_bootstrap_session(
     protocol_controller: Box<dyn ProtocolController>,
 ) {
     debug!("running bootstrap for peer {}", remote_addr);
+    server.set_bandwidth_limiter(session.bandwidth_limiter());
     let deadline = Instant::now() + config.bootstrap_timeout.to_duration();
     // TODO: reinstate prevention of bootstrap slot camping. Deadline cancellation is one option
     let res = manage_bootstrap(
@@ -409,11 +452,8 @@ fn run_bootstrap_session(
     );
 
     // This drop allows the server to accept new connections before having to complete the error notifications
-    // account for this session being finished, as well as the root-instance
-    massa_trace!("bootstrap.session.finished", {
-        "sessions_remaining": Arc::strong_count(&arc_counter) - 2
-    });
-    drop(arc_counter);
+    drop(session);
+    massa_trace!("bootstrap.session.finished", {});
     match res {
         Err(BootstrapError::TimedOut(_)) => {
             debug!("bootstrap timeout for peer {}", remote_addr);
@@ -440,6 +480,41 @@ fn run_bootstrap_session(
     }
 }
 
+/// Keeps a queued client informed of its position and estimated wait, until its session starts.
+///
+/// Returns None if the client can't be reached anymore, which gives its place in the queue back
+fn wait_in_queue(
+    server: &mut BootstrapServerBinder,
+    ticket: QueueTicket,
+    write_timeout: Duration,
+    remote_addr: SocketAddr,
+) -> Option<SessionToken> {
+    // the first update is sent right away: the client only waits for an error for a short time
+    let mut wait_timeout = Duration::ZERO;
+    loop {
+        let (position, estimated_wait, session) = match ticket.wait_turn(wait_timeout) {
+            QueueStatus::Session(session) => (0, Duration::ZERO, Some(session)),
+            QueueStatus::Waiting(position, estimated_wait) => (position, estimated_wait, None),
+        };
+        let message = BootstrapServerMessage::BootstrapQueued {
+            position: position.try_into().unwrap_or(u32::MAX),
+            estimated_wait: MassaTime::try_from(estimated_wait)
+                .unwrap_or_else(|_| MassaTime::max()),
+        };
+        if let Err(err) = server.send_msg(write_timeout, message) {
+            debug!(
+                "bootstrap client {} left the queue at position {}: {}",
+                remote_addr, position, err
+            );
+            return None;
+        }
+        if session.is_some() {
+            return session;
+        }
+        wait_timeout = QUEUE_UPDATE_INTERVAL;
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn stream_bootstrap_information(
     server: &mut BootstrapServerBinder,
@@ -460,14 +535,18 @@ pub fn stream_bootstrap_information(
             std::thread::sleep(Duration::from_millis(500));
         }
 
+        // A resuming client needs the changes since its last slot: restart it from scratch
+        // if they are no longer in our history
+        if let Some(slot) = last_slot && final_state.read().db.read().is_change_id_too_old(&slot) {
+            return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
+        }
+
         let current_slot;
         let state_part;
         let versioning_part;
         let last_start_period;
         let last_slot_before_downtime;
 
-        let slot_too_old = false;
-
         // Scope of the final state read
         {
             let final_state_read = final_state.read();
@@ -590,10 +669,6 @@ pub fn stream_bootstrap_information(
             send_last_start_period = false;
         }
 
-        if slot_too_old {
-            return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
-        }
-
         // Setup final state global cursor
         let final_state_global_step =
             if last_state_step.finished() && last_versioning_step.finished() {
@@ -656,6 +731,153 @@ pub fn stream_bootstrap_information(
     Ok(())
 }
 
+/// Streams a key range of the final state to a client bootstrapping from several servers at once.
+///
+/// Only the state is streamed, in `BootstrapPart` messages without versioning nor consensus data,
+/// until the range is complete.
+#[allow(clippy::too_many_arguments)]
+fn stream_bootstrap_range(
+    server: &mut BootstrapServerBinder,
+    final_state: &Arc<RwLock<FinalState>>,
+    range_start: Vec<u8>,
+    range_end: Option<Vec<u8>>,
+    mut last_slot: Option<Slot>,
+    mut last_state_step: StreamingStep<Vec<u8>>,
+    bs_deadline: &Instant,
+    write_timeout: Duration,
+) -> Result<(), BootstrapError> {
+    if let Some(slot) = last_slot {
+        wait_for_slot(final_state, slot, write_timeout)?;
+    }
+    loop {
+        let (current_slot, state_part) = {
+            let final_state_read = final_state.read();
+            let db = final_state_read.db.read();
+            if let Some(slot) = last_slot && db.is_change_id_too_old(&slot) {
+                return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
+            }
+            let state_part = db
+                .get_range_batch_to_stream(
+                    &range_start,
+                    range_end.as_deref(),
+                    &last_state_step,
+                    last_slot,
+                )
+                .map_err(|e| {
+                    BootstrapError::GeneralError(format!("Error get_range_batch_to_stream: {}", e))
+                })?;
+            (db.get_change_id().expect(CHANGE_ID_DESER_ERROR), state_part)
+        };
+
+        // the range is complete once there are no new elements left in it
+        last_state_step = match state_part.new_elements.last_key_value() {
+            Some((last_key, _)) => StreamingStep::Ongoing(last_key.clone()),
+            None => StreamingStep::Finished(None),
+        };
+        last_slot = Some(current_slot);
+
+        let Some(step_write_timeout) = step_timeout_duration(bs_deadline, &write_timeout) else {
+            return Err(BootstrapError::Interupted("insufficient time left to provide next range part".to_string()));
+        };
+        server.send_msg(
+            step_write_timeout,
+            BootstrapServerMessage::BootstrapPart {
+                slot: current_slot,
+                versioning_part: StreamBatch {
+                    new_elements: Default::default(),
+                    updates_on_previous_elements: Default::default(),
+                    change_id: current_slot,
+                },
+                state_part,
+                consensus_part: BootstrapableGraph {
+                    final_blocks: Default::default(),
+                },
+                consensus_outdated_ids: PreHashSet::default(),
+                last_start_period: None,
+                last_slot_before_downtime: None,
+            },
+        )?;
+
+        if last_state_step.finished() {
+            return server.send_msg(write_timeout, BootstrapServerMessage::BootstrapFinished);
+        }
+    }
+}
+
+/// Waits for our final state to reach `slot`.
+///
+/// A client bootstrapping from several servers may ask for a key range at the slot it got it from
+/// another server, which can be a few slots ahead of us.
+fn wait_for_slot(
+    final_state: &Arc<RwLock<FinalState>>,
+    slot: Slot,
+    timeout: Duration,
+) -> Result<(), BootstrapError> {
+    let deadline = Instant::now() + timeout;
+    while final_state
+        .read()
+        .db
+        .read()
+        .get_change_id()
+        .expect(CHANGE_ID_DESER_ERROR)
+        < slot
+    {
+        if Instant::now() >= deadline {
+            return Err(BootstrapError::GeneralError(
+                "Bootstrap cursor set to future slot".to_string(),
+            ));
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(())
+}
+
+/// Sends the state root, along with the changes of the state since the slot of the client copy,
+/// so that the client can check its copy, assembled from several servers, at the same slot.
+fn send_state_root(
+    server: &mut BootstrapServerBinder,
+    final_state: &Arc<RwLock<FinalState>>,
+    last_slot: Slot,
+    write_timeout: Duration,
+) -> Result<(), BootstrapError> {
+    wait_for_slot(final_state, last_slot, write_timeout)?;
+    let (slot, state_part, root) = {
+        let final_state_read = final_state.read();
+        let db = final_state_read.db.read();
+        if db.is_change_id_too_old(&last_slot) {
+            return server.send_msg(write_timeout, BootstrapServerMessage::SlotTooOld);
+        }
+        let Some(root) = db.get_state_root() else {
+            return server.send_msg(
+                write_timeout,
+                BootstrapServerMessage::BootstrapError {
+                    error: "the state hash of the server is not a sparse merkle tree root"
+                        .to_string(),
+                },
+            );
+        };
+        let state_part = db
+            .get_range_batch_to_stream(&[], None, &StreamingStep::Finished(None), Some(last_slot))
+            .map_err(|e| {
+                BootstrapError::GeneralError(format!("Error get_range_batch_to_stream: {}", e))
+            })?;
+        (
+            db.get_change_id().expect(CHANGE_ID_DESER_ERROR),
+            state_part,
+            root,
+        )
+    };
+
+    server.send_msg(
+        write_timeout,
+        BootstrapServerMessage::StateRoot {
+            slot,
+            state_part,
+            root,
+        },
+    )
+}
+
 // derives the duration allowed for a step in the bootstrap process.
 // Returns None if the deadline for the entire bs-process has been reached
 fn step_timeout_duration(bs_deadline: &Instant, step_timeout: &Duration) -> Option<Duration> {
@@ -755,6 +977,31 @@ fn manage_bootstrap(
                         bootstrap_config.write_timeout.to_duration(),
                     )?;
                 }
+                BootstrapClientMessage::AskBootstrapRange {
+                    range_start,
+                    range_end,
+                    last_slot,
+                    last_state_step,
+                } => {
+                    stream_bootstrap_range(
+                        server,
+                        &final_state,
+                        range_start,
+                        range_end,
+                        last_slot,
+                        last_state_step,
+                        &deadline,
+                        bootstrap_config.write_timeout.to_duration(),
+                    )?;
+                }
+                BootstrapClientMessage::AskStateRoot { last_slot } => {
+                    let Some(write_timeout) = step_timeout_duration(&deadline, &bootstrap_config.write_timeout.to_duration()) else {
+                        return Err(BootstrapError::Interupted("insufficient time left to respond to the request for the state root".to_string()));
+                    };
+                    send_state_root(server, &final_state, last_slot, write_timeout)?;
+                }
+                // negotiated by the binder, not answered
+                BootstrapClientMessage::AskCompression { .. } => {}
                 BootstrapClientMessage::BootstrapSuccess => break Ok(()),
                 BootstrapClientMessage::BootstrapError { error } => {
                     break Err(BootstrapError::ReceivedError(error));
@@ -0,0 +1,332 @@
+//! Admission control of the bootstrap server.
+//!
+//! At most `max_simultaneous_bootstraps` sessions run at once. Clients arriving when they are all taken
+//! wait in a queue of at most `max_bootstrap_queue_length` clients, and are told their position and
+//! estimated wait until their session starts. Only clients arriving when the queue is full are refused.
+//!
+//! Running sessions share a global bandwidth budget: it is split evenly between the IPs being served,
+//! each IP being further capped by its own budget, then split evenly between the sessions of that IP.
+//! A session is paced by delaying each message until the previous ones fit in its share.
+
+use massa_metrics::MassaMetrics;
+use parking_lot::{Condvar, Mutex};
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Weight of the last session in the average session duration used to estimate waits
+const SESSION_DURATION_SMOOTHING: f64 = 0.25;
+
+/// A running session
+struct Session {
+    addr: SocketAddr,
+    started: Instant,
+    /// Time before which the next message of the session must not be sent
+    next_send: Instant,
+    bytes_sent: u64,
+}
+
+struct SchedulerState {
+    max_sessions: usize,
+    max_queue_length: usize,
+    /// Bytes per second shared by all the sessions
+    global_budget: f64,
+    /// Bytes per second shared by the sessions of an IP
+    per_ip_budget: f64,
+    next_id: u64,
+    sessions: HashMap<u64, Session>,
+    queue: VecDeque<u64>,
+    /// Average duration of the completed sessions
+    average_session_duration: Duration,
+    metrics: MassaMetrics,
+}
+
+impl SchedulerState {
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Bytes per second allowed to the sessions of the IP of `addr`
+    fn session_bandwidth(&self, addr: &SocketAddr) -> f64 {
+        let mut ips = Vec::with_capacity(self.sessions.len());
+        let mut ip_sessions = 0usize;
+        for session in self.sessions.values() {
+            if !ips.contains(&session.addr.ip()) {
+                ips.push(session.addr.ip());
+            }
+            if session.addr.ip() == addr.ip() {
+                ip_sessions += 1;
+            }
+        }
+        let ip_budget = (self.global_budget / ips.len().max(1) as f64).min(self.per_ip_budget);
+        ip_budget / ip_sessions.max(1) as f64
+    }
+
+    /// Estimated time before the client at `position` (starting at 0) in the queue gets a session
+    fn estimated_wait(&self, position: usize) -> Duration {
+        let now = Instant::now();
+        // time left to each session slot, assuming sessions last the average duration
+        let mut slots_left: Vec<Duration> = self
+            .sessions
+            .values()
+            .map(|session| {
+                self.average_session_duration
+                    .saturating_sub(now.duration_since(session.started))
+            })
+            .collect();
+        slots_left.resize(
+            self.max_sessions.max(slots_left.len()).max(1),
+            Duration::ZERO,
+        );
+        slots_left.sort_unstable();
+        let rounds = u32::try_from(position / slots_left.len()).unwrap_or(u32::MAX);
+        slots_left[position % slots_left.len()]
+            .saturating_add(self.average_session_duration.saturating_mul(rounds))
+    }
+
+    fn start_session(&mut self, id: u64, addr: SocketAddr) {
+        let now = Instant::now();
+        self.sessions.insert(
+            id,
+            Session {
+                addr,
+                started: now,
+                next_send: now,
+                bytes_sent: 0,
+            },
+        );
+        self.update_metrics();
+    }
+
+    fn update_metrics(&self) {
+        self.metrics
+            .set_bootstrap_scheduler(self.sessions.len(), self.queue.len());
+        for session in self.sessions.values() {
+            self.metrics.set_bootstrap_session(
+                &session.addr.to_string(),
+                session.bytes_sent,
+                self.session_bandwidth(&session.addr),
+            );
+        }
+    }
+}
+
+/// Shared admission control of the bootstrap server sessions
+#[derive(Clone)]
+pub(crate) struct BootstrapScheduler {
+    state: Arc<(Mutex<SchedulerState>, Condvar)>,
+}
+
+/// Admission of a client of the bootstrap server
+pub(crate) enum Admission {
+    /// A session is available
+    Session(SessionToken),
+    /// All sessions are taken, the client waits in the queue
+    Queued(QueueTicket),
+}
+
+/// Status of a queued client
+pub(crate) enum QueueStatus {
+    /// The session of the client starts
+    Session(SessionToken),
+    /// The client is still waiting, at the given position (starting at 1) and estimated wait
+    Waiting(usize, Duration),
+}
+
+impl BootstrapScheduler {
+    /// Creates the scheduler.
+    ///
+    /// `average_session_duration` is the duration sessions are assumed to last until one completes
+    pub(crate) fn new(
+        max_sessions: usize,
+        max_queue_length: usize,
+        global_budget: f64,
+        per_ip_budget: f64,
+        average_session_duration: Duration,
+        metrics: MassaMetrics,
+    ) -> Self {
+        BootstrapScheduler {
+            state: Arc::new((
+                Mutex::new(SchedulerState {
+                    max_sessions,
+                    max_queue_length,
+                    global_budget,
+                    per_ip_budget,
+                    next_id: 0,
+                    sessions: HashMap::new(),
+                    queue: VecDeque::new(),
+                    average_session_duration,
+                    metrics,
+                }),
+                Condvar::new(),
+            )),
+        }
+    }
+
+    /// Starts a session for the client at `addr` if one is available, or queues it.
+    ///
+    /// # Error
+    /// The queue is full: the estimated wait before a place frees up in it
+    pub(crate) fn admit(&self, addr: SocketAddr) -> Result<Admission, Duration> {
+        let mut state = self.state.0.lock();
+        let id = state.next_id();
+        if state.sessions.len() < state.max_sessions && state.queue.is_empty() {
+            state.start_session(id, addr);
+            return Ok(Admission::Session(SessionToken {
+                scheduler: self.clone(),
+                id,
+            }));
+        }
+        if state.queue.len() >= state.max_queue_length {
+            return Err(state.estimated_wait(state.queue.len()));
+        }
+        state.queue.push_back(id);
+        state.update_metrics();
+        Ok(Admission::Queued(QueueTicket {
+            scheduler: self.clone(),
+            id,
+            addr,
+        }))
+    }
+
+    /// Number of running sessions
+    pub(crate) fn session_count(&self) -> usize {
+        self.state.0.lock().sessions.len()
+    }
+}
+
+/// A running session, ended when dropped
+pub(crate) struct SessionToken {
+    scheduler: BootstrapScheduler,
+    id: u64,
+}
+
+impl SessionToken {
+    /// Paces the bandwidth of the session
+    pub(crate) fn bandwidth_limiter(&self) -> SessionBandwidth {
+        SessionBandwidth {
+            scheduler: self.scheduler.clone(),
+            id: self.id,
+        }
+    }
+}
+
+impl Drop for SessionToken {
+    fn drop(&mut self) {
+        let (lock, condvar) = &*self.scheduler.state;
+        let mut state = lock.lock();
+        if let Some(session) = state.sessions.remove(&self.id) {
+            let duration = session.started.elapsed();
+            state.average_session_duration = state
+                .average_session_duration
+                .mul_f64(1.0 - SESSION_DURATION_SMOOTHING)
+                .saturating_add(duration.mul_f64(SESSION_DURATION_SMOOTHING));
+            state
+                .metrics
+                .remove_bootstrap_session(&session.addr.to_string());
+            state.update_metrics();
+        }
+        condvar.notify_all();
+    }
+}
+
+/// A client waiting for a session, removed from the queue when dropped
+pub(crate) struct QueueTicket {
+    scheduler: BootstrapScheduler,
+    id: u64,
+    addr: SocketAddr,
+}
+
+impl QueueTicket {
+    /// Waits at most `timeout` for the session of the client to start
+    pub(crate) fn wait_turn(&self, timeout: Duration) -> QueueStatus {
+        let (lock, condvar) = &*self.scheduler.state;
+        let deadline = Instant::now() + timeout;
+        let mut state = lock.lock();
+        loop {
+            let position = state
+                .queue
+                .iter()
+                .position(|id| *id == self.id)
+                .expect("a queue ticket is in the queue until it is dropped");
+            if position == 0 && state.sessions.len() < state.max_sessions {
+                state.queue.pop_front();
+                state.start_session(self.id, self.addr);
+                // the next client may get a session as well
+                condvar.notify_all();
+                return QueueStatus::Session(SessionToken {
+                    scheduler: self.scheduler.clone(),
+                    id: self.id,
+                });
+            }
+            if condvar.wait_until(&mut state, deadline).timed_out() {
+                let position = state
+                    .queue
+                    .iter()
+                    .position(|id| *id == self.id)
+                    .expect("a queue ticket is in the queue until it is dropped");
+                return QueueStatus::Waiting(position + 1, state.estimated_wait(position));
+            }
+        }
+    }
+}
+
+impl Drop for QueueTicket {
+    fn drop(&mut self) {
+        let (lock, condvar) = &*self.scheduler.state;
+        let mut state = lock.lock();
+        // the ticket was already taken out of the queue if its session started
+        if let Some(position) = state.queue.iter().position(|id| *id == self.id) {
+            state.queue.remove(position);
+            state.update_metrics();
+            condvar.notify_all();
+        }
+    }
+}
+
+/// Paces the messages sent during a session to its share of the bandwidth
+#[derive(Clone)]
+pub(crate) struct SessionBandwidth {
+    scheduler: BootstrapScheduler,
+    id: u64,
+}
+
+impl SessionBandwidth {
+    /// Blocks until a message of `bytes` bytes can be sent.
+    ///
+    /// The message is accounted for right away: the delay it adds is waited before the next message.
+    pub(crate) fn wait_to_send(&self, bytes: usize) {
+        let wait = {
+            let mut state = self.scheduler.state.0.lock();
+            let Some(addr) = state.sessions.get(&self.id).map(|session| session.addr) else {
+                return;
+            };
+            let bandwidth = state.session_bandwidth(&addr);
+            let send_duration =
+                Duration::try_from_secs_f64(bytes as f64 / bandwidth).unwrap_or(Duration::MAX);
+            let now = Instant::now();
+            let session = state
+                .sessions
+                .get_mut(&self.id)
+                .expect("session was found above");
+            let send_at = session.next_send.max(now);
+            session.next_send = send_at.checked_add(send_duration).unwrap_or(send_at);
+            session.bytes_sent = session.bytes_sent.saturating_add(bytes as u64);
+            let bytes_sent = session.bytes_sent;
+            state.metrics.inc_bootstrap_bytes_sent(bytes as u64);
+            state
+                .metrics
+                .set_bootstrap_session(&addr.to_string(), bytes_sent, bandwidth);
+            send_at.saturating_duration_since(now)
+        };
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
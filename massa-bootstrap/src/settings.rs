@@ -3,6 +3,7 @@
 use massa_models::block::BlockDeserializerArgs;
 use massa_models::node::NodeId;
 use massa_time::MassaTime;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::Deserialize;
 use std::{net::SocketAddr, path::PathBuf};
 
@@ -19,6 +20,16 @@ pub enum IpType {
     IPv6,
 }
 
+/// Compression codec of the bootstrap state parts.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum BootstrapCompression {
+    /// Send the state parts uncompressed.
+    None = 0u8,
+    /// Compress the state parts with zstd (default).
+    Zstd = 1u8,
+}
+
 /// Bootstrap configuration.
 #[derive(Debug, Deserialize, Clone)]
 pub struct BootstrapConfig {
@@ -57,12 +68,22 @@ pub struct BootstrapConfig {
     pub keep_ledger: bool,
     /// Max simultaneous bootstraps
     pub max_simultaneous_bootstraps: u32,
+    /// Max number of clients waiting for a bootstrap session once all are taken
+    pub max_bootstrap_queue_length: usize,
+    /// Max estimated wait in the queue of a bootstrap server before trying another server
+    pub max_queue_wait: MassaTime,
     /// Minimum interval between two bootstrap attempts from a given IP
     pub per_ip_min_interval: MassaTime,
     /// Max size of the IP list
     pub ip_list_max_size: usize,
-    /// Read-Write limitation for a connection in bytes per seconds
+    /// Bandwidth limitation of the bootstrap sessions of an IP in bytes per seconds
     pub max_bytes_read_write: f64,
+    /// Bandwidth limitation of the bootstrap server in bytes per seconds, shared fairly between sessions
+    pub max_bootstrap_bandwidth: f64,
+    /// Compression codec asked for as a client, and allowed as a server
+    pub compression: BootstrapCompression,
+    /// Number of servers the state is downloaded from at once (1 to bootstrap from one server at a time)
+    pub parallel_servers: usize,
     /// thread count
     pub thread_count: u8,
     /// period per cycle
@@ -139,6 +160,7 @@ pub struct BootstrapConfig {
 #[parent(type = "BootstrapConfig")]
 pub struct BootstrapSrvBindCfg {
     pub max_bytes_read_write: f64,
+    pub compression: BootstrapCompression,
     pub thread_count: u8,
     pub max_datastore_key_length: u8,
     pub randomness_size_bytes: usize,
@@ -152,6 +174,7 @@ pub struct BootstrapSrvBindCfg {
 #[parent(type = "BootstrapConfig")]
 pub struct BootstrapClientConfig {
     pub max_bytes_read_write: f64,
+    pub compression: BootstrapCompression,
     pub endorsement_count: u32,
     pub max_listeners_per_peer: u32,
     pub max_advertise_length: u32,
@@ -0,0 +1,333 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Portable snapshots of the bootstrap state, to start nodes without a bootstrap server.
+//!
+//! A snapshot file starts with a header holding its format version, the version of the node
+//! that wrote it, the final slot and the db hash of the exported final state.
+//! It is followed by the same `BootstrapServerMessage::BootstrapPart` messages a bootstrap server
+//! would stream for that final state (final state, MIP store and consensus graph),
+//! and ends with a `BootstrapServerMessage::BootstrapFinished` message.
+//! Every record is prefixed by its length as a big-endian `u32`.
+
+use crate::{
+    client::{apply_bootstrap_part, finish_state_bootstrap},
+    error::BootstrapError,
+    messages::{
+        BootstrapServerMessage, BootstrapServerMessageDeserializer,
+        BootstrapServerMessageSerializer,
+    },
+    settings::BootstrapClientConfig,
+    BootstrapConfig, GlobalBootstrapState,
+};
+use massa_consensus_exports::{bootstrapable_graph::BootstrapableGraph, ConsensusController};
+use massa_db::{StreamBatch, CHANGE_ID_DESER_ERROR};
+use massa_final_state::FinalState;
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::{
+    config::MAX_BOOTSTRAP_MESSAGE_SIZE,
+    slot::{Slot, SlotDeserializer, SlotSerializer},
+    streaming_step::StreamingStep,
+    version::{Version, VersionDeserializer, VersionSerializer},
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use parking_lot::RwLock;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    ops::Bound::{Excluded, Included},
+    path::Path,
+    sync::Arc,
+};
+use tracing::info;
+
+/// First bytes of every snapshot file
+const SNAPSHOT_MAGIC: &[u8; 8] = b"MASSASNP";
+
+/// Version of the snapshot file format
+const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Writes a length-prefixed record
+fn write_record(writer: &mut impl Write, record: &[u8]) -> Result<(), BootstrapError> {
+    let len: u32 = record
+        .len()
+        .try_into()
+        .map_err(|_| BootstrapError::GeneralError("snapshot record is too large".to_string()))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(record)?;
+    Ok(())
+}
+
+/// Reads a length-prefixed record
+fn read_record(reader: &mut impl Read) -> Result<Vec<u8>, BootstrapError> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_BOOTSTRAP_MESSAGE_SIZE {
+        return Err(BootstrapError::DeserializeError(format!(
+            "snapshot record of {} bytes is too large",
+            len
+        )));
+    }
+    let mut record = vec![0u8; len as usize];
+    reader.read_exact(&mut record)?;
+    Ok(record)
+}
+
+/// Part of the final state with no elements, sent along the consensus blocks
+fn empty_stream_batch(slot: Slot) -> StreamBatch<Slot> {
+    StreamBatch {
+        new_elements: BTreeMap::new(),
+        updates_on_previous_elements: BTreeMap::new(),
+        change_id: slot,
+    }
+}
+
+/// Writes a snapshot of the current final state and consensus graph to `path`.
+///
+/// The final state is locked for reading while it is exported, so that the snapshot is consistent.
+/// The locks are released before the consensus graph is asked for, so that consensus is never called
+/// while holding them.
+///
+/// # Returns
+/// The final slot of the exported state
+pub fn export_snapshot(
+    path: &Path,
+    final_state: Arc<RwLock<FinalState>>,
+    consensus_controller: Box<dyn ConsensusController>,
+    version: Version,
+) -> Result<Slot, BootstrapError> {
+    let message_serializer = BootstrapServerMessageSerializer::new();
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let slot = {
+        let final_state = final_state.read();
+        let db = final_state.db.read();
+        let slot = db.get_change_id().expect(CHANGE_ID_DESER_ERROR);
+
+        // header
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&SNAPSHOT_FORMAT_VERSION.to_be_bytes())?;
+        let mut header = Vec::new();
+        VersionSerializer::new().serialize(&version, &mut header)?;
+        SlotSerializer::new().serialize(&slot, &mut header)?;
+        header.extend(db.get_db_hash().to_bytes());
+        write_record(&mut writer, &header)?;
+
+        // final state parts, as a bootstrap server would stream them.
+        // The state can't change during the export, so there are never updates on previous elements.
+        let mut state_step = StreamingStep::Started;
+        let mut versioning_step = StreamingStep::Started;
+        let mut first_part = true;
+        while !(state_step.finished() && versioning_step.finished()) {
+            let state_part = db
+                .get_batch_to_stream(&state_step, Some(slot))
+                .map_err(|e| {
+                    BootstrapError::GeneralError(format!("Error get_batch_to_stream: {}", e))
+                })?;
+            state_step = match state_part.new_elements.last_key_value() {
+                Some((last_key, _)) => StreamingStep::Ongoing(last_key.clone()),
+                None => StreamingStep::Finished(None),
+            };
+
+            let versioning_part = db
+                .get_versioning_batch_to_stream(&versioning_step, Some(slot))
+                .map_err(|e| {
+                    BootstrapError::GeneralError(format!(
+                        "Error get_versioning_batch_to_stream: {}",
+                        e
+                    ))
+                })?;
+            versioning_step = match versioning_part.new_elements.last_key_value() {
+                Some((last_key, _)) => StreamingStep::Ongoing(last_key.clone()),
+                None => StreamingStep::Finished(None),
+            };
+
+            let mut message = Vec::new();
+            message_serializer.serialize(
+                &BootstrapServerMessage::BootstrapPart {
+                    slot,
+                    state_part,
+                    versioning_part,
+                    consensus_part: BootstrapableGraph {
+                        final_blocks: Default::default(),
+                    },
+                    consensus_outdated_ids: Default::default(),
+                    last_start_period: first_part.then_some(final_state.last_start_period),
+                    last_slot_before_downtime: first_part
+                        .then_some(final_state.last_slot_before_downtime),
+                },
+                &mut message,
+            )?;
+            write_record(&mut writer, &message)?;
+            first_part = false;
+        }
+        slot
+    };
+
+    // consensus blocks, once the final state is complete
+    let mut consensus_step = StreamingStep::Started;
+    loop {
+        let (consensus_part, consensus_outdated_ids, new_consensus_step) = consensus_controller
+            .get_bootstrap_part(consensus_step, StreamingStep::Finished(Some(slot)))?;
+        consensus_step = new_consensus_step;
+
+        if consensus_step.finished() {
+            let mut message = Vec::new();
+            message_serializer
+                .serialize(&BootstrapServerMessage::BootstrapFinished, &mut message)?;
+            write_record(&mut writer, &message)?;
+            break;
+        }
+
+        let mut message = Vec::new();
+        message_serializer.serialize(
+            &BootstrapServerMessage::BootstrapPart {
+                slot,
+                state_part: empty_stream_batch(slot),
+                versioning_part: empty_stream_batch(slot),
+                consensus_part,
+                consensus_outdated_ids,
+                last_start_period: None,
+                last_slot_before_downtime: None,
+            },
+            &mut message,
+        )?;
+        write_record(&mut writer, &message)?;
+    }
+    writer.flush()?;
+
+    info!(
+        "Exported a snapshot of the final state at slot {} to {}",
+        slot,
+        path.display()
+    );
+    Ok(slot)
+}
+
+/// Gets the state from a snapshot file written by `export_snapshot`, instead of a bootstrap server.
+///
+/// The imported final state is checked against the db hash recorded in the snapshot.
+/// Peers are not part of a snapshot: the node then connects to its initial peers,
+/// and catches up the slots that followed the snapshot from them.
+pub fn get_state_from_snapshot(
+    path: &Path,
+    bootstrap_config: &BootstrapConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    version: Version,
+) -> Result<GlobalBootstrapState, BootstrapError> {
+    let client_config: BootstrapClientConfig = bootstrap_config.into();
+    let message_deserializer = BootstrapServerMessageDeserializer::new((&client_config).into());
+    let mut reader = BufReader::new(File::open(path)?);
+
+    // header
+    let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if &magic != SNAPSHOT_MAGIC {
+        return Err(BootstrapError::GeneralError(format!(
+            "{} is not a snapshot file",
+            path.display()
+        )));
+    }
+    let mut format_version = [0u8; 4];
+    reader.read_exact(&mut format_version)?;
+    let format_version = u32::from_be_bytes(format_version);
+    if format_version != SNAPSHOT_FORMAT_VERSION {
+        return Err(BootstrapError::GeneralError(format!(
+            "unsupported snapshot format version {} (expected {})",
+            format_version, SNAPSHOT_FORMAT_VERSION
+        )));
+    }
+    let header = read_record(&mut reader)?;
+    let (rest, snapshot_version) = VersionDeserializer::new()
+        .deserialize::<DeserializeError>(&header)
+        .map_err(|err| BootstrapError::DeserializeError(err.to_string()))?;
+    if !version.is_compatible(&snapshot_version) {
+        return Err(BootstrapError::IncompatibleVersionError(format!(
+            "snapshot was written by an incompatible version: {} (local node version: {})",
+            snapshot_version, version
+        )));
+    }
+    let (rest, snapshot_slot) = SlotDeserializer::new(
+        (Included(0), Included(u64::MAX)),
+        (Included(0), Excluded(bootstrap_config.thread_count)),
+    )
+    .deserialize::<DeserializeError>(rest)
+    .map_err(|err| BootstrapError::DeserializeError(err.to_string()))?;
+    let snapshot_hash = Hash::from_bytes(rest.try_into().map_err(|_| {
+        BootstrapError::DeserializeError(format!(
+            "snapshot db hash should be {} bytes long",
+            HASH_SIZE_BYTES
+        ))
+    })?);
+
+    info!(
+        "Bootstrapping from the snapshot of slot {} in {}",
+        snapshot_slot,
+        path.display()
+    );
+
+    // bootstrap parts
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
+    loop {
+        let record = read_record(&mut reader)?;
+        let (rest, message) = message_deserializer
+            .deserialize::<DeserializeError>(&record)
+            .map_err(|err| BootstrapError::DeserializeError(err.to_string()))?;
+        if !rest.is_empty() {
+            return Err(BootstrapError::DeserializeError(
+                "trailing bytes after a snapshot message".to_string(),
+            ));
+        }
+        match message {
+            BootstrapServerMessage::BootstrapPart {
+                slot,
+                state_part,
+                versioning_part,
+                consensus_part,
+                consensus_outdated_ids,
+                last_start_period,
+                last_slot_before_downtime,
+            } => {
+                apply_bootstrap_part(
+                    &mut global_bootstrap_state,
+                    slot,
+                    state_part,
+                    versioning_part,
+                    consensus_part,
+                    consensus_outdated_ids,
+                    last_start_period,
+                    last_slot_before_downtime,
+                )?;
+            }
+            BootstrapServerMessage::BootstrapFinished => {
+                finish_state_bootstrap(&mut global_bootstrap_state)?;
+                break;
+            }
+            other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+        }
+    }
+
+    // check the imported state against the snapshot header
+    {
+        let final_state = global_bootstrap_state.final_state.read();
+        let db = final_state.db.read();
+        let slot = db.get_change_id().expect(CHANGE_ID_DESER_ERROR);
+        if slot != snapshot_slot {
+            return Err(BootstrapError::GeneralError(format!(
+                "snapshot state is at slot {} but its header records slot {}",
+                slot, snapshot_slot
+            )));
+        }
+        let hash = db.get_db_hash();
+        if hash != snapshot_hash {
+            return Err(BootstrapError::GeneralError(format!(
+                "snapshot state hash {} does not match the recorded hash {}",
+                hash, snapshot_hash
+            )));
+        }
+    }
+
+    info!("State bootstrap from snapshot complete");
+    Ok(global_bootstrap_state)
+}
@@ -1,11 +1,14 @@
 use crate::messages::{BootstrapClientMessage, BootstrapServerMessage};
-use crate::settings::{BootstrapClientConfig, BootstrapSrvBindCfg};
+use crate::settings::{BootstrapClientConfig, BootstrapCompression, BootstrapSrvBindCfg};
 use crate::BootstrapConfig;
 use crate::{
     bindings::{BootstrapClientBinder, BootstrapServerBinder},
     tests::tools::get_bootstrap_config,
     BootstrapPeers,
 };
+use massa_consensus_exports::bootstrapable_graph::BootstrapableGraph;
+use massa_db::StreamBatch;
+use massa_hash::Hash;
 use massa_models::config::{
     BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CONSENSUS_BOOTSTRAP_PART_SIZE, ENDORSEMENT_COUNT,
     MAX_ADVERTISE_LENGTH, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
@@ -18,11 +21,13 @@ use massa_models::config::{
     MIP_STORE_STATS_COUNTERS_MAX, THREAD_COUNT,
 };
 use massa_models::node::NodeId;
+use massa_models::slot::Slot;
+use massa_models::streaming_step::StreamingStep;
 use massa_models::version::Version;
 use massa_protocol_exports::{PeerId, TransportType};
 use massa_signature::{KeyPair, PublicKey};
 use massa_time::MassaTime;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::net::TcpStream;
 use std::str::FromStr;
 
@@ -37,6 +42,7 @@ impl BootstrapClientBinder {
     pub fn test_default(client_duplex: TcpStream, remote_pubkey: PublicKey) -> Self {
         let cfg = BootstrapClientConfig {
             max_bytes_read_write: f64::INFINITY,
+            compression: BootstrapCompression::Zstd,
             max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
             endorsement_count: ENDORSEMENT_COUNT,
             max_advertise_length: MAX_ADVERTISE_LENGTH,
@@ -82,6 +88,7 @@ fn test_binders() {
         server_keypair.clone(),
         BootstrapSrvBindCfg {
             max_bytes_read_write: f64::INFINITY,
+            compression: BootstrapCompression::Zstd,
             thread_count: THREAD_COUNT,
             max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
             randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
@@ -232,6 +239,7 @@ fn test_binders_double_send_server_works() {
         server_keypair.clone(),
         BootstrapSrvBindCfg {
             max_bytes_read_write: f64::INFINITY,
+            compression: BootstrapCompression::Zstd,
             thread_count: THREAD_COUNT,
             max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
             randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
@@ -363,6 +371,7 @@ fn test_binders_try_double_send_client_works() {
         server_keypair.clone(),
         BootstrapSrvBindCfg {
             max_bytes_read_write: f64::INFINITY,
+            compression: BootstrapCompression::Zstd,
             thread_count: THREAD_COUNT,
             max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
             randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
@@ -484,3 +493,271 @@ fn test_binders_try_double_send_client_works() {
     server_thread.join().unwrap();
     client_thread.join().unwrap();
 }
+
+/// A state part that compresses well, with its state elements
+fn compressible_bootstrap_part(slot: Slot) -> (BootstrapServerMessage, BTreeMap<Vec<u8>, Vec<u8>>) {
+    let new_elements: BTreeMap<Vec<u8>, Vec<u8>> = (0..100u32)
+        .map(|i| (format!("ledger/{:08}", i).into_bytes(), vec![7u8; 100]))
+        .collect();
+    let message = BootstrapServerMessage::BootstrapPart {
+        slot,
+        state_part: StreamBatch {
+            new_elements: new_elements.clone(),
+            updates_on_previous_elements: BTreeMap::new(),
+            change_id: slot,
+        },
+        versioning_part: StreamBatch {
+            new_elements: BTreeMap::new(),
+            updates_on_previous_elements: BTreeMap::new(),
+            change_id: slot,
+        },
+        consensus_part: BootstrapableGraph {
+            final_blocks: Vec::new(),
+        },
+        consensus_outdated_ids: Default::default(),
+        last_start_period: Some(0),
+        last_slot_before_downtime: Some(None),
+    };
+    (message, new_elements)
+}
+
+/// Runs a session where the client may ask for compression after the handshake,
+/// and checks that it receives the state part and the end of the bootstrap
+fn run_compression_session(ask_compression: bool) {
+    let (bootstrap_config, server_keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+
+    let server = std::net::TcpListener::bind("localhost:0").unwrap();
+    let client = std::net::TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    let server = server.accept().unwrap();
+
+    let mut server = BootstrapServerBinder::new(
+        server.0,
+        server_keypair.clone(),
+        BootstrapSrvBindCfg {
+            max_bytes_read_write: f64::INFINITY,
+            compression: BootstrapCompression::Zstd,
+            thread_count: THREAD_COUNT,
+            max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+            randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+            consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+            write_error_timeout: MassaTime::from_millis(1000),
+        },
+    );
+    let mut client = BootstrapClientBinder::test_default(
+        client,
+        bootstrap_config.bootstrap_list[0].1.get_public_key(),
+    );
+
+    let slot = Slot::new(1, 0);
+    let (bootstrap_part, new_elements) = compressible_bootstrap_part(slot);
+
+    let server_thread = std::thread::Builder::new()
+        .name("run_compression_session::server_thread".to_string())
+        .spawn(move || {
+            let version: Version = Version::from_str("TEST.1.10").unwrap();
+            server.handshake_timeout(version, None).unwrap();
+
+            server
+                .send_timeout(
+                    BootstrapServerMessage::BootstrapTime {
+                        server_time: MassaTime::now().unwrap(),
+                        version,
+                    },
+                    None,
+                )
+                .unwrap();
+
+            // the session goes on with the first request of the client
+            let message = server.next_timeout(None).unwrap();
+            if ask_compression {
+                match message {
+                    BootstrapClientMessage::AskCompression {
+                        codec: BootstrapCompression::Zstd,
+                    } => {}
+                    _ => panic!("Bad message receive: Expected an ask compression message"),
+                }
+                match server.next_timeout(None).unwrap() {
+                    BootstrapClientMessage::AskBootstrapPart { .. } => {}
+                    _ => panic!("Bad message receive: Expected an ask bootstrap part message"),
+                }
+            } else {
+                match message {
+                    BootstrapClientMessage::AskBootstrapPart { .. } => {}
+                    _ => panic!("Bad message receive: Expected an ask bootstrap part message"),
+                }
+            }
+
+            server.send_timeout(bootstrap_part, None).unwrap();
+            server
+                .send_timeout(BootstrapServerMessage::BootstrapFinished, None)
+                .unwrap();
+        })
+        .unwrap();
+
+    let client_thread = std::thread::Builder::new()
+        .name("run_compression_session::client_thread".to_string())
+        .spawn(move || {
+            let version: Version = Version::from_str("TEST.1.10").unwrap();
+            client.handshake(version).unwrap();
+
+            match client.next_timeout(None).unwrap() {
+                BootstrapServerMessage::BootstrapTime { .. } => {}
+                _ => panic!("Bad message receive: Expected a bootstrap time message"),
+            }
+
+            if ask_compression {
+                client
+                    .send_timeout(
+                        &BootstrapClientMessage::AskCompression {
+                            codec: BootstrapCompression::Zstd,
+                        },
+                        None,
+                    )
+                    .unwrap();
+            }
+            client
+                .send_timeout(
+                    &BootstrapClientMessage::AskBootstrapPart {
+                        last_slot: None,
+                        last_state_step: StreamingStep::Started,
+                        last_versioning_step: StreamingStep::Started,
+                        last_consensus_step: StreamingStep::Started,
+                        send_last_start_period: true,
+                    },
+                    None,
+                )
+                .unwrap();
+
+            match client.next_timeout(None).unwrap() {
+                BootstrapServerMessage::BootstrapPart {
+                    slot: received_slot,
+                    state_part,
+                    ..
+                } => {
+                    assert_eq!(received_slot, slot);
+                    assert_eq!(state_part.new_elements, new_elements);
+                }
+                _ => panic!("Bad message receive: Expected a bootstrap part message"),
+            }
+            match client.next_timeout(None).unwrap() {
+                BootstrapServerMessage::BootstrapFinished => {}
+                _ => panic!("Bad message receive: Expected a bootstrap finished message"),
+            }
+        })
+        .unwrap();
+
+    server_thread.join().unwrap();
+    client_thread.join().unwrap();
+}
+
+/// The server compresses the state parts it sends once the client asked for it
+#[test]
+fn test_binders_compressed_bootstrap_part() {
+    run_compression_session(true);
+}
+
+/// A client that never asks for compression, as the ones released before it, still bootstraps
+/// from a server allowing compression: the handshake and the messages keep their former format
+#[test]
+fn test_binders_old_client_uncompressed_bootstrap_part() {
+    run_compression_session(false);
+}
+
+#[test]
+fn test_binders_state_root() {
+    let (bootstrap_config, server_keypair): &(BootstrapConfig, KeyPair) = &BOOTSTRAP_CONFIG_KEYPAIR;
+
+    let server = std::net::TcpListener::bind("localhost:0").unwrap();
+    let client = std::net::TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    let server = server.accept().unwrap();
+
+    let mut server = BootstrapServerBinder::new(
+        server.0,
+        server_keypair.clone(),
+        BootstrapSrvBindCfg {
+            max_bytes_read_write: f64::INFINITY,
+            compression: BootstrapCompression::Zstd,
+            thread_count: THREAD_COUNT,
+            max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+            randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+            consensus_bootstrap_part_size: CONSENSUS_BOOTSTRAP_PART_SIZE,
+            write_error_timeout: MassaTime::from_millis(1000),
+        },
+    );
+    let mut client = BootstrapClientBinder::test_default(
+        client,
+        bootstrap_config.bootstrap_list[0].1.get_public_key(),
+    );
+
+    let slot = Slot::new(3, 1);
+    let updates: BTreeMap<Vec<u8>, Option<Vec<u8>>> = BTreeMap::from([
+        (b"ledger/a".to_vec(), Some(vec![1, 2, 3])),
+        (b"ledger/b".to_vec(), None),
+    ]);
+    let root = Hash::compute_from(b"state root");
+
+    let server_thread = std::thread::Builder::new()
+        .name("test_binders_state_root::server_thread".to_string())
+        .spawn({
+            let updates = updates.clone();
+            move || {
+                let version: Version = Version::from_str("TEST.1.10").unwrap();
+                server.handshake_timeout(version, None).unwrap();
+
+                match server.next_timeout(None).unwrap() {
+                    BootstrapClientMessage::AskStateRoot { last_slot } => {
+                        assert_eq!(last_slot, Slot::new(2, 0));
+                    }
+                    _ => panic!("Bad message receive: Expected a state root request"),
+                }
+                server
+                    .send_timeout(
+                        BootstrapServerMessage::StateRoot {
+                            slot,
+                            state_part: StreamBatch {
+                                new_elements: BTreeMap::new(),
+                                updates_on_previous_elements: updates,
+                                change_id: slot,
+                            },
+                            root,
+                        },
+                        None,
+                    )
+                    .unwrap();
+            }
+        })
+        .unwrap();
+
+    let client_thread = std::thread::Builder::new()
+        .name("test_binders_state_root::client_thread".to_string())
+        .spawn(move || {
+            let version: Version = Version::from_str("TEST.1.10").unwrap();
+            client.handshake(version).unwrap();
+
+            client
+                .send_timeout(
+                    &BootstrapClientMessage::AskStateRoot {
+                        last_slot: Slot::new(2, 0),
+                    },
+                    None,
+                )
+                .unwrap();
+            match client.next_timeout(None).unwrap() {
+                BootstrapServerMessage::StateRoot {
+                    slot: received_slot,
+                    state_part,
+                    root: received_root,
+                } => {
+                    assert_eq!(received_slot, slot);
+                    assert_eq!(state_part.updates_on_previous_elements, updates);
+                    assert_eq!(state_part.change_id, slot);
+                    assert_eq!(received_root, root);
+                }
+                _ => panic!("Bad message receive: Expected a state root message"),
+            }
+        })
+        .unwrap();
+
+    server_thread.join().unwrap();
+    client_thread.join().unwrap();
+}
@@ -1,5 +1,8 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 mod binders;
+mod parallel;
 mod scenarios;
+mod scheduler;
+mod snapshot;
 pub(crate) mod tools;
@@ -0,0 +1,395 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use super::tools::{
+    assert_eq_bootstrap_graph, create_test_final_state, get_boot_state, get_bootstrap_config,
+    get_peers, get_random_address, start_test_selector,
+};
+use crate::{
+    client::MockBSConnector,
+    error::BootstrapError,
+    get_state,
+    parallel::{get_state_ranges, split_state, ParallelProgress},
+    start_bootstrap_server, BootstrapConfig, BootstrapManager, BootstrapTcpListener,
+};
+use massa_consensus_exports::{
+    bootstrapable_graph::BootstrapableGraph, test_exports::MockConsensusControllerImpl,
+};
+use massa_db::{
+    is_key_in_range, StreamBatch, ASYNC_POOL_PREFIX, CF_ERROR, LEDGER_PREFIX, STATE_CF,
+};
+use massa_final_state::{
+    test_exports::{assert_eq_final_state, assert_eq_final_state_hash},
+    FinalState,
+};
+use massa_ledger_exports::{Key, KeySerializer, KeyType};
+use massa_metrics::MassaMetrics;
+use massa_models::{
+    address::{Address, UserAddress, UserAddressV0},
+    node::NodeId,
+    prehash::PreHashSet,
+    slot::Slot,
+    streaming_step::StreamingStep,
+    version::Version,
+};
+use massa_protocol_exports::MockProtocolController;
+use massa_serialization::Serializer;
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::{
+    collections::BTreeMap,
+    net::{SocketAddr, TcpStream},
+    str::FromStr,
+    sync::{Arc, Condvar, Mutex},
+};
+use tempfile::TempDir;
+
+/// Consensus mock streaming `graph` in a single part to every session
+fn consensus_mock(graph: BootstrapableGraph) -> Box<MockConsensusControllerImpl> {
+    let mut mock = Box::new(MockConsensusControllerImpl::new());
+    let sent_graph = graph.clone();
+    mock.expect_get_bootstrap_part()
+        .returning(
+            move |last_consensus_step, _slot| match last_consensus_step {
+                StreamingStep::Started => Ok((
+                    sent_graph.clone(),
+                    PreHashSet::default(),
+                    StreamingStep::Ongoing(PreHashSet::default()),
+                )),
+                _ => Ok((
+                    BootstrapableGraph {
+                        final_blocks: vec![],
+                    },
+                    PreHashSet::default(),
+                    StreamingStep::Finished(None),
+                )),
+            },
+        );
+    mock.expect_clone_box()
+        .returning(move || consensus_mock(graph.clone()));
+    mock
+}
+
+/// Protocol mock sending the peers of `keypair` to every session
+fn protocol_mock(keypair: KeyPair) -> Box<MockProtocolController> {
+    let mut mock = Box::new(MockProtocolController::new());
+    let peers_keypair = keypair.clone();
+    mock.expect_get_bootstrap_peers()
+        .returning(move || Ok(get_peers(&peers_keypair)));
+    mock.expect_clone_box()
+        .returning(move || protocol_mock(keypair.clone()));
+    mock
+}
+
+/// Starts a bootstrap server on `addr` serving `final_state`, accepting several sessions of the same client
+fn start_server(
+    addr: SocketAddr,
+    keypair: &KeyPair,
+    final_state: Arc<RwLock<FinalState>>,
+) -> BootstrapManager {
+    let mut config = get_bootstrap_config(NodeId::new(keypair.get_public_key()));
+    config.per_ip_min_interval = MassaTime::from_millis(0);
+    let (listener_stopper, listener) = BootstrapTcpListener::new(&addr).unwrap();
+    let mut manager = start_bootstrap_server(
+        listener,
+        consensus_mock(get_boot_state()),
+        protocol_mock(keypair.clone()),
+        final_state,
+        config,
+        keypair.clone(),
+        Version::from_str("TEST.1.10").unwrap(),
+        MassaMetrics::new(false, 32),
+    )
+    .unwrap();
+    manager.set_listener_stopper(listener_stopper);
+    manager
+}
+
+/// Connector opening a new connection to the asked address every time
+fn connector_mock() -> MockBSConnector {
+    let mut connector = MockBSConnector::new();
+    connector
+        .expect_connect_timeout()
+        .returning(|addr, _| Ok(TcpStream::connect(addr).unwrap()));
+    connector
+}
+
+/// Config of a client bootstrapping from the servers of `bootstrap_list` two at a time
+fn parallel_client_config(bootstrap_list: Vec<(SocketAddr, NodeId)>) -> BootstrapConfig {
+    let mut config = get_bootstrap_config(bootstrap_list[0].1);
+    config.bootstrap_list = bootstrap_list;
+    config.parallel_servers = 2;
+    config
+}
+
+#[test]
+fn test_split_state() {
+    for count in [0, 2, 3, 8, 300] {
+        let range_starts = split_state(count);
+        assert_eq!(range_starts.len(), count.clamp(2, 257));
+        assert!(range_starts[0].is_empty());
+        assert_eq!(range_starts[1], LEDGER_PREFIX.as_bytes());
+        assert!(
+            range_starts.windows(2).all(|pair| pair[0] < pair[1]),
+            "the ranges should be sorted and not empty"
+        );
+    }
+
+    // the keys before the ledger are in the first range
+    let range_starts = split_state(8);
+    let range_of = |key: &[u8]| {
+        (0..range_starts.len())
+            .find(|index| {
+                is_key_in_range(
+                    key,
+                    &range_starts[*index],
+                    range_starts.get(index + 1).map(Vec::as_slice),
+                )
+            })
+            .unwrap()
+    };
+    assert_eq!(range_of(ASYNC_POOL_PREFIX.as_bytes()), 0);
+
+    // the entries of a user address are in the range of the first byte of its hash
+    let ledger_parts = range_starts.len() - 1;
+    let key_serializer = KeySerializer::new(false);
+    for _ in 0..100 {
+        let address = get_random_address();
+        let Address::User(UserAddress::UserAddressV0(UserAddressV0(hash))) = address else {
+            panic!("a random address should be a user address");
+        };
+        let first_byte = hash.to_bytes()[0] as usize;
+        let expected_range = 1
+            + (0..ledger_parts)
+                .rev()
+                .find(|&part| part * 256 / ledger_parts <= first_byte)
+                .unwrap();
+        for key_type in [
+            KeyType::BALANCE,
+            KeyType::BYTECODE,
+            KeyType::DATASTORE(vec![0xff; 8]),
+        ] {
+            let mut key = Vec::new();
+            key_serializer
+                .serialize(&Key::new(&address, key_type), &mut key)
+                .unwrap();
+            assert_eq!(range_of(&key), expected_range);
+        }
+    }
+}
+
+#[test]
+fn test_restart_range() {
+    let dir = TempDir::new().unwrap();
+    let final_state = create_test_final_state(dir.path(), start_test_selector(), false);
+    let range_starts = split_state(4);
+    let kept_key = [ASYNC_POOL_PREFIX.as_bytes(), b"kept"].concat();
+    let deleted_keys = [
+        [range_starts[2].as_slice(), b"first"].concat(),
+        [range_starts[2].as_slice(), b"second"].concat(),
+    ];
+    final_state
+        .read()
+        .db
+        .write()
+        .write_range_batch_bootstrap_client(StreamBatch {
+            new_elements: deleted_keys
+                .iter()
+                .chain([&kept_key])
+                .map(|key| (key.clone(), vec![1, 2, 3]))
+                .collect(),
+            updates_on_previous_elements: BTreeMap::new(),
+            change_id: Slot::new(1, 0),
+        })
+        .unwrap();
+
+    let mut progress = ParallelProgress::new(&range_starts);
+    progress.tasks.clear();
+    for range in progress.ranges.iter_mut() {
+        range.step = StreamingStep::Finished(None);
+        range.slot = Some(Slot::new(1, 0));
+    }
+    assert!(progress.is_complete());
+
+    progress.restart_range(2, &final_state).unwrap();
+
+    // the range is streamed again from scratch, and our copy of it is deleted
+    assert!(!progress.is_complete());
+    assert_eq!(progress.tasks, [2]);
+    assert_eq!(progress.ranges[2].step, StreamingStep::Started);
+    assert_eq!(progress.ranges[2].slot, None);
+    assert!(progress.ranges[2].servers.is_empty());
+    assert_eq!(progress.ranges[1].slot, Some(Slot::new(1, 0)));
+    let final_state = final_state.read();
+    let db = final_state.db.read();
+    let handle_state = db.db.cf_handle(STATE_CF).expect(CF_ERROR);
+    for key in deleted_keys.iter() {
+        assert_eq!(db.db.get_cf(handle_state, key).unwrap(), None);
+    }
+    assert_eq!(
+        db.db.get_cf(handle_state, &kept_key).unwrap(),
+        Some(vec![1, 2, 3])
+    );
+}
+
+#[test]
+fn test_bootstrap_state_ranges() {
+    let dir = TempDir::new().unwrap();
+    let server_state =
+        create_test_final_state(&dir.path().join("server"), start_test_selector(), true);
+    let client_state =
+        create_test_final_state(&dir.path().join("client"), start_test_selector(), false);
+
+    // two servers sharing the same state
+    let servers: Vec<(SocketAddr, KeyPair)> = ["127.0.0.1:8070", "127.0.0.1:8071"]
+        .iter()
+        .map(|addr| (addr.parse().unwrap(), KeyPair::generate(0).unwrap()))
+        .collect();
+    let managers: Vec<BootstrapManager> = servers
+        .iter()
+        .map(|(addr, keypair)| start_server(*addr, keypair, server_state.clone()))
+        .collect();
+    let config = parallel_client_config(
+        servers
+            .iter()
+            .map(|(addr, keypair)| (*addr, NodeId::new(keypair.get_public_key())))
+            .collect(),
+    );
+
+    let bootstrap_res = get_state(
+        &config,
+        client_state.clone(),
+        connector_mock(),
+        Version::from_str("TEST.1.10").unwrap(),
+        MassaTime::now()
+            .unwrap()
+            .saturating_sub(MassaTime::from_millis(1000)),
+        None,
+        None,
+        Arc::new((Mutex::new(false), Condvar::new())),
+    )
+    .unwrap();
+
+    {
+        let mut client_state = client_state.write();
+        assert!(
+            client_state.is_db_valid(),
+            "Client's DB is not valid after bootstrap"
+        );
+        client_state.recompute_caches();
+    }
+    assert_eq_final_state(&server_state.read(), &client_state.read());
+    assert_eq_final_state_hash(&server_state.read(), &client_state.read());
+    assert_eq_bootstrap_graph(&get_boot_state(), &bootstrap_res.graph.unwrap());
+    assert!(bootstrap_res.peers.is_some());
+
+    for manager in managers {
+        manager.stop().expect("could not stop bootstrap server");
+    }
+}
+
+#[test]
+fn test_bootstrap_state_ranges_root_mismatch() {
+    let dir = TempDir::new().unwrap();
+    let server_state =
+        create_test_final_state(&dir.path().join("server"), start_test_selector(), true);
+    let client_state =
+        create_test_final_state(&dir.path().join("client"), start_test_selector(), false);
+
+    // an entry the server does not have: the assembled state can't match the state root of the server
+    client_state
+        .read()
+        .db
+        .write()
+        .write_range_batch_bootstrap_client(StreamBatch {
+            new_elements: BTreeMap::from([(
+                [ASYNC_POOL_PREFIX.as_bytes(), b"stray"].concat(),
+                vec![1, 2, 3],
+            )]),
+            updates_on_previous_elements: BTreeMap::new(),
+            change_id: Slot::new(0, 0),
+        })
+        .unwrap();
+
+    let addr: SocketAddr = "127.0.0.1:8072".parse().unwrap();
+    let keypair = KeyPair::generate(0).unwrap();
+    let manager = start_server(addr, &keypair, server_state);
+    let node_id = NodeId::new(keypair.get_public_key());
+    let config = parallel_client_config(vec![(addr, node_id), (addr, node_id)]);
+
+    let result = get_state_ranges(
+        &config,
+        &client_state,
+        &mut connector_mock(),
+        &config.bootstrap_list,
+        Version::from_str("TEST.1.10").unwrap(),
+        &(Mutex::new(false), Condvar::new()),
+    );
+    let Err(BootstrapError::GeneralError(message)) = result else {
+        panic!("a state that does not match the state root should be rejected");
+    };
+    assert!(
+        message.contains("does not match the state root"),
+        "{}",
+        message
+    );
+    // the state is not marked as bootstrapped
+    assert!(client_state
+        .read()
+        .db
+        .read()
+        .get_bootstrap_cursors()
+        .is_none());
+
+    manager.stop().expect("could not stop bootstrap server");
+}
+
+#[test]
+fn test_bootstrap_state_ranges_lying_server() {
+    let dir = TempDir::new().unwrap();
+    let client_state =
+        create_test_final_state(&dir.path().join("client"), start_test_selector(), false);
+
+    // two servers with different states: the one checking the state root sees the ranges of the other as lies
+    let servers: Vec<(SocketAddr, KeyPair, Arc<RwLock<FinalState>>)> =
+        ["127.0.0.1:8073", "127.0.0.1:8074"]
+            .iter()
+            .map(|addr| {
+                (
+                    addr.parse().unwrap(),
+                    KeyPair::generate(0).unwrap(),
+                    create_test_final_state(&dir.path().join(addr), start_test_selector(), true),
+                )
+            })
+            .collect();
+    let managers: Vec<BootstrapManager> = servers
+        .iter()
+        .map(|(addr, keypair, final_state)| start_server(*addr, keypair, final_state.clone()))
+        .collect();
+    let config = parallel_client_config(
+        servers
+            .iter()
+            .map(|(addr, keypair, _)| (*addr, NodeId::new(keypair.get_public_key())))
+            .collect(),
+    );
+
+    get_state_ranges(
+        &config,
+        &client_state,
+        &mut connector_mock(),
+        &config.bootstrap_list,
+        Version::from_str("TEST.1.10").unwrap(),
+        &(Mutex::new(false), Condvar::new()),
+    )
+    .expect("the ranges of the other server should be streamed again from the trusted one");
+
+    // the state is the one of a single server, not a mix of both
+    let client_hash = client_state.read().db.read().get_db_hash();
+    assert!(servers
+        .iter()
+        .any(|(_, _, final_state)| final_state.read().db.read().get_db_hash() == client_hash));
+
+    for manager in managers {
+        manager.stop().expect("could not stop bootstrap server");
+    }
+}
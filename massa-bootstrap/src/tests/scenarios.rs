@@ -24,6 +24,7 @@ use massa_final_state::{
     FinalState, FinalStateConfig, StateChanges,
 };
 use massa_ledger_exports::LedgerConfig;
+use massa_metrics::MassaMetrics;
 use massa_models::config::{
     DENUNCIATION_EXPIRE_PERIODS, ENDORSEMENT_COUNT, GENESIS_TIMESTAMP, MAX_DEFERRED_CREDITS_LENGTH,
     MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, T0,
@@ -93,6 +94,8 @@ fn mock_bootstrap_manager(addr: SocketAddr, bootstrap_config: BootstrapConfig) -
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
     let final_state_local_config = FinalStateConfig {
@@ -165,6 +168,7 @@ fn mock_bootstrap_manager(addr: SocketAddr, bootstrap_config: BootstrapConfig) -
         bootstrap_config.clone(),
         keypair.clone(),
         Version::from_str("TEST.1.10").unwrap(),
+        MassaMetrics::new(false, 32),
     )
     .unwrap()
 }
@@ -198,6 +202,8 @@ fn test_bootstrap_server() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db_server = Arc::new(RwLock::new(MassaDB::new(db_server_config)));
     let temp_dir_client = TempDir::new().unwrap();
@@ -206,6 +212,8 @@ fn test_bootstrap_server() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db_client = Arc::new(RwLock::new(MassaDB::new(db_client_config)));
     let final_state_local_config = FinalStateConfig {
@@ -410,6 +418,7 @@ fn test_bootstrap_server() {
                 bootstrap_config.clone(),
                 keypair.clone(),
                 Version::from_str("TEST.1.10").unwrap(),
+                MassaMetrics::new(false, 32),
             )
             .unwrap()
         })
@@ -0,0 +1,64 @@
+use crate::server::scheduler::{Admission, BootstrapScheduler, QueueStatus};
+use massa_metrics::MassaMetrics;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+fn get_scheduler(max_sessions: usize, max_queue_length: usize) -> BootstrapScheduler {
+    BootstrapScheduler::new(
+        max_sessions,
+        max_queue_length,
+        f64::INFINITY,
+        f64::INFINITY,
+        Duration::from_secs(60),
+        MassaMetrics::new(false, 32),
+    )
+}
+
+#[test]
+fn test_scheduler_queues_clients_when_sessions_are_taken() {
+    let scheduler = get_scheduler(1, 1);
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+
+    let Ok(Admission::Session(session)) = scheduler.admit(addr) else {
+        panic!("the first client should get a session");
+    };
+    let Ok(Admission::Queued(ticket)) = scheduler.admit(addr) else {
+        panic!("the second client should be queued");
+    };
+    let Err(refused_wait) = scheduler.admit(addr) else {
+        panic!("the third client should be refused: the queue is full");
+    };
+    assert!(refused_wait > Duration::from_secs(60));
+    assert_eq!(scheduler.session_count(), 1);
+
+    match ticket.wait_turn(Duration::ZERO) {
+        QueueStatus::Waiting(position, estimated_wait) => {
+            assert_eq!(position, 1);
+            assert!(estimated_wait <= Duration::from_secs(60));
+            assert!(estimated_wait > Duration::from_secs(50));
+        }
+        QueueStatus::Session(_) => panic!("no session should be available"),
+    }
+
+    // ending the session gives it to the queued client
+    drop(session);
+    let QueueStatus::Session(_session) = ticket.wait_turn(Duration::from_secs(1)) else {
+        panic!("the queued client should get the freed session");
+    };
+    assert_eq!(scheduler.session_count(), 1);
+}
+
+#[test]
+fn test_scheduler_frees_queue_place_of_leaving_clients() {
+    let scheduler = get_scheduler(1, 1);
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+
+    let Ok(Admission::Session(_session)) = scheduler.admit(addr) else {
+        panic!("the first client should get a session");
+    };
+    let Ok(Admission::Queued(ticket)) = scheduler.admit(addr) else {
+        panic!("the second client should be queued");
+    };
+    drop(ticket);
+    assert!(matches!(scheduler.admit(addr), Ok(Admission::Queued(_))));
+}
@@ -0,0 +1,204 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use super::tools::{
+    assert_eq_bootstrap_graph, create_test_final_state, get_boot_state, get_bootstrap_config,
+    start_test_selector,
+};
+use crate::{error::BootstrapError, export_snapshot, get_state_from_snapshot, BootstrapConfig};
+use massa_consensus_exports::{
+    bootstrapable_graph::BootstrapableGraph, test_exports::MockConsensusControllerImpl,
+};
+use massa_final_state::{test_exports::assert_eq_final_state, FinalState};
+use massa_models::{
+    node::NodeId, prehash::PreHashSet, streaming_step::StreamingStep, version::Version,
+};
+use massa_pos_exports::SelectorController;
+use massa_signature::KeyPair;
+use parking_lot::RwLock;
+use std::{path::Path, path::PathBuf, str::FromStr, sync::Arc};
+use tempfile::TempDir;
+
+/// Consensus mock streaming `graph` in a single part
+fn consensus_mock(graph: BootstrapableGraph) -> Box<MockConsensusControllerImpl> {
+    let mut consensus_mock = Box::new(MockConsensusControllerImpl::new());
+    consensus_mock
+        .expect_get_bootstrap_part()
+        .times(2)
+        .returning(
+            move |last_consensus_step, _slot| match last_consensus_step {
+                StreamingStep::Started => Ok((
+                    graph.clone(),
+                    PreHashSet::default(),
+                    StreamingStep::Ongoing(PreHashSet::default()),
+                )),
+                _ => Ok((
+                    BootstrapableGraph {
+                        final_blocks: vec![],
+                    },
+                    PreHashSet::default(),
+                    StreamingStep::Finished(None),
+                )),
+            },
+        );
+    consensus_mock
+}
+
+/// Exports a random final state to a snapshot file in `dir`
+fn write_snapshot(
+    dir: &TempDir,
+    selector_controller: Box<dyn SelectorController>,
+) -> (PathBuf, Arc<RwLock<FinalState>>) {
+    let final_state =
+        create_test_final_state(&dir.path().join("server"), selector_controller, true);
+    let path = dir.path().join("snapshot");
+    export_snapshot(
+        &path,
+        final_state.clone(),
+        consensus_mock(get_boot_state()),
+        Version::from_str("TEST.1.10").unwrap(),
+    )
+    .unwrap();
+    (path, final_state)
+}
+
+/// Imports the snapshot at `path` into a new empty final state
+fn read_snapshot(
+    dir: &TempDir,
+    path: &Path,
+    config: &BootstrapConfig,
+    selector_controller: Box<dyn SelectorController>,
+) -> Result<crate::GlobalBootstrapState, BootstrapError> {
+    let final_state =
+        create_test_final_state(&dir.path().join("client"), selector_controller, false);
+    get_state_from_snapshot(
+        path,
+        config,
+        final_state,
+        Version::from_str("TEST.1.10").unwrap(),
+    )
+}
+
+fn bootstrap_config() -> BootstrapConfig {
+    get_bootstrap_config(NodeId::new(KeyPair::generate(0).unwrap().get_public_key()))
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let selector_controller = start_test_selector();
+    let (path, server_state) = write_snapshot(&dir, selector_controller.clone());
+
+    let bootstrap_state =
+        read_snapshot(&dir, &path, &bootstrap_config(), selector_controller).unwrap();
+
+    {
+        let mut client_state = bootstrap_state.final_state.write();
+        assert!(
+            client_state.is_db_valid(),
+            "DB is not valid after importing the snapshot"
+        );
+        client_state.recompute_caches();
+    }
+    assert_eq_final_state(&server_state.read(), &bootstrap_state.final_state.read());
+    assert_eq_bootstrap_graph(
+        &bootstrap_state
+            .graph
+            .expect("snapshot should hold the graph"),
+        &get_boot_state(),
+    );
+    assert!(bootstrap_state.peers.is_none());
+}
+
+#[test]
+fn test_snapshot_bad_magic() {
+    let dir = TempDir::new().unwrap();
+    let selector_controller = start_test_selector();
+    let (path, _) = write_snapshot(&dir, selector_controller.clone());
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[..8].copy_from_slice(b"NOTASNAP");
+    std::fs::write(&path, bytes).unwrap();
+
+    let Err(BootstrapError::GeneralError(message)) =
+        read_snapshot(&dir, &path, &bootstrap_config(), selector_controller)
+    else {
+        panic!("a file without the snapshot magic should be rejected");
+    };
+    assert!(message.contains("is not a snapshot file"), "{}", message);
+}
+
+#[test]
+fn test_snapshot_bad_format_version() {
+    let dir = TempDir::new().unwrap();
+    let selector_controller = start_test_selector();
+    let (path, _) = write_snapshot(&dir, selector_controller.clone());
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[8..12].copy_from_slice(&2u32.to_be_bytes());
+    std::fs::write(&path, bytes).unwrap();
+
+    let Err(BootstrapError::GeneralError(message)) =
+        read_snapshot(&dir, &path, &bootstrap_config(), selector_controller)
+    else {
+        panic!("an unknown snapshot format version should be rejected");
+    };
+    assert!(
+        message.contains("unsupported snapshot format version 2"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_snapshot_hash_mismatch() {
+    let dir = TempDir::new().unwrap();
+    let selector_controller = start_test_selector();
+    let (path, _) = write_snapshot(&dir, selector_controller.clone());
+
+    // the db hash ends the header record, which follows the magic and the format version
+    let mut bytes = std::fs::read(&path).unwrap();
+    let header_len = u32::from_be_bytes(bytes[12..16].try_into().unwrap()) as usize;
+    bytes[16 + header_len - 1] ^= 0xff;
+    std::fs::write(&path, bytes).unwrap();
+
+    let Err(BootstrapError::GeneralError(message)) =
+        read_snapshot(&dir, &path, &bootstrap_config(), selector_controller)
+    else {
+        panic!("a state that does not match the recorded hash should be rejected");
+    };
+    assert!(
+        message.contains("does not match the recorded hash"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_snapshot_export_releases_state_locks() {
+    let dir = TempDir::new().unwrap();
+    let final_state =
+        create_test_final_state(&dir.path().join("server"), start_test_selector(), true);
+
+    // consensus is still running during the export: it must not be called with the state locked
+    let mut consensus_mock = Box::new(MockConsensusControllerImpl::new());
+    let exported_state = final_state.clone();
+    consensus_mock
+        .expect_get_bootstrap_part()
+        .times(1)
+        .returning(move |_, _| {
+            assert!(exported_state.read().db.try_write().is_some());
+            assert!(exported_state.try_write().is_some());
+            Ok((
+                BootstrapableGraph {
+                    final_blocks: vec![],
+                },
+                PreHashSet::default(),
+                StreamingStep::Finished(None),
+            ))
+        });
+    export_snapshot(
+        &dir.path().join("snapshot"),
+        final_state,
+        consensus_mock,
+        Version::from_str("TEST.1.10").unwrap(),
+    )
+    .unwrap();
+}
@@ -1,16 +1,16 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::settings::{BootstrapConfig, IpType};
+use crate::settings::{BootstrapCompression, BootstrapConfig, IpType};
 use bitvec::vec::BitVec;
-use massa_async_pool::AsyncPoolChanges;
 use massa_async_pool::{test_exports::get_random_message, AsyncPool};
+use massa_async_pool::{AsyncPoolChanges, AsyncPoolConfig};
 use massa_consensus_exports::{
     bootstrapable_graph::{
         BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
     },
     export_active_block::{ExportActiveBlock, ExportActiveBlockSerializer},
 };
-use massa_db::{DBBatch, MassaDB};
+use massa_db::{DBBatch, MassaDB, MassaDBConfig};
 use massa_executed_ops::{
     ExecutedDenunciations, ExecutedDenunciationsChanges, ExecutedDenunciationsConfig, ExecutedOps,
     ExecutedOpsConfig,
@@ -18,22 +18,23 @@ use massa_executed_ops::{
 use massa_final_state::test_exports::create_final_state;
 use massa_final_state::{FinalState, FinalStateConfig};
 use massa_hash::Hash;
-use massa_ledger_exports::{LedgerChanges, LedgerEntry, SetUpdateOrDelete};
+use massa_ledger_exports::{LedgerChanges, LedgerConfig, LedgerEntry, SetUpdateOrDelete};
 use massa_ledger_worker::test_exports::create_final_ledger;
 use massa_models::block::BlockDeserializerArgs;
 use massa_models::bytecode::Bytecode;
 use massa_models::config::{
-    BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CONSENSUS_BOOTSTRAP_PART_SIZE, ENDORSEMENT_COUNT,
-    MAX_ADVERTISE_LENGTH, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
-    MAX_BOOTSTRAPPED_NEW_ELEMENTS, MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS,
-    MAX_BOOTSTRAP_ERROR_LENGTH, MAX_CONSENSUS_BLOCKS_IDS, MAX_DATASTORE_ENTRY_COUNT,
-    MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH,
-    MAX_DENUNCIATIONS_PER_BLOCK_HEADER, MAX_DENUNCIATION_CHANGES_LENGTH,
-    MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH, MAX_FUNCTION_NAME_LENGTH,
-    MAX_LEDGER_CHANGES_COUNT, MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
-    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
-    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, MIP_STORE_STATS_BLOCK_CONSIDERED,
-    MIP_STORE_STATS_COUNTERS_MAX, PERIODS_PER_CYCLE, THREAD_COUNT,
+    BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CONSENSUS_BOOTSTRAP_PART_SIZE, DENUNCIATION_EXPIRE_PERIODS,
+    ENDORSEMENT_COUNT, GENESIS_TIMESTAMP, MAX_ADVERTISE_LENGTH, MAX_ASYNC_MESSAGE_DATA,
+    MAX_ASYNC_POOL_LENGTH, MAX_BOOTSTRAPPED_NEW_ELEMENTS, MAX_BOOTSTRAP_ASYNC_POOL_CHANGES,
+    MAX_BOOTSTRAP_BLOCKS, MAX_BOOTSTRAP_ERROR_LENGTH, MAX_CONSENSUS_BLOCKS_IDS,
+    MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH,
+    MAX_DEFERRED_CREDITS_LENGTH, MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+    MAX_DENUNCIATION_CHANGES_LENGTH, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
+    MAX_FUNCTION_NAME_LENGTH, MAX_LEDGER_CHANGES_COUNT, MAX_OPERATIONS_PER_BLOCK,
+    MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
+    MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE, MAX_PRODUCTION_STATS_LENGTH,
+    MAX_ROLLS_COUNT_LENGTH, MIP_STORE_STATS_BLOCK_CONSIDERED, MIP_STORE_STATS_COUNTERS_MAX,
+    PERIODS_PER_CYCLE, POS_SAVED_CYCLES, T0, THREAD_COUNT,
 };
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::node::NodeId;
@@ -52,7 +53,11 @@ use massa_models::{
     secure_share::SecureShareContent,
     slot::Slot,
 };
-use massa_pos_exports::{DeferredCredits, PoSChanges, PoSFinalState, ProductionStats};
+use massa_pos_exports::{
+    DeferredCredits, PoSChanges, PoSConfig, PoSFinalState, ProductionStats, SelectorConfig,
+    SelectorController,
+};
+use massa_pos_worker::start_selector_worker;
 use massa_protocol_exports::{BootstrapPeers, PeerId, TransportType};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::KeyPair;
@@ -66,7 +71,7 @@ use std::sync::Arc;
 use std::{
     collections::BTreeMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 // Use loop-back address. use port 0 to auto-assign a port
@@ -362,6 +367,101 @@ pub fn get_random_final_state_bootstrap(
     )
 }
 
+/// Thread count of the final states of the bootstrap tests
+const TEST_THREAD_COUNT: u8 = 2;
+/// Periods per cycle of the final states of the bootstrap tests
+const TEST_PERIODS_PER_CYCLE: u64 = 2;
+
+/// Config of the final states of the bootstrap tests, stored in `path`
+fn get_test_final_state_config(path: &Path) -> FinalStateConfig {
+    FinalStateConfig {
+        ledger_config: LedgerConfig {
+            thread_count: TEST_THREAD_COUNT,
+            initial_ledger_path: "".into(),
+            disk_ledger_path: path.to_path_buf(),
+            max_key_length: MAX_DATASTORE_KEY_LENGTH,
+            max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
+        },
+        async_pool_config: AsyncPoolConfig {
+            thread_count: TEST_THREAD_COUNT,
+            max_length: MAX_ASYNC_POOL_LENGTH,
+            max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
+            max_key_length: MAX_DATASTORE_KEY_LENGTH as u32,
+        },
+        pos_config: PoSConfig {
+            periods_per_cycle: TEST_PERIODS_PER_CYCLE,
+            thread_count: TEST_THREAD_COUNT,
+            cycle_history_length: POS_SAVED_CYCLES,
+            max_rolls_length: MAX_ROLLS_COUNT_LENGTH,
+            max_production_stats_length: MAX_PRODUCTION_STATS_LENGTH,
+            max_credit_length: MAX_DEFERRED_CREDITS_LENGTH,
+        },
+        executed_ops_config: ExecutedOpsConfig {
+            thread_count: TEST_THREAD_COUNT,
+        },
+        executed_denunciations_config: ExecutedDenunciationsConfig {
+            denunciation_expire_periods: DENUNCIATION_EXPIRE_PERIODS,
+            thread_count: TEST_THREAD_COUNT,
+            endorsement_count: ENDORSEMENT_COUNT,
+        },
+        final_history_length: 100,
+        initial_seed_string: "".into(),
+        initial_rolls_path: "".into(),
+        endorsement_count: ENDORSEMENT_COUNT,
+        max_executed_denunciations_length: 1000,
+        thread_count: TEST_THREAD_COUNT,
+        periods_per_cycle: TEST_PERIODS_PER_CYCLE,
+        max_denunciations_per_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
+        t0: T0,
+        genesis_timestamp: *GENESIS_TIMESTAMP,
+    }
+}
+
+/// Creates a final state in `path`, filled with random data if `random` is set
+pub fn create_test_final_state(
+    path: &Path,
+    selector_controller: Box<dyn SelectorController>,
+    random: bool,
+) -> Arc<RwLock<FinalState>> {
+    let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
+    let db = Arc::new(RwLock::new(MassaDB::new(MassaDBConfig {
+        path: path.to_path_buf(),
+        max_history_length: 10,
+        max_new_elements: 100,
+        thread_count: TEST_THREAD_COUNT,
+        enable_archive: false,
+        max_archive_periods: 1000,
+    })));
+    let config = get_test_final_state_config(path);
+    let pos_state = PoSFinalState::new(
+        config.pos_config.clone(),
+        "",
+        &rolls_path,
+        selector_controller,
+        db.clone(),
+    )
+    .unwrap();
+    let final_state = if random {
+        get_random_final_state_bootstrap(pos_state, config, db)
+    } else {
+        FinalState::create_final_state(pos_state, config, db)
+    };
+    Arc::new(RwLock::new(final_state))
+}
+
+/// Starts a selector for the final states of the bootstrap tests
+pub fn start_test_selector() -> Box<dyn SelectorController> {
+    let genesis_address = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+    let (_selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
+        thread_count: TEST_THREAD_COUNT,
+        periods_per_cycle: TEST_PERIODS_PER_CYCLE,
+        genesis_address,
+        ..Default::default()
+    })
+    .expect("could not start selector controller");
+    selector_controller
+}
+
 pub fn get_dummy_block_id(s: &str) -> BlockId {
     BlockId(Hash::compute_from(s.as_bytes()))
 }
@@ -398,9 +498,14 @@ pub fn get_bootstrap_config(bootstrap_public_key: NodeId) -> BootstrapConfig {
         max_clock_delta: MassaTime::from_millis(1000),
         cache_duration: MassaTime::from_millis(10000),
         max_simultaneous_bootstraps: 2,
+        max_bootstrap_queue_length: 2,
+        max_queue_wait: MassaTime::from_millis(120000),
         ip_list_max_size: 10,
         per_ip_min_interval: MassaTime::from_millis(10000),
         max_bytes_read_write: std::f64::INFINITY,
+        max_bootstrap_bandwidth: std::f64::INFINITY,
+        compression: BootstrapCompression::Zstd,
+        parallel_servers: 1,
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
         randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
         thread_count: THREAD_COUNT,
@@ -7,8 +7,11 @@ use console::style;
 use massa_api_exports::{
     address::{AddressInfo, CompactAddressInfo},
     datastore::DatastoreEntryInput,
-    execution::{ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        GasEstimationRequest, GasEstimationTarget, ReadOnlyBytecodeExecution, ReadOnlyCall,
+    },
     operation::OperationInput,
+    page::AddressHistoryPageRequest,
 };
 use massa_models::node::NodeId;
 use massa_models::prehash::PreHashMap;
@@ -18,7 +21,7 @@ use massa_models::{
     amount::Amount,
     block_id::BlockId,
     endorsement::EndorsementId,
-    execution::EventFilter,
+    execution::{AddressHistoryCursor, EventFilter},
     operation::{Operation, OperationId, OperationType},
     slot::Slot,
 };
@@ -33,7 +36,7 @@ use serde::Serialize;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write as _;
 use std::fmt::{Debug, Display};
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use strum::{EnumMessage, EnumProperty, IntoEnumIterator};
 use strum_macros::{Display, EnumIter, EnumString};
@@ -88,6 +91,27 @@ pub enum Command {
     )]
     node_ban_by_id,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(pwd_not_needed = "true"),
+        message = "show the peers currently connected to the node"
+    )]
+    node_get_peers,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SocketAddr", pwd_not_needed = "true"),
+        message = "try to connect to the given peer address"
+    )]
+    node_connect_peer,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Id", pwd_not_needed = "true"),
+        message = "close the connection with the given peer id"
+    )]
+    node_disconnect_peer,
+
     #[strum(
         ascii_case_insensitive,
         props(pwd_not_needed = "true"),
@@ -186,10 +210,27 @@ pub enum Command {
     )]
     get_operations,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationId1 OperationId2 ...", pwd_not_needed = "true"),
+        message = "show the execution receipts of a list of operations (gas used, balance changes, events ...)"
+    )]
+    get_operation_receipts,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "Address Limit CursorSlot CursorOperationId",
+            pwd_not_needed = "true"
+        ),
+        message = "show the final operations sent or received by an address, most recent first. The cursor (slot_period,slot_thread OperationId) is optional"
+    )]
+    get_address_history,
+
     #[strum(
         ascii_case_insensitive,
         props(
-            args = "start=slot_period,slot_thread end=slot_period,slot_thread emitter_address=Address caller_address=Address operation_id=OperationId is_final=bool is_error=bool",
+            args = "start=slot_period,slot_thread end=slot_period,slot_thread emitter_address=Address caller_address=Address operation_id=OperationId is_final=bool is_error=bool data_prefix=String data_substring=String call_stack_address=Address",
             pwd_not_needed = "true"
         ),
         message = "show events emitted by smart contracts with various filters"
@@ -268,14 +309,14 @@ pub enum Command {
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress PathToBytecode MaxGas MaxCoins Fee"),
-        message = "create and send an operation containing byte code"
+        message = "create and send an operation containing byte code, MaxGas can be set to auto to estimate it"
     )]
     execute_smart_contract,
 
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress TargetAddress FunctionName Parameter MaxGas Coins Fee"),
-        message = "create and send an operation to call a function of a smart contract"
+        message = "create and send an operation to call a function of a smart contract, MaxGas can be set to auto to estimate it"
     )]
     call_smart_contract,
 
@@ -518,6 +559,43 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_get_peers => match client.private.node_get_peers().await {
+                Ok(peers) => Ok(Box::new(peers)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_connect_peer => {
+                if parameters.len() != 1 {
+                    bail!("invalid number of parameters");
+                }
+                let addr = parameters[0].parse::<SocketAddr>()?;
+                match client.private.node_connect_peer(addr).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Request of connection successfully sent!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_disconnect_peer => {
+                if parameters.len() != 1 {
+                    bail!("invalid number of parameters");
+                }
+                let node_id = parameters[0].parse::<NodeId>()?;
+                match client.private.node_disconnect_peer(node_id).await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Request of disconnection successfully sent!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
             Command::node_stop => {
                 match client.private.stop_node().await {
                     Ok(()) => {
@@ -626,8 +704,40 @@ impl Command {
                 }
             }
 
+            Command::get_operation_receipts => {
+                let operations = parse_vec::<OperationId>(parameters)?;
+                match client.public.get_operation_receipts(operations).await {
+                    Ok(receipts) => Ok(Box::new(receipts)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_address_history => {
+                if parameters.len() != 2 && parameters.len() != 4 {
+                    bail!("wrong number of parameters");
+                }
+                let address = parameters[0].parse::<Address>()?;
+                let limit = parameters[1].parse::<usize>()?;
+                let cursor = if parameters.len() == 4 {
+                    Some(AddressHistoryCursor {
+                        slot: parameters[2].parse::<Slot>()?,
+                        operation_id: parameters[3].parse::<OperationId>()?,
+                    })
+                } else {
+                    None
+                };
+                match client
+                    .public
+                    .get_address_history(address, AddressHistoryPageRequest { limit, cursor })
+                    .await
+                {
+                    Ok(page) => Ok(Box::new(page)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_filtered_sc_output_event => {
-                let p_list: [&str; 7] = [
+                let p_list: [&str; 10] = [
                     "start",
                     "end",
                     "emitter_address",
@@ -635,6 +745,9 @@ impl Command {
                     "operation_id",
                     "is_final",
                     "is_error",
+                    "data_prefix",
+                    "data_substring",
+                    "call_stack_address",
                 ];
                 let mut p: HashMap<&str, &str> = HashMap::new();
                 for v in parameters {
@@ -653,6 +766,9 @@ impl Command {
                     original_operation_id: parse_key_value(&p, p_list[4])?,
                     is_final: parse_key_value(&p, p_list[5])?,
                     is_error: parse_key_value(&p, p_list[6])?,
+                    data_prefix: parse_key_value(&p, p_list[7])?,
+                    data_substring: parse_key_value(&p, p_list[8])?,
+                    call_stack_address: parse_key_value(&p, p_list[9])?,
                 };
                 match client.public.get_filtered_sc_output_event(filter).await {
                     Ok(events) => Ok(Box::new(events)),
@@ -1030,7 +1146,7 @@ impl Command {
                 }
                 let addr = parameters[0].parse::<Address>()?;
                 let path = parameters[1].parse::<PathBuf>()?;
-                let max_gas = parameters[2].parse::<u64>()?;
+                let max_gas = parse_max_gas(&parameters[2])?;
                 let max_coins = parameters[3].parse::<Amount>()?;
                 let fee = parameters[4].parse::<Amount>()?;
                 if !json {
@@ -1058,6 +1174,23 @@ impl Command {
                     }
                 }
                 let datastore = BTreeMap::new();
+                let max_gas = match max_gas {
+                    Some(max_gas) => max_gas,
+                    None => {
+                        estimate_max_gas(
+                            client,
+                            GasEstimationRequest {
+                                caller_address: addr,
+                                target: GasEstimationTarget::ExecuteSC {
+                                    bytecode: data.clone(),
+                                    operation_datastore: None,
+                                },
+                            },
+                            json,
+                        )
+                        .await?
+                    }
+                };
 
                 send_operation(
                     client,
@@ -1084,7 +1217,7 @@ impl Command {
                 let target_addr = parameters[1].parse::<Address>()?;
                 let target_func = parameters[2].clone();
                 let param = parameters[3].clone().into_bytes();
-                let max_gas = parameters[4].parse::<u64>()?;
+                let max_gas = parse_max_gas(&parameters[4])?;
                 let coins = parameters[5].parse::<Amount>()?;
                 let fee = parameters[6].parse::<Amount>()?;
                 if !json {
@@ -1113,6 +1246,25 @@ impl Command {
                         }
                     }
                 };
+                let max_gas = match max_gas {
+                    Some(max_gas) => max_gas,
+                    None => {
+                        estimate_max_gas(
+                            client,
+                            GasEstimationRequest {
+                                caller_address: addr,
+                                target: GasEstimationTarget::CallSC {
+                                    target_address: target_addr,
+                                    target_function: target_func.clone(),
+                                    parameter: param.clone(),
+                                    coins,
+                                },
+                            },
+                            json,
+                        )
+                        .await?
+                    }
+                };
                 send_operation(
                     client,
                     wallet,
@@ -1169,6 +1321,8 @@ impl Command {
                         address,
                         operation_datastore: None, // TODO - #3072
                         is_final,
+                        at_slot: None,
+                        trace: false,
                     })
                     .await
                 {
@@ -1204,6 +1358,8 @@ impl Command {
                         parameter,
                         max_gas,
                         is_final,
+                        at_slot: None,
+                        trace: false,
                     })
                     .await
                 {
@@ -1443,6 +1599,37 @@ async fn send_operation(
     }
 }
 
+/// parses a `MaxGas` parameter, `None` meaning that it should be estimated
+fn parse_max_gas(value: &str) -> Result<Option<u64>> {
+    if value.eq_ignore_ascii_case("auto") {
+        Ok(None)
+    } else {
+        Ok(Some(value.parse::<u64>()?))
+    }
+}
+
+/// helper to estimate the `max_gas` of a smart contract operation through the node
+async fn estimate_max_gas(
+    client: &Client,
+    request: GasEstimationRequest,
+    json: bool,
+) -> Result<u64> {
+    let estimation = match client.public.estimate_gas(request).await {
+        Ok(estimation) => estimation,
+        Err(e) => rpc_error!(e),
+    };
+    if let Some(e) = estimation.error {
+        bail!("unable to estimate the max gas: {}", e);
+    }
+    if !json {
+        println!(
+            "Estimated gas: {} (max gas set to {})",
+            estimation.gas_used, estimation.max_gas
+        );
+    }
+    Ok(estimation.max_gas)
+}
+
 /// TODO: ugly utilities functions
 /// takes a slice of string and makes it into a `Vec<T>`
 pub fn parse_vec<T: std::str::FromStr>(args: &[String]) -> anyhow::Result<Vec<T>, anyhow::Error>
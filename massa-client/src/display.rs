@@ -4,17 +4,25 @@ use crate::cmds::ExtendedWallet;
 use console::style;
 use erased_serde::{Serialize, Serializer};
 use massa_api_exports::{
-    address::AddressInfo, block::BlockInfo, datastore::DatastoreEntryOutput,
-    endorsement::EndorsementInfo, execution::ExecuteReadOnlyResponse, node::NodeStatus,
-    operation::OperationInfo,
+    address::AddressInfo,
+    block::BlockInfo,
+    datastore::DatastoreEntryOutput,
+    endorsement::EndorsementInfo,
+    execution::ExecuteReadOnlyResponse,
+    node::NodeStatus,
+    operation::{OperationInfo, OperationReceipt},
+    page::AddressHistoryPage,
 };
 use massa_models::composite::PubkeySig;
+use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
 use massa_models::stats::{ConsensusStats, ExecutionStats, NetworkStats};
 use massa_models::{address::Address, config::CompactConfig, operation::OperationId};
+use massa_protocol_exports::ConnectedPeerInfo;
 use massa_signature::{KeyPair, PublicKey};
 use massa_wallet::Wallet;
+use std::collections::BTreeMap;
 use std::net::IpAddr;
 use std::str;
 
@@ -298,6 +306,53 @@ impl Output for ExecutionStats {
             "\tActive cursor: {}",
             Style::Protocol.style(self.active_cursor)
         );
+        println!("\tFinal gas used by operation type:");
+        for (operation_type, gas_used) in &self.gas_used_by_operation_type {
+            println!(
+                "\t\t{}: {}",
+                operation_type,
+                Style::Protocol.style(gas_used)
+            );
+        }
+        println!("\tTop gas consuming functions:");
+        for function in &self.top_gas_functions {
+            println!(
+                "\t\t{}::{}: {} gas over {} calls",
+                Style::Wallet.style(function.address),
+                function.function,
+                Style::Protocol.style(function.gas_used),
+                Style::Protocol.style(function.call_count)
+            );
+        }
+        println!("\tSlowest final slots:");
+        for slot in &self.slowest_slots {
+            println!(
+                "\t\t{}: {} ms, {} gas",
+                Style::Protocol.style(slot.slot),
+                Style::Time.style(slot.execution_time.to_millis()),
+                Style::Protocol.style(slot.gas_used)
+            );
+        }
+        let module_cache = &self.module_cache;
+        println!("\tModule cache:");
+        println!(
+            "\t\tHits: {} in memory, {} on disk",
+            Style::Protocol.style(module_cache.lru_hits),
+            Style::Protocol.style(module_cache.hd_hits)
+        );
+        println!("\t\tMisses: {}", Style::Protocol.style(module_cache.misses));
+        if let Some(hit_rate) = module_cache.hit_rate() {
+            println!(
+                "\t\tHit rate: {}",
+                Style::Protocol.style(format!("{:.2}%", hit_rate * 100.0))
+            );
+        }
+        println!(
+            "\t\tCompilations: {} in {} ms (longest {} ms)",
+            Style::Protocol.style(module_cache.compilation_count),
+            Style::Time.style(module_cache.compilation_time.to_millis()),
+            Style::Time.style(module_cache.max_compilation_time.to_millis())
+        );
     }
 }
 
@@ -487,6 +542,46 @@ impl Output for Vec<IpAddr> {
     }
 }
 
+impl Output for BTreeMap<NodeId, ConnectedPeerInfo> {
+    fn pretty_print(&self) {
+        for (node_id, peer) in self {
+            println!(
+                "Node {} at {} ({}{})",
+                Style::Id.style(node_id),
+                Style::Protocol.style(peer.address),
+                if peer.is_outgoing { "out" } else { "in" },
+                peer.category
+                    .as_ref()
+                    .map(|category| format!(", {}", category))
+                    .unwrap_or_default()
+            );
+            if let Some(connected_since) = peer.connected_since {
+                println!(
+                    "\tConnected since: {}",
+                    Style::Time.style(connected_since.format_instant())
+                );
+            }
+            if let Some(last_message_time) = peer.last_message_time {
+                println!(
+                    "\tLast message: {}",
+                    Style::Time.style(last_message_time.format_instant())
+                );
+            }
+            println!(
+                "\tBytes received/sent: {}/{}",
+                Style::Protocol.style(peer.bytes_received),
+                Style::Protocol.style(peer.bytes_sent)
+            );
+            println!(
+                "\tKnown blocks/operations/endorsements: {}/{}/{}",
+                Style::Block.style(peer.known_blocks),
+                Style::Protocol.style(peer.known_operations),
+                Style::Protocol.style(peer.known_endorsements)
+            );
+        }
+    }
+}
+
 impl Output for Vec<OperationInfo> {
     fn pretty_print(&self) {
         for info in self {
@@ -548,6 +643,26 @@ impl Output for Vec<OperationInfo> {
     }
 }
 
+impl Output for Vec<OperationReceipt> {
+    fn pretty_print(&self) {
+        for receipt in self {
+            println!("{}", style("==========").color256(237));
+            println!("{}", receipt);
+        }
+    }
+}
+
+impl Output for AddressHistoryPage {
+    fn pretty_print(&self) {
+        for entry in &self.content {
+            println!("{}", entry);
+        }
+        if let Some(cursor) = &self.next_cursor {
+            println!("Next page cursor: {} {}", cursor.slot, cursor.operation_id);
+        }
+    }
+}
+
 impl Output for Vec<BlockInfo> {
     fn pretty_print(&self) {
         for block_info in self {
@@ -0,0 +1,334 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Archive of the previous values of the final state entries, to read the state at past slots.
+//!
+//! When the archive is enabled, every change written to the state at a slot also stores the
+//! value the key had before that slot, in the same write batch as the change itself.
+//! The value of a key after a slot is then the previous value stored by the first change
+//! archived after that slot, or its current value if it has not changed since.
+//!
+//! Two column families are used:
+//! * `ARCHIVE_CF` maps `key length || key || slot` to the archived value, so that the changes
+//!   of a key are contiguous and ordered by slot;
+//! * `ARCHIVE_SLOTS_CF` maps `slot || key` to nothing, so that the changes are ordered by slot,
+//!   to prune the oldest ones and to list the keys changed since a slot.
+//!
+//! The archive covers the slots finalized since `ARCHIVE_START_KEY`, at most `max_archive_periods`
+//! periods back. It restarts from scratch when the state is reset or received through a bootstrap.
+
+use crate::{
+    DBBatch, MassaDBError, RawMassaDB, ARCHIVE_CF, ARCHIVE_SLOTS_CF, ARCHIVE_START_KEY, CF_ERROR,
+    CRUD_ERROR, METADATA_CF, STATE_CF,
+};
+use massa_models::slot::{Slot, SlotDeserializer, SlotSerializer, SLOT_KEY_SIZE};
+use massa_serialization::{Deserializer, Serializer};
+use rocksdb::{Direction, IteratorMode, ReadOptions, WriteBatch};
+use std::collections::{BTreeSet, HashSet};
+
+/// Archived value of a key that did not exist
+const ABSENT: u8 = 0;
+/// Prefix of the archived value of a key that existed
+const PRESENT: u8 = 1;
+
+/// Prefix of the archive keys of the changes of `key`
+fn archive_key_prefix(key: &[u8]) -> Vec<u8> {
+    [&(key.len() as u32).to_be_bytes()[..], key].concat()
+}
+
+/// Archive key of the value of `key` before the changes of `slot`
+fn archive_key(key: &[u8], slot: &Slot) -> Vec<u8> {
+    [&archive_key_prefix(key)[..], &slot.to_bytes_key()[..]].concat()
+}
+
+/// Key of the change of `key` at `slot` in the slot index
+fn slot_index_key(key: &[u8], slot: &Slot) -> Vec<u8> {
+    [&slot.to_bytes_key()[..], key].concat()
+}
+
+/// Splits a key of the slot index into the slot of the change and the state key.
+/// Returns None for malformed keys.
+fn split_slot_index_key(index_key: &[u8]) -> Option<(Slot, &[u8])> {
+    if index_key.len() < SLOT_KEY_SIZE {
+        return None;
+    }
+    let (slot, key) = index_key.split_at(SLOT_KEY_SIZE);
+    Some((Slot::from_bytes_key(slot.try_into().ok()?), key))
+}
+
+/// Decodes an archived value
+fn decode_archived_value(value: &[u8]) -> Result<Option<Vec<u8>>, MassaDBError> {
+    match value.split_first() {
+        Some((&ABSENT, [])) => Ok(None),
+        Some((&PRESENT, value)) => Ok(Some(value.to_vec())),
+        _ => Err(MassaDBError::ArchiveError(
+            "malformed archived value".to_string(),
+        )),
+    }
+}
+
+impl<ChangeID, ChangeIDSerializer, ChangeIDDeserializer>
+    RawMassaDB<ChangeID, ChangeIDSerializer, ChangeIDDeserializer>
+where
+    ChangeID: PartialOrd + Ord + PartialEq + Eq + Clone + std::fmt::Debug,
+    ChangeIDSerializer: Serializer<ChangeID>,
+    ChangeIDDeserializer: Deserializer<ChangeID>,
+{
+    /// Whether the previous values of the state entries are archived
+    pub fn archive_enabled(&self) -> bool {
+        self.config.enable_archive
+    }
+
+    /// Deletes the whole archive, which will restart with the next archived change
+    pub fn clear_archive(&self) {
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        if self
+            .db
+            .get_cf(handle_metadata, ARCHIVE_START_KEY)
+            .expect(CRUD_ERROR)
+            .is_none()
+        {
+            return;
+        }
+
+        // range deletions keep the batch small whatever the size of the archive
+        let mut batch = WriteBatch::default();
+        for cf in [ARCHIVE_CF, ARCHIVE_SLOTS_CF] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            let last_entry = self
+                .db
+                .iterator_cf(handle, IteratorMode::End)
+                .flatten()
+                .next();
+            let Some((last_key, _)) = last_entry else {
+                continue;
+            };
+            // the end of a range deletion is excluded
+            batch.delete_range_cf(handle, &[][..], &last_key[..]);
+            batch.delete_cf(handle, last_key);
+        }
+        batch.delete_cf(handle_metadata, ARCHIVE_START_KEY);
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+}
+
+impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
+    /// Gets the first slot after which the state can be read from the archive, if any
+    fn get_archive_start(&self) -> Result<Option<Slot>, MassaDBError> {
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle_metadata, ARCHIVE_START_KEY)
+            .map_err(|e| MassaDBError::RocksDBError(e.to_string()))?
+            .map(|bytes| {
+                Ok(Slot::from_bytes_key(&bytes.try_into().map_err(|_| {
+                    MassaDBError::ArchiveError("malformed archive start slot".to_string())
+                })?))
+            })
+            .transpose()
+    }
+
+    /// Builds the batch archiving the current value of every key changed by `changes`,
+    /// before they are written at `change_id` (or at the current change_id if None).
+    /// The batch also prunes the changes older than `max_archive_periods`.
+    ///
+    /// If a key changes several times in the same slot, only its value before the first change is kept.
+    pub(crate) fn archive_changes(
+        &self,
+        changes: &DBBatch,
+        change_id: Option<Slot>,
+    ) -> Result<WriteBatch, MassaDBError> {
+        let current_slot = self
+            .get_change_id()
+            .map_err(|e| MassaDBError::ArchiveError(e.to_string()))?;
+        let slot = change_id.unwrap_or(current_slot);
+
+        let handle_state = self.db.cf_handle(STATE_CF).expect(CF_ERROR);
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let handle_archive = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+        let handle_slots = self.db.cf_handle(ARCHIVE_SLOTS_CF).expect(CF_ERROR);
+
+        let mut batch = WriteBatch::default();
+        let mut start = match self.get_archive_start()? {
+            Some(start) => start,
+            None => {
+                batch.put_cf(
+                    handle_metadata,
+                    ARCHIVE_START_KEY,
+                    current_slot.to_bytes_key(),
+                );
+                current_slot
+            }
+        };
+
+        for key in changes.keys() {
+            let archive_key = archive_key(key, &slot);
+            if self
+                .db
+                .get_pinned_cf(handle_archive, &archive_key)
+                .map_err(|e| MassaDBError::RocksDBError(e.to_string()))?
+                .is_some()
+            {
+                continue;
+            }
+            let archived_value = match self
+                .db
+                .get_cf(handle_state, key)
+                .map_err(|e| MassaDBError::RocksDBError(e.to_string()))?
+            {
+                Some(value) => [&[PRESENT][..], &value[..]].concat(),
+                None => vec![ABSENT],
+            };
+            batch.put_cf(handle_archive, archive_key, archived_value);
+            batch.put_cf(handle_slots, slot_index_key(key, &slot), []);
+        }
+
+        // prune the changes that are no longer needed to read the retained slots
+        if let Some(period) = slot.period.checked_sub(self.config.max_archive_periods) {
+            let cutoff = Slot::new(period, slot.thread);
+            if cutoff > start {
+                // the changes up to the archive start are already pruned:
+                // only the slot index entries from the start to the cutoff are read
+                let range_start = start.to_bytes_key().to_vec();
+                let range_end = cutoff
+                    .get_next_slot(self.config.thread_count)
+                    .map_err(|e| MassaDBError::ArchiveError(e.to_string()))?
+                    .to_bytes_key()
+                    .to_vec();
+                let mut opt = ReadOptions::default();
+                opt.set_iterate_range(range_start.clone()..range_end.clone());
+                for (index_key, _) in self
+                    .db
+                    .iterator_cf_opt(handle_slots, opt, IteratorMode::Start)
+                    .flatten()
+                {
+                    if let Some((change_slot, key)) = split_slot_index_key(&index_key) {
+                        batch.delete_cf(handle_archive, archive_key(key, &change_slot));
+                    }
+                }
+                batch.delete_range_cf(handle_slots, range_start, range_end);
+                start = cutoff;
+                batch.put_cf(handle_metadata, ARCHIVE_START_KEY, start.to_bytes_key());
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Checks that the state after `slot` can be read from the archive
+    pub fn check_archived_slot(&self, slot: &Slot) -> Result<(), MassaDBError> {
+        if !self.archive_enabled() {
+            return Err(MassaDBError::ArchiveError(
+                "archive mode is disabled on this node".to_string(),
+            ));
+        }
+        let current_slot = self
+            .get_change_id()
+            .map_err(|e| MassaDBError::ArchiveError(e.to_string()))?;
+        let start = self.get_archive_start()?.unwrap_or(current_slot);
+        if *slot < start {
+            return Err(MassaDBError::ArchiveError(format!(
+                "slot {} is before the start of the archive at slot {}",
+                slot, start
+            )));
+        }
+        if *slot > current_slot {
+            return Err(MassaDBError::ArchiveError(format!(
+                "slot {} is after the last final slot {}",
+                slot, current_slot
+            )));
+        }
+        Ok(())
+    }
+
+    /// Gets the archived value of `key` before its first change after `slot`, if it changed since
+    fn get_first_archived_change_after(
+        &self,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Option<Vec<u8>>>, MassaDBError> {
+        let handle_archive = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+        let prefix = archive_key_prefix(key);
+        // the changes of the key are contiguous and ordered by slot
+        for (archive_key, value) in self
+            .db
+            .iterator_cf(
+                handle_archive,
+                IteratorMode::From(&archive_key(key, slot), Direction::Forward),
+            )
+            .flatten()
+        {
+            let Some(change_slot) = archive_key.strip_prefix(&prefix[..]) else {
+                break;
+            };
+            let change_slot =
+                Slot::from_bytes_key(change_slot.try_into().map_err(|_| {
+                    MassaDBError::ArchiveError("malformed archive key".to_string())
+                })?);
+            if change_slot > *slot {
+                return decode_archived_value(&value).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Gets the value of a state key right after the changes of `slot`
+    pub fn get_value_at(&self, key: &[u8], slot: &Slot) -> Result<Option<Vec<u8>>, MassaDBError> {
+        self.check_archived_slot(slot)?;
+
+        if let Some(archived_value) = self.get_first_archived_change_after(key, slot)? {
+            return Ok(archived_value);
+        }
+        let handle_state = self.db.cf_handle(STATE_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle_state, key)
+            .map_err(|e| MassaDBError::RocksDBError(e.to_string()))
+    }
+
+    /// Gets the state keys starting with `prefix` that existed right after the changes of `slot`
+    pub fn get_keys_with_prefix_at(
+        &self,
+        prefix: &[u8],
+        slot: &Slot,
+    ) -> Result<BTreeSet<Vec<u8>>, MassaDBError> {
+        self.check_archived_slot(slot)?;
+
+        let handle_state = self.db.cf_handle(STATE_CF).expect(CF_ERROR);
+        let mut keys: BTreeSet<Vec<u8>> = self
+            .db
+            .iterator_cf(handle_state, IteratorMode::From(prefix, Direction::Forward))
+            .flatten()
+            .map(|(key, _)| key.to_vec())
+            .take_while(|key| key.starts_with(prefix))
+            .collect();
+
+        // the keys changed since the slot existed at that slot if their first change archived a value
+        let handle_slots = self.db.cf_handle(ARCHIVE_SLOTS_CF).expect(CF_ERROR);
+        let mut seen = HashSet::new();
+        for (index_key, _) in self
+            .db
+            .iterator_cf(
+                handle_slots,
+                IteratorMode::From(&slot.to_bytes_key(), Direction::Forward),
+            )
+            .flatten()
+        {
+            let Some((change_slot, key)) = split_slot_index_key(&index_key) else {
+                continue;
+            };
+            if change_slot <= *slot || !key.starts_with(prefix) || !seen.insert(key.to_vec()) {
+                continue;
+            }
+            match self.get_first_archived_change_after(key, slot)? {
+                Some(Some(_)) => keys.insert(key.to_vec()),
+                Some(None) => keys.remove(key),
+                None => {
+                    return Err(MassaDBError::ArchiveError(format!(
+                        "missing archived change of key {:?} at slot {}",
+                        key, change_slot
+                    )))
+                }
+            };
+        }
+
+        Ok(keys)
+    }
+}
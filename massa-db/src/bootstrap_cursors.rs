@@ -0,0 +1,260 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Streaming cursors of an ongoing bootstrap, persisted so that an interrupted bootstrap
+//! can resume where it stopped, even after the node restarts.
+//!
+//! The cursors are written after each received part, so they never get ahead of the state:
+//! if the node stops in between, the last part is simply streamed again.
+
+use crate::{MassaDBError, RawMassaDB, BOOTSTRAP_CURSORS_KEY, CF_ERROR, CRUD_ERROR, METADATA_CF};
+use massa_models::{
+    serialization::{VecU8Deserializer, VecU8Serializer},
+    streaming_step::{StreamingStep, StreamingStepDeserializer, StreamingStepSerializer},
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{Options, DB};
+use std::{ops::Bound::Included, path::Path};
+
+type Key = Vec<u8>;
+
+/// Serializes the state and versioning cursors
+fn serialize_cursors(
+    state_step: &StreamingStep<Key>,
+    versioning_step: &StreamingStep<Key>,
+) -> Result<Vec<u8>, MassaDBError> {
+    let serializer = StreamingStepSerializer::new(VecU8Serializer::new());
+    let mut bytes = Vec::new();
+    for step in [state_step, versioning_step] {
+        serializer
+            .serialize(step, &mut bytes)
+            .map_err(|e| MassaDBError::SerializeError(e.to_string()))?;
+    }
+    Ok(bytes)
+}
+
+/// Deserializes the state and versioning cursors
+fn deserialize_cursors(bytes: &[u8]) -> Option<(StreamingStep<Key>, StreamingStep<Key>)> {
+    let deserializer =
+        StreamingStepDeserializer::new(VecU8Deserializer::new(Included(0), Included(u64::MAX)));
+    let (rest, state_step) = deserializer.deserialize::<DeserializeError>(bytes).ok()?;
+    let (rest, versioning_step) = deserializer.deserialize::<DeserializeError>(rest).ok()?;
+    rest.is_empty().then_some((state_step, versioning_step))
+}
+
+/// Whether the database at `path` holds the partial state of an interrupted bootstrap.
+///
+/// Opens the database read-only, so it must be called before the node opens it.
+pub fn has_pending_bootstrap(path: &Path) -> bool {
+    if !path.exists() {
+        return false;
+    }
+    let Ok(db) = DB::open_cf_for_read_only(&Options::default(), path, [METADATA_CF], false)
+    else {
+        return false;
+    };
+    let Some(handle_metadata) = db.cf_handle(METADATA_CF) else {
+        return false;
+    };
+    matches!(
+        db.get_cf(handle_metadata, BOOTSTRAP_CURSORS_KEY),
+        Ok(Some(_))
+    )
+}
+
+impl<ChangeID, ChangeIDSerializer, ChangeIDDeserializer>
+    RawMassaDB<ChangeID, ChangeIDSerializer, ChangeIDDeserializer>
+where
+    ChangeID: PartialOrd + Ord + PartialEq + Eq + Clone + std::fmt::Debug,
+    ChangeIDSerializer: Serializer<ChangeID>,
+    ChangeIDDeserializer: Deserializer<ChangeID>,
+{
+    /// Saves the state and versioning cursors of the ongoing bootstrap
+    pub fn save_bootstrap_cursors(
+        &self,
+        state_step: &StreamingStep<Key>,
+        versioning_step: &StreamingStep<Key>,
+    ) -> Result<(), MassaDBError> {
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .put_cf(
+                handle_metadata,
+                BOOTSTRAP_CURSORS_KEY,
+                serialize_cursors(state_step, versioning_step)?,
+            )
+            .map_err(|e| MassaDBError::RocksDBError(format!("Can't save bootstrap cursors: {}", e)))
+    }
+
+    /// Gets the state and versioning cursors of an interrupted bootstrap, if any
+    pub fn get_bootstrap_cursors(&self) -> Option<(StreamingStep<Key>, StreamingStep<Key>)> {
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle_metadata, BOOTSTRAP_CURSORS_KEY)
+            .expect(CRUD_ERROR)
+            .and_then(|bytes| deserialize_cursors(&bytes))
+    }
+
+    /// Deletes the cursors once the bootstrap is over, or restarts from scratch
+    pub fn clear_bootstrap_cursors(&self) {
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .delete_cf(handle_metadata, BOOTSTRAP_CURSORS_KEY)
+            .expect(CRUD_ERROR);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DBBatch, MassaDB, MassaDBConfig, STATE_CF};
+    use massa_models::slot::Slot;
+    use rocksdb::IteratorMode;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    fn new_db(path: &Path, max_new_elements: usize) -> MassaDB {
+        MassaDB::new(MassaDBConfig {
+            path: path.to_path_buf(),
+            max_history_length: 10,
+            max_new_elements,
+            thread_count: 2,
+            enable_archive: false,
+            max_archive_periods: 1000,
+        })
+    }
+
+    fn state_entries(db: &MassaDB) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        let handle_state = db.db.cf_handle(STATE_CF).expect(CF_ERROR);
+        db.db
+            .iterator_cf(handle_state, IteratorMode::Start)
+            .flatten()
+            .map(|(key, value)| (key.to_vec(), value.to_vec()))
+            .collect()
+    }
+
+    /// Streams one part of the server state to the client, from the given cursors
+    fn stream_part(
+        server: &MassaDB,
+        client: &mut MassaDB,
+        state_step: &StreamingStep<Key>,
+        versioning_step: &StreamingStep<Key>,
+    ) -> (StreamingStep<Key>, StreamingStep<Key>) {
+        let last_slot = Some(client.get_change_id().unwrap());
+        let state_part = server.get_batch_to_stream(state_step, last_slot).unwrap();
+        let versioning_part = server
+            .get_versioning_batch_to_stream(versioning_step, last_slot)
+            .unwrap();
+        client
+            .write_batch_bootstrap_client(state_part, versioning_part)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_save_get_clear_bootstrap_cursors() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = new_db(temp_dir.path(), 100);
+        assert_eq!(db.get_bootstrap_cursors(), None);
+
+        let state_step = StreamingStep::Ongoing(b"key".to_vec());
+        let versioning_step = StreamingStep::Finished(Some(b"versioning_key".to_vec()));
+        db.save_bootstrap_cursors(&state_step, &versioning_step)
+            .unwrap();
+        assert_eq!(
+            db.get_bootstrap_cursors(),
+            Some((state_step, versioning_step.clone()))
+        );
+
+        // saving again overwrites the previous cursors
+        db.save_bootstrap_cursors(&StreamingStep::Started, &versioning_step)
+            .unwrap();
+        assert_eq!(
+            db.get_bootstrap_cursors(),
+            Some((StreamingStep::Started, versioning_step))
+        );
+
+        db.clear_bootstrap_cursors();
+        assert_eq!(db.get_bootstrap_cursors(), None);
+    }
+
+    #[test]
+    fn test_has_pending_bootstrap() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("db");
+        assert!(!has_pending_bootstrap(&path));
+
+        {
+            let db = new_db(&path, 100);
+            db.save_bootstrap_cursors(&StreamingStep::Started, &StreamingStep::Started)
+                .unwrap();
+        }
+        assert!(has_pending_bootstrap(&path));
+
+        {
+            let db = new_db(&path, 100);
+            db.clear_bootstrap_cursors();
+        }
+        assert!(!has_pending_bootstrap(&path));
+    }
+
+    #[test]
+    fn test_is_change_id_too_old() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = new_db(temp_dir.path(), 100);
+
+        // 12 slots are written but only the last 10 changes are kept in the history
+        let mut slot = Slot::new(0, 1);
+        let mut slots = Vec::new();
+        for i in 0..12u8 {
+            slot = slot.get_next_slot(2).unwrap();
+            let mut batch = DBBatch::new();
+            db.put_or_update_entry_value(&mut batch, vec![i], &[i]);
+            db.write_batch(batch, DBBatch::new(), Some(slot), false);
+            slots.push(slot);
+        }
+
+        assert!(db.is_change_id_too_old(&slots[0]));
+        assert!(!db.is_change_id_too_old(&slots[2]));
+        assert!(!db.is_change_id_too_old(&slots[11]));
+    }
+
+    #[test]
+    fn test_resume_interrupted_bootstrap() {
+        let server_dir = TempDir::new().unwrap();
+        let mut server = new_db(server_dir.path(), 2);
+        let mut batch = DBBatch::new();
+        for i in 0..5u8 {
+            server.put_or_update_entry_value(&mut batch, vec![i], &[i]);
+        }
+        server.write_batch(batch, DBBatch::new(), Some(Slot::new(1, 0)), false);
+
+        // the client stops after a first part
+        let client_dir = TempDir::new().unwrap();
+        let (state_step, _) = {
+            let mut client = new_db(client_dir.path(), 2);
+            let cursors = stream_part(
+                &server,
+                &mut client,
+                &StreamingStep::Started,
+                &StreamingStep::Started,
+            );
+            assert_eq!(client.get_bootstrap_cursors(), Some(cursors.clone()));
+            cursors
+        };
+        assert_eq!(state_step, StreamingStep::Ongoing(vec![1]));
+        assert!(has_pending_bootstrap(client_dir.path()));
+
+        // after a restart, it resumes from the saved cursors until the state is complete
+        let mut client = new_db(client_dir.path(), 2);
+        let (mut state_step, mut versioning_step) = client
+            .get_bootstrap_cursors()
+            .expect("cursors should survive a restart");
+        assert_eq!(state_step, StreamingStep::Ongoing(vec![1]));
+        while !(state_step.finished() && versioning_step.finished()) {
+            (state_step, versioning_step) =
+                stream_part(&server, &mut client, &state_step, &versioning_step);
+        }
+        assert_eq!(state_entries(&client), state_entries(&server));
+
+        client.clear_bootstrap_cursors();
+        assert_eq!(client.get_bootstrap_cursors(), None);
+    }
+}
@@ -6,12 +6,16 @@ pub const LSMTREE_VALUES_CF: &str = "lsmtree_values";
 pub const METADATA_CF: &str = "metadata";
 pub const STATE_CF: &str = "state";
 pub const VERSIONING_CF: &str = "versioning";
+pub const ARCHIVE_CF: &str = "archive";
+pub const ARCHIVE_SLOTS_CF: &str = "archive_slots";
 
 pub const STATE_HASH_KEY: &[u8; 1] = b"h";
 pub const STATE_HASH_XOR_KEY: &[u8; 1] = b"x";
 pub const STATE_HASH_KEY_IS_XOR_KEY: &[u8; 6] = b"is_xor";
 pub const STATE_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
 pub const CHANGE_ID_KEY: &[u8; 1] = b"c";
+pub const ARCHIVE_START_KEY: &[u8; 13] = b"archive_start";
+pub const BOOTSTRAP_CURSORS_KEY: &[u8; 17] = b"bootstrap_cursors";
 
 pub const CHANGE_ID_DESER_ERROR: &str = "critical: change_id deserialization failed";
 pub const CHANGE_ID_SER_ERROR: &str = "critical: change_id serialization failed";
@@ -17,4 +17,12 @@ pub enum MassaDBError {
     RocksDBError(String),
     /// hash error: {0}
     HashError(String),
+    /// state proof error: {0}
+    ProofError(String),
+    /// archive error: {0}
+    ArchiveError(String),
+    /// serialization error: {0}
+    SerializeError(String),
+    /// invalid key range: {0}
+    InvalidRange(String),
 }
@@ -1,9 +1,16 @@
 #![feature(btree_cursors)]
 
+mod archive;
+mod bootstrap_cursors;
 mod constants;
 mod error;
 mod massa_db;
+mod state_proof;
+mod state_ranges;
 
 pub use crate::massa_db::*;
+pub use bootstrap_cursors::has_pending_bootstrap;
 pub use constants::*;
 pub use error::*;
+pub use state_proof::*;
+pub use state_ranges::{compute_state_root_from_snapshot, is_key_in_range};
@@ -1,8 +1,9 @@
 use crate::{
-    MassaDBError, CF_ERROR, CHANGE_ID_DESER_ERROR, CHANGE_ID_KEY, CHANGE_ID_SER_ERROR, CRUD_ERROR,
-    LSMTREE_ERROR, LSMTREE_NODES_CF, LSMTREE_VALUES_CF, METADATA_CF, OPEN_ERROR, STATE_CF,
-    STATE_HASH_ERROR, STATE_HASH_INITIAL_BYTES, STATE_HASH_KEY, STATE_HASH_KEY_IS_XOR_KEY,
-    STATE_HASH_XOR_KEY, VERSIONING_CF,
+    MassaDBError, StateProof, ARCHIVE_CF, ARCHIVE_SLOTS_CF, CF_ERROR, CHANGE_ID_DESER_ERROR,
+    CHANGE_ID_KEY, CHANGE_ID_SER_ERROR, CRUD_ERROR, LSMTREE_ERROR, LSMTREE_NODES_CF,
+    LSMTREE_VALUES_CF, METADATA_CF, OPEN_ERROR, STATE_CF, STATE_HASH_ERROR,
+    STATE_HASH_INITIAL_BYTES, STATE_HASH_KEY, STATE_HASH_KEY_IS_XOR_KEY, STATE_HASH_XOR_KEY,
+    VERSIONING_CF,
 };
 use lsmtree::{bytes::Bytes, BadProof, KVStore, SparseMerkleTree};
 use massa_hash::{Hash, SmtHasher};
@@ -49,6 +50,10 @@ pub struct MassaDBConfig {
     pub max_new_elements: usize,
     /// Thread count for slot serialization
     pub thread_count: u8,
+    /// Whether to keep the previous value of every changed key, to read the state at past slots
+    pub enable_archive: bool,
+    /// Number of periods of changes kept in the archive
+    pub max_archive_periods: u64,
 }
 
 /// A Batch of elements from the database, used by a bootstrap server.
@@ -84,7 +89,7 @@ pub struct RawMassaDB<
     /// The rocksdb instance
     pub db: Arc<DB>,
     /// configuration for the `RawMassaDB`
-    config: MassaDBConfig,
+    pub(crate) config: MassaDBConfig,
     /// In change_history, we keep the latest changes made to the database, useful for streaming them to a client.
     pub change_history: BTreeMap<ChangeID, BTreeMap<Key, Option<Value>>>,
     /// same as change_history but for versioning
@@ -200,6 +205,17 @@ where
     ChangeIDSerializer: Serializer<ChangeID>,
     ChangeIDDeserializer: Deserializer<ChangeID>,
 {
+    /// Whether the changes since `change_id` are no longer in the change history,
+    /// so that a bootstrap client at `change_id` can't be sent the updates it missed
+    pub fn is_change_id_too_old(&self, change_id: &ChangeID) -> bool {
+        *change_id < self.get_change_id().expect(CHANGE_ID_DESER_ERROR)
+            && self
+                .change_history
+                .range((Unbounded, Included(change_id)))
+                .next()
+                .is_none()
+    }
+
     /// Used for bootstrap servers (get a new batch to stream to the client)
     ///
     /// Returns a StreamBatch<ChangeID>
@@ -432,6 +448,29 @@ where
         reset_history: bool,
         only_use_xor: bool,
         compute_hash: bool,
+    ) -> Result<(), MassaDBError> {
+        self.write_changes_with(
+            WriteBatch::default(),
+            changes,
+            versioning_changes,
+            change_id,
+            reset_history,
+            only_use_xor,
+            compute_hash,
+        )
+    }
+
+    /// Same as `write_changes`, also writing the entries of `initial_batch` atomically with the changes
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn write_changes_with(
+        &mut self,
+        initial_batch: WriteBatch,
+        changes: BTreeMap<Key, Option<Value>>,
+        versioning_changes: BTreeMap<Key, Option<Value>>,
+        change_id: Option<ChangeID>,
+        reset_history: bool,
+        only_use_xor: bool,
+        compute_hash: bool,
     ) -> Result<(), MassaDBError> {
         if let Some(change_id) = change_id.clone() {
             if change_id < self.get_change_id().expect(CHANGE_ID_DESER_ERROR) {
@@ -447,7 +486,7 @@ where
 
         let mut current_xor_hash = self.get_db_hash_xor();
 
-        *self.current_batch.lock() = WriteBatch::default();
+        *self.current_batch.lock() = initial_batch;
 
         for (key, value) in changes.iter() {
             if let Some(value) = value {
@@ -628,6 +667,9 @@ where
         stream_changes: StreamBatch<ChangeID>,
         stream_changes_versioning: StreamBatch<ChangeID>,
     ) -> Result<(StreamingStep<Key>, StreamingStep<Key>), MassaDBError> {
+        // the streamed state is not archived: any previous history no longer applies
+        self.clear_archive();
+
         let mut changes = BTreeMap::new();
 
         let new_cursor: StreamingStep<Vec<u8>> = match stream_changes.new_elements.last_key_value()
@@ -668,6 +710,9 @@ where
             false,
         )?;
 
+        // saved once the part is written, to resume from it if the bootstrap gets interrupted
+        self.save_bootstrap_cursors(&new_cursor, &new_cursor_versioning)?;
+
         Ok((new_cursor, new_cursor_versioning))
     }
 
@@ -759,6 +804,34 @@ where
             })
     }
 
+    /// Get the value of a state entry along with a proof of its inclusion (or of its absence)
+    /// in the sparse merkle tree whose root is the current state hash.
+    ///
+    /// Fails if the state hash is computed as a XOR of the entries, as such a hash can't be proven.
+    pub fn get_state_proof(&self, key: &[u8]) -> Result<(Option<Value>, StateProof), MassaDBError> {
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        if self
+            .db
+            .get_cf(handle_metadata, STATE_HASH_KEY_IS_XOR_KEY)
+            .expect(CRUD_ERROR)
+            .is_some()
+        {
+            return Err(MassaDBError::ProofError(String::from(
+                "the state hash is a XOR of the entries and can't be proven",
+            )));
+        }
+
+        let handle_state = self.db.cf_handle(STATE_CF).expect(CF_ERROR);
+        let value = self.db.get_cf(handle_state, key).expect(CRUD_ERROR);
+
+        let proof = self
+            .lsmtree
+            .prove(Hash::compute_from(key).to_bytes())
+            .map_err(|e| MassaDBError::ProofError(e.to_string()))?;
+
+        Ok((value, StateProof::from(proof)))
+    }
+
     /// Get the current state hash xor of the database
     pub fn get_db_hash_xor(&self) -> Hash {
         self.get_db_hash_opt_xor()
@@ -795,6 +868,8 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
                 ColumnFamilyDescriptor::new(LSMTREE_NODES_CF, Options::default()),
                 ColumnFamilyDescriptor::new(LSMTREE_VALUES_CF, Options::default()),
                 ColumnFamilyDescriptor::new(VERSIONING_CF, Options::default()),
+                ColumnFamilyDescriptor::new(ARCHIVE_CF, Options::default()),
+                ColumnFamilyDescriptor::new(ARCHIVE_SLOTS_CF, Options::default()),
             ],
         )
         .expect(OPEN_ERROR);
@@ -883,7 +958,13 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
         change_id: Option<Slot>,
         only_use_xor: bool,
     ) {
-        self.write_changes(
+        let archive_batch = if self.config.enable_archive {
+            self.archive_changes(&batch, change_id).expect(CRUD_ERROR)
+        } else {
+            WriteBatch::default()
+        };
+        self.write_changes_with(
+            archive_batch,
             batch,
             versioning_batch,
             change_id,
@@ -937,6 +1018,8 @@ impl RawMassaDB<Slot, SlotSerializer, SlotDeserializer> {
 
     /// Reset the database, and attach it to the given slot.
     pub fn reset(&mut self, slot: Slot) {
+        self.clear_archive();
+        self.clear_bootstrap_cursors();
         self.set_initial_change_id(slot);
         self.change_history.clear();
         self.current_hashmap.write().clear();
@@ -0,0 +1,256 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Sparse merkle proofs of final state entries, and their verification.
+//!
+//! The final state hash (see `RawMassaDB::get_db_hash`) is the root of a sparse merkle tree
+//! in which every state entry is inserted as `Hash(key) -> Hash(value)`.
+//! A proof is the list of sibling nodes on the path from the entry leaf to the root,
+//! so anyone knowing the state hash can check a value without trusting the node that served it.
+
+use lsmtree::{bytes::Bytes, SparseMerkleProof};
+use massa_hash::{Hash, SmtHasher};
+
+/// Proof of inclusion (or of absence) of a key in the final state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateProof {
+    /// Sibling nodes on the path from the leaf of the key to the root
+    pub side_nodes: Vec<Vec<u8>>,
+    /// Data of the unrelated leaf found on the path of the key.
+    /// Only set for some proofs of absence.
+    pub non_membership_leaf_data: Option<Vec<u8>>,
+}
+
+impl From<SparseMerkleProof<SmtHasher>> for StateProof {
+    fn from(proof: SparseMerkleProof<SmtHasher>) -> Self {
+        StateProof {
+            side_nodes: proof
+                .side_nodes()
+                .iter()
+                .map(|node| node.to_vec())
+                .collect(),
+            non_membership_leaf_data: proof.non_membership_leaf_data().map(|data| data.to_vec()),
+        }
+    }
+}
+
+impl From<&StateProof> for SparseMerkleProof<SmtHasher> {
+    fn from(proof: &StateProof) -> Self {
+        SparseMerkleProof::new(
+            proof
+                .side_nodes
+                .iter()
+                .map(|node| Bytes::from(node.clone()))
+                .collect(),
+            proof.non_membership_leaf_data.clone().map(Bytes::from),
+            None,
+        )
+    }
+}
+
+/// Checks a state proof against a final state hash.
+///
+/// # Arguments
+/// * `state_hash`: final state hash, as returned by `get_db_hash`
+/// * `key`: serialized database key of the entry
+/// * `value`: serialized value of the entry, or `None` to check that the key is absent
+/// * `proof`: proof returned by the node alongside the value
+///
+/// # Returns
+/// true if the proof is valid for this state hash, key and value
+pub fn verify_state_proof(
+    state_hash: &Hash,
+    key: &[u8],
+    value: Option<&[u8]>,
+    proof: &StateProof,
+) -> bool {
+    let smt_proof = SparseMerkleProof::<SmtHasher>::from(proof);
+    let key_hash = Hash::compute_from(key);
+    match value {
+        Some(value) => smt_proof.verify(
+            state_hash.to_bytes(),
+            key_hash.to_bytes(),
+            Hash::compute_from(value).to_bytes(),
+        ),
+        // an empty value is interpreted as a proof of absence by the tree
+        None => smt_proof.verify(state_hash.to_bytes(), key_hash.to_bytes(), Bytes::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DBBatch, MassaDB, MassaDBConfig};
+    use massa_models::slot::Slot;
+    use tempfile::TempDir;
+
+    fn new_db(temp_dir: &TempDir) -> MassaDB {
+        MassaDB::new(MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 2,
+            enable_archive: false,
+            max_archive_periods: 1000,
+        })
+    }
+
+    fn write_entries(db: &mut MassaDB, entries: &[(&str, &str)], only_use_xor: bool) {
+        let mut batch = DBBatch::new();
+        for (key, value) in entries {
+            db.put_or_update_entry_value(&mut batch, key.as_bytes().to_vec(), value.as_bytes());
+        }
+        db.write_batch(batch, DBBatch::new(), Some(Slot::new(1, 0)), only_use_xor);
+    }
+
+    #[test]
+    fn test_state_proof_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = new_db(&temp_dir);
+        write_entries(
+            &mut db,
+            &[("key_1", "value_1"), ("key_2", "value_2"), ("key_3", "")],
+            false,
+        );
+        let state_hash = db.get_db_hash();
+
+        for (key, expected) in [("key_1", "value_1"), ("key_2", "value_2")] {
+            let (key, expected) = (key.as_bytes(), expected.as_bytes());
+            let (value, proof) = db.get_state_proof(key).unwrap();
+            assert_eq!(value.as_deref(), Some(expected));
+            assert!(verify_state_proof(&state_hash, key, Some(expected), &proof));
+
+            // the proof survives a conversion to the tree representation and back
+            let converted = StateProof::from(SparseMerkleProof::<SmtHasher>::from(&proof));
+            assert_eq!(converted, proof);
+            assert!(verify_state_proof(
+                &state_hash,
+                key,
+                Some(expected),
+                &converted
+            ));
+        }
+    }
+
+    #[test]
+    fn test_state_proof_rejects_tampered_value() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = new_db(&temp_dir);
+        write_entries(
+            &mut db,
+            &[("key_1", "value_1"), ("key_2", "value_2")],
+            false,
+        );
+        let state_hash = db.get_db_hash();
+
+        let (_, proof) = db.get_state_proof(b"key_1").unwrap();
+        assert!(!verify_state_proof(
+            &state_hash,
+            b"key_1",
+            Some(b"value_2"),
+            &proof
+        ));
+        // a proof of inclusion can't be used to claim the key is absent
+        assert!(!verify_state_proof(&state_hash, b"key_1", None, &proof));
+        // nor to prove the value of another key
+        assert!(!verify_state_proof(
+            &state_hash,
+            b"key_2",
+            Some(b"value_1"),
+            &proof
+        ));
+
+        let mut tampered_proof = proof.clone();
+        if let Some(node) = tampered_proof.side_nodes.first_mut() {
+            node[0] ^= 1;
+            assert!(!verify_state_proof(
+                &state_hash,
+                b"key_1",
+                Some(b"value_1"),
+                &tampered_proof
+            ));
+        }
+    }
+
+    #[test]
+    fn test_state_proof_rejects_wrong_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = new_db(&temp_dir);
+        write_entries(
+            &mut db,
+            &[("key_1", "value_1"), ("key_2", "value_2")],
+            false,
+        );
+        let old_state_hash = db.get_db_hash();
+        let (_, old_proof) = db.get_state_proof(b"key_1").unwrap();
+
+        write_entries(&mut db, &[("key_1", "value_3")], false);
+        let state_hash = db.get_db_hash();
+        assert_ne!(state_hash, old_state_hash);
+
+        // a proof is only valid against the state hash it was built for
+        assert!(!verify_state_proof(
+            &state_hash,
+            b"key_1",
+            Some(b"value_1"),
+            &old_proof
+        ));
+        assert!(!verify_state_proof(
+            &Hash::compute_from(b"not a state hash"),
+            b"key_1",
+            Some(b"value_1"),
+            &old_proof
+        ));
+
+        let (value, proof) = db.get_state_proof(b"key_1").unwrap();
+        assert_eq!(value.as_deref(), Some(&b"value_3"[..]));
+        assert!(verify_state_proof(
+            &state_hash,
+            b"key_1",
+            Some(b"value_3"),
+            &proof
+        ));
+        assert!(!verify_state_proof(
+            &old_state_hash,
+            b"key_1",
+            Some(b"value_3"),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_state_proof_non_membership() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = new_db(&temp_dir);
+        write_entries(
+            &mut db,
+            &[("key_1", "value_1"), ("key_2", "value_2")],
+            false,
+        );
+        let state_hash = db.get_db_hash();
+
+        let (value, proof) = db.get_state_proof(b"missing_key").unwrap();
+        assert_eq!(value, None);
+        assert!(verify_state_proof(
+            &state_hash,
+            b"missing_key",
+            None,
+            &proof
+        ));
+        // the absence proof can't be turned into a proof of inclusion
+        assert!(!verify_state_proof(
+            &state_hash,
+            b"missing_key",
+            Some(b"value_1"),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_state_proof_unavailable_with_xor_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = new_db(&temp_dir);
+        write_entries(&mut db, &[("key_1", "value_1")], true);
+
+        assert!(db.get_state_proof(b"key_1").is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Key ranges of the state, used to bootstrap disjoint parts of the state from several servers at once.
+//!
+//! The sparse merkle tree of the state is keyed by the hashes of the state keys, so a key range is not
+//! one of its subtrees and can't be checked on its own. Once every range is streamed, the whole state
+//! is checked against the state root: the root of the sparse merkle tree of the state.
+
+use crate::{
+    MassaDBError, RawMassaDB, StreamBatch, CF_ERROR, CHANGE_ID_DESER_ERROR, CRUD_ERROR,
+    LSMTREE_ERROR, METADATA_CF, STATE_CF, STATE_HASH_ERROR, STATE_HASH_KEY_IS_XOR_KEY,
+};
+use lsmtree::{bytes::Bytes, BadProof, KVStore, SparseMerkleTree};
+use massa_hash::{Hash, SmtHasher};
+use massa_models::streaming_step::StreamingStep;
+use massa_serialization::{Deserializer, Serializer};
+use rocksdb::{Direction, IteratorMode, Snapshot, DB};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Bound::{self, Excluded, Included, Unbounded},
+};
+
+type Key = Vec<u8>;
+type Value = Vec<u8>;
+
+/// Whether `key` is in the range going from `range_start` (included) to `range_end` (excluded),
+/// or to the last key of the state if `range_end` is None
+pub fn is_key_in_range(key: &[u8], range_start: &[u8], range_end: Option<&[u8]>) -> bool {
+    key >= range_start && range_end.map_or(true, |end| key < end)
+}
+
+/// In-memory store of a sparse merkle tree, used to compute a state root from scratch
+#[derive(Default)]
+struct MemoryStore(HashMap<Vec<u8>, Bytes>);
+
+impl KVStore for MemoryStore {
+    type Hasher = SmtHasher;
+    type Error = BadProof;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Bytes>, Self::Error> {
+        Ok(self.0.get(key).cloned())
+    }
+
+    fn set(&mut self, key: Bytes, value: Bytes) -> Result<(), Self::Error> {
+        self.0.insert(key.to_vec(), value);
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Result<Bytes, Self::Error> {
+        Ok(self.0.remove(key).expect(LSMTREE_ERROR))
+    }
+
+    fn contains(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        Ok(self.0.contains_key(key))
+    }
+}
+
+/// Root of the sparse merkle tree of the state entries read from a snapshot of the database,
+/// computed from scratch in memory, so that it can be computed while the state keeps changing.
+///
+/// It is the state hash of a database that does not use the XOR hash.
+pub fn compute_state_root_from_snapshot(db: &DB, snapshot: &Snapshot) -> Hash {
+    let handle_state = db.cf_handle(STATE_CF).expect(CF_ERROR);
+    let mut tree =
+        SparseMerkleTree::new_with_stores(MemoryStore::default(), MemoryStore::default());
+    for (key, value) in snapshot
+        .iterator_cf(handle_state, IteratorMode::Start)
+        .flatten()
+    {
+        tree.update(
+            Hash::compute_from(&key).to_bytes(),
+            Bytes::from(Hash::compute_from(&value).to_bytes().to_vec()),
+        )
+        .expect(LSMTREE_ERROR);
+    }
+    let root = tree.root();
+    Hash::from_bytes(root[..].try_into().expect(STATE_HASH_ERROR))
+}
+
+impl<ChangeID, ChangeIDSerializer, ChangeIDDeserializer>
+    RawMassaDB<ChangeID, ChangeIDSerializer, ChangeIDDeserializer>
+where
+    ChangeID: PartialOrd + Ord + PartialEq + Eq + Clone + std::fmt::Debug,
+    ChangeIDSerializer: Serializer<ChangeID>,
+    ChangeIDDeserializer: Deserializer<ChangeID>,
+{
+    /// Used for bootstrap servers (get a new batch of a key range of the state to stream to the client)
+    ///
+    /// Same as `get_batch_to_stream`, restricted to the keys going from `range_start` (included)
+    /// to `range_end` (excluded, or to the last key if None).
+    pub fn get_range_batch_to_stream(
+        &self,
+        range_start: &[u8],
+        range_end: Option<&[u8]>,
+        last_state_step: &StreamingStep<Key>,
+        last_change_id: Option<ChangeID>,
+    ) -> Result<StreamBatch<ChangeID>, MassaDBError> {
+        if let StreamingStep::Ongoing(max_key) = last_state_step {
+            if !is_key_in_range(max_key, range_start, range_end) {
+                return Err(MassaDBError::InvalidRange(String::from(
+                    "the streaming cursor is out of the streamed range",
+                )));
+            }
+        }
+        if let Some(range_end) = range_end {
+            if range_end <= range_start {
+                return Err(MassaDBError::InvalidRange(String::from(
+                    "the end of the range should be after its start",
+                )));
+            }
+        }
+
+        let current_change_id = self.get_change_id().expect(CHANGE_ID_DESER_ERROR);
+        let end_bound_for_changes = match (last_state_step, range_end) {
+            (StreamingStep::Ongoing(max_key), _) => Included(max_key.clone()),
+            (_, Some(range_end)) => Excluded(range_end.to_vec()),
+            (_, None) => Unbounded,
+        };
+
+        let updates_on_previous_elements = match (last_state_step, last_change_id) {
+            (StreamingStep::Started, _) => BTreeMap::new(),
+            (_, Some(last_change_id)) => {
+                if last_change_id > current_change_id {
+                    return Err(MassaDBError::TimeError(String::from(
+                        "we don't have this change yet on this node (it's in the future for us)",
+                    )));
+                }
+                if self.is_change_id_too_old(&last_change_id) {
+                    return Err(MassaDBError::TimeError(String::from(
+                        "all our changes are strictly after last_change_id, we can't be sure we did not miss any",
+                    )));
+                }
+                let mut updates: BTreeMap<Key, Option<Value>> = BTreeMap::new();
+                for (_change_id, changes) in self
+                    .change_history
+                    .range((Excluded(last_change_id), Unbounded))
+                {
+                    updates.extend(
+                        changes
+                            .range::<Key, (Bound<Key>, Bound<Key>)>((
+                                Included(range_start.to_vec()),
+                                end_bound_for_changes.clone(),
+                            ))
+                            .map(|(k, v)| (k.clone(), v.clone())),
+                    );
+                }
+                updates
+            }
+            _ => {
+                return Err(MassaDBError::TimeError(String::from(
+                    "State streaming was ongoing or finished, but no last_change_id was provided",
+                )));
+            }
+        };
+
+        let mut new_elements = BTreeMap::new();
+        if !last_state_step.finished() {
+            let handle = self.db.cf_handle(STATE_CF).expect(CF_ERROR);
+            let db_iterator = match last_state_step {
+                StreamingStep::Ongoing(max_key) => {
+                    let mut iter = self
+                        .db
+                        .iterator_cf(handle, IteratorMode::From(max_key, Direction::Forward));
+                    iter.next();
+                    iter
+                }
+                _ => self
+                    .db
+                    .iterator_cf(handle, IteratorMode::From(range_start, Direction::Forward)),
+            };
+            for (serialized_key, serialized_value) in db_iterator.flatten() {
+                if !is_key_in_range(&serialized_key, range_start, range_end)
+                    || new_elements.len() >= self.config.max_new_elements
+                {
+                    break;
+                }
+                new_elements.insert(serialized_key.to_vec(), serialized_value.to_vec());
+            }
+        }
+
+        Ok(StreamBatch {
+            new_elements,
+            updates_on_previous_elements,
+            change_id: current_change_id,
+        })
+    }
+
+    /// Gets the state root: the root of the sparse merkle tree of the state,
+    /// or None if the state hash is computed as a XOR of the entries instead
+    pub fn get_state_root(&self) -> Option<Hash> {
+        let handle_metadata = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle_metadata, STATE_HASH_KEY_IS_XOR_KEY)
+            .expect(CRUD_ERROR)
+            .is_none()
+            .then(|| self.get_db_hash())
+    }
+
+    /// Write a stream_batch of a key range of the state received from a bootstrap server.
+    ///
+    /// The ranges of the state are streamed at different slots, so the change_id is left untouched:
+    /// it is set once every range is complete.
+    ///
+    /// Returns the streaming cursor of the range
+    pub fn write_range_batch_bootstrap_client(
+        &mut self,
+        stream_changes: StreamBatch<ChangeID>,
+    ) -> Result<StreamingStep<Key>, MassaDBError> {
+        // the streamed state is not archived: any previous history no longer applies
+        self.clear_archive();
+
+        let new_cursor = match stream_changes.new_elements.last_key_value() {
+            Some((k, _)) => StreamingStep::Ongoing(k.clone()),
+            None => StreamingStep::Finished(None),
+        };
+
+        let mut changes = stream_changes.updates_on_previous_elements;
+        changes.extend(
+            stream_changes
+                .new_elements
+                .into_iter()
+                .map(|(k, v)| (k, Some(v))),
+        );
+        self.write_changes(changes, BTreeMap::new(), None, true, false, false)?;
+
+        Ok(new_cursor)
+    }
+
+    /// Deletes the state entries of a key range, so that it can be streamed again from the start
+    pub fn delete_range(
+        &mut self,
+        range_start: &[u8],
+        range_end: Option<&[u8]>,
+    ) -> Result<(), MassaDBError> {
+        let handle_state = self.db.cf_handle(STATE_CF).expect(CF_ERROR);
+        let deletions: BTreeMap<Key, Option<Value>> = self
+            .db
+            .iterator_cf(
+                handle_state,
+                IteratorMode::From(range_start, Direction::Forward),
+            )
+            .flatten()
+            .map(|(key, _)| key.to_vec())
+            .take_while(|key| is_key_in_range(key, range_start, range_end))
+            .map(|key| (key, None))
+            .collect();
+        self.write_changes(deletions, BTreeMap::new(), None, true, false, false)
+    }
+}
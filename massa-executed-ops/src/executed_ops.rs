@@ -286,12 +286,16 @@ fn test_executed_ops_hash_computing() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db_c_config = MassaDBConfig {
         path: tempdir_c.path().to_path_buf(),
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db_a = Arc::new(RwLock::new(MassaDB::new(db_a_config)));
     let db_c = Arc::new(RwLock::new(MassaDB::new(db_c_config)));
@@ -4,19 +4,27 @@
 
 use crate::types::ReadOnlyExecutionRequest;
 use crate::ExecutionError;
-use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
+use crate::{
+    ExecutionAddressInfo, FinalStateProofs, OperationReceipt, PendingAsyncMessage,
+    ReadOnlyExecutionOutput, SlotDeterminismCheckOutput,
+};
+use massa_hash::Hash;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
 use massa_models::block_id::BlockId;
 use massa_models::denunciation::DenunciationIndex;
-use massa_models::execution::EventFilter;
+use massa_models::execution::{
+    AddressHistoryCursor, AddressHistoryEntry, AsyncMessageFilter, EventCursor, EventFilter,
+};
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::slot::Slot;
 use massa_models::stats::ExecutionStats;
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 
 #[cfg_attr(any(test, feature = "testing"), mockall::automock)]
@@ -43,6 +51,17 @@ pub trait ExecutionController: Send + Sync {
     /// * operation id
     fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent>;
 
+    /// Get at most `limit` execution events matching a filter,
+    /// located strictly after `cursor` in the list of events ordered by slot and index in slot.
+    ///
+    /// The cursor of the next page is the position of the last returned event.
+    fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventCursor>,
+        limit: usize,
+    ) -> Vec<SCOutputEvent>;
+
     /// Get the final and active values of balance.
     ///
     /// # Return value
@@ -61,6 +80,56 @@ pub trait ExecutionController: Send + Sync {
     /// Otherwise, the status is a boolean indicating whether the execution was successful (true) or if there was an error (false.)
     fn get_ops_exec_status(&self, batch: &[OperationId]) -> Vec<(Option<bool>, Option<bool>)>;
 
+    /// Get the execution receipts of a batch of operations.
+    ///
+    /// The receipt of the latest candidate execution of an operation is returned over its final one.
+    /// `None` means that no execution was found for the operation.
+    /// Note that old receipts are forgotten.
+    fn get_operation_receipts(&self, batch: &[OperationId]) -> Vec<Option<OperationReceipt>>;
+
+    /// Get the asynchronous messages of the candidate pool matching a filter
+    fn get_async_messages(&self, filter: &AsyncMessageFilter) -> Vec<PendingAsyncMessage>;
+
+    /// Get at most `limit` final operations sent or received by an address,
+    /// from the most recent slot, located strictly before `cursor`.
+    ///
+    /// The cursor of the next page is the position of the last returned entry.
+    /// `None` means that the node does not maintain the address history index.
+    fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<AddressHistoryCursor>,
+        limit: usize,
+    ) -> Option<Vec<AddressHistoryEntry>>;
+
+    /// Get the balances of a batch of addresses right after a past final slot,
+    /// read from the archive of the final state.
+    ///
+    /// # Return value
+    /// The balance of each address, or an error if the node does not archive that slot
+    fn get_archived_balances(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError>;
+
+    /// Get the datastore keys of a batch of addresses right after a past final slot,
+    /// read from the archive of the final state.
+    #[allow(clippy::type_complexity)]
+    fn get_archived_datastore_keys(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError>;
+
+    /// Get a batch of datastore entries right after a past final slot,
+    /// read from the archive of the final state.
+    fn get_archived_data_entries(
+        &self,
+        input: Vec<(Address, Vec<u8>)>,
+        slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError>;
+
     /// Get a copy of a single datastore entry with its final and active values
     ///
     /// # Return value
@@ -71,12 +140,29 @@ pub trait ExecutionController: Send + Sync {
         input: Vec<(Address, Vec<u8>)>,
     ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>;
 
+    /// Get the final values of a batch of serialized state keys,
+    /// with a proof of each of them against the final state hash.
+    ///
+    /// # Return value
+    /// The final slot and state hash, and a `(value, proof)` pair per requested key,
+    /// or an error if the final state hash can't be proven.
+    fn get_final_state_proofs(&self, keys: &[Vec<u8>]) -> Result<FinalStateProofs, ExecutionError>;
+
     /// Returns for a given cycle the stakers taken into account
     /// by the selector. That correspond to the `roll_counts` in `cycle - 3`.
     ///
     /// By default it returns an empty map.
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64>;
 
+    /// Returns the inputs of the selector draws of a given cycle:
+    /// the look back rolls (`cycle - 3`) and the look back seed (`cycle - 2`).
+    ///
+    /// Nodes that do not hold the final state feed them to their own selector.
+    fn get_selector_draw_inputs(
+        &self,
+        cycle: u64,
+    ) -> Result<(BTreeMap<Address, u64>, Hash), ExecutionError>;
+
     /// Execute read-only SC function call without causing modifications to the consensus state
     ///
     /// # arguments
@@ -90,6 +176,24 @@ pub trait ExecutionController: Send + Sync {
         req: ReadOnlyExecutionRequest,
     ) -> Result<ReadOnlyExecutionOutput, ExecutionError>;
 
+    /// Checks that the execution of an upcoming final slot is deterministic, for debugging purposes.
+    /// When the slot becomes final, it is executed again on top of the final state right before it,
+    /// ignoring the speculative history, and the state changes of both executions are compared.
+    /// Past final slots are rejected: re-executing them would need the execution environment as it was
+    /// before them (final state, PoS draws, MIP store, module cache), which is not kept, even in archive mode.
+    /// Blocks until the slot is finalized, or until `timeout` elapses.
+    ///
+    /// # arguments
+    /// * `slot`: slot to check, after the last final slot
+    /// * `trace`: whether to record the trace of the operations executed during the re-execution
+    /// * `timeout`: maximum time to wait for the slot to be finalized and checked
+    fn check_slot_determinism(
+        &self,
+        slot: Slot,
+        trace: bool,
+        timeout: MassaTime,
+    ) -> Result<SlotDeterminismCheckOutput, ExecutionError>;
+
     /// Check if a denunciation has been executed given a `DenunciationIndex`
     fn is_denunciation_executed(&self, denunciation_index: &DenunciationIndex) -> bool;
 
@@ -2,7 +2,9 @@
 
 //! this file defines all possible execution error categories
 
+use crate::ExecutionTraceFrame;
 use displaydoc::Display;
+use massa_models::slot::Slot;
 use massa_module_cache::error::CacheError;
 use massa_sc_runtime::VMError;
 use massa_versioning::versioning_factory::FactoryError;
@@ -54,6 +56,9 @@ pub enum ExecutionError {
     /// Include denunciation error: {0}
     IncludeDenunciationError(String),
 
+    /// Archive error: {0}
+    ArchiveError(String),
+
     /// Error when initialize vesting addresses: {0}
     InitVestingError(String),
 
@@ -73,4 +78,21 @@ pub enum ExecutionError {
 
     /// Factory error: {0}
     FactoryError(#[from] FactoryError),
+
+    /// Final state proof error: {0}
+    StateProofError(String),
+
+    /// Selector draw inputs error: {0}
+    DrawInputsError(String),
+
+    /// Canceled slot {0} has no execution output
+    CanceledSlot(Slot),
+
+    /// {error}
+    TracedError {
+        /// error that interrupted the traced read-only execution
+        error: Box<ExecutionError>,
+        /// trace of the execution until the error
+        trace: Box<ExecutionTraceFrame>,
+    },
 }
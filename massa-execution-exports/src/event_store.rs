@@ -3,7 +3,7 @@
 //! This module represents an event store allowing to store, search and retrieve
 //! a config-limited number of execution-generated events
 
-use massa_models::execution::EventFilter;
+use massa_models::execution::{EventCursor, EventFilter};
 use massa_models::output_event::SCOutputEvent;
 use std::collections::VecDeque;
 
@@ -56,47 +56,93 @@ impl EventStore {
     pub fn get_filtered_sc_output_events(&self, filter: &EventFilter) -> VecDeque<SCOutputEvent> {
         self.0
             .iter()
-            .filter(|x| {
-                if let Some(start) = filter.start {
-                    if x.context.slot < start {
-                        return false;
-                    }
-                }
-                if let Some(end) = filter.end {
-                    if x.context.slot >= end {
-                        return false;
-                    }
-                }
-                if let Some(is_final) = filter.is_final {
-                    if x.context.is_final != is_final {
-                        return false;
-                    }
-                }
-                if let Some(is_error) = filter.is_error {
-                    if x.context.is_error != is_error {
-                        return false;
-                    }
-                }
-                match (filter.emitter_address, x.context.call_stack.front()) {
-                    (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                match (filter.original_caller_address, x.context.call_stack.back()) {
-                    (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                match (filter.original_operation_id, x.context.origin_operation_id) {
-                    (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
-                    (Some(_), None) => return false,
-                    _ => (),
-                }
-                true
-            })
+            .filter(|x| event_matches_filter(x, filter))
             .cloned()
             .collect()
     }
+
+    /// Get at most `limit` events matching a filter, located after an optional cursor
+    pub fn get_filtered_sc_output_events_page(
+        &self,
+        filter: &EventFilter,
+        cursor: Option<&EventCursor>,
+        limit: usize,
+    ) -> Vec<SCOutputEvent> {
+        self.0
+            .iter()
+            .filter(|x| event_is_after_cursor(x, cursor) && event_matches_filter(x, filter))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Checks whether an event is located strictly after a cursor, if any
+pub fn event_is_after_cursor(event: &SCOutputEvent, cursor: Option<&EventCursor>) -> bool {
+    cursor.map_or(true, |cursor| {
+        (event.context.slot, event.context.index_in_slot) > (cursor.slot, cursor.index_in_slot)
+    })
+}
+
+/// Checks whether an event matches all the criteria of a filter
+pub fn event_matches_filter(event: &SCOutputEvent, filter: &EventFilter) -> bool {
+    if let Some(start) = filter.start {
+        if event.context.slot < start {
+            return false;
+        }
+    }
+    if let Some(end) = filter.end {
+        if event.context.slot >= end {
+            return false;
+        }
+    }
+    if let Some(is_final) = filter.is_final {
+        if event.context.is_final != is_final {
+            return false;
+        }
+    }
+    if let Some(is_error) = filter.is_error {
+        if event.context.is_error != is_error {
+            return false;
+        }
+    }
+    match (filter.emitter_address, event.context.call_stack.front()) {
+        (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+        (Some(_), None) => return false,
+        _ => (),
+    }
+    match (
+        filter.original_caller_address,
+        event.context.call_stack.back(),
+    ) {
+        (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+        (Some(_), None) => return false,
+        _ => (),
+    }
+    match (
+        filter.original_operation_id,
+        event.context.origin_operation_id,
+    ) {
+        (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
+        (Some(_), None) => return false,
+        _ => (),
+    }
+    if let Some(prefix) = &filter.data_prefix {
+        if !event.data.starts_with(prefix.as_str()) {
+            return false;
+        }
+    }
+    if let Some(substring) = &filter.data_substring {
+        if !event.data.contains(substring.as_str()) {
+            return false;
+        }
+    }
+    if let Some(address) = &filter.call_stack_address {
+        if !event.context.call_stack.contains(address) {
+            return false;
+        }
+    }
+    true
 }
 
 #[test]
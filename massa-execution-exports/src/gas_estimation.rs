@@ -0,0 +1,131 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Estimation of the `max_gas` of smart contract operations through read-only executions
+
+use crate::{
+    ExecutionController, ExecutionError, ExecutionStackElement, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
+};
+use massa_models::{address::Address, amount::Amount, datastore::Datastore, slot::Slot};
+
+/// Gas estimation settings
+#[derive(Debug, Clone)]
+pub struct GasEstimationConfig {
+    /// safety margin added to the measured gas, in percent
+    pub margin_percent: u64,
+    /// maximum gas of an operation
+    pub max_gas_per_block: u64,
+    /// maximum gas of a read-only execution
+    pub max_read_only_gas: u64,
+    /// gas paid by `ExecuteSC` operations for the compilation of their bytecode
+    pub sp_compilation_cost: u64,
+}
+
+/// Smart contract operation whose gas is estimated
+#[derive(Debug, Clone)]
+pub enum GasEstimationTarget {
+    /// `ExecuteSC` operation
+    ExecuteSC {
+        /// bytecode to execute
+        bytecode: Vec<u8>,
+        /// operation datastore
+        datastore: Datastore,
+    },
+    /// `CallSC` operation
+    CallSC {
+        /// target address
+        target_addr: Address,
+        /// target function
+        target_func: String,
+        /// parameter passed to the target function
+        parameter: Vec<u8>,
+        /// coins sent to the target address
+        coins: Amount,
+    },
+}
+
+/// Outcome of a gas estimation
+#[derive(Debug, Clone)]
+pub struct GasEstimation {
+    /// slot at which the read-only execution happened
+    pub slot: Slot,
+    /// gas used by the read-only execution, compilation included
+    pub gas_used: u64,
+    /// recommended `max_gas`: the gas used increased by the safety margin,
+    /// capped at the maximum gas of an operation
+    pub max_gas: u64,
+}
+
+/// Estimates the gas of a smart contract operation sent by `caller_addr`
+/// by running it as a read-only execution on top of the candidate state.
+pub fn estimate_gas(
+    controller: &dyn ExecutionController,
+    config: &GasEstimationConfig,
+    caller_addr: Address,
+    target: GasEstimationTarget,
+) -> Result<GasEstimation, ExecutionError> {
+    let budget = config.max_gas_per_block.min(config.max_read_only_gas);
+    let caller = ExecutionStackElement {
+        address: caller_addr,
+        coins: Amount::zero(),
+        owned_addresses: vec![caller_addr],
+        operation_datastore: None,
+    };
+    let (req, compilation_cost) = match target {
+        GasEstimationTarget::ExecuteSC {
+            bytecode,
+            datastore,
+        } => (
+            ReadOnlyExecutionRequest {
+                max_gas: budget.saturating_sub(config.sp_compilation_cost),
+                call_stack: vec![ExecutionStackElement {
+                    operation_datastore: Some(datastore),
+                    ..caller
+                }],
+                target: ReadOnlyExecutionTarget::BytecodeExecution(bytecode),
+                is_final: false,
+                at_slot: None,
+                trace: false,
+            },
+            config.sp_compilation_cost,
+        ),
+        GasEstimationTarget::CallSC {
+            target_addr,
+            target_func,
+            parameter,
+            coins,
+        } => (
+            ReadOnlyExecutionRequest {
+                max_gas: budget,
+                call_stack: vec![
+                    caller,
+                    ExecutionStackElement {
+                        address: target_addr,
+                        coins,
+                        owned_addresses: vec![target_addr],
+                        operation_datastore: None,
+                    },
+                ],
+                target: ReadOnlyExecutionTarget::FunctionCall {
+                    target_addr,
+                    target_func,
+                    parameter,
+                },
+                is_final: false,
+                at_slot: None,
+                trace: false,
+            },
+            0,
+        ),
+    };
+
+    let output = controller.execute_readonly_request(req)?;
+    let gas_used = output.gas_cost.saturating_add(compilation_cost);
+    let max_gas = (gas_used as u128 * (100 + config.margin_percent as u128) / 100)
+        .min(config.max_gas_per_block as u128) as u64;
+    Ok(GasEstimation {
+        slot: output.out.slot,
+        gas_used,
+        max_gas,
+    })
+}
@@ -34,6 +34,12 @@
 //! ## `event_store.rs`
 //! Defines an indexed, finite-size storage system for execution events.
 //!
+//! ## `gas_estimation.rs`
+//! Estimates the `max_gas` of smart contract operations through read-only executions.
+//!
+//! ## `trace.rs`
+//! Defines the trace of the ABI calls made during a read-only execution.
+//!
 //! ## `types.rs`
 //! Defines useful shared structures.
 //!
@@ -48,8 +54,10 @@ mod channels;
 mod controller_traits;
 mod error;
 mod event_store;
+mod gas_estimation;
 mod mapping_grpc;
 mod settings;
+mod trace;
 mod types;
 
 pub use channels::ExecutionChannels;
@@ -57,13 +65,16 @@ pub use channels::ExecutionChannels;
 pub use controller_traits::MockExecutionController;
 pub use controller_traits::{ExecutionController, ExecutionManager};
 pub use error::ExecutionError;
-pub use event_store::EventStore;
+pub use event_store::{event_is_after_cursor, event_matches_filter, EventStore};
+pub use gas_estimation::{estimate_gas, GasEstimation, GasEstimationConfig, GasEstimationTarget};
 pub use massa_sc_runtime::GasCosts;
 pub use settings::{ExecutionConfig, StorageCostsConstants};
+pub use trace::{ExecutionTraceFrame, ExecutionTraceStep};
 pub use types::{
-    ExecutionAddressInfo, ExecutionOutput, ExecutionStackElement, ReadOnlyCallRequest,
+    AsyncMessageExecution, ExecutionAddressInfo, ExecutionOutput, ExecutionStackElement,
+    FinalStateProofs, OperationReceipt, PendingAsyncMessage, ReadOnlyCallRequest,
     ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
-    SlotExecutionOutput,
+    SlotDeterminismCheckOutput, SlotExecutionOutput, SlotGasProfile,
 };
 
 #[cfg(any(feature = "testing", feature = "gas_calibration"))]
@@ -1,23 +1,26 @@
 // Copyright (c) 2023 MASSA LABS <info@massa.net>
 
-use crate::{ExecutionOutput, SlotExecutionOutput};
+use crate::{ExecutionError, ExecutionOutput, SlotExecutionOutput};
 use massa_proto_rs::massa::model::v1 as grpc_model;
 
-impl From<SlotExecutionOutput> for grpc_model::SlotExecutionOutput {
-    fn from(value: SlotExecutionOutput) -> Self {
+impl TryFrom<SlotExecutionOutput> for grpc_model::SlotExecutionOutput {
+    type Error = ExecutionError;
+
+    fn try_from(value: SlotExecutionOutput) -> Result<Self, Self::Error> {
         match value {
             SlotExecutionOutput::ExecutedSlot(execution_output) => {
-                grpc_model::SlotExecutionOutput {
+                Ok(grpc_model::SlotExecutionOutput {
                     status: vec![grpc_model::ExecutionOutputStatus::Candidate as i32],
                     execution_output: Some(execution_output.into()),
-                }
+                })
             }
             SlotExecutionOutput::FinalizedSlot(execution_output) => {
-                grpc_model::SlotExecutionOutput {
+                Ok(grpc_model::SlotExecutionOutput {
                     status: vec![grpc_model::ExecutionOutputStatus::Final as i32],
                     execution_output: Some(execution_output.into()),
-                }
+                })
             }
+            SlotExecutionOutput::CanceledSlot(slot) => Err(ExecutionError::CanceledSlot(slot)),
         }
     }
 }
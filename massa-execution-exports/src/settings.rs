@@ -26,6 +26,18 @@ pub struct ExecutionConfig {
     pub readonly_queue_length: usize,
     /// maximum number of SC output events kept in cache
     pub max_final_events: usize,
+    /// whether final SC output events are also archived on disk
+    pub event_store_enabled: bool,
+    /// path to the on-disk event archive
+    pub event_store_path: PathBuf,
+    /// number of periods the archived events are kept for, 0 to keep them forever
+    pub event_store_retention_periods: u64,
+    /// maximum number of final operation receipts kept in cache
+    pub max_final_receipts: usize,
+    /// whether the on-disk index of the operations affecting each address is maintained
+    pub address_history_enabled: bool,
+    /// path to the on-disk address history index
+    pub address_history_path: PathBuf,
     /// maximum available gas for asynchronous messages execution
     pub max_async_gas: u64,
     /// maximum gas per block
@@ -23,6 +23,12 @@ impl Default for ExecutionConfig {
         Self {
             readonly_queue_length: 100,
             max_final_events: 1000,
+            event_store_enabled: false,
+            event_store_path: TempDir::new().unwrap().path().to_path_buf(),
+            event_store_retention_periods: 0,
+            max_final_receipts: 1000,
+            address_history_enabled: false,
+            address_history_path: TempDir::new().unwrap().path().to_path_buf(),
             max_async_gas: MAX_ASYNC_GAS,
             thread_count: THREAD_COUNT,
             roll_price: ROLL_PRICE,
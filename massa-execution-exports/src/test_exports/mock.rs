@@ -3,16 +3,20 @@
 //! This file defines utilities to mock the crate for testing purposes
 
 use crate::{
-    ExecutionAddressInfo, ExecutionController, ExecutionError, ReadOnlyExecutionOutput,
-    ReadOnlyExecutionRequest,
+    ExecutionAddressInfo, ExecutionController, ExecutionError, FinalStateProofs, OperationReceipt,
+    PendingAsyncMessage, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    SlotDeterminismCheckOutput,
 };
+use massa_hash::Hash;
 use massa_ledger_exports::LedgerEntry;
 use massa_models::denunciation::DenunciationIndex;
 use massa_models::{
     address::Address,
     amount::Amount,
     block_id::BlockId,
-    execution::EventFilter,
+    execution::{
+        AddressHistoryCursor, AddressHistoryEntry, AsyncMessageFilter, EventCursor, EventFilter,
+    },
     operation::OperationId,
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
@@ -23,7 +27,7 @@ use massa_storage::Storage;
 use massa_time::MassaTime;
 use parking_lot::Mutex;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     sync::{
         mpsc::{self, Receiver},
         Arc,
@@ -54,6 +58,17 @@ pub enum MockExecutionControllerMessage {
         /// response channel
         response_tx: mpsc::Sender<Vec<SCOutputEvent>>,
     },
+    /// paginated filter for smart contract output event request
+    GetFilteredScOutputEventPage {
+        /// filter
+        filter: EventFilter,
+        /// position of the last event of the previous page
+        cursor: Option<EventCursor>,
+        /// maximum number of events
+        limit: usize,
+        /// response channel
+        response_tx: mpsc::Sender<Vec<SCOutputEvent>>,
+    },
     /// get full ledger entry
     GetFullLedgerEntry {
         /// address
@@ -129,6 +144,10 @@ impl ExecutionController for MockExecutionController {
             final_block_count: 0,
             final_executed_operations_count: 0,
             active_cursor: Slot::new(0, 0),
+            gas_used_by_operation_type: Default::default(),
+            top_gas_functions: Vec::new(),
+            slowest_slots: Vec::new(),
+            module_cache: Default::default(),
         }
     }
 
@@ -160,6 +179,27 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventCursor>,
+        limit: usize,
+    ) -> Vec<SCOutputEvent> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .send(
+                MockExecutionControllerMessage::GetFilteredScOutputEventPage {
+                    filter,
+                    cursor,
+                    limit,
+                    response_tx,
+                },
+            )
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_final_and_candidate_balance(
         &self,
         addresses: &[Address],
@@ -185,6 +225,15 @@ impl ExecutionController for MockExecutionController {
         Vec::default()
     }
 
+    fn get_final_state_proofs(
+        &self,
+        _keys: &[Vec<u8>],
+    ) -> Result<FinalStateProofs, ExecutionError> {
+        Err(ExecutionError::StateProofError(
+            "final state proofs are not available in the mock".into(),
+        ))
+    }
+
     fn get_addresses_infos(&self, _addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
         Vec::default()
     }
@@ -193,6 +242,15 @@ impl ExecutionController for MockExecutionController {
         BTreeMap::default()
     }
 
+    fn get_selector_draw_inputs(
+        &self,
+        _cycle: u64,
+    ) -> Result<(BTreeMap<Address, u64>, Hash), ExecutionError> {
+        Err(ExecutionError::DrawInputsError(
+            "selector draw inputs are not available in the mock".to_string(),
+        ))
+    }
+
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
@@ -205,6 +263,17 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    fn check_slot_determinism(
+        &self,
+        _slot: Slot,
+        _trace: bool,
+        _timeout: MassaTime,
+    ) -> Result<SlotDeterminismCheckOutput, ExecutionError> {
+        Err(ExecutionError::RuntimeError(
+            "slot determinism checks are not available in the mock".to_string(),
+        ))
+    }
+
     fn is_denunciation_executed(&self, denunciation_index: &DenunciationIndex) -> bool {
         let (response_tx, response_rx) = mpsc::channel();
         if let Err(err) =
@@ -229,4 +298,51 @@ impl ExecutionController for MockExecutionController {
     fn get_ops_exec_status(&self, batch: &[OperationId]) -> Vec<(Option<bool>, Option<bool>)> {
         vec![(None, None); batch.len()]
     }
+
+    fn get_operation_receipts(&self, batch: &[OperationId]) -> Vec<Option<OperationReceipt>> {
+        vec![None; batch.len()]
+    }
+
+    fn get_async_messages(&self, _filter: &AsyncMessageFilter) -> Vec<PendingAsyncMessage> {
+        Vec::new()
+    }
+
+    fn get_address_history(
+        &self,
+        _address: &Address,
+        _cursor: Option<AddressHistoryCursor>,
+        _limit: usize,
+    ) -> Option<Vec<AddressHistoryEntry>> {
+        None
+    }
+
+    fn get_archived_balances(
+        &self,
+        _addresses: &[Address],
+        _slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError> {
+        Err(ExecutionError::ArchiveError(
+            "the archive is not available in the mock".into(),
+        ))
+    }
+
+    fn get_archived_datastore_keys(
+        &self,
+        _addresses: &[Address],
+        _slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError> {
+        Err(ExecutionError::ArchiveError(
+            "the archive is not available in the mock".into(),
+        ))
+    }
+
+    fn get_archived_data_entries(
+        &self,
+        _input: Vec<(Address, Vec<u8>)>,
+        _slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        Err(ExecutionError::ArchiveError(
+            "the archive is not available in the mock".into(),
+        ))
+    }
 }
@@ -0,0 +1,114 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! This file defines the structures describing the trace of a read-only execution
+
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use serde::{Deserialize, Serialize};
+
+/// Call frame recorded by the execution tracer:
+/// the root frame is the read-only execution itself, nested frames are calls to other smart contracts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionTraceFrame {
+    /// address executing the frame
+    pub address: Address,
+    /// coins transferred to the address when entering the frame
+    pub coins: Amount,
+    /// remaining gas when entering the frame: the max gas of the execution for the root frame,
+    /// the gas the VM gives to the callee when loading its module for nested frames.
+    /// `None` if the call failed before its module was loaded.
+    pub gas_at_entry: Option<u64>,
+    /// remaining gas when leaving the frame.
+    /// Only known for the root frame, and `None` if the execution failed:
+    /// the VM does not report to the interface the gas left by nested calls.
+    pub gas_at_exit: Option<u64>,
+    /// ABI calls made within the frame, in execution order
+    pub steps: Vec<ExecutionTraceStep>,
+}
+
+impl ExecutionTraceFrame {
+    /// Creates an empty frame for an address
+    pub fn new(address: Address, coins: Amount) -> Self {
+        ExecutionTraceFrame {
+            address,
+            coins,
+            gas_at_entry: None,
+            gas_at_exit: None,
+            steps: Vec::new(),
+        }
+    }
+}
+
+/// ABI call recorded by the execution tracer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionTraceStep {
+    /// read of a datastore entry
+    DatastoreRead {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+        /// value of the entry, `None` if it was not found
+        value: Option<Vec<u8>>,
+    },
+    /// check of the existence of a datastore entry
+    DatastoreHas {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+        /// whether the entry exists
+        exists: bool,
+    },
+    /// listing of the keys of a datastore
+    DatastoreKeys {
+        /// address owning the datastore
+        address: Address,
+        /// prefix of the listed keys, if any
+        prefix: Option<Vec<u8>>,
+        /// keys found, `None` if the address was not found
+        keys: Option<Vec<Vec<u8>>>,
+    },
+    /// write of a datastore entry
+    DatastoreWrite {
+        /// address owning the datastore
+        address: Address,
+        /// key of the entry
+        key: Vec<u8>,
+        /// new value of the entry, `None` if it was deleted
+        value: Option<Vec<u8>>,
+    },
+    /// transfer of coins between two addresses
+    Transfer {
+        /// address sending the coins
+        from: Address,
+        /// address receiving the coins
+        to: Address,
+        /// amount of coins transferred
+        amount: Amount,
+    },
+    /// call to another smart contract
+    Call(ExecutionTraceFrame),
+    /// asynchronous message sent
+    AsyncMessage {
+        /// destination address of the message
+        destination: Address,
+        /// handler function called on the destination
+        handler: String,
+        /// maximum gas of the message execution
+        max_gas: u64,
+        /// fee paid by the sender
+        fee: Amount,
+        /// coins sent to the destination
+        coins: Amount,
+        /// first slot at which the message can be executed
+        validity_start: Slot,
+        /// last slot at which the message can be executed
+        validity_end: Slot,
+    },
+    /// event emitted
+    Event {
+        /// payload of the event
+        data: String,
+    },
+}
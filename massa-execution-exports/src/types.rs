@@ -3,13 +3,23 @@
 //! This file exports useful types used to interact with the execution worker
 
 use crate::event_store::EventStore;
+use crate::ExecutionTraceFrame;
+use massa_async_pool::{AsyncMessage, AsyncMessageId};
+use massa_db::StateProof;
 use massa_final_state::StateChanges;
+use massa_hash::Hash;
 use massa_models::datastore::Datastore;
 use massa_models::{
-    address::Address, address::ExecutionAddressCycleInfo, amount::Amount, block_id::BlockId,
+    address::Address,
+    address::ExecutionAddressCycleInfo,
+    amount::Amount,
+    block_id::BlockId,
+    operation::{OperationId, OperationType, SecureShareOperation},
+    prehash::PreHashMap,
     slot::Slot,
 };
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 /// Execution info about an address
 #[derive(Clone, Debug)]
@@ -44,6 +54,10 @@ pub enum SlotExecutionOutput {
 
     /// Finalized slot output
     FinalizedSlot(ExecutionOutput),
+
+    /// Candidate execution of a slot dropped from the speculative history,
+    /// along with its events. The slot may be executed again later.
+    CanceledSlot(Slot),
 }
 
 /// structure describing the output of a single execution
@@ -57,6 +71,144 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// receipts of the operations executed during the execution step
+    pub receipts: PreHashMap<OperationId, OperationReceipt>,
+    /// outcomes of the asynchronous messages executed during the execution step
+    pub async_message_executions: Vec<AsyncMessageExecution>,
+    /// gas profile of the execution step
+    pub gas_profile: SlotGasProfile,
+}
+
+/// Gas used during the execution of a slot, broken down for profiling
+#[derive(Debug, Clone, Default)]
+pub struct SlotGasProfile {
+    /// gas used by the executed operations, by operation type
+    pub operation_types: BTreeMap<&'static str, u64>,
+    /// gas used by the executed asynchronous messages
+    pub async_messages: u64,
+    /// call count and gas used of the smart contract functions called by operations and asynchronous messages
+    pub functions: BTreeMap<(Address, String), (u64, u64)>,
+    /// time taken to execute the slot
+    pub execution_time: Duration,
+}
+
+impl SlotGasProfile {
+    /// Records the gas used by an executed operation.
+    /// Smart contract calls are also counted for the called function.
+    pub fn record_operation(&mut self, operation: &OperationType, gas_used: u64) {
+        let operation_type = match operation {
+            OperationType::Transaction { .. } => "Transaction",
+            OperationType::RollBuy { .. } => "RollBuy",
+            OperationType::RollSell { .. } => "RollSell",
+            OperationType::ExecuteSC { .. } => "ExecuteSC",
+            OperationType::CallSC {
+                target_addr,
+                target_func,
+                ..
+            } => {
+                self.record_call(*target_addr, target_func, gas_used);
+                "CallSC"
+            }
+        };
+        *self.operation_types.entry(operation_type).or_default() += gas_used;
+    }
+
+    /// Records the gas used by an executed asynchronous message, counted for its handler function
+    pub fn record_async_message(&mut self, destination: Address, handler: &str, gas_used: u64) {
+        self.record_call(destination, handler, gas_used);
+        self.async_messages += gas_used;
+    }
+
+    /// Records the gas used by a call to a smart contract function
+    fn record_call(&mut self, address: Address, function: &str, gas_used: u64) {
+        let (call_count, total_gas) = self
+            .functions
+            .entry((address, function.to_string()))
+            .or_default();
+        *call_count += 1;
+        *total_gas += gas_used;
+    }
+
+    /// Total gas used by the operations and asynchronous messages executed at the slot
+    pub fn gas_used(&self) -> u64 {
+        self.operation_types.values().sum::<u64>() + self.async_messages
+    }
+}
+
+/// Outcome of the execution of an asynchronous message
+#[derive(Debug, Clone)]
+pub struct AsyncMessageExecution {
+    /// id of the executed message
+    pub message_id: AsyncMessageId,
+    /// slot at which the message was executed
+    pub slot: Slot,
+    /// address that sent the message
+    pub sender: Address,
+    /// address targeted by the message
+    pub destination: Address,
+    /// handler function called on the destination
+    pub handler: String,
+    /// coins sent along with the message, reimbursed to the sender on failure
+    pub coins: Amount,
+    /// fee paid by the sender
+    pub fee: Amount,
+    /// error message if the execution failed, `None` if it succeeded
+    pub error: Option<String>,
+}
+
+/// Asynchronous message waiting in the speculative pool
+#[derive(Debug, Clone)]
+pub struct PendingAsyncMessage {
+    /// id of the message
+    pub id: AsyncMessageId,
+    /// the message itself
+    pub message: AsyncMessage,
+    /// whether the trigger of the message was matched.
+    /// Always false for messages without trigger.
+    pub trigger_fired: bool,
+    /// whether the message can be executed at the next slot:
+    /// its trigger, if any, was matched and the slot is within its validity range
+    pub executable: bool,
+}
+
+/// Receipt describing the outcome of the execution of an operation
+#[derive(Debug, Clone)]
+pub struct OperationReceipt {
+    /// id of the executed operation
+    pub operation_id: OperationId,
+    /// address of the operation sender
+    pub sender_address: Address,
+    /// slot at which the operation was executed
+    pub slot: Slot,
+    /// block in which the operation was included
+    pub block_id: Option<BlockId>,
+    /// error message if the execution failed, `None` if it succeeded
+    pub error: Option<String>,
+    /// gas consumed by the execution.
+    /// The whole max gas of a smart contract operation is counted if it failed.
+    pub gas_used: u64,
+    /// fee paid by the operation sender
+    pub fee: Amount,
+    /// balances changed by the operation, with their values before and after its execution
+    pub balance_changes: BTreeMap<Address, (Amount, Amount)>,
+    /// datastore entries written or deleted by the operation
+    pub datastore_keys: BTreeSet<(Address, Vec<u8>)>,
+    /// indexes in the slot of the events emitted during the execution of the operation,
+    /// the events themselves being kept by the event store
+    pub event_indexes: Vec<u64>,
+    /// whether the slot of the operation is final
+    pub is_final: bool,
+}
+
+/// Final state entries with their proofs, all checked against the same final state hash
+#[derive(Debug, Clone)]
+pub struct FinalStateProofs {
+    /// final slot at the output of which the proofs were computed
+    pub slot: Slot,
+    /// final state hash at that slot, root of the proofs
+    pub state_hash: Hash,
+    /// for each requested key: its value (`None` if absent from the final state) and its proof
+    pub entries: Vec<(Option<Vec<u8>>, StateProof)>,
 }
 
 /// structure describing the output of a read only execution
@@ -68,6 +220,23 @@ pub struct ReadOnlyExecutionOutput {
     pub gas_cost: u64,
     /// Returned value from the module call
     pub call_result: Vec<u8>,
+    /// Trace of the ABI calls made during the execution, if requested
+    pub trace: Option<ExecutionTraceFrame>,
+}
+
+/// Output of the determinism check of a final slot
+#[derive(Debug, Clone)]
+pub struct SlotDeterminismCheckOutput {
+    /// checked slot
+    pub slot: Slot,
+    /// block executed at the slot, if any
+    pub block_id: Option<BlockId>,
+    /// hash of the state changes finalized at the slot
+    pub final_state_changes_hash: Hash,
+    /// hash of the state changes of the re-execution of the slot
+    pub reexecution_state_changes_hash: Hash,
+    /// traces of the operations executed during the re-execution, in execution order, if requested
+    pub operation_traces: Vec<(OperationId, ExecutionTraceFrame)>,
 }
 
 /// structure describing different types of read-only execution request
@@ -83,6 +252,14 @@ pub struct ReadOnlyExecutionRequest {
     ///
     /// Whether to start execution from final or active state
     pub is_final: bool,
+    /// Past final slot on top of which to execute, reading the ledger from the archive.
+    /// Takes precedence over `is_final`.
+    ///
+    /// Only the ledger is archived: the async pool, deferred calls, executed operations
+    /// and proof-of-stake state are read from the current final state.
+    pub at_slot: Option<Slot>,
+    /// Whether to record the trace of the ABI calls made during the execution
+    pub trace: bool,
 }
 
 /// structure describing different possible targets of a read-only execution request
@@ -100,6 +277,11 @@ pub enum ReadOnlyExecutionTarget {
         /// Parameter to pass to the target function
         parameter: Vec<u8>,
     },
+
+    /// Simulate the inclusion of a signed operation in a block
+    /// at the next slot of the thread of its sender.
+    /// The receipt of the operation is part of the execution output.
+    Operation(SecureShareOperation),
 }
 
 /// structure describing a read-only call
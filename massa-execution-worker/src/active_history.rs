@@ -1,5 +1,5 @@
 use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncMessageUpdate};
-use massa_execution_exports::ExecutionOutput;
+use massa_execution_exports::{ExecutionOutput, OperationReceipt};
 use massa_ledger_exports::{
     Applicable, LedgerEntry, LedgerEntryUpdate, SetOrDelete, SetOrKeep, SetUpdateOrDelete,
 };
@@ -338,4 +338,12 @@ impl ActiveHistory {
             .map(|op_id| found.get(op_id).copied())
             .collect()
     }
+
+    /// Get the receipt of the latest execution of an operation, if any
+    pub fn fetch_operation_receipt(&self, op_id: &OperationId) -> Option<OperationReceipt> {
+        self.0
+            .iter()
+            .rev()
+            .find_map(|output| output.receipts.get(op_id).cloned())
+    }
 }
@@ -0,0 +1,135 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Persistent index of the final operations affecting each address.
+//!
+//! Each entry is keyed by `(address, slot, operation id)` so that the history of an address
+//! can be iterated by slot, and its value is the direction of the operation for that address.
+//!
+//! The index is not part of the final state: it is neither hashed nor bootstrapped,
+//! and only holds the operations of the slots finalized while the node was running.
+
+use massa_execution_exports::OperationReceipt;
+use massa_models::{
+    address::Address,
+    execution::{AddressHistoryCursor, AddressHistoryDirection, AddressHistoryEntry},
+    operation::{OperationId, OPERATION_ID_SIZE_BYTES},
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB};
+use std::{collections::BTreeMap, path::PathBuf};
+use tracing::warn;
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+
+const SENT: u8 = 0;
+const RECEIVED: u8 = 1;
+
+/// Size of the part of a key following the address: slot key followed by the operation id
+const ENTRY_KEY_SIZE: usize = SLOT_KEY_SIZE + OPERATION_ID_SIZE_BYTES;
+
+/// Sortable position of an entry in the history of an address
+fn entry_key(slot: &Slot, operation_id: &OperationId) -> Vec<u8> {
+    [&slot.to_bytes_key()[..], &operation_id.to_bytes()[..]].concat()
+}
+
+/// Persistent index of the final operations affecting each address
+pub(crate) struct AddressHistoryDB {
+    /// RocksDB database
+    db: DB,
+}
+
+impl AddressHistoryDB {
+    /// Opens the index, creating it if needed
+    ///
+    /// # Arguments
+    /// * `path`: where to store the db
+    pub fn new(path: PathBuf) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+
+        let db = DB::open(&db_opts, path).expect(OPEN_ERROR);
+
+        AddressHistoryDB { db }
+    }
+
+    /// Indexes the sender of each operation, and every other address
+    /// whose balance or datastore was changed by it.
+    ///
+    /// Writing the receipts of a re-executed slot again overwrites their entries.
+    pub fn insert_receipts<'a>(&self, receipts: impl Iterator<Item = &'a OperationReceipt>) {
+        let mut batch = WriteBatch::default();
+        for receipt in receipts {
+            let mut directions = BTreeMap::new();
+            for address in receipt
+                .balance_changes
+                .keys()
+                .chain(receipt.datastore_keys.iter().map(|(address, _)| address))
+            {
+                directions.insert(*address, RECEIVED);
+            }
+            directions.insert(receipt.sender_address, SENT);
+
+            let key = entry_key(&receipt.slot, &receipt.operation_id);
+            for (address, direction) in directions {
+                batch.put(
+                    [&address.to_prefixed_bytes()[..], &key[..]].concat(),
+                    [direction],
+                );
+            }
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Gets at most `limit` entries of the history of an address,
+    /// from the most recent slot, located strictly before an optional cursor.
+    pub fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<&AddressHistoryCursor>,
+        limit: usize,
+    ) -> Vec<AddressHistoryEntry> {
+        let prefix = address.to_prefixed_bytes();
+        let start_key = match cursor {
+            Some(cursor) => [&prefix[..], &entry_key(&cursor.slot, &cursor.operation_id)].concat(),
+            // past every entry of the address
+            None => [&prefix[..], &[u8::MAX; ENTRY_KEY_SIZE + 1][..]].concat(),
+        };
+
+        let mut entries = Vec::new();
+        for (key, value) in self
+            .db
+            .iterator(IteratorMode::From(&start_key, Direction::Reverse))
+            .flatten()
+        {
+            if entries.len() >= limit || !key.starts_with(&prefix) {
+                break;
+            }
+            if key.len() != prefix.len() + ENTRY_KEY_SIZE || key[..] == start_key[..] {
+                continue;
+            }
+            match Self::deserialize_entry(&key[prefix.len()..], &value) {
+                Some(entry) => entries.push(entry),
+                None => warn!("could not deserialize an address history entry"),
+            }
+        }
+        entries
+    }
+
+    /// Deserializes a stored entry from the part of its key following the address, and its value
+    fn deserialize_entry(key: &[u8], value: &[u8]) -> Option<AddressHistoryEntry> {
+        let (slot_key, operation_id) = key.split_at(SLOT_KEY_SIZE);
+        let slot = Slot::from_bytes_key(slot_key.try_into().ok()?);
+        let operation_id = OperationId::from_bytes(operation_id.try_into().ok()?);
+        let direction = match value {
+            [SENT] => AddressHistoryDirection::Sent,
+            [RECEIVED] => AddressHistoryDirection::Received,
+            _ => return None,
+        };
+        Some(AddressHistoryEntry {
+            operation_id,
+            slot,
+            direction,
+        })
+    }
+}
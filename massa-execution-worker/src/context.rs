@@ -11,12 +11,15 @@ use crate::speculative_async_pool::SpeculativeAsyncPool;
 use crate::speculative_executed_denunciations::SpeculativeExecutedDenunciations;
 use crate::speculative_executed_ops::SpeculativeExecutedOps;
 use crate::speculative_ledger::SpeculativeLedger;
+use crate::tracer::ExecutionTracer;
 use crate::vesting_manager::VestingManager;
 use crate::{active_history::ActiveHistory, speculative_roll_state::SpeculativeRollState};
 use massa_async_pool::{AsyncMessage, AsyncPoolChanges};
 use massa_executed_ops::{ExecutedDenunciationsChanges, ExecutedOpsChanges};
 use massa_execution_exports::{
-    EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
+    AsyncMessageExecution, EventStore, ExecutionConfig, ExecutionError, ExecutionOutput,
+    ExecutionStackElement, ExecutionTraceFrame, ExecutionTraceStep, OperationReceipt,
+    SlotGasProfile,
 };
 use massa_final_state::{FinalState, StateChanges};
 use massa_hash::Hash;
@@ -31,6 +34,7 @@ use massa_models::{
     block_id::BlockId,
     operation::OperationId,
     output_event::{EventExecutionContext, SCOutputEvent},
+    prehash::PreHashMap,
     slot::Slot,
 };
 use massa_module_cache::controller::ModuleCache;
@@ -154,6 +158,27 @@ pub struct ExecutionContext {
     /// operation id that originally caused this execution (if any)
     pub origin_operation_id: Option<OperationId>,
 
+    /// balances changed since the start of the current operation, with their value before the first change
+    touched_balances: BTreeMap<Address, Amount>,
+
+    /// datastore entries written or deleted since the start of the current operation
+    touched_datastore_keys: BTreeSet<(Address, Vec<u8>)>,
+
+    /// receipts of the operations executed so far during this execution
+    pub receipts: PreHashMap<OperationId, OperationReceipt>,
+
+    /// outcomes of the asynchronous messages executed so far during this execution
+    pub async_message_executions: Vec<AsyncMessageExecution>,
+
+    /// recorder of the ABI calls, only set for traced executions
+    pub tracer: Option<ExecutionTracer>,
+
+    /// traces of the operations executed so far, for slot determinism checks recording them
+    pub operation_traces: Vec<(OperationId, ExecutionTraceFrame)>,
+
+    /// gas used by the operations and asynchronous messages executed so far
+    pub gas_profile: SlotGasProfile,
+
     // cache of compiled runtime modules
     pub module_cache: Arc<RwLock<ModuleCache>>,
 
@@ -221,6 +246,13 @@ impl ExecutionContext {
             unsafe_rng: Xoshiro256PlusPlus::from_seed([0u8; 32]),
             creator_address: Default::default(),
             origin_operation_id: Default::default(),
+            touched_balances: Default::default(),
+            touched_datastore_keys: Default::default(),
+            receipts: Default::default(),
+            async_message_executions: Default::default(),
+            tracer: None,
+            operation_traces: Default::default(),
+            gas_profile: Default::default(),
             module_cache,
             config,
             vesting_manager,
@@ -269,6 +301,10 @@ impl ExecutionContext {
         self.stack = snapshot.stack;
         self.unsafe_rng = snapshot.unsafe_rng;
 
+        // Datastore writes since the snapshot are reverted.
+        // Reverted balances are filtered out when the operation changes are taken.
+        self.touched_datastore_keys.clear();
+
         // For events, set snapshot delta to error events.
         // Start iterating from snapshot events length because we are dealing with a VecDeque.
         for event in self.events.0.range_mut(snapshot.events.0.len()..) {
@@ -338,6 +374,24 @@ impl ExecutionContext {
         }
     }
 
+    /// Records a step of the execution trace if tracing is enabled.
+    /// The step is only built when it is recorded.
+    pub(crate) fn trace(&mut self, step: impl FnOnce() -> ExecutionTraceStep) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record(step());
+        }
+    }
+
+    /// Makes the context read the final ledger as it was right after a past final slot, from the archive
+    pub(crate) fn read_ledger_at(&mut self, slot: Slot) {
+        self.speculative_ledger.set_archive_slot(slot);
+    }
+
+    /// Takes the first error raised by a read of the archived ledger, if any
+    pub(crate) fn take_archive_error(&self) -> Option<ExecutionError> {
+        self.speculative_ledger.take_archive_error()
+    }
+
     /// This function takes a batch of asynchronous operations to execute, removing them from the speculative pool.
     ///
     /// # Arguments
@@ -576,6 +630,7 @@ impl ExecutionContext {
         }
 
         // set data entry
+        self.touched_datastore_keys.insert((*address, key.clone()));
         self.speculative_ledger
             .set_data_entry(&self.get_current_address()?, address, key, data)
     }
@@ -617,6 +672,7 @@ impl ExecutionContext {
         res_data.extend(data);
 
         // set data entry
+        self.touched_datastore_keys.insert((*address, key.clone()));
         self.speculative_ledger
             .set_data_entry(&self.get_current_address()?, address, key, res_data)
     }
@@ -641,6 +697,7 @@ impl ExecutionContext {
         }
 
         // delete entry
+        self.touched_datastore_keys.insert((*address, key.to_vec()));
         self.speculative_ledger
             .delete_data_entry(&self.get_current_address()?, address, key)
     }
@@ -688,6 +745,14 @@ impl ExecutionContext {
             }
         };
 
+        // keep the balances before the first change of the current operation
+        for addr in from_addr.iter().chain(to_addr.iter()) {
+            if !self.touched_balances.contains_key(addr) {
+                let balance = self.get_balance(addr).unwrap_or_default();
+                self.touched_balances.insert(*addr, balance);
+            }
+        }
+
         // do the transfer
         let result = self
             .speculative_ledger
@@ -913,6 +978,9 @@ impl ExecutionContext {
             block_id: std::mem::take(&mut self.opt_block_id),
             state_changes,
             events: std::mem::take(&mut self.events),
+            receipts: std::mem::take(&mut self.receipts),
+            async_message_executions: std::mem::take(&mut self.async_message_executions),
+            gas_profile: std::mem::take(&mut self.gas_profile),
         }
     }
 
@@ -1011,6 +1079,35 @@ impl ExecutionContext {
             .insert_executed_op(op_id, op_exec_status, op_valid_until_slot)
     }
 
+    /// Starts tracking the balances and datastore entries changed by a new operation.
+    /// Must be called before debiting the operation fee.
+    pub(crate) fn start_operation_tracking(&mut self) {
+        self.touched_balances.clear();
+        self.touched_datastore_keys.clear();
+    }
+
+    /// Takes the changes caused by the current operation:
+    /// the changed balances with their values before and after the operation,
+    /// and the written or deleted datastore entries.
+    pub(crate) fn take_operation_changes(
+        &mut self,
+    ) -> (
+        BTreeMap<Address, (Amount, Amount)>,
+        BTreeSet<(Address, Vec<u8>)>,
+    ) {
+        let balance_changes = std::mem::take(&mut self.touched_balances)
+            .into_iter()
+            .filter_map(|(addr, before)| {
+                let after = self.get_balance(&addr).unwrap_or_default();
+                (after != before).then_some((addr, (before, after)))
+            })
+            .collect();
+        (
+            balance_changes,
+            std::mem::take(&mut self.touched_datastore_keys),
+        )
+    }
+
     /// Insert a executed denunciation.
     ///
     pub fn insert_executed_denunciation(&mut self, denunciation_idx: &DenunciationIndex) {
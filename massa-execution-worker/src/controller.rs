@@ -3,23 +3,28 @@
 //! This module implements an execution controller.
 //! See `massa-execution-exports/controller_traits.rs` for functional details.
 
-use crate::execution::ExecutionState;
+use crate::execution::{ExecutionState, SlotDeterminismCheckRequest};
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
 use massa_channel::MassaChannel;
 use massa_execution_exports::{
     ExecutionAddressInfo, ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    FinalStateProofs, OperationReceipt, PendingAsyncMessage, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest, SlotDeterminismCheckOutput,
 };
+use massa_hash::Hash;
 use massa_models::denunciation::DenunciationIndex;
-use massa_models::execution::EventFilter;
+use massa_models::execution::{
+    AddressHistoryCursor, AddressHistoryEntry, AsyncMessageFilter, EventCursor, EventFilter,
+};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::stats::ExecutionStats;
 use massa_models::{address::Address, amount::Amount, operation::OperationId};
 use massa_models::{block_id::BlockId, slot::Slot};
 use massa_storage::Storage;
+use massa_time::MassaTime;
 use parking_lot::{Condvar, Mutex, RwLock};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Display;
 use std::sync::Arc;
 use tracing::info;
@@ -139,6 +144,18 @@ impl ExecutionController for ExecutionControllerImpl {
             .get_filtered_sc_output_event(filter)
     }
 
+    /// Get a page of the generated execution events matching a filter, after an optional cursor
+    fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventCursor>,
+        limit: usize,
+    ) -> Vec<SCOutputEvent> {
+        self.execution_state
+            .read()
+            .get_filtered_sc_output_event_page(filter, cursor, limit)
+    }
+
     /// Get the final and candidate values of balance.
     ///
     /// # Return value
@@ -171,11 +188,25 @@ impl ExecutionController for ExecutionControllerImpl {
         result
     }
 
+    /// Get the final values of a batch of serialized state keys, with their proofs
+    /// against the final state hash
+    fn get_final_state_proofs(&self, keys: &[Vec<u8>]) -> Result<FinalStateProofs, ExecutionError> {
+        self.execution_state.read().get_final_state_proofs(keys)
+    }
+
     /// Return the active rolls distribution for the given `cycle`
     fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64> {
         self.execution_state.read().get_cycle_active_rolls(cycle)
     }
 
+    /// Return the inputs of the selector draws for the given `cycle`
+    fn get_selector_draw_inputs(
+        &self,
+        cycle: u64,
+    ) -> Result<(BTreeMap<Address, u64>, Hash), ExecutionError> {
+        self.execution_state.read().get_selector_draw_inputs(cycle)
+    }
+
     /// Executes a read-only request
     /// Read-only requests do not modify consensus state
     fn execute_readonly_request(
@@ -264,6 +295,95 @@ impl ExecutionController for ExecutionControllerImpl {
     fn get_ops_exec_status(&self, batch: &[OperationId]) -> Vec<(Option<bool>, Option<bool>)> {
         self.execution_state.read().get_ops_exec_status(batch)
     }
+
+    /// See trait definition
+    fn get_operation_receipts(&self, batch: &[OperationId]) -> Vec<Option<OperationReceipt>> {
+        self.execution_state.read().get_operation_receipts(batch)
+    }
+
+    /// See trait definition
+    fn get_async_messages(&self, filter: &AsyncMessageFilter) -> Vec<PendingAsyncMessage> {
+        self.execution_state.read().get_async_messages(filter)
+    }
+
+    /// See trait definition
+    fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<AddressHistoryCursor>,
+        limit: usize,
+    ) -> Option<Vec<AddressHistoryEntry>> {
+        self.execution_state
+            .read()
+            .get_address_history(address, cursor, limit)
+    }
+
+    /// Checks the determinism of the execution of an upcoming final slot, once it is finalized
+    fn check_slot_determinism(
+        &self,
+        slot: Slot,
+        trace: bool,
+        timeout: MassaTime,
+    ) -> Result<SlotDeterminismCheckOutput, ExecutionError> {
+        // prepare the channel to send back the result of the check
+        let (resp_tx, resp_rx) =
+            MassaChannel::new("slot_determinism_check_request".to_string(), None);
+
+        // the check is done by the execution worker when the slot is finalized
+        self.execution_state
+            .write()
+            .request_determinism_check(RequestWithResponseSender::new(
+                SlotDeterminismCheckRequest { slot, trace },
+                resp_tx,
+            ));
+
+        // Wait for the result of the check. On timeout, the worker still checks the slot
+        // when it is finalized, but its result is dropped with the channel.
+        match resp_rx.recv_timeout(timeout.to_duration()) {
+            Ok(result) => result,
+            Err(err) if err.is_timeout() => Err(ExecutionError::ChannelError(format!(
+                "slot {} was not finalized and checked within {}",
+                slot, timeout
+            ))),
+            Err(err) => Err(ExecutionError::ChannelError(format!(
+                "slot determinism check response channel readout failed: {}",
+                err
+            ))),
+        }
+    }
+
+    /// Get the balances of a batch of addresses right after a past final slot
+    fn get_archived_balances(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_archived_balances(addresses, &slot)
+    }
+
+    /// Get the datastore keys of a batch of addresses right after a past final slot
+    fn get_archived_datastore_keys(
+        &self,
+        addresses: &[Address],
+        slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_archived_datastore_keys(addresses, &slot)
+    }
+
+    /// Get a batch of datastore entries right after a past final slot
+    fn get_archived_data_entries(
+        &self,
+        input: Vec<(Address, Vec<u8>)>,
+        slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_archived_data_entries(input, &slot)
+    }
 }
 
 /// Execution manager
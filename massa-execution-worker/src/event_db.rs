@@ -0,0 +1,280 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Persistent archive of the final execution events.
+//!
+//! Events are stored by `(slot, index_in_slot)` so that slot ranges can be iterated in order,
+//! and indexed by emitter address, original caller address and origin operation id.
+//! Each index entry is `(indexed value, slot, index_in_slot)` with an empty value.
+//!
+//! The archive is not part of the final state: it is neither hashed nor bootstrapped,
+//! and only holds the events of the slots finalized while the node was running.
+//! Events older than the configured retention are pruned along with their index entries.
+
+use massa_execution_exports::{event_matches_filter, EventStore};
+use massa_models::{
+    execution::{EventCursor, EventFilter},
+    output_event::SCOutputEvent,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::path::PathBuf;
+use tracing::warn;
+
+const EVENTS_CF: &str = "events";
+const EMITTER_INDEX_CF: &str = "emitter_index";
+const CALLER_INDEX_CF: &str = "caller_index";
+const OPERATION_INDEX_CF: &str = "operation_index";
+
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const EVENT_SER_ERROR: &str = "critical: event serialization failed";
+
+/// Size of the key of an event: slot key followed by the big endian index in slot
+const EVENT_KEY_SIZE: usize = SLOT_KEY_SIZE + 8;
+
+/// Maximum number of events removed by a single pruning,
+/// so that catching up with a long history does not stall the finalization of a slot
+const MAX_PRUNED_EVENTS: usize = 10_000;
+
+/// Sortable key of an event
+fn event_key(slot: &Slot, index_in_slot: u64) -> Vec<u8> {
+    [&slot.to_bytes_key()[..], &index_in_slot.to_be_bytes()[..]].concat()
+}
+
+/// Index entries of an event: column family and key
+fn index_keys(event: &SCOutputEvent, key: &[u8]) -> Vec<(&'static str, Vec<u8>)> {
+    let mut index_keys = Vec::with_capacity(3);
+    if let Some(emitter) = event.context.call_stack.front() {
+        index_keys.push((
+            EMITTER_INDEX_CF,
+            [&emitter.to_prefixed_bytes()[..], key].concat(),
+        ));
+    }
+    if let Some(caller) = event.context.call_stack.back() {
+        index_keys.push((
+            CALLER_INDEX_CF,
+            [&caller.to_prefixed_bytes()[..], key].concat(),
+        ));
+    }
+    if let Some(operation_id) = event.context.origin_operation_id {
+        index_keys.push((
+            OPERATION_INDEX_CF,
+            [&operation_id.to_bytes()[..], key].concat(),
+        ));
+    }
+    index_keys
+}
+
+/// Persistent store of the final execution events
+pub(crate) struct EventDB {
+    /// RocksDB database
+    db: DB,
+}
+
+impl EventDB {
+    /// Opens the event store, creating it if needed
+    ///
+    /// # Arguments
+    /// * `path`: where to store the db
+    pub fn new(path: PathBuf) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![
+                ColumnFamilyDescriptor::new(EVENTS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(EMITTER_INDEX_CF, Options::default()),
+                ColumnFamilyDescriptor::new(CALLER_INDEX_CF, Options::default()),
+                ColumnFamilyDescriptor::new(OPERATION_INDEX_CF, Options::default()),
+            ],
+        )
+        .expect(OPEN_ERROR);
+
+        EventDB { db }
+    }
+
+    /// Writes final events along with their index entries.
+    ///
+    /// Events are keyed by slot and index in slot,
+    /// so writing the events of a re-executed slot again overwrites them.
+    pub fn insert_events(&self, events: &EventStore) {
+        let events_cf = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+
+        let mut batch = WriteBatch::default();
+        for event in events.0.iter() {
+            let key = event_key(&event.context.slot, event.context.index_in_slot);
+            batch.put_cf(
+                events_cf,
+                &key,
+                serde_json::to_vec(event).expect(EVENT_SER_ERROR),
+            );
+            for (cf, index_key) in index_keys(event, &key) {
+                batch.put_cf(self.db.cf_handle(cf).expect(CF_ERROR), index_key, []);
+            }
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Removes the events of the slots before `slot`, along with their index entries.
+    ///
+    /// At most `MAX_PRUNED_EVENTS` events are removed per call:
+    /// the remaining ones are removed by the next calls.
+    pub fn prune_events_before(&self, slot: &Slot) {
+        let events_cf = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        let end_key = event_key(slot, 0);
+
+        let mut batch = WriteBatch::default();
+        for (key, value) in self
+            .db
+            .iterator_cf(events_cf, IteratorMode::Start)
+            .flatten()
+            .take(MAX_PRUNED_EVENTS)
+        {
+            if key[..] >= end_key[..] {
+                break;
+            }
+            if let Some(event) = Self::deserialize_event(&value) {
+                for (cf, index_key) in index_keys(&event, &key) {
+                    batch.delete_cf(self.db.cf_handle(cf).expect(CF_ERROR), index_key);
+                }
+            }
+            batch.delete_cf(events_cf, &key);
+        }
+        if !batch.is_empty() {
+            self.db.write(batch).expect(CRUD_ERROR);
+        }
+    }
+
+    /// Gets at most `limit` stored events matching a filter and located after an optional cursor,
+    /// ordered by slot and index in slot.
+    ///
+    /// The most selective criterion of the filter is answered with its index:
+    /// origin operation id, then emitter address, then original caller address.
+    /// Without any of them, the events of the slot range are iterated.
+    pub fn get_filtered_sc_output_events(
+        &self,
+        filter: &EventFilter,
+        cursor: Option<&EventCursor>,
+        limit: usize,
+    ) -> Vec<SCOutputEvent> {
+        self.iter_filtered_sc_output_events(filter, cursor, limit, Direction::Forward)
+    }
+
+    /// Gets the last `limit` stored events matching a filter, ordered by slot and index in slot.
+    pub fn get_latest_filtered_sc_output_events(
+        &self,
+        filter: &EventFilter,
+        limit: usize,
+    ) -> Vec<SCOutputEvent> {
+        let mut events =
+            self.iter_filtered_sc_output_events(filter, None, limit, Direction::Reverse);
+        events.reverse();
+        events
+    }
+
+    /// Collects at most `limit` events matching a filter and located after an optional cursor,
+    /// from the start of the range going forward, or from its end going in reverse
+    fn iter_filtered_sc_output_events(
+        &self,
+        filter: &EventFilter,
+        cursor: Option<&EventCursor>,
+        limit: usize,
+        direction: Direction,
+    ) -> Vec<SCOutputEvent> {
+        let (cf, prefix) = if let Some(operation_id) = filter.original_operation_id {
+            (OPERATION_INDEX_CF, operation_id.to_bytes().to_vec())
+        } else if let Some(emitter) = filter.emitter_address {
+            (EMITTER_INDEX_CF, emitter.to_prefixed_bytes())
+        } else if let Some(caller) = filter.original_caller_address {
+            (CALLER_INDEX_CF, caller.to_prefixed_bytes())
+        } else {
+            (EVENTS_CF, Vec::new())
+        };
+        let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+
+        // start at the latest of the filter start and the cursor
+        let mut start_key = filter
+            .start
+            .map(|slot| event_key(&slot, 0))
+            .unwrap_or_default();
+        let cursor_key = cursor.map(|cursor| event_key(&cursor.slot, cursor.index_in_slot));
+        if let Some(cursor_key) = &cursor_key && *cursor_key > start_key {
+            start_key = cursor_key.clone();
+        }
+        let end_key = filter.end.map(|slot| event_key(&slot, 0));
+        // reverse iteration starts at the last key lower or equal to the seek key
+        let seek_key = match direction {
+            Direction::Forward => [&prefix[..], &start_key[..]].concat(),
+            Direction::Reverse => match &end_key {
+                Some(end_key) => [&prefix[..], &end_key[..]].concat(),
+                None => [&prefix[..], &[u8::MAX; EVENT_KEY_SIZE][..]].concat(),
+            },
+        };
+
+        let mut events = Vec::new();
+        for (key, value) in self
+            .db
+            .iterator_cf(handle, IteratorMode::From(&seek_key, direction))
+            .flatten()
+        {
+            if events.len() >= limit || !key.starts_with(&prefix) {
+                break;
+            }
+            if key.len() != prefix.len() + EVENT_KEY_SIZE {
+                continue;
+            }
+            let stored_key = &key[prefix.len()..];
+            match direction {
+                Direction::Forward => {
+                    if matches!(&end_key, Some(end_key) if stored_key >= &end_key[..]) {
+                        break;
+                    }
+                }
+                Direction::Reverse => {
+                    if stored_key < &start_key[..] {
+                        break;
+                    }
+                    // the end of the slot range is excluded
+                    if matches!(&end_key, Some(end_key) if stored_key >= &end_key[..]) {
+                        continue;
+                    }
+                }
+            }
+            if cursor_key.as_deref() == Some(stored_key) {
+                continue;
+            }
+            // index entries have an empty value: the event is read from the events column
+            let event = if cf == EVENTS_CF {
+                Self::deserialize_event(&value)
+            } else {
+                self.get_event(stored_key)
+            };
+            if let Some(event) = event && event_matches_filter(&event, filter) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Reads a single event by key
+    fn get_event(&self, key: &[u8]) -> Option<SCOutputEvent> {
+        let events_cf = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        let value = self.db.get_cf(events_cf, key).expect(CRUD_ERROR)?;
+        Self::deserialize_event(&value)
+    }
+
+    /// Deserializes a stored event, logging a warning on failure
+    fn deserialize_event(value: &[u8]) -> Option<SCOutputEvent> {
+        match serde_json::from_slice(value) {
+            Ok(event) => Some(event),
+            Err(err) => {
+                warn!("could not deserialize a stored event: {}", err);
+                None
+            }
+        }
+    }
+}
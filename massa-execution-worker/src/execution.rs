@@ -9,25 +9,34 @@
 //! * the output of the execution is extracted from the context
 
 use crate::active_history::{ActiveHistory, HistorySearchResult};
+use crate::address_history_db::AddressHistoryDB;
 use crate::context::{ExecutionContext, ExecutionContextSnapshot};
+use crate::event_db::EventDB;
 use crate::interface_impl::InterfaceImpl;
-use crate::stats::ExecutionStatsCounter;
+use crate::request_queue::RequestWithResponseSender;
+use crate::stats::{ExecutionStatsCounter, MAX_TOP_GAS_FUNCTIONS};
+use crate::tracer::ExecutionTracer;
 use crate::vesting_manager::VestingManager;
-use massa_async_pool::AsyncMessage;
+use massa_async_pool::{AsyncMessage, AsyncMessageId};
 use massa_db::DBBatch;
 use massa_execution_exports::{
-    EventStore, ExecutionChannels, ExecutionConfig, ExecutionError, ExecutionOutput,
-    ExecutionStackElement, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
-    ReadOnlyExecutionTarget, SlotExecutionOutput,
+    AsyncMessageExecution, EventStore, ExecutionChannels, ExecutionConfig, ExecutionError,
+    ExecutionOutput, ExecutionStackElement, FinalStateProofs, OperationReceipt,
+    PendingAsyncMessage, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget, SlotDeterminismCheckOutput, SlotExecutionOutput, SlotGasProfile,
 };
 use massa_final_state::FinalState;
-use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
+use massa_hash::Hash;
+use massa_ledger_exports::{Applicable, SetOrDelete, SetUpdateOrDelete};
 use massa_metrics::MassaMetrics;
 use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::bytecode::Bytecode;
 use massa_models::denunciation::{Denunciation, DenunciationIndex};
-use massa_models::execution::EventFilter;
+use massa_models::execution::{
+    AddressHistoryCursor, AddressHistoryEntry, AsyncMessageFilter, EventCursor, EventFilter,
+};
 use massa_models::output_event::SCOutputEvent;
+use massa_models::prehash::PreHashMap;
 use massa_models::stats::ExecutionStats;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_models::{
@@ -43,8 +52,9 @@ use massa_sc_runtime::{Interface, Response, VMError};
 use massa_storage::Storage;
 use massa_versioning::versioning::MipStore;
 use parking_lot::{Mutex, RwLock};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, info, trace, warn};
 
 /// Used to acquire a lock on the execution context
@@ -71,6 +81,14 @@ pub(crate) struct ExecutionState {
     pub final_cursor: Slot,
     // store containing execution events that became final
     final_events: EventStore,
+    // on-disk archive of the final execution events, if enabled
+    event_db: Option<EventDB>,
+    // receipts of the operations executed in final slots
+    final_receipts: PreHashMap<OperationId, OperationReceipt>,
+    // ids of the final receipts, oldest at the front, used to prune them
+    final_receipts_order: VecDeque<OperationId>,
+    // on-disk index of the final operations affecting each address, if enabled
+    address_history_db: Option<AddressHistoryDB>,
     // final state with atomic R/W access
     final_state: Arc<RwLock<FinalState>>,
     // execution context (see documentation in context.rs)
@@ -91,6 +109,17 @@ pub(crate) struct ExecutionState {
     channels: ExecutionChannels,
     /// prometheus metrics
     massa_metrics: MassaMetrics,
+    /// determinism checks of upcoming final slots, with the channels to send back their outputs
+    determinism_checks:
+        Vec<RequestWithResponseSender<SlotDeterminismCheckRequest, SlotDeterminismCheckOutput>>,
+}
+
+/// Request to check the determinism of the execution of an upcoming final slot
+pub(crate) struct SlotDeterminismCheckRequest {
+    /// slot to check
+    pub slot: Slot,
+    /// whether to record the trace of the operations executed during the re-execution
+    pub trace: bool,
 }
 
 impl ExecutionState {
@@ -162,6 +191,16 @@ impl ExecutionState {
             execution_context.clone(),
         ));
 
+        // Open the final events archive
+        let event_db = config
+            .event_store_enabled
+            .then(|| EventDB::new(config.event_store_path.clone()));
+
+        // Open the address history index
+        let address_history_db = config
+            .address_history_enabled
+            .then(|| AddressHistoryDB::new(config.address_history_path.clone()));
+
         // build the execution state
         ExecutionState {
             final_state,
@@ -171,6 +210,11 @@ impl ExecutionState {
             active_history,
             // empty final event store: it is not recovered through bootstrap
             final_events: Default::default(),
+            event_db,
+            // empty final receipts: they are not recovered through bootstrap
+            final_receipts: Default::default(),
+            final_receipts_order: Default::default(),
+            address_history_db,
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
@@ -182,12 +226,51 @@ impl ExecutionState {
             selector,
             channels,
             massa_metrics,
+            determinism_checks: Vec::new(),
         }
     }
 
     /// Get execution statistics
     pub fn get_stats(&self) -> ExecutionStats {
-        self.stats_counter.get_stats(self.active_cursor)
+        self.stats_counter
+            .get_stats(self.active_cursor, self.module_cache.read().get_stats())
+    }
+
+    /// Updates the execution profiling metrics with the gas profile of a newly final slot
+    fn update_profiling_metrics(&self, gas_profile: &SlotGasProfile) {
+        for (operation_type, gas_used) in &gas_profile.operation_types {
+            self.massa_metrics
+                .inc_execution_operation_gas(operation_type, *gas_used);
+        }
+        self.massa_metrics
+            .inc_execution_async_message_gas(gas_profile.async_messages);
+        self.massa_metrics
+            .observe_final_slot_execution_time(gas_profile.execution_time.as_secs_f64());
+
+        let top_gas_functions: Vec<(String, String, u64)> = self
+            .stats_counter
+            .get_top_gas_functions(MAX_TOP_GAS_FUNCTIONS)
+            .into_iter()
+            .map(|function| {
+                (
+                    function.address.to_string(),
+                    function.function,
+                    function.gas_used,
+                )
+            })
+            .collect();
+        self.massa_metrics
+            .set_execution_top_gas_functions(&top_gas_functions);
+
+        let module_cache_stats = self.module_cache.read().get_stats();
+        self.massa_metrics.set_module_cache_metrics(
+            module_cache_stats.lru_hits,
+            module_cache_stats.hd_hits,
+            module_cache_stats.misses,
+            module_cache_stats.compilation_count,
+            module_cache_stats.compilation_time.to_millis() as f64 / 1000.0,
+            module_cache_stats.hit_rate().unwrap_or(0.0),
+        );
     }
 
     /// Applies the output of an execution to the final execution state.
@@ -210,6 +293,9 @@ impl ExecutionState {
                 exec_out.state_changes.executed_denunciations_changes.len(),
             );
         }
+        self.stats_counter
+            .register_final_gas_profile(exec_out.slot, exec_out.gas_profile.clone());
+        self.update_profiling_metrics(&exec_out.gas_profile);
 
         // apply state changes to the final ledger
         self.final_state
@@ -227,9 +313,39 @@ impl ExecutionState {
 
         // append generated events to the final event store
         exec_out.events.finalize();
+        if let Some(event_db) = &self.event_db {
+            event_db.insert_events(&exec_out.events);
+            if self.config.event_store_retention_periods > 0 {
+                event_db.prune_events_before(&Slot::new(
+                    exec_out
+                        .slot
+                        .period
+                        .saturating_sub(self.config.event_store_retention_periods),
+                    0,
+                ));
+            }
+        }
         self.final_events.extend(exec_out.events);
         self.final_events.prune(self.config.max_final_events);
 
+        // index the final operations by the addresses they affected
+        if let Some(address_history_db) = &self.address_history_db {
+            address_history_db.insert_receipts(exec_out.receipts.values());
+        }
+
+        // keep the receipts of the final operations, up to the configured limit
+        for (op_id, mut receipt) in exec_out.receipts {
+            receipt.is_final = true;
+            if self.final_receipts.insert(op_id, receipt).is_none() {
+                self.final_receipts_order.push_back(op_id);
+            }
+        }
+        while self.final_receipts_order.len() > self.config.max_final_receipts {
+            if let Some(op_id) = self.final_receipts_order.pop_front() {
+                self.final_receipts.remove(&op_id);
+            }
+        }
+
         // update the prometheus metrics
         self.massa_metrics
             .set_active_cursor(self.active_cursor.period, self.active_cursor.thread);
@@ -282,6 +398,9 @@ impl ExecutionState {
             ));
         }
 
+        // track the changes of the operation for its receipt, fee included
+        context.start_operation_tracking();
+
         // Set the creator coin spending allowance.
         // Note that this needs to be initialized before any spending from the creator.
         context.creator_coin_spending_allowance =
@@ -373,7 +492,11 @@ impl ExecutionState {
         // update block credits
         *block_credits = new_block_credits;
 
+        // index of the first event emitted by the operation itself
+        let first_event_index = context_snapshot.events.0.len();
+
         // Call the execution process specific to the operation type.
+        // Only smart contract executions consume gas.
         let execution_result = match &operation.content.op {
             OperationType::ExecuteSC { .. } => {
                 self.execute_executesc_op(&operation.content.op, sender_addr)
@@ -381,15 +504,15 @@ impl ExecutionState {
             OperationType::CallSC { .. } => {
                 self.execute_callsc_op(&operation.content.op, sender_addr)
             }
-            OperationType::RollBuy { .. } => {
-                self.execute_roll_buy_op(&operation.content.op, sender_addr, block_slot)
-            }
-            OperationType::RollSell { .. } => {
-                self.execute_roll_sell_op(&operation.content.op, sender_addr)
-            }
-            OperationType::Transaction { .. } => {
-                self.execute_transaction_op(&operation.content.op, sender_addr)
-            }
+            OperationType::RollBuy { .. } => self
+                .execute_roll_buy_op(&operation.content.op, sender_addr, block_slot)
+                .map(|_| 0),
+            OperationType::RollSell { .. } => self
+                .execute_roll_sell_op(&operation.content.op, sender_addr)
+                .map(|_| 0),
+            OperationType::Transaction { .. } => self
+                .execute_transaction_op(&operation.content.op, sender_addr)
+                .map(|_| 0),
         };
 
         {
@@ -397,12 +520,15 @@ impl ExecutionState {
             let mut context = context_guard!(self);
 
             // check execution results
-            match execution_result {
-                Ok(_) => context.insert_executed_op(
-                    operation_id,
-                    true,
-                    Slot::new(operation.content.expire_period, op_thread),
-                ),
+            let (gas_used, error) = match execution_result {
+                Ok(gas_used) => {
+                    context.insert_executed_op(
+                        operation_id,
+                        true,
+                        Slot::new(operation.content.expire_period, op_thread),
+                    );
+                    (gas_used, None)
+                }
                 Err(err) => {
                     // an error occurred: emit error event and reset context to snapshot
                     let err = ExecutionError::RuntimeError(format!(
@@ -410,16 +536,42 @@ impl ExecutionState {
                         operation_id, &err
                     ));
                     debug!("{}", &err);
-                    context.reset_to_snapshot(context_snapshot, err);
+                    context.reset_to_snapshot(context_snapshot, err.clone());
 
                     // Insert op AFTER the context has been restore (else it would be overwritten)
                     context.insert_executed_op(
                         operation_id,
                         false,
                         Slot::new(operation.content.expire_period, op_thread),
-                    )
+                    );
+                    (op_gas, Some(err.to_string()))
                 }
-            }
+            };
+
+            // write the receipt of the operation
+            let (balance_changes, datastore_keys) = context.take_operation_changes();
+            let receipt = OperationReceipt {
+                operation_id,
+                sender_address: sender_addr,
+                slot: block_slot,
+                block_id: context.opt_block_id,
+                error,
+                gas_used,
+                fee: operation.content.fee,
+                balance_changes,
+                datastore_keys,
+                event_indexes: context
+                    .events
+                    .0
+                    .range(first_event_index..)
+                    .map(|event| event.context.index_in_slot)
+                    .collect(),
+                is_final: false,
+            };
+            context.receipts.insert(operation_id, receipt);
+            context
+                .gas_profile
+                .record_operation(&operation.content.op, gas_used);
         }
 
         Ok(())
@@ -713,11 +865,14 @@ impl ExecutionState {
     /// # Arguments
     /// * `operation`: the `WrappedOperation` to process, must be an `ExecuteSC`
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas consumed by the execution, compilation included
     pub fn execute_executesc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process ExecuteSC operations only
         let (bytecode, max_gas, datastore) = match &operation {
             OperationType::ExecuteSC {
@@ -757,7 +912,7 @@ impl ExecutionState {
                 "not enough gas to pay for singlepass compilation".to_string(),
             ))?;
         // run the VM
-        massa_sc_runtime::run_main(
+        let response = massa_sc_runtime::run_main(
             &*self.execution_interface,
             module,
             remaining_gas,
@@ -768,7 +923,7 @@ impl ExecutionState {
             error,
         })?;
 
-        Ok(())
+        Ok(max_gas.saturating_sub(response.remaining_gas))
     }
 
     /// Execute an operation of type `CallSC`
@@ -779,11 +934,14 @@ impl ExecutionState {
     /// * `block_creator_addr`: address of the block creator
     /// * `operation_id`: ID of the operation
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas consumed by the execution
     pub fn execute_callsc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process CallSC operations only
         let (max_gas, target_addr, target_func, param, coins) = match &operation {
             OperationType::CallSC {
@@ -838,7 +996,7 @@ impl ExecutionState {
 
             // quit if there is no function to be called
             if target_func.is_empty() {
-                return Ok(());
+                return Ok(0);
             }
 
             // Load bytecode. Assume empty bytecode if not found.
@@ -865,11 +1023,11 @@ impl ExecutionState {
             }
             _ => (),
         }
-        response.map_err(|error| ExecutionError::VMError {
+        let response = response.map_err(|error| ExecutionError::VMError {
             context: "CallSC".to_string(),
             error,
         })?;
-        Ok(())
+        Ok(max_gas.saturating_sub(response.remaining_gas))
     }
 
     /// Tries to execute an asynchronous message
@@ -956,10 +1114,19 @@ impl ExecutionState {
             self.config.gas_costs.clone(),
         );
         match response {
-            Ok(Response { init_gas_cost, .. }) => {
+            Ok(Response {
+                init_gas_cost,
+                remaining_gas,
+                ..
+            }) => {
                 self.module_cache
                     .write()
                     .set_init_cost(&bytecode, init_gas_cost);
+                context_guard!(self).gas_profile.record_async_message(
+                    message.destination,
+                    &message.handler,
+                    message.max_gas.saturating_sub(remaining_gas),
+                );
                 Ok(())
             }
             Err(error) => {
@@ -976,6 +1143,12 @@ impl ExecutionState {
                 let mut context = context_guard!(self);
                 context.reset_to_snapshot(context_snapshot, err.clone());
                 context.cancel_async_message(&message);
+                // as for operations, the whole max gas is counted for failed executions
+                context.gas_profile.record_async_message(
+                    message.destination,
+                    &message.handler,
+                    message.max_gas,
+                );
                 Err(err)
             }
         }
@@ -997,13 +1170,59 @@ impl ExecutionState {
         exec_target: Option<&(BlockId, Storage)>,
         selector: Box<dyn SelectorController>,
     ) -> ExecutionOutput {
+        let exec_out = self.run_slot(
+            slot,
+            exec_target,
+            selector,
+            self.active_history.clone(),
+            false,
+        );
+
+        // Broadcast a slot execution output to active channel subscribers.
+        if self.config.broadcast_enabled {
+            let slot_exec_out = SlotExecutionOutput::ExecutedSlot(exec_out.clone());
+            if let Err(err) = self
+                .channels
+                .slot_execution_output_sender
+                .send(slot_exec_out)
+            {
+                trace!(
+                    "error, failed to broadcast execution output for slot {} due to: {}",
+                    exec_out.slot.clone(),
+                    err
+                );
+            }
+        }
+
+        // Return the execution output
+        exec_out
+    }
+
+    /// Executes a slot on top of the given active history, without broadcasting its output
+    ///
+    /// # Arguments
+    /// * `slot`: slot to execute
+    /// * `exec_target`: metadata of the block to execute, if not miss
+    /// * `selector`: selector controller, used to get the producer of missed slots
+    /// * `active_history`: history of the candidate slots executed before this one
+    /// * `trace_operations`: whether to record the trace of each operation in the execution context
+    fn run_slot(
+        &self,
+        slot: &Slot,
+        exec_target: Option<&(BlockId, Storage)>,
+        selector: Box<dyn SelectorController>,
+        active_history: Arc<RwLock<ActiveHistory>>,
+        trace_operations: bool,
+    ) -> ExecutionOutput {
+        let execution_start = Instant::now();
+
         // Create a new execution context for the whole active slot
         let mut execution_context = ExecutionContext::active_slot(
             self.config.clone(),
             *slot,
             exec_target.as_ref().map(|(b_id, _)| *b_id),
             self.final_state.clone(),
-            self.active_history.clone(),
+            active_history,
             self.module_cache.clone(),
             self.vesting_manager.clone(),
             self.mip_store.clone(),
@@ -1019,9 +1238,23 @@ impl ExecutionState {
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
+            let mut execution = AsyncMessageExecution {
+                message_id: message.compute_id(),
+                slot: *slot,
+                sender: message.sender,
+                destination: message.destination,
+                handler: message.handler.clone(),
+                coins: message.coins,
+                fee: message.fee,
+                error: None,
+            };
             if let Err(err) = self.execute_async_message(message, opt_bytecode) {
                 debug!("failed executing async message: {}", err);
+                execution.error = Some(err.to_string());
             }
+            context_guard!(self)
+                .async_message_executions
+                .push(execution);
         }
 
         // Check if there is a block at this slot
@@ -1084,6 +1317,13 @@ impl ExecutionState {
             // Try executing the operations of this block in the order in which they appear in the block.
             // Errors are logged but do not interrupt the execution of the slot.
             for operation in operations.into_iter() {
+                if trace_operations {
+                    context_guard!(self).tracer = Some(ExecutionTracer::new(
+                        operation.content_creator_address,
+                        Amount::zero(),
+                        operation.get_gas_usage(),
+                    ));
+                }
                 if let Err(err) = self.execute_operation(
                     &operation,
                     stored_block.content.header.content.slot,
@@ -1095,6 +1335,17 @@ impl ExecutionState {
                         operation.id, block_id, err
                     );
                 }
+                if trace_operations {
+                    let mut context = context_guard!(self);
+                    let remaining_gas = context
+                        .receipts
+                        .get(&operation.id)
+                        .map(|receipt| operation.get_gas_usage().saturating_sub(receipt.gas_used));
+                    if let Some(tracer) = context.tracer.take() {
+                        let trace = tracer.finish(remaining_gas);
+                        context.operation_traces.push((operation.id, trace));
+                    }
+                }
             }
 
             // Try executing the denunciations of this block
@@ -1184,25 +1435,8 @@ impl ExecutionState {
         }
 
         // Finish slot
-        let exec_out = context_guard!(self).settle_slot();
-
-        // Broadcast a slot execution output to active channel subscribers.
-        if self.config.broadcast_enabled {
-            let slot_exec_out = SlotExecutionOutput::ExecutedSlot(exec_out.clone());
-            if let Err(err) = self
-                .channels
-                .slot_execution_output_sender
-                .send(slot_exec_out)
-            {
-                trace!(
-                    "error, failed to broadcast execution output for slot {} due to: {}",
-                    exec_out.slot.clone(),
-                    err
-                );
-            }
-        }
-
-        // Return the execution output
+        let mut exec_out = context_guard!(self).settle_slot();
+        exec_out.gas_profile.execution_time = execution_start.elapsed();
         exec_out
     }
 
@@ -1232,9 +1466,18 @@ impl ExecutionState {
                 "execute_candidate_slot: truncating down from slot {}",
                 self.active_cursor
             );
+            let canceled_slots: Vec<Slot> = self
+                .active_history
+                .read()
+                .0
+                .iter()
+                .map(|exec_out| exec_out.slot)
+                .filter(|history_slot| history_slot >= slot)
+                .collect();
             self.active_history
                 .write()
                 .truncate_from(slot, self.config.thread_count);
+            self.broadcast_canceled_slots(canceled_slots);
             self.active_cursor = slot
                 .get_prev_slot(self.config.thread_count)
                 .expect("overflow when iterating on slots");
@@ -1267,11 +1510,21 @@ impl ExecutionState {
             return;
         }
 
+        // determinism checks requested for this slot, done right before applying its output to the final state
+        let checks: Vec<_> = self
+            .determinism_checks
+            .drain_filter(|check| &check.request().slot == slot)
+            .collect();
+
+        // candidate executions dropped because they do not match the final one
+        let mut canceled_slots = Vec::new();
+
         // check if the final slot execution result is already cached at the front of the speculative execution history
         let first_exec_output = self.active_history.write().0.pop_front();
         if let Some(exec_out) = first_exec_output {
             if &exec_out.slot == slot && exec_out.block_id == target_id {
                 // speculative execution front result matches what we want to compute
+                self.check_determinism(checks, slot, exec_target, selector, &exec_out);
 
                 // apply the cached output and return
                 self.apply_final_execution_output(exec_out.clone());
@@ -1301,6 +1554,7 @@ impl ExecutionState {
                     "speculative execution cache mismatch (final slot={}/block={:?}, front speculative slot={}/block={:?}). Resetting the cache.",
                     slot, target_id, exec_out.slot, exec_out.block_id
                 );
+                canceled_slots.push(exec_out.slot);
             }
         } else {
             // cache entry absent
@@ -1311,12 +1565,20 @@ impl ExecutionState {
         }
 
         // truncate the whole execution queue
-        self.active_history.write().0.clear();
+        canceled_slots.extend(
+            self.active_history
+                .write()
+                .0
+                .drain(..)
+                .map(|exec_out| exec_out.slot),
+        );
         self.active_cursor = self.final_cursor;
+        self.broadcast_canceled_slots(canceled_slots);
 
         // execute slot
         debug!("execute_final_slot: execution started");
-        let exec_out = self.execute_slot(slot, exec_target, selector);
+        let exec_out = self.execute_slot(slot, exec_target, selector.clone());
+        self.check_determinism(checks, slot, exec_target, selector, &exec_out);
 
         // apply execution output to final state
         self.apply_final_execution_output(exec_out.clone());
@@ -1343,6 +1605,110 @@ impl ExecutionState {
         }
     }
 
+    /// Queues the determinism check of an upcoming final slot, done when the slot is finalized.
+    /// Past final slots can't be checked: the execution environment before them is not kept.
+    /// The request is canceled if the slot is already final or if too many checks are queued.
+    pub fn request_determinism_check(
+        &mut self,
+        check: RequestWithResponseSender<SlotDeterminismCheckRequest, SlotDeterminismCheckOutput>,
+    ) {
+        let slot = check.request().slot;
+        if slot <= self.final_cursor {
+            check.cancel(ExecutionError::RuntimeError(format!(
+                "slot {} is already final (last final slot: {}): past final slots can't be re-executed, only upcoming ones can be checked",
+                slot, self.final_cursor
+            )));
+        } else if self.determinism_checks.len() >= self.config.readonly_queue_length {
+            check.cancel(ExecutionError::ChannelError(
+                "too many queued slot determinism checks".into(),
+            ));
+        } else {
+            self.determinism_checks.push(check);
+        }
+    }
+
+    /// Cancels all the queued slot determinism checks
+    pub fn cancel_determinism_checks(&mut self, err: ExecutionError) {
+        for check in self.determinism_checks.drain(..) {
+            check.cancel(err.clone());
+        }
+    }
+
+    /// Executes again a slot about to be finalized on top of the final state, which is right before the slot,
+    /// and sends back the comparison of its state changes with the ones about to be finalized.
+    ///
+    /// # Arguments
+    /// * `checks`: determinism check requests of the slot
+    /// * `slot`: slot about to be finalized
+    /// * `exec_target`: metadata of the block executed at the slot, if not miss
+    /// * `selector`: selector controller, used to get the producer of missed slots
+    /// * `final_output`: execution output about to be finalized
+    fn check_determinism(
+        &self,
+        checks: Vec<
+            RequestWithResponseSender<SlotDeterminismCheckRequest, SlotDeterminismCheckOutput>,
+        >,
+        slot: &Slot,
+        exec_target: Option<&(BlockId, Storage)>,
+        selector: Box<dyn SelectorController>,
+        final_output: &ExecutionOutput,
+    ) {
+        if checks.is_empty() {
+            return;
+        }
+
+        // an empty active history makes the execution read the final state only
+        let trace = checks.iter().any(|check| check.request().trace);
+        let reexecution_output =
+            self.run_slot(slot, exec_target, selector, Default::default(), trace);
+        let operation_traces = std::mem::take(&mut context_guard!(self).operation_traces);
+
+        let final_state_changes_hash = final_output.state_changes.compute_hash();
+        let reexecution_state_changes_hash = reexecution_output.state_changes.compute_hash();
+        if final_state_changes_hash != reexecution_state_changes_hash {
+            warn!(
+                "the re-execution of final slot {} does not match its finalized state changes (finalized hash {}, re-execution hash {})",
+                slot, final_state_changes_hash, reexecution_state_changes_hash
+            );
+        }
+
+        for check in checks {
+            let (request, resp_tx) = check.into_request_sender_pair();
+            // Ignore errors because they just mean that the request emitter dropped the receiver
+            let _ = resp_tx.send(Ok(SlotDeterminismCheckOutput {
+                slot: *slot,
+                block_id: final_output.block_id,
+                final_state_changes_hash,
+                reexecution_state_changes_hash,
+                operation_traces: if request.trace {
+                    operation_traces.clone()
+                } else {
+                    Vec::new()
+                },
+            }));
+        }
+    }
+
+    /// Notifies the channel subscribers that the candidate executions of some slots were dropped
+    fn broadcast_canceled_slots(&self, canceled_slots: Vec<Slot>) {
+        if !self.config.broadcast_enabled {
+            return;
+        }
+        for slot in canceled_slots {
+            if let Err(err) = self
+                .channels
+                .slot_execution_output_sender
+                .send(SlotExecutionOutput::CanceledSlot(slot))
+            {
+                trace!(
+                    "error, failed to broadcast canceled slot {} due to: {}",
+                    slot,
+                    err
+                );
+            }
+        }
+    }
+
     /// Runs a read-only execution request.
     /// The executed bytecode appears to be able to read and write the consensus state,
     /// but all accumulated changes are simply returned as an `ExecutionOutput` object,
@@ -1368,8 +1734,19 @@ impl ExecutionState {
             )));
         }
 
-        // set the execution slot to be the one after the latest executed active or final slot
-        let slot = if req.is_final {
+        // set the execution slot to be the one after the requested past final slot,
+        // or after the latest executed active or final slot
+        let mut slot = if let Some(at_slot) = req.at_slot {
+            self.final_state
+                .read()
+                .db
+                .read()
+                .check_archived_slot(&at_slot)
+                .map_err(|e| ExecutionError::ArchiveError(e.to_string()))?;
+            at_slot
+                .get_next_slot(self.config.thread_count)
+                .expect("slot overflow in readonly execution from archived slot")
+        } else if req.is_final {
             self.final_cursor
                 .get_next_slot(self.config.thread_count)
                 .expect("slot overflow in readonly execution from final slot")
@@ -1379,21 +1756,109 @@ impl ExecutionState {
                 .expect("slot overflow in readonly execution from active slot")
         };
 
-        // create a readonly execution context
-        let execution_context = ExecutionContext::readonly(
+        // an operation can only be included in a block of the thread of its sender
+        if let ReadOnlyExecutionTarget::Operation(operation) = &req.target {
+            let op_thread = operation
+                .content_creator_address
+                .get_thread(self.config.thread_count);
+            while slot.thread != op_thread {
+                slot = slot
+                    .get_next_slot(self.config.thread_count)
+                    .expect("slot overflow in readonly operation simulation");
+            }
+        }
+
+        // create a readonly execution context.
+        // Executions at a past final slot ignore the active history and read the archived ledger.
+        let active_history = match req.at_slot {
+            Some(_) => Arc::new(RwLock::new(ActiveHistory::default())),
+            None => self.active_history.clone(),
+        };
+        let mut execution_context = ExecutionContext::readonly(
             self.config.clone(),
             slot,
             req.max_gas,
             req.call_stack,
             self.final_state.clone(),
-            self.active_history.clone(),
+            active_history,
             self.module_cache.clone(),
             self.vesting_manager.clone(),
             self.mip_store.clone(),
         );
+        if let Some(at_slot) = req.at_slot {
+            execution_context.read_ledger_at(at_slot);
+        }
+        if req.trace {
+            // the root frame is the operation sender or the top of the simulated call stack
+            let (address, coins) = match &req.target {
+                ReadOnlyExecutionTarget::Operation(operation) => {
+                    (operation.content_creator_address, Amount::zero())
+                }
+                _ => match execution_context.stack.last() {
+                    Some(element) => (element.address, element.coins),
+                    None => {
+                        return Err(ExecutionError::RuntimeError(
+                            "cannot trace a read-only execution with an empty call stack"
+                                .to_string(),
+                        ))
+                    }
+                },
+            };
+            execution_context.tracer = Some(ExecutionTracer::new(address, coins, req.max_gas));
+        }
+
+        // run the interpreter, then collect the trace even if the execution failed
+        let result = self
+            .run_readonly_target(req.target, req.max_gas, slot, execution_context)
+            .and_then(|result| match context_guard!(self).take_archive_error() {
+                // the execution may have relied on archived entries that could not be read
+                Some(error) => Err(error),
+                None => Ok(result),
+            });
+        let trace = context_guard!(self).tracer.take().map(|tracer| {
+            tracer.finish(
+                result
+                    .as_ref()
+                    .ok()
+                    .map(|(gas_cost, _)| req.max_gas.saturating_sub(*gas_cost)),
+            )
+        });
+        let (gas_cost, call_result) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                return Err(match trace {
+                    Some(trace) => ExecutionError::TracedError {
+                        error: Box::new(error),
+                        trace: Box::new(trace),
+                    },
+                    None => error,
+                })
+            }
+        };
+
+        // return the execution output
+        let execution_output = context_guard!(self).settle_slot();
+        Ok(ReadOnlyExecutionOutput {
+            out: execution_output,
+            gas_cost,
+            call_result,
+            trace,
+        })
+    }
 
+    /// Runs the target of a read-only execution in the given context
+    ///
+    /// # Returns
+    /// The gas cost of the execution and the value returned by the target
+    fn run_readonly_target(
+        &self,
+        target: ReadOnlyExecutionTarget,
+        max_gas: u64,
+        slot: Slot,
+        mut execution_context: ExecutionContext,
+    ) -> Result<(u64, Vec<u8>), ExecutionError> {
         // run the interpreter according to the target type
-        let exec_response = match req.target {
+        let result = match target {
             ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
                 // set the execution context
                 *context_guard!(self) = execution_context;
@@ -1402,24 +1867,42 @@ impl ExecutionState {
                 let module = self
                     .module_cache
                     .read()
-                    .load_tmp_module(&bytecode, req.max_gas)?;
+                    .load_tmp_module(&bytecode, max_gas)?;
                 // run the VM
-                massa_sc_runtime::run_main(
+                let response = massa_sc_runtime::run_main(
                     &*self.execution_interface,
                     module,
-                    req.max_gas,
+                    max_gas,
                     self.config.gas_costs.clone(),
                 )
                 .map_err(|error| ExecutionError::VMError {
                     context: "ReadOnlyExecutionTarget::BytecodeExecution".to_string(),
                     error,
-                })?
+                })?;
+                (max_gas.saturating_sub(response.remaining_gas), response.ret)
             }
             ReadOnlyExecutionTarget::FunctionCall {
                 target_addr,
                 target_func,
                 parameter,
             } => {
+                // transfer the coins sent along with the call from the caller to the target,
+                // as `execute_callsc_op` does for `CallSC` operations
+                if let [.., caller, callee] = &execution_context.stack[..] {
+                    let (caller_addr, callee_addr, coins) =
+                        (caller.address, callee.address, callee.coins);
+                    if !coins.is_zero() {
+                        execution_context
+                            .transfer_coins(Some(caller_addr), Some(callee_addr), coins, false)
+                            .map_err(|err| {
+                                ExecutionError::RuntimeError(format!(
+                                    "failed to transfer {} call coins from {} to {}: {}",
+                                    coins, caller_addr, callee_addr, err
+                                ))
+                            })?;
+                    }
+                }
+
                 // get the bytecode, default to an empty vector
                 let bytecode = execution_context
                     .get_bytecode(&target_addr)
@@ -1431,16 +1914,13 @@ impl ExecutionState {
 
                 // load and execute the compiled module
                 // IMPORTANT: do not keep a lock here as `run_function` uses the `get_module` interface
-                let module = self
-                    .module_cache
-                    .write()
-                    .load_module(&bytecode, req.max_gas)?;
+                let module = self.module_cache.write().load_module(&bytecode, max_gas)?;
                 let response = massa_sc_runtime::run_function(
                     &*self.execution_interface,
                     module,
                     &target_func,
                     &parameter,
-                    req.max_gas,
+                    max_gas,
                     self.config.gas_costs.clone(),
                 );
                 match response {
@@ -1452,20 +1932,35 @@ impl ExecutionState {
                     }
                     _ => (),
                 }
-                response.map_err(|error| ExecutionError::VMError {
+                let response = response.map_err(|error| ExecutionError::VMError {
                     context: "ReadOnlyExecutionTarget::FunctionCall".to_string(),
                     error,
-                })?
+                })?;
+                (max_gas.saturating_sub(response.remaining_gas), response.ret)
             }
-        };
+            ReadOnlyExecutionTarget::Operation(operation) => {
+                // set the execution context
+                *context_guard!(self) = execution_context;
 
-        // return the execution output
-        let execution_output = context_guard!(self).settle_slot();
-        Ok(ReadOnlyExecutionOutput {
-            out: execution_output,
-            gas_cost: req.max_gas.saturating_sub(exec_response.remaining_gas),
-            call_result: exec_response.ret,
-        })
+                // execute the operation as the only one of an empty block:
+                // the errors preventing its inclusion are returned,
+                // while execution errors are reported in its receipt
+                let mut remaining_block_gas = self.config.max_gas_per_block;
+                let mut block_credits = Amount::zero();
+                self.execute_operation(
+                    &operation,
+                    slot,
+                    &mut remaining_block_gas,
+                    &mut block_credits,
+                )?;
+                let gas_used = context_guard!(self)
+                    .receipts
+                    .get(&operation.id)
+                    .map_or(0, |receipt| receipt.gas_used);
+                (gas_used, Vec::new())
+            }
+        };
+        Ok(result)
     }
 
     /// Gets a balance both at the latest final and candidate executed slots
@@ -1517,6 +2012,30 @@ impl ExecutionState {
         )
     }
 
+    /// Gets the final values of a batch of serialized state keys, with their proofs against the final state hash.
+    /// The database lock is held for the whole batch so that every proof refers to the same state hash.
+    pub fn get_final_state_proofs(
+        &self,
+        keys: &[Vec<u8>],
+    ) -> Result<FinalStateProofs, ExecutionError> {
+        let final_state = self.final_state.read();
+        let db = final_state.db.read();
+        let slot = db.get_change_id()?;
+        let state_hash = db.get_db_hash();
+        let entries = keys
+            .iter()
+            .map(|key| {
+                db.get_state_proof(key)
+                    .map_err(|e| ExecutionError::StateProofError(e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(FinalStateProofs {
+            slot,
+            state_hash,
+            entries,
+        })
+    }
+
     /// Get every final and active datastore key of the given address
     pub fn get_final_and_candidate_datastore_keys(
         &self,
@@ -1579,6 +2098,19 @@ impl ExecutionState {
         }
     }
 
+    /// Returns the inputs of the selector draws of a given cycle:
+    /// the look back rolls and the look back seed, as fed to the selector.
+    pub fn get_selector_draw_inputs(
+        &self,
+        cycle: u64,
+    ) -> Result<(BTreeMap<Address, u64>, Hash), ExecutionError> {
+        self.final_state
+            .read()
+            .pos_state
+            .get_draw_inputs(cycle)
+            .map_err(|err| ExecutionError::DrawInputsError(err.to_string()))
+    }
+
     /// Gets execution events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -1586,33 +2118,80 @@ impl ExecutionState {
     /// * original caller address
     /// * operation id
     /// * event state (final, candidate or both)
+    /// * event data prefix or substring
+    /// * address in the call stack
+    ///
+    /// Only the latest `max_final_events` final events are returned, as when they are read from memory,
+    /// even if the archive holds more of them: paginated requests must be used to read the others.
     pub fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent> {
-        match filter.is_final {
-            Some(true) => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
-                .into_iter()
-                .collect(),
-            Some(false) => self
-                .active_history
-                .read()
-                .0
-                .iter()
-                .flat_map(|item| item.events.get_filtered_sc_output_events(&filter))
-                .collect(),
-            None => self
-                .final_events
-                .get_filtered_sc_output_events(&filter)
-                .into_iter()
-                .chain(
-                    self.active_history
-                        .read()
-                        .0
-                        .iter()
-                        .flat_map(|item| item.events.get_filtered_sc_output_events(&filter)),
-                )
-                .collect(),
+        let mut events = Vec::new();
+        if filter.is_final != Some(false) {
+            let final_filter = EventFilter {
+                is_final: Some(true),
+                ..filter.clone()
+            };
+            events = match &self.event_db {
+                Some(event_db) => event_db.get_latest_filtered_sc_output_events(
+                    &final_filter,
+                    self.config.max_final_events,
+                ),
+                None => self
+                    .final_events
+                    .get_filtered_sc_output_events(&final_filter)
+                    .into(),
+            };
+        }
+        if filter.is_final != Some(true) {
+            // candidate events are only held in memory, in the active history
+            events.extend(self.get_filtered_sc_output_event_page(
+                EventFilter {
+                    is_final: Some(false),
+                    ..filter
+                },
+                None,
+                usize::MAX,
+            ));
+        }
+        events
+    }
+
+    /// Gets at most `limit` execution events matching a filter and located after an optional cursor.
+    ///
+    /// Events are ordered by slot and index in slot: final events come before candidate ones.
+    /// Final events are read from the on-disk archive when it is enabled,
+    /// and from the in-memory store of the latest final events otherwise.
+    pub fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+        cursor: Option<EventCursor>,
+        limit: usize,
+    ) -> Vec<SCOutputEvent> {
+        let mut events = Vec::new();
+        if filter.is_final != Some(false) {
+            events = match &self.event_db {
+                Some(event_db) => {
+                    event_db.get_filtered_sc_output_events(&filter, cursor.as_ref(), limit)
+                }
+                None => self.final_events.get_filtered_sc_output_events_page(
+                    &filter,
+                    cursor.as_ref(),
+                    limit,
+                ),
+            };
         }
+        if filter.is_final != Some(true) {
+            for item in self.active_history.read().0.iter() {
+                if events.len() >= limit {
+                    break;
+                }
+                events.extend(item.events.get_filtered_sc_output_events_page(
+                    &filter,
+                    cursor.as_ref(),
+                    limit - events.len(),
+                ));
+            }
+        }
+        events
     }
 
     /// Check if a denunciation has been executed given a `DenunciationIndex`
@@ -1670,6 +2249,173 @@ impl ExecutionState {
             .collect()
     }
 
+    /// Gets the execution receipts of a batch of operations.
+    /// The receipt of the latest candidate execution is returned over the final one.
+    /// `None` is returned for operations that were not executed or whose final receipt was pruned.
+    pub fn get_operation_receipts(&self, batch: &[OperationId]) -> Vec<Option<OperationReceipt>> {
+        let active_history = self.active_history.read();
+        batch
+            .iter()
+            .map(|op_id| {
+                active_history
+                    .fetch_operation_receipt(op_id)
+                    .or_else(|| self.final_receipts.get(op_id).cloned())
+            })
+            .collect()
+    }
+
+    /// Gets the asynchronous messages of the candidate pool matching a filter,
+    /// along with whether they could be executed at the next slot.
+    pub fn get_async_messages(&self, filter: &AsyncMessageFilter) -> Vec<PendingAsyncMessage> {
+        let final_state = self.final_state.read();
+        let active_history = self.active_history.read();
+
+        // messages changed by the active history can't be filtered on their final info
+        let changed_ids: BTreeSet<AsyncMessageId> = active_history
+            .0
+            .iter()
+            .flat_map(|output| output.state_changes.async_pool_changes.0.keys().copied())
+            .collect();
+
+        // fetch the final messages that may match the filter
+        let mut messages: BTreeMap<AsyncMessageId, AsyncMessage> = final_state
+            .async_pool
+            .message_info_cache
+            .iter()
+            .filter(|(id, info)| {
+                changed_ids.contains(id)
+                    || filter.valid_at_slot.map_or(true, |slot| {
+                        info.validity_start <= slot && slot < info.validity_end
+                    })
+            })
+            .filter_map(|(id, _)| {
+                final_state
+                    .async_pool
+                    .fetch_message(id)
+                    .map(|message| (*id, message))
+            })
+            .collect();
+
+        // apply the candidate changes on top of them, from the oldest
+        for output in active_history.0.iter() {
+            for (id, change) in output.state_changes.async_pool_changes.0.iter() {
+                match change {
+                    SetUpdateOrDelete::Set(message) => {
+                        messages.insert(*id, message.clone());
+                    }
+                    SetUpdateOrDelete::Update(update) => {
+                        if let Some(message) = messages.get_mut(id) {
+                            message.apply(update.clone());
+                        }
+                    }
+                    SetUpdateOrDelete::Delete => {
+                        messages.remove(id);
+                    }
+                }
+            }
+        }
+
+        let next_slot = self
+            .active_cursor
+            .get_next_slot(self.config.thread_count)
+            .ok();
+        messages
+            .into_iter()
+            .filter(|(_, message)| {
+                filter
+                    .sender
+                    .map_or(true, |sender| message.sender == sender)
+                    && filter
+                        .destination
+                        .map_or(true, |destination| message.destination == destination)
+                    && filter
+                        .handler
+                        .as_ref()
+                        .map_or(true, |handler| &message.handler == handler)
+                    && filter.valid_at_slot.map_or(true, |slot| {
+                        message.validity_start <= slot && slot < message.validity_end
+                    })
+            })
+            .map(|(id, message)| PendingAsyncMessage {
+                id,
+                trigger_fired: message.trigger.is_some() && message.can_be_executed,
+                executable: message.can_be_executed
+                    && next_slot.map_or(false, |slot| {
+                        message.validity_start <= slot && slot < message.validity_end
+                    }),
+                message,
+            })
+            .collect()
+    }
+
+    /// Gets at most `limit` final operations that affected an address,
+    /// from the most recent slot, located strictly before `cursor`.
+    /// `None` is returned if the address history index is disabled.
+    pub fn get_address_history(
+        &self,
+        address: &Address,
+        cursor: Option<AddressHistoryCursor>,
+        limit: usize,
+    ) -> Option<Vec<AddressHistoryEntry>> {
+        self.address_history_db
+            .as_ref()
+            .map(|db| db.get_address_history(address, cursor.as_ref(), limit))
+    }
+
+    /// Gets the balances of a batch of addresses right after a past final slot, from the archive
+    pub fn get_archived_balances(
+        &self,
+        addresses: &[Address],
+        slot: &Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        addresses
+            .iter()
+            .map(|addr| {
+                final_state
+                    .ledger
+                    .get_balance_at(addr, slot)
+                    .map_err(|e| ExecutionError::ArchiveError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Gets the datastore keys of a batch of addresses right after a past final slot, from the archive
+    pub fn get_archived_datastore_keys(
+        &self,
+        addresses: &[Address],
+        slot: &Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        addresses
+            .iter()
+            .map(|addr| {
+                final_state
+                    .ledger
+                    .get_datastore_keys_at(addr, slot)
+                    .map_err(|e| ExecutionError::ArchiveError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Gets a batch of datastore entries right after a past final slot, from the archive
+    pub fn get_archived_data_entries(
+        &self,
+        input: Vec<(Address, Vec<u8>)>,
+        slot: &Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        input
+            .iter()
+            .map(|(addr, key)| {
+                final_state
+                    .ledger
+                    .get_data_entry_at(addr, key, slot)
+                    .map_err(|e| ExecutionError::ArchiveError(e.to_string()))
+            })
+            .collect()
+    }
+
     /// Update MipStore with block header stats
     pub fn update_versioning_stats(
         &mut self,
@@ -10,6 +10,7 @@ use anyhow::{anyhow, bail, Result};
 use massa_async_pool::{AsyncMessage, AsyncMessageTrigger};
 use massa_execution_exports::ExecutionConfig;
 use massa_execution_exports::ExecutionStackElement;
+use massa_execution_exports::ExecutionTraceStep;
 use massa_models::bytecode::Bytecode;
 use massa_models::config::MAX_DATASTORE_KEY_LENGTH;
 use massa_models::{
@@ -205,6 +206,9 @@ impl Interface for InterfaceImpl {
             owned_addresses: vec![to_address],
             operation_datastore: None,
         });
+        if let Some(tracer) = context.tracer.as_mut() {
+            tracer.enter_frame(to_address, coins);
+        }
 
         // return the target bytecode
         Ok(bytecode.0)
@@ -218,6 +222,9 @@ impl Interface for InterfaceImpl {
         if context.stack.pop().is_none() {
             bail!("call stack out of bounds")
         }
+        if let Some(tracer) = context.tracer.as_mut() {
+            tracer.exit_frame();
+        }
 
         Ok(())
     }
@@ -227,7 +234,11 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// A `massa-sc-runtime` compiled module
     fn get_module(&self, bytecode: &[u8], limit: u64) -> Result<RuntimeModule> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
+        // right after `init_call`, the VM loads the callee module with the gas left to the callee
+        if let Some(tracer) = context.tracer.as_mut() {
+            tracer.record_entry_gas(limit);
+        }
         let module = context.module_cache.write().load_module(bytecode, limit)?;
         Ok(module)
     }
@@ -279,15 +290,20 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// A list of keys (keys are byte arrays)
     fn get_keys(&self, prefix_opt: Option<&[u8]>) -> Result<BTreeSet<Vec<u8>>> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
-        match (context.get_keys(&addr), prefix_opt) {
-            (Some(value), None) => Ok(value),
+        let keys = match (context.get_keys(&addr), prefix_opt) {
+            (Some(value), None) => Some(value),
             (Some(mut value), Some(prefix)) => {
                 value.retain(|key| key.iter().zip(prefix.iter()).all(|(k, p)| k == p));
-                Ok(value)
+                Some(value)
             }
-            _ => bail!("data entry not found"),
+            _ => None,
+        };
+        trace_keys(&mut context, addr, prefix_opt, keys.as_ref());
+        match keys {
+            Some(keys) => Ok(keys),
+            None => bail!("data entry not found"),
         }
     }
 
@@ -296,15 +312,20 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// A list of keys (keys are byte arrays)
     fn get_keys_for(&self, address: &str, prefix_opt: Option<&[u8]>) -> Result<BTreeSet<Vec<u8>>> {
-        let addr = &Address::from_str(address)?;
-        let context = context_guard!(self);
-        match (context.get_keys(addr), prefix_opt) {
-            (Some(value), None) => Ok(value),
+        let addr = Address::from_str(address)?;
+        let mut context = context_guard!(self);
+        let keys = match (context.get_keys(&addr), prefix_opt) {
+            (Some(value), None) => Some(value),
             (Some(mut value), Some(prefix)) => {
                 value.retain(|key| key.iter().zip(prefix.iter()).all(|(k, p)| k == p));
-                Ok(value)
+                Some(value)
             }
-            _ => bail!("data entry not found"),
+            _ => None,
+        };
+        trace_keys(&mut context, addr, prefix_opt, keys.as_ref());
+        match keys {
+            Some(keys) => Ok(keys),
+            None => bail!("data entry not found"),
         }
     }
 
@@ -317,9 +338,15 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data_for(&self, address: &str, key: &[u8]) -> Result<Vec<u8>> {
-        let addr = &massa_models::address::Address::from_str(address)?;
-        let context = context_guard!(self);
-        match context.get_data_entry(addr, key) {
+        let addr = massa_models::address::Address::from_str(address)?;
+        let mut context = context_guard!(self);
+        let value = context.get_data_entry(&addr, key);
+        context.trace(|| ExecutionTraceStep::DatastoreRead {
+            address: addr,
+            key: key.to_vec(),
+            value: value.clone(),
+        });
+        match value {
             Some(value) => Ok(value),
             _ => bail!("data entry not found"),
         }
@@ -337,6 +364,7 @@ impl Interface for InterfaceImpl {
         let addr = massa_models::address::Address::from_str(address)?;
         let mut context = context_guard!(self);
         context.set_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        trace_write(&mut context, addr, key, Some(value));
         Ok(())
     }
 
@@ -349,7 +377,9 @@ impl Interface for InterfaceImpl {
     /// * value: value to append
     fn raw_append_data_for(&self, address: &str, key: &[u8], value: &[u8]) -> Result<()> {
         let addr = massa_models::address::Address::from_str(address)?;
-        context_guard!(self).append_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        let mut context = context_guard!(self);
+        context.append_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        trace_append(&mut context, addr, key);
         Ok(())
     }
 
@@ -360,8 +390,10 @@ impl Interface for InterfaceImpl {
     /// * address: string representation of the address
     /// * key: string key of the datastore entry to delete
     fn raw_delete_data_for(&self, address: &str, key: &[u8]) -> Result<()> {
-        let addr = &massa_models::address::Address::from_str(address)?;
-        context_guard!(self).delete_data_entry(addr, key)?;
+        let addr = massa_models::address::Address::from_str(address)?;
+        let mut context = context_guard!(self);
+        context.delete_data_entry(&addr, key)?;
+        trace_write(&mut context, addr, key, None);
         Ok(())
     }
 
@@ -375,8 +407,10 @@ impl Interface for InterfaceImpl {
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data_for(&self, address: &str, key: &[u8]) -> Result<bool> {
         let addr = massa_models::address::Address::from_str(address)?;
-        let context = context_guard!(self);
-        Ok(context.has_data_entry(&addr, key))
+        let mut context = context_guard!(self);
+        let exists = context.has_data_entry(&addr, key);
+        trace_has(&mut context, addr, key, exists);
+        Ok(exists)
     }
 
     /// Gets a datastore value by key for the current address (top of the call stack).
@@ -387,9 +421,15 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data(&self, key: &[u8]) -> Result<Vec<u8>> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
-        match context.get_data_entry(&addr, key) {
+        let value = context.get_data_entry(&addr, key);
+        context.trace(|| ExecutionTraceStep::DatastoreRead {
+            address: addr,
+            key: key.to_vec(),
+            value: value.clone(),
+        });
+        match value {
             Some(data) => Ok(data),
             _ => bail!("data entry not found"),
         }
@@ -407,6 +447,7 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.set_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        trace_write(&mut context, addr, key, Some(value));
         Ok(())
     }
 
@@ -421,6 +462,7 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.append_data_entry(&addr, key.to_vec(), value.to_vec())?;
+        trace_append(&mut context, addr, key);
         Ok(())
     }
 
@@ -433,6 +475,7 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.delete_data_entry(&addr, key)?;
+        trace_write(&mut context, addr, key, None);
         Ok(())
     }
 
@@ -444,9 +487,11 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data(&self, key: &[u8]) -> Result<bool> {
-        let context = context_guard!(self);
+        let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
-        Ok(context.has_data_entry(&addr, key))
+        let exists = context.has_data_entry(&addr, key);
+        trace_has(&mut context, addr, key, exists);
+        Ok(exists)
     }
 
     /// Check whether or not the caller has write access in the current context
@@ -607,6 +652,11 @@ impl Interface for InterfaceImpl {
         let mut context = context_guard!(self);
         let from_address = context.get_current_address()?;
         context.transfer_coins(Some(from_address), Some(to_address), amount, true)?;
+        context.trace(|| ExecutionTraceStep::Transfer {
+            from: from_address,
+            to: to_address,
+            amount,
+        });
         Ok(())
     }
 
@@ -627,6 +677,11 @@ impl Interface for InterfaceImpl {
         let amount = Amount::from_raw(raw_amount);
         let mut context = context_guard!(self);
         context.transfer_coins(Some(from_address), Some(to_address), amount, true)?;
+        context.trace(|| ExecutionTraceStep::Transfer {
+            from: from_address,
+            to: to_address,
+            amount,
+        });
         Ok(())
     }
 
@@ -673,6 +728,7 @@ impl Interface for InterfaceImpl {
     /// data: the string data that is the payload of the event
     fn generate_event(&self, data: String) -> Result<()> {
         let mut context = context_guard!(self);
+        context.trace(|| ExecutionTraceStep::Event { data: data.clone() });
         let event = context.event_create(data, false);
         context.event_emit(event);
         Ok(())
@@ -748,7 +804,7 @@ impl Interface for InterfaceImpl {
         let fee = Amount::from_raw(raw_fee);
         execution_context.transfer_coins(Some(sender), None, coins, true)?;
         execution_context.transfer_coins(Some(sender), None, fee, true)?;
-        execution_context.push_new_message(AsyncMessage::new_with_hash(
+        let message = AsyncMessage::new_with_hash(
             emission_slot,
             emission_index,
             sender,
@@ -760,22 +816,19 @@ impl Interface for InterfaceImpl {
             Slot::new(validity_start.0, validity_start.1),
             Slot::new(validity_end.0, validity_end.1),
             data.to_vec(),
-            filter
-                .map(|(addr, key)| {
-                    let datastore_key = key.map(|k| k.to_vec());
-                    if let Some(ref k) = datastore_key {
-                        if k.len() > MAX_DATASTORE_KEY_LENGTH as usize {
-                            bail!("datastore key is too long")
-                        }
-                    }
-                    Ok::<AsyncMessageTrigger, _>(AsyncMessageTrigger {
-                        address: Address::from_str(addr)?,
-                        datastore_key,
-                    })
-                })
-                .transpose()?,
+            filter.map(parse_message_trigger).transpose()?,
             None,
-        ));
+        );
+        execution_context.trace(|| ExecutionTraceStep::AsyncMessage {
+            destination: message.destination,
+            handler: message.handler.clone(),
+            max_gas: message.max_gas,
+            fee: message.fee,
+            coins: message.coins,
+            validity_start: message.validity_start,
+            validity_end: message.validity_end,
+        });
+        execution_context.push_new_message(message);
         execution_context.created_message_index += 1;
         Ok(())
     }
@@ -827,3 +880,61 @@ impl Interface for InterfaceImpl {
         Ok(hash)
     }
 }
+
+/// Records the check of the existence of a datastore entry in the execution trace
+fn trace_has(context: &mut ExecutionContext, address: Address, key: &[u8], exists: bool) {
+    context.trace(|| ExecutionTraceStep::DatastoreHas {
+        address,
+        key: key.to_vec(),
+        exists,
+    });
+}
+
+/// Records the listing of datastore keys in the execution trace, `None` meaning the address was not found
+fn trace_keys(
+    context: &mut ExecutionContext,
+    address: Address,
+    prefix: Option<&[u8]>,
+    keys: Option<&BTreeSet<Vec<u8>>>,
+) {
+    context.trace(|| ExecutionTraceStep::DatastoreKeys {
+        address,
+        prefix: prefix.map(|p| p.to_vec()),
+        keys: keys.map(|keys| keys.iter().cloned().collect()),
+    });
+}
+
+/// Records the write of a datastore entry in the execution trace, `None` meaning deleted
+fn trace_write(context: &mut ExecutionContext, address: Address, key: &[u8], value: Option<&[u8]>) {
+    context.trace(|| ExecutionTraceStep::DatastoreWrite {
+        address,
+        key: key.to_vec(),
+        value: value.map(|v| v.to_vec()),
+    });
+}
+
+/// Records an append to a datastore entry in the execution trace, with the resulting value of the entry
+fn trace_append(context: &mut ExecutionContext, address: Address, key: &[u8]) {
+    if context.tracer.is_some() {
+        let value = context.get_data_entry(&address, key);
+        context.trace(|| ExecutionTraceStep::DatastoreWrite {
+            address,
+            key: key.to_vec(),
+            value,
+        });
+    }
+}
+
+/// Builds the trigger of an asynchronous message from the address and optional datastore key it watches
+fn parse_message_trigger((addr, key): (&str, Option<&[u8]>)) -> Result<AsyncMessageTrigger> {
+    let datastore_key = key.map(|k| k.to_vec());
+    if let Some(ref k) = datastore_key {
+        if k.len() > MAX_DATASTORE_KEY_LENGTH as usize {
+            bail!("datastore key is too long")
+        }
+    }
+    Ok(AsyncMessageTrigger {
+        address: Address::from_str(addr)?,
+        datastore_key,
+    })
+}
@@ -69,12 +69,21 @@
 //! ## `speculative_executed_ops.rs`
 //! A speculative (non-final) list of previously executed operations to prevent reuse.
 //!
+//! ## `event_db.rs`
+//! Persistent, indexed archive of the final execution events.
+//!
+//! ## `address_history_db.rs`
+//! Persistent index of the final operations sent or received by each address.
+//!
 //! ## `request_queue.rs`
 //! This module contains the implementation of a generic finite-size execution request queue.
 //! It handles requests that come with an MPSC to send back the result of their execution once it's done.
 //!
 //! ## `stats.rs`
 //! Defines a structure that gathers execution statistics.
+//!
+//! ## `tracer.rs`
+//! Records the ABI calls made during a read-only execution, when a trace is requested.
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
@@ -86,8 +95,10 @@
 #![feature(btree_drain_filter)]
 
 mod active_history;
+mod address_history_db;
 mod context;
 mod controller;
+mod event_db;
 mod execution;
 mod interface_impl;
 mod request_queue;
@@ -98,6 +109,7 @@ mod speculative_executed_ops;
 mod speculative_ledger;
 mod speculative_roll_state;
 mod stats;
+mod tracer;
 mod vesting_manager;
 mod worker;
 
@@ -39,6 +39,11 @@ impl<T, R> RequestWithResponseSender<T, R> {
         let _ = self.response_tx.send(Err(err));
     }
 
+    /// Get a reference to the underlying request
+    pub fn request(&self) -> &T {
+        &self.request
+    }
+
     /// Destructure self into a (request, response sender) pair
     pub fn into_request_sender_pair(self) -> (T, MassaSender<Result<R, ExecutionError>>) {
         (self.request, self.response_tx)
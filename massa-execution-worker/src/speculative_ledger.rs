@@ -11,8 +11,8 @@ use massa_execution_exports::StorageCostsConstants;
 use massa_final_state::FinalState;
 use massa_ledger_exports::{Applicable, LedgerChanges, SetOrDelete, SetUpdateOrDelete};
 use massa_models::bytecode::Bytecode;
-use massa_models::{address::Address, amount::Amount};
-use parking_lot::RwLock;
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use parking_lot::{Mutex, RwLock};
 use std::collections::BTreeSet;
 use std::sync::Arc;
 use tracing::debug;
@@ -56,6 +56,12 @@ pub(crate) struct SpeculativeLedger {
 
     /// storage cost constants
     storage_costs_constants: StorageCostsConstants,
+
+    /// If set, the final ledger is read as it was right after this past final slot, from the archive
+    archive_slot: Option<Slot>,
+
+    /// First error raised by a read of the archive, returned as the error of the execution
+    archive_error: Mutex<Option<ExecutionError>>,
 }
 
 impl SpeculativeLedger {
@@ -80,6 +86,83 @@ impl SpeculativeLedger {
             max_datastore_value_size,
             max_bytecode_size,
             storage_costs_constants,
+            archive_slot: None,
+            archive_error: Mutex::new(None),
+        }
+    }
+
+    /// Reads the final ledger as it was right after a past final slot, from the archive.
+    /// Reads that fail are considered absent and their error is kept, see `take_archive_error`.
+    pub fn set_archive_slot(&mut self, slot: Slot) {
+        self.archive_slot = Some(slot);
+    }
+
+    /// Takes the first error raised by a read of the archive, if any.
+    /// An execution reading the archive must fail with it, as its result may rely on missing entries.
+    pub fn take_archive_error(&self) -> Option<ExecutionError> {
+        self.archive_error.lock().take()
+    }
+
+    /// Keeps the first archive read error and considers the entry absent
+    fn record_archive_error<T>(&self, result: Result<Option<T>, ExecutionError>) -> Option<T> {
+        result.unwrap_or_else(|error| {
+            self.archive_error.lock().get_or_insert(error);
+            None
+        })
+    }
+
+    /// Gets the balance of an address in the final ledger
+    fn get_final_balance(&self, addr: &Address) -> Result<Option<Amount>, ExecutionError> {
+        let final_state = self.final_state.read();
+        match &self.archive_slot {
+            Some(slot) => final_state
+                .ledger
+                .get_balance_at(addr, slot)
+                .map_err(|e| ExecutionError::ArchiveError(e.to_string())),
+            None => Ok(final_state.ledger.get_balance(addr)),
+        }
+    }
+
+    /// Gets the bytecode of an address in the final ledger
+    fn get_final_bytecode(&self, addr: &Address) -> Result<Option<Bytecode>, ExecutionError> {
+        let final_state = self.final_state.read();
+        match &self.archive_slot {
+            Some(slot) => final_state
+                .ledger
+                .get_bytecode_at(addr, slot)
+                .map_err(|e| ExecutionError::ArchiveError(e.to_string())),
+            None => Ok(final_state.ledger.get_bytecode(addr)),
+        }
+    }
+
+    /// Gets the datastore keys of an address in the final ledger
+    fn get_final_datastore_keys(
+        &self,
+        addr: &Address,
+    ) -> Result<Option<BTreeSet<Vec<u8>>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        match &self.archive_slot {
+            Some(slot) => final_state
+                .ledger
+                .get_datastore_keys_at(addr, slot)
+                .map_err(|e| ExecutionError::ArchiveError(e.to_string())),
+            None => Ok(final_state.ledger.get_datastore_keys(addr)),
+        }
+    }
+
+    /// Gets a datastore entry of an address in the final ledger
+    fn get_final_data_entry(
+        &self,
+        addr: &Address,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, ExecutionError> {
+        let final_state = self.final_state.read();
+        match &self.archive_slot {
+            Some(slot) => final_state
+                .ledger
+                .get_data_entry_at(addr, key, slot)
+                .map_err(|e| ExecutionError::ArchiveError(e.to_string())),
+            None => Ok(final_state.ledger.get_data_entry(addr, key)),
         }
     }
 
@@ -111,7 +194,9 @@ impl SpeculativeLedger {
         self.added_changes.get_balance_or_else(addr, || {
             match self.active_history.read().fetch_balance(addr) {
                 HistorySearchResult::Present(par_balance) => Some(par_balance),
-                HistorySearchResult::NoInfo => self.final_state.read().ledger.get_balance(addr),
+                HistorySearchResult::NoInfo => {
+                    self.record_archive_error(self.get_final_balance(addr))
+                }
                 HistorySearchResult::Absent => None,
             }
         })
@@ -129,7 +214,9 @@ impl SpeculativeLedger {
         self.added_changes.get_bytecode_or_else(addr, || {
             match self.active_history.read().fetch_bytecode(addr) {
                 HistorySearchResult::Present(bytecode) => Some(bytecode),
-                HistorySearchResult::NoInfo => self.final_state.read().ledger.get_bytecode(addr),
+                HistorySearchResult::NoInfo => {
+                    self.record_archive_error(self.get_final_bytecode(addr))
+                }
                 HistorySearchResult::Absent => None,
             }
         })
@@ -245,7 +332,12 @@ impl SpeculativeLedger {
         self.added_changes.entry_exists_or_else(addr, || {
             match self.active_history.read().fetch_balance(addr) {
                 HistorySearchResult::Present(_balance) => true,
-                HistorySearchResult::NoInfo => self.final_state.read().ledger.entry_exists(addr),
+                HistorySearchResult::NoInfo => match self.archive_slot {
+                    Some(_) => self
+                        .record_archive_error(self.get_final_balance(addr))
+                        .is_some(),
+                    None => self.final_state.read().ledger.entry_exists(addr),
+                },
                 HistorySearchResult::Absent => false,
             }
         })
@@ -387,7 +479,7 @@ impl SpeculativeLedger {
     /// `Some(Vec<Vec<u8>>)` for found keys, `None` if the address does not exist.
     pub fn get_keys(&self, addr: &Address) -> Option<BTreeSet<Vec<u8>>> {
         let mut keys: Option<BTreeSet<Vec<u8>>> =
-            self.final_state.read().ledger.get_datastore_keys(addr);
+            self.record_archive_error(self.get_final_datastore_keys(addr));
 
         // here, traverse the history from oldest to newest with added_changes at the end, applying additions and deletions
         let active_history = self.active_history.read();
@@ -445,7 +537,7 @@ impl SpeculativeLedger {
             {
                 HistorySearchResult::Present(entry) => Some(entry),
                 HistorySearchResult::NoInfo => {
-                    self.final_state.read().ledger.get_data_entry(addr, key)
+                    self.record_archive_error(self.get_final_data_entry(addr, key))
                 }
                 HistorySearchResult::Absent => None,
             }
@@ -470,10 +562,7 @@ impl SpeculativeLedger {
             {
                 HistorySearchResult::Present(_entry) => true,
                 HistorySearchResult::NoInfo => self
-                    .final_state
-                    .read()
-                    .ledger
-                    .get_data_entry(addr, key)
+                    .record_archive_error(self.get_final_data_entry(addr, key))
                     .is_some(),
                 HistorySearchResult::Absent => false,
             }
@@ -1,9 +1,18 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_execution_exports::SlotGasProfile;
+use massa_models::address::Address;
 use massa_models::slot::Slot;
-use massa_models::stats::ExecutionStats;
+use massa_models::stats::{ExecutionStats, FunctionGasStats, ModuleCacheStats, SlotExecutionTime};
 use massa_time::MassaTime;
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+
+/// maximum number of functions listed in the top gas consumers of the statistics
+pub(crate) const MAX_TOP_GAS_FUNCTIONS: usize = 10;
+
+/// maximum number of slots listed in the slowest slots of the statistics
+const MAX_SLOWEST_SLOTS: usize = 10;
 
 /// Execution statistics counter
 pub struct ExecutionStatsCounter {
@@ -15,6 +24,8 @@ pub struct ExecutionStatsCounter {
     final_executed_ops: VecDeque<(usize, MassaTime)>,
     /// final denunciations executed in the time window (count, instant)
     final_executed_denunciations: VecDeque<(usize, MassaTime)>,
+    /// gas profiles of the final slots executed in the time window (slot, profile, instant)
+    final_gas_profiles: VecDeque<(Slot, SlotGasProfile, MassaTime)>,
 }
 
 impl ExecutionStatsCounter {
@@ -25,6 +36,7 @@ impl ExecutionStatsCounter {
             final_blocks: Default::default(),
             final_executed_ops: Default::default(),
             final_executed_denunciations: Default::default(),
+            final_gas_profiles: Default::default(),
         }
     }
 
@@ -49,6 +61,15 @@ impl ExecutionStatsCounter {
                 break;
             }
         }
+
+        // prune final gas profiles
+        while let Some((_, _, t)) = self.final_gas_profiles.front() {
+            if t < &start_time {
+                self.final_gas_profiles.pop_front();
+            } else {
+                break;
+            }
+        }
     }
 
     /// register final blocks
@@ -73,8 +94,55 @@ impl ExecutionStatsCounter {
         self.refresh(current_time);
     }
 
+    /// register the gas profile of a final slot
+    pub fn register_final_gas_profile(&mut self, slot: Slot, gas_profile: SlotGasProfile) {
+        let current_time = MassaTime::now().expect("could not get current time");
+        self.final_gas_profiles
+            .push_back((slot, gas_profile, current_time));
+        self.refresh(current_time);
+    }
+
+    /// gas profiles of the final slots executed between `start_time` and `end_time`
+    fn gas_profiles_between(
+        &self,
+        start_time: MassaTime,
+        end_time: MassaTime,
+    ) -> impl Iterator<Item = &(Slot, SlotGasProfile, MassaTime)> {
+        self.final_gas_profiles
+            .iter()
+            .filter(move |(_, _, t)| t >= &start_time && t <= &end_time)
+    }
+
+    /// get the smart contract functions that used the most gas in the time window, most consuming first
+    pub fn get_top_gas_functions(&self, count: usize) -> Vec<FunctionGasStats> {
+        let current_time = MassaTime::now().expect("could not get current time");
+        let start_time = current_time.saturating_sub(self.time_window_duration);
+        let mut functions: BTreeMap<(Address, &str), (u64, u64)> = BTreeMap::new();
+        for (_, gas_profile, _) in self.gas_profiles_between(start_time, current_time) {
+            for ((address, function), (call_count, gas_used)) in &gas_profile.functions {
+                let entry = functions.entry((*address, function.as_str())).or_default();
+                entry.0 += call_count;
+                entry.1 += gas_used;
+            }
+        }
+        let mut functions: Vec<FunctionGasStats> = functions
+            .into_iter()
+            .map(
+                |((address, function), (call_count, gas_used))| FunctionGasStats {
+                    address,
+                    function: function.to_string(),
+                    call_count,
+                    gas_used,
+                },
+            )
+            .collect();
+        functions.sort_by_key(|function| Reverse(function.gas_used));
+        functions.truncate(count);
+        functions
+    }
+
     /// get statistics
-    pub fn get_stats(&self, active_cursor: Slot) -> ExecutionStats {
+    pub fn get_stats(&self, active_cursor: Slot, module_cache: ModuleCacheStats) -> ExecutionStats {
         let current_time = MassaTime::now().expect("could not get current time");
         let start_time = current_time.saturating_sub(self.time_window_duration);
         let map_func = |pair: &(usize, MassaTime)| -> usize {
@@ -85,12 +153,38 @@ impl ExecutionStatsCounter {
                 0
             }
         };
+        let mut gas_used_by_operation_type: BTreeMap<String, u64> = BTreeMap::new();
+        let mut slowest_slots = Vec::new();
+        for (slot, gas_profile, _) in self.gas_profiles_between(start_time, current_time) {
+            for (operation_type, gas_used) in &gas_profile.operation_types {
+                *gas_used_by_operation_type
+                    .entry(operation_type.to_string())
+                    .or_default() += gas_used;
+            }
+            slowest_slots.push((*slot, gas_profile));
+        }
+        slowest_slots.sort_by_key(|(_, gas_profile)| Reverse(gas_profile.execution_time));
+        let slowest_slots = slowest_slots
+            .into_iter()
+            .take(MAX_SLOWEST_SLOTS)
+            .map(|(slot, gas_profile)| SlotExecutionTime {
+                slot,
+                execution_time: MassaTime::from_millis(
+                    gas_profile.execution_time.as_millis() as u64
+                ),
+                gas_used: gas_profile.gas_used(),
+            })
+            .collect();
         ExecutionStats {
             final_block_count: self.final_blocks.iter().map(map_func).sum(),
             final_executed_operations_count: self.final_executed_ops.iter().map(map_func).sum(),
             time_window_start: start_time,
             time_window_end: current_time,
             active_cursor,
+            gas_used_by_operation_type,
+            top_gas_functions: self.get_top_gas_functions(MAX_TOP_GAS_FUNCTIONS),
+            slowest_slots,
+            module_cache,
         }
     }
 }
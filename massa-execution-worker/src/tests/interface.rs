@@ -3,11 +3,21 @@
 #[cfg(test)]
 mod tests {
     use hex_literal::hex;
+    use massa_execution_exports::{ExecutionConfig, ExecutionTraceStep};
     use massa_models::address::Address;
+    use massa_models::amount::Amount;
+    use massa_models::bytecode::Bytecode;
     use massa_sc_runtime::Interface;
+    use massa_signature::KeyPair;
+    use parking_lot::Mutex;
+    use std::collections::BTreeSet;
     use std::str::FromStr;
+    use std::sync::Arc;
 
     use crate::interface_impl::InterfaceImpl;
+    use crate::tests::mock::{get_random_address_full, get_sample_context, get_sample_state};
+    use crate::tracer::ExecutionTracer;
+
     #[test]
     fn test_hash_sha256() {
         let interface = InterfaceImpl::new_default(
@@ -19,4 +29,102 @@ mod tests {
             &hex!("3fc9b689459d738f8c88a3a48aa9e33542016b7a4052e001aaa536fca74813cb")[..];
         assert_eq!(actual_hash, expected_hash);
     }
+
+    #[test]
+    fn test_trace_datastore_queries() {
+        let (final_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+        let keypair =
+            KeyPair::from_str("S18r2i8oJJyhF7Kprx98zwxAc3W4szf7RKuVMX6JydZz8zSxHeC").unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        let mut context = get_sample_context(final_state, address);
+        context
+            .set_data_entry(&address, b"key".to_vec(), b"value".to_vec())
+            .unwrap();
+        context.tracer = Some(ExecutionTracer::new(address, Amount::zero(), 1_000_000));
+        let context = Arc::new(Mutex::new(context));
+        let interface = InterfaceImpl::new(ExecutionConfig::default(), context.clone());
+
+        assert!(interface.has_data(b"key").unwrap());
+        assert!(!interface
+            .has_data_for(&address.to_string(), b"other")
+            .unwrap());
+        assert_eq!(
+            interface.get_keys(Some(b"k")).unwrap(),
+            BTreeSet::from([b"key".to_vec()])
+        );
+        let (unknown, _) = get_random_address_full();
+        assert!(interface.get_keys_for(&unknown.to_string(), None).is_err());
+
+        // the datastore queries are recorded in order, including the failed ones
+        let trace = context.lock().tracer.take().unwrap().finish(None);
+        assert_eq!(
+            trace.steps,
+            vec![
+                ExecutionTraceStep::DatastoreHas {
+                    address,
+                    key: b"key".to_vec(),
+                    exists: true,
+                },
+                ExecutionTraceStep::DatastoreHas {
+                    address,
+                    key: b"other".to_vec(),
+                    exists: false,
+                },
+                ExecutionTraceStep::DatastoreKeys {
+                    address,
+                    prefix: Some(b"k".to_vec()),
+                    keys: Some(vec![b"key".to_vec()]),
+                },
+                ExecutionTraceStep::DatastoreKeys {
+                    address: unknown,
+                    prefix: None,
+                    keys: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_nested_call_gas() {
+        let (final_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+        let keypair =
+            KeyPair::from_str("S18r2i8oJJyhF7Kprx98zwxAc3W4szf7RKuVMX6JydZz8zSxHeC").unwrap();
+        let address = Address::from_public_key(&keypair.get_public_key());
+        let mut context = get_sample_context(final_state, address);
+        // you can check the source code of the following wasm file in massa-unit-tests-src
+        let bytecode = include_bytes!("./wasm/test.wasm").to_vec();
+        let callee = context
+            .create_new_sc_address(Bytecode(bytecode.clone()))
+            .unwrap();
+        context.tracer = Some(ExecutionTracer::new(address, Amount::zero(), 2_000_000_000));
+        let context = Arc::new(Mutex::new(context));
+        let interface = InterfaceImpl::new(ExecutionConfig::default(), context.clone());
+
+        // a call to the callee making a local call, in the order the VM makes them.
+        // The gas is recorded whether or not the module loads.
+        assert_eq!(
+            interface.init_call(&callee.to_string(), 0).unwrap(),
+            bytecode
+        );
+        let _ = interface.get_module(&bytecode, 1_500_000_000);
+        let _ = interface.get_module(&bytecode, 1_000_000_000);
+        interface.finish_call().unwrap();
+        // a second call, failing before its module is loaded
+        interface.init_call(&callee.to_string(), 0).unwrap();
+
+        let trace = context.lock().tracer.take().unwrap().finish(None);
+        assert_eq!(trace.gas_at_entry, Some(2_000_000_000));
+        assert_eq!(trace.gas_at_exit, None);
+        match &trace.steps[..] {
+            [ExecutionTraceStep::Call(first), ExecutionTraceStep::Call(second)] => {
+                assert_eq!(first.address, callee);
+                // the local call made within the frame does not override its entry gas
+                assert_eq!(first.gas_at_entry, Some(1_500_000_000));
+                assert_eq!(first.gas_at_exit, None);
+                assert_eq!(second.address, callee);
+                assert_eq!(second.gas_at_entry, None);
+            }
+            steps => panic!("unexpected trace steps: {:?}", steps),
+        }
+    }
 }
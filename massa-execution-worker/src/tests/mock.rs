@@ -1,5 +1,7 @@
+use crate::context::ExecutionContext;
+use crate::vesting_manager::VestingManager;
 use massa_db::{DBBatch, MassaDB, MassaDBConfig};
-use massa_execution_exports::ExecutionError;
+use massa_execution_exports::{ExecutionConfig, ExecutionError, ExecutionStackElement};
 use massa_final_state::{FinalState, FinalStateConfig};
 use massa_hash::Hash;
 use massa_ledger_exports::{LedgerConfig, LedgerController, LedgerEntry, LedgerError};
@@ -18,6 +20,7 @@ use massa_models::{
     secure_share::SecureShareContent,
     slot::Slot,
 };
+use massa_module_cache::{config::ModuleCacheConfig, controller::ModuleCache};
 use massa_pos_exports::SelectorConfig;
 use massa_pos_worker::start_selector_worker;
 use massa_signature::KeyPair;
@@ -85,6 +88,8 @@ pub fn get_sample_state(
         max_history_length: 10,
         max_new_elements: 100,
         thread_count: THREAD_COUNT,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
 
@@ -191,6 +196,61 @@ pub fn create_block(
     )?)
 }
 
+/// Creates an execution context on top of `final_state`, executing as `address`
+#[allow(dead_code)] // to avoid warnings on gas_calibration feature
+pub fn get_sample_context(
+    final_state: Arc<RwLock<FinalState>>,
+    address: Address,
+) -> ExecutionContext {
+    let vesting_file = get_initials_vesting(false);
+    let config = ExecutionConfig {
+        initial_vesting_path: vesting_file.path().to_path_buf(),
+        ..ExecutionConfig::default()
+    };
+    let module_cache = Arc::new(RwLock::new(ModuleCache::new(ModuleCacheConfig {
+        hd_cache_path: config.hd_cache_path.clone(),
+        gas_costs: config.gas_costs.clone(),
+        compilation_gas: config.max_gas_per_block,
+        lru_cache_size: config.lru_cache_size,
+        hd_cache_size: config.hd_cache_size,
+        snip_amount: config.snip_amount,
+    })));
+    let vesting_manager = Arc::new(
+        VestingManager::new(
+            config.thread_count,
+            config.t0,
+            config.genesis_timestamp,
+            config.periods_per_cycle,
+            config.roll_price,
+            config.initial_vesting_path.clone(),
+        )
+        .unwrap(),
+    );
+    let mip_store = MipStore::try_from((
+        [],
+        MipStatsConfig {
+            block_count_considered: 10,
+            counters_max: 10,
+        },
+    ))
+    .unwrap();
+    let mut context = ExecutionContext::new(
+        config,
+        final_state,
+        Default::default(),
+        module_cache,
+        vesting_manager,
+        mip_store,
+    );
+    context.stack = vec![ExecutionStackElement {
+        address,
+        coins: Amount::zero(),
+        owned_addresses: vec![address],
+        operation_datastore: None,
+    }];
+    context
+}
+
 /// get the mocked file for initial vesting
 #[allow(dead_code)]
 pub fn get_initials_vesting(with_value: bool) -> NamedTempFile {
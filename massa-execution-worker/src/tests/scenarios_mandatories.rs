@@ -7,13 +7,15 @@ mod tests {
     use crate::tests::mock::{
         create_block, get_initials_vesting, get_random_address_full, get_sample_state,
     };
-    use massa_async_pool::AsyncMessage;
+    use massa_async_pool::{AsyncMessage, AsyncMessageTrigger, AsyncPoolChanges};
     use massa_db::DBBatch;
     use massa_execution_exports::{
-        ExecutionChannels, ExecutionConfig, ExecutionController, ExecutionError,
-        ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+        estimate_gas, ExecutionChannels, ExecutionConfig, ExecutionController, ExecutionError,
+        ExecutionStackElement, ExecutionTraceStep, GasEstimationConfig, GasEstimationTarget,
+        ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, SlotExecutionOutput,
     };
     use massa_hash::Hash;
+    use massa_ledger_exports::SetUpdateOrDelete;
     use massa_metrics::MassaMetrics;
     use massa_models::config::{
         LEDGER_ENTRY_BASE_COST, LEDGER_ENTRY_DATASTORE_BASE_SIZE, MIP_STORE_STATS_BLOCK_CONSIDERED,
@@ -30,7 +32,7 @@ mod tests {
         block_id::BlockId,
         datastore::Datastore,
         denunciation::Denunciation,
-        execution::EventFilter,
+        execution::{AsyncMessageFilter, EventFilter},
         operation::{Operation, OperationSerializer, OperationType, SecureShareOperation},
         secure_share::SecureShareContent,
     };
@@ -171,12 +173,47 @@ mod tests {
                     include_bytes!("./wasm/event_test.wasm").to_vec(),
                 ),
                 is_final: true,
+                at_slot: None,
+                trace: false,
             })
             .expect("readonly execution failed");
 
         assert_eq!(res.out.slot, Slot::new(1, 0));
         assert!(res.gas_cost > 0);
         assert_eq!(res.out.events.take().len(), 1, "wrong number of events");
+        assert!(res.trace.is_none());
+
+        // the same execution, traced
+        let caller_address =
+            Address::from_str("AU12cMW9zRKFDS43Z2W88VCmdQFxmHjAo54XvuVV34UzJeXRLXW9M").unwrap();
+        let res = controller
+            .execute_readonly_request(ReadOnlyExecutionRequest {
+                max_gas: 1_000_000,
+                call_stack: vec![ExecutionStackElement {
+                    address: caller_address,
+                    coins: Amount::zero(),
+                    owned_addresses: vec![caller_address],
+                    operation_datastore: None,
+                }],
+                target: ReadOnlyExecutionTarget::BytecodeExecution(
+                    include_bytes!("./wasm/event_test.wasm").to_vec(),
+                ),
+                is_final: true,
+                at_slot: None,
+                trace: true,
+            })
+            .expect("traced readonly execution failed");
+        let trace = res.trace.expect("missing execution trace");
+        assert_eq!(trace.address, caller_address);
+        assert_eq!(trace.gas_at_entry, Some(1_000_000));
+        assert_eq!(trace.gas_at_exit, Some(1_000_000 - res.gas_cost));
+        assert!(
+            trace
+                .steps
+                .iter()
+                .any(|step| matches!(step, ExecutionTraceStep::Event { .. })),
+            "the emitted event is missing from the trace"
+        );
 
         let res = controller
             .execute_readonly_request(ReadOnlyExecutionRequest {
@@ -186,6 +223,8 @@ mod tests {
                     include_bytes!("./wasm/event_test.wasm").to_vec(),
                 ),
                 is_final: false,
+                at_slot: None,
+                trace: false,
             })
             .expect("readonly execution failed");
 
@@ -194,6 +233,193 @@ mod tests {
         manager.stop();
     }
 
+    #[test]
+    #[serial]
+    fn test_simulate_operation() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: MassaTime::from_millis(100),
+            cursor_delay: MassaTime::from_millis(0),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // init the MIP store
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            counters_max: MIP_STORE_STATS_COUNTERS_MAX,
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+        // init the storage
+        let storage = Storage::create_root();
+
+        let slot_execution_output_sender = broadcast::channel(5000).0;
+
+        let channels = ExecutionChannels {
+            slot_execution_output_sender,
+        };
+
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            MassaMetrics::new(false, 32),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        std::thread::sleep(Duration::from_millis(1000));
+
+        // create a transaction valid at the current period
+        let sender_keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let (recipient_address, _keypair) = get_random_address_full();
+        let operation = Operation::new_verifiable(
+            Operation {
+                fee: Amount::from_str("1").unwrap(),
+                expire_period: controller.get_stats().active_cursor.period + 5,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        let operation_id = operation.id;
+
+        let mut res = controller
+            .execute_readonly_request(ReadOnlyExecutionRequest {
+                max_gas: operation.get_gas_usage(),
+                call_stack: vec![],
+                target: ReadOnlyExecutionTarget::Operation(operation),
+                is_final: false,
+                at_slot: None,
+                trace: false,
+            })
+            .expect("operation simulation failed");
+
+        // the operation is simulated in the thread of its sender
+        assert_eq!(
+            res.out.slot.thread,
+            sender_address.get_thread(exec_cfg.thread_count)
+        );
+        let receipt = res
+            .out
+            .receipts
+            .remove(&operation_id)
+            .expect("missing operation receipt");
+        assert_eq!(receipt.error, None);
+        assert_eq!(receipt.fee, Amount::from_str("1").unwrap());
+        let (sender_before, sender_after) = receipt.balance_changes[&sender_address];
+        assert_eq!(
+            sender_before.saturating_sub(sender_after),
+            Amount::from_str("101").unwrap()
+        );
+        // nothing was applied
+        assert_eq!(
+            controller.get_final_and_candidate_balance(&[recipient_address])[0],
+            (None, None)
+        );
+        assert_eq!(
+            controller.get_ops_exec_status(&[operation_id])[0],
+            (None, None)
+        );
+
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn test_estimate_gas() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: MassaTime::from_millis(100),
+            cursor_delay: MassaTime::from_millis(0),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // init the MIP store
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            counters_max: MIP_STORE_STATS_COUNTERS_MAX,
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+        // init the storage
+        let storage = Storage::create_root();
+
+        let slot_execution_output_sender = broadcast::channel(5000).0;
+
+        let channels = ExecutionChannels {
+            slot_execution_output_sender,
+        };
+
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            MassaMetrics::new(false, 32),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+        std::thread::sleep(Duration::from_millis(1000));
+
+        let keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+        let config = GasEstimationConfig {
+            margin_percent: 20,
+            max_gas_per_block: exec_cfg.max_gas_per_block,
+            max_read_only_gas: exec_cfg.max_read_only_gas,
+            sp_compilation_cost: exec_cfg.gas_costs.sp_compilation_cost,
+        };
+        let estimation = estimate_gas(
+            controller.as_ref(),
+            &config,
+            Address::from_public_key(&keypair.get_public_key()),
+            GasEstimationTarget::ExecuteSC {
+                bytecode: include_bytes!("./wasm/event_test.wasm").to_vec(),
+                datastore: Datastore::default(),
+            },
+        )
+        .expect("gas estimation failed");
+
+        // the compilation cost is paid by the operation
+        assert!(estimation.gas_used > config.sp_compilation_cost);
+        assert_eq!(
+            estimation.max_gas,
+            (estimation.gas_used * 120 / 100).min(config.max_gas_per_block)
+        );
+
+        // the coins of a call are transferred from the caller, who must be able to pay them
+        let (caller_address, _keypair) = get_random_address_full();
+        let (target_address, _keypair) = get_random_address_full();
+        let error = estimate_gas(
+            controller.as_ref(),
+            &config,
+            caller_address,
+            GasEstimationTarget::CallSC {
+                target_addr: target_address,
+                target_func: "receive".to_string(),
+                parameter: Vec::new(),
+                coins: Amount::from_str("100").unwrap(),
+            },
+        )
+        .expect_err("the caller cannot pay the call coins");
+        assert!(error.to_string().contains("call coins"));
+
+        manager.stop();
+    }
+
     /// Feeds the execution worker with genesis blocks to start it
     fn init_execution_worker(
         config: &ExecutionConfig,
@@ -562,6 +788,7 @@ mod tests {
         let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
 
         let slot_execution_output_sender = broadcast::channel(5000).0;
+        let mut slot_execution_output_receiver = slot_execution_output_sender.subscribe();
 
         let channels = ExecutionChannels {
             slot_execution_output_sender,
@@ -628,10 +855,210 @@ mod tests {
         // match the events
         assert!(events.len() == 1, "One event was expected");
         assert_eq!(events[0].data, "message correctly received: 42,42,42,42");
+
+        // the executed message left the pool
+        assert!(controller
+            .get_async_messages(&AsyncMessageFilter::default())
+            .is_empty());
+
+        // its successful execution was broadcast along with the output of its slot
+        let mut executions = Vec::new();
+        while let Ok(output) = slot_execution_output_receiver.try_recv() {
+            if let SlotExecutionOutput::ExecutedSlot(exec_out) = output {
+                executions.extend(exec_out.async_message_executions);
+            }
+        }
+        assert_eq!(executions.len(), 1, "One message execution was expected");
+        assert_eq!(executions[0].slot, events[0].context.slot);
+        assert!(executions[0].error.is_none());
         // stop the execution controller
         manager.stop();
     }
 
+    /// Seeds the final asynchronous pool with messages of various senders, destinations, handlers,
+    /// validity ranges and triggers, and checks the messages returned by `get_async_messages` for each filter.
+    #[test]
+    #[serial]
+    fn get_async_messages_filter() {
+        let vesting = get_initials_vesting(false);
+        // genesis is far in the future so that no slot is executed during the test
+        let exec_cfg = ExecutionConfig {
+            genesis_timestamp: MassaTime::now()
+                .unwrap()
+                .saturating_add(MassaTime::from_millis(1_000_000)),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+        let next_slot = sample_state
+            .read()
+            .db
+            .read()
+            .get_change_id()
+            .unwrap()
+            .get_next_slot(exec_cfg.thread_count)
+            .unwrap();
+        let later_slot = Slot::new(next_slot.period + 10, 0);
+
+        let (sender_a, _) = get_random_address_full();
+        let (sender_b, _) = get_random_address_full();
+        let (sc, _) = get_random_address_full();
+        let message = |index: u64,
+                       sender: Address,
+                       destination: Address,
+                       handler: &str,
+                       validity: (Slot, Slot),
+                       trigger_fired: Option<bool>| {
+            AsyncMessage::new_with_hash(
+                Slot::new(0, 0),
+                index,
+                sender,
+                destination,
+                handler.to_string(),
+                1_000,
+                Amount::zero(),
+                Amount::zero(),
+                validity.0,
+                validity.1,
+                Vec::new(),
+                trigger_fired.map(|_| AsyncMessageTrigger {
+                    address: sc,
+                    datastore_key: None,
+                }),
+                trigger_fired,
+            )
+        };
+        let current = (next_slot, later_slot);
+        let future = (later_slot, Slot::new(later_slot.period + 10, 0));
+        let messages = vec![
+            // no trigger: can be executed
+            message(0, sender_a, sc, "receive", current, None),
+            // trigger not fired yet
+            message(1, sender_b, sc, "receive", current, Some(false)),
+            // trigger fired
+            message(2, sender_a, sender_b, "refund", current, Some(true)),
+            // not valid yet
+            message(3, sender_b, sc, "refund", future, None),
+        ];
+        let mut changes = AsyncPoolChanges::default();
+        for message in messages.iter() {
+            changes.0.insert(
+                message.compute_id(),
+                SetUpdateOrDelete::Set(message.clone()),
+            );
+        }
+        let mut batch = DBBatch::new();
+        sample_state
+            .write()
+            .async_pool
+            .apply_changes_to_batch(&changes, &mut batch);
+        sample_state
+            .write()
+            .db
+            .write()
+            .write_batch(batch, Default::default(), None, false);
+
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            counters_max: MIP_STORE_STATS_COUNTERS_MAX,
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+        let channels = ExecutionChannels {
+            slot_execution_output_sender: broadcast::channel(5000).0,
+        };
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg,
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            MassaMetrics::new(false, 32),
+        );
+
+        // returns the emission indexes of the messages matching a filter
+        let matching = |filter: AsyncMessageFilter| {
+            let mut indexes: Vec<u64> = controller
+                .get_async_messages(&filter)
+                .iter()
+                .map(|pending| pending.message.emission_index)
+                .collect();
+            indexes.sort_unstable();
+            indexes
+        };
+        assert_eq!(matching(AsyncMessageFilter::default()), vec![0, 1, 2, 3]);
+        assert_eq!(
+            matching(AsyncMessageFilter {
+                sender: Some(sender_a),
+                ..Default::default()
+            }),
+            vec![0, 2]
+        );
+        assert_eq!(
+            matching(AsyncMessageFilter {
+                destination: Some(sc),
+                ..Default::default()
+            }),
+            vec![0, 1, 3]
+        );
+        assert_eq!(
+            matching(AsyncMessageFilter {
+                handler: Some("refund".to_string()),
+                ..Default::default()
+            }),
+            vec![2, 3]
+        );
+        assert_eq!(
+            matching(AsyncMessageFilter {
+                valid_at_slot: Some(next_slot),
+                ..Default::default()
+            }),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            matching(AsyncMessageFilter {
+                valid_at_slot: Some(later_slot),
+                ..Default::default()
+            }),
+            vec![3]
+        );
+        // all the criteria of the filter must match
+        assert_eq!(
+            matching(AsyncMessageFilter {
+                sender: Some(sender_b),
+                handler: Some("refund".to_string()),
+                valid_at_slot: Some(next_slot),
+                ..Default::default()
+            }),
+            Vec::<u64>::new()
+        );
+
+        // the trigger of a message fired if it has one and it can be executed,
+        // and it is executable at the next slot if it can be executed and is valid at that slot
+        let mut states: Vec<(u64, bool, bool)> = controller
+            .get_async_messages(&AsyncMessageFilter::default())
+            .into_iter()
+            .map(|pending| {
+                (
+                    pending.message.emission_index,
+                    pending.trigger_fired,
+                    pending.executable,
+                )
+            })
+            .collect();
+        states.sort_unstable();
+        assert_eq!(
+            states,
+            vec![
+                (0, false, true),
+                (1, false, false),
+                (2, true, true),
+                (3, false, false)
+            ]
+        );
+
+        manager.stop();
+    }
+
     /// # Context
     ///
     /// Mostly the same as send_and_receive_async_message
@@ -1243,6 +1670,275 @@ mod tests {
         manager.stop();
     }
 
+    #[test]
+    #[serial]
+    fn operation_receipts() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: MassaTime::from_millis(100),
+            cursor_delay: MassaTime::from_millis(0),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+
+        // init the MIP store
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            counters_max: MIP_STORE_STATS_COUNTERS_MAX,
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+
+        let slot_execution_output_sender = broadcast::channel(5000).0;
+
+        let channels = ExecutionChannels {
+            slot_execution_output_sender,
+        };
+
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            MassaMetrics::new(false, 32),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+
+        let sender_keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+        let sender_address = Address::from_public_key(&sender_keypair.get_public_key());
+        let (recipient_address, _keypair) = get_random_address_full();
+
+        // create a transaction and an erroneous smart contract execution
+        let transaction = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        // you can check the source code of the following wasm file in massa-unit-tests-src
+        let bytecode = include_bytes!("./wasm/execution_error.wasm");
+        let execution =
+            create_execute_sc_operation(&sender_keypair, bytecode, BTreeMap::default()).unwrap();
+        let (transaction_id, execution_id) = (transaction.id, execution.id);
+        storage.store_operations(vec![transaction.clone(), execution.clone()]);
+        let block = create_block(
+            KeyPair::generate(0).unwrap(),
+            vec![transaction, execution.clone()],
+            vec![],
+            Slot::new(1, 0),
+        )
+        .unwrap();
+        // store the block in storage
+        storage.store_block(block.clone());
+        // set our block as a final block so the operations are processed
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(
+            finalized_blocks,
+            Default::default(),
+            block_storage.clone(),
+        );
+        std::thread::sleep(Duration::from_millis(100));
+
+        let receipts = controller.get_operation_receipts(&[transaction_id, execution_id]);
+
+        // check the receipt of the transaction
+        let receipt = receipts[0].clone().expect("missing transaction receipt");
+        assert!(receipt.is_final);
+        assert_eq!(receipt.slot, Slot::new(1, 0));
+        assert_eq!(receipt.block_id, Some(block.id));
+        assert_eq!(receipt.error, None);
+        assert_eq!(receipt.gas_used, 0);
+        assert_eq!(
+            receipt.balance_changes.get(&recipient_address),
+            Some(&(
+                Amount::zero(),
+                Amount::from_str("100")
+                    .unwrap()
+                    .saturating_sub(LEDGER_ENTRY_BASE_COST)
+            ))
+        );
+        let (sender_before, sender_after) = receipt.balance_changes[&sender_address];
+        assert!(sender_after < sender_before);
+        assert!(receipt.datastore_keys.is_empty());
+        assert!(receipt.event_indexes.is_empty());
+
+        // check the receipt of the failed execution: only its fee is paid
+        let receipt = receipts[1].clone().expect("missing execution receipt");
+        assert!(receipt.is_final);
+        assert!(receipt
+            .error
+            .as_ref()
+            .expect("missing execution error")
+            .contains("address parsing error"));
+        assert_eq!(receipt.gas_used, execution.get_gas_usage());
+        let (sender_before, sender_after) = receipt.balance_changes[&sender_address];
+        assert_eq!(
+            sender_before.saturating_sub(sender_after),
+            execution.content.fee
+        );
+        assert!(receipt.datastore_keys.is_empty());
+
+        // the receipt points to the events of the operation kept by the event store
+        let events = controller.get_filtered_sc_output_event(EventFilter {
+            original_operation_id: Some(execution_id),
+            ..Default::default()
+        });
+        assert_eq!(events.len(), 2, "2 events were expected");
+        assert_eq!(
+            receipt.event_indexes,
+            events
+                .iter()
+                .map(|event| event.context.index_in_slot)
+                .collect::<Vec<_>>()
+        );
+        // stop the execution controller
+        manager.stop();
+    }
+
+    #[test]
+    #[serial]
+    fn check_slot_determinism() {
+        let vesting = get_initials_vesting(false);
+        // setup the period duration
+        let exec_cfg = ExecutionConfig {
+            t0: MassaTime::from_millis(100),
+            cursor_delay: MassaTime::from_millis(0),
+            initial_vesting_path: vesting.path().to_path_buf(),
+            ..ExecutionConfig::default()
+        };
+        // get a sample final state
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state(0).unwrap();
+
+        // init the MIP store
+        let mip_stats_config = MipStatsConfig {
+            block_count_considered: MIP_STORE_STATS_BLOCK_CONSIDERED,
+            counters_max: MIP_STORE_STATS_COUNTERS_MAX,
+        };
+        let mip_store = MipStore::try_from(([], mip_stats_config)).unwrap();
+
+        // init the storage
+        let mut storage = Storage::create_root();
+
+        let slot_execution_output_sender = broadcast::channel(5000).0;
+
+        let channels = ExecutionChannels {
+            slot_execution_output_sender,
+        };
+
+        // start the execution worker
+        let (mut manager, controller) = start_execution_worker(
+            exec_cfg.clone(),
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            mip_store,
+            channels,
+            MassaMetrics::new(false, 32),
+        );
+        // initialize the execution system with genesis blocks
+        init_execution_worker(&exec_cfg, &storage, controller.clone());
+
+        // request the check of the slot before it becomes final
+        let check = std::thread::spawn({
+            let controller = controller.clone();
+            move || {
+                controller.check_slot_determinism(
+                    Slot::new(1, 0),
+                    true,
+                    MassaTime::from_millis(10_000),
+                )
+            }
+        });
+        std::thread::sleep(Duration::from_millis(100));
+
+        // create a block with a transaction and a smart contract execution
+        let sender_keypair = KeyPair::from_str(TEST_SK_1).unwrap();
+        let (recipient_address, _keypair) = get_random_address_full();
+        let transaction = Operation::new_verifiable(
+            Operation {
+                fee: Amount::zero(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                },
+            },
+            OperationSerializer::new(),
+            &sender_keypair,
+        )
+        .unwrap();
+        // you can check the source code of the following wasm file in massa-unit-tests-src
+        let bytecode = include_bytes!("./wasm/execution_error.wasm");
+        let execution =
+            create_execute_sc_operation(&sender_keypair, bytecode, BTreeMap::default()).unwrap();
+        let (transaction_id, execution_id) = (transaction.id, execution.id);
+        storage.store_operations(vec![transaction.clone(), execution.clone()]);
+        let block = create_block(
+            KeyPair::generate(0).unwrap(),
+            vec![transaction, execution],
+            vec![],
+            Slot::new(1, 0),
+        )
+        .unwrap();
+        storage.store_block(block.clone());
+
+        // finalize the block, which runs the check
+        let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+        finalized_blocks.insert(block.content.header.content.slot, block.id);
+        let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+        block_storage.insert(block.id, storage.clone());
+        controller.update_blockclique_status(finalized_blocks, Default::default(), block_storage);
+
+        // both executions of the slot lead to the same state changes
+        let output = check.join().unwrap().expect("the determinism check failed");
+        assert_eq!(output.slot, Slot::new(1, 0));
+        assert_eq!(output.block_id, Some(block.id));
+        assert_eq!(
+            output.final_state_changes_hash,
+            output.reexecution_state_changes_hash
+        );
+        assert_eq!(
+            output
+                .operation_traces
+                .iter()
+                .map(|(operation_id, _)| *operation_id)
+                .collect::<Vec<_>>(),
+            vec![transaction_id, execution_id]
+        );
+
+        // the execution environment before a final slot is not kept: it can't be checked anymore
+        assert!(controller
+            .check_slot_determinism(Slot::new(1, 0), false, MassaTime::from_millis(10_000))
+            .is_err());
+
+        // the wait for a slot that is not finalized in time is bounded
+        let start = std::time::Instant::now();
+        assert!(controller
+            .check_slot_determinism(Slot::new(1_000, 0), false, MassaTime::from_millis(200))
+            .is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // stop the execution controller
+        manager.stop();
+    }
+
     #[test]
     #[serial]
     fn vesting_transfer_coins() {
@@ -57,6 +57,9 @@ mod tests {
                 executed_denunciations_changes: Default::default(),
             },
             events: Default::default(),
+            receipts: Default::default(),
+            async_message_executions: Default::default(),
+            gas_profile: Default::default(),
         };
 
         let active_history = ActiveHistory {
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use crate::address_history_db::AddressHistoryDB;
+    use massa_execution_exports::OperationReceipt;
+    use massa_hash::Hash;
+    use massa_models::address::{Address, UserAddress, UserAddressV0};
+    use massa_models::amount::Amount;
+    use massa_models::execution::{AddressHistoryCursor, AddressHistoryDirection};
+    use massa_models::operation::OperationId;
+    use massa_models::slot::Slot;
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::str::FromStr;
+    use tempfile::TempDir;
+
+    fn address(name: &str) -> Address {
+        Address::User(UserAddress::UserAddressV0(UserAddressV0(
+            Hash::compute_from(name.as_bytes()),
+        )))
+    }
+
+    fn receipt(slot: Slot, index: u64, sender: Address, recipient: Address) -> OperationReceipt {
+        let balance = Amount::from_str("100").unwrap();
+        OperationReceipt {
+            operation_id: OperationId::from_bytes(
+                Hash::compute_from(&[&slot.to_bytes_key()[..], &index.to_be_bytes()[..]].concat())
+                    .to_bytes(),
+            ),
+            sender_address: sender,
+            slot,
+            block_id: None,
+            error: None,
+            gas_used: 0,
+            fee: Amount::zero(),
+            balance_changes: BTreeMap::from([
+                (sender, (balance, Amount::zero())),
+                (recipient, (Amount::zero(), balance)),
+            ]),
+            datastore_keys: BTreeSet::new(),
+            event_indexes: Vec::new(),
+            is_final: true,
+        }
+    }
+
+    #[test]
+    fn test_address_history_db_directions_and_pagination() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = AddressHistoryDB::new(temp_dir.path().to_path_buf());
+        let alice = address("alice");
+        let bob = address("bob");
+        let carol = address("carol");
+
+        // alice sends 2 operations to bob per slot, bob sends 1 to carol
+        let mut receipts = Vec::new();
+        for period in 1..=5 {
+            let slot = Slot::new(period, 0);
+            receipts.push(receipt(slot, 0, alice, bob));
+            receipts.push(receipt(slot, 1, alice, bob));
+            receipts.push(receipt(slot, 2, bob, carol));
+        }
+        db.insert_receipts(receipts.iter());
+
+        let alice_history = db.get_address_history(&alice, None, usize::MAX);
+        assert_eq!(alice_history.len(), 10);
+        assert!(alice_history
+            .iter()
+            .all(|entry| entry.direction == AddressHistoryDirection::Sent));
+        // most recent first
+        assert_eq!(alice_history[0].slot, Slot::new(5, 0));
+        assert_eq!(alice_history[9].slot, Slot::new(1, 0));
+
+        let bob_history = db.get_address_history(&bob, None, usize::MAX);
+        assert_eq!(bob_history.len(), 15);
+        assert_eq!(
+            bob_history
+                .iter()
+                .filter(|entry| entry.direction == AddressHistoryDirection::Sent)
+                .count(),
+            5
+        );
+
+        let carol_history = db.get_address_history(&carol, None, usize::MAX);
+        assert_eq!(carol_history.len(), 5);
+        assert!(carol_history
+            .iter()
+            .all(|entry| entry.direction == AddressHistoryDirection::Received));
+        assert!(db
+            .get_address_history(&address("dave"), None, usize::MAX)
+            .is_empty());
+
+        // walk through the history of bob page by page
+        let mut cursor = None;
+        let mut pages = Vec::new();
+        let mut walked = Vec::new();
+        loop {
+            let page = db.get_address_history(&bob, cursor.as_ref(), 4);
+            let Some(last) = page.last() else {
+                break;
+            };
+            cursor = Some(AddressHistoryCursor {
+                slot: last.slot,
+                operation_id: last.operation_id,
+            });
+            pages.push(page.len());
+            walked.extend(page);
+        }
+        assert_eq!(pages, vec![4, 4, 4, 3]);
+        assert_eq!(walked, bob_history);
+    }
+}
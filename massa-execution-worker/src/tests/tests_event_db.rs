@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod tests {
+    use crate::event_db::EventDB;
+    use massa_execution_exports::EventStore;
+    use massa_hash::Hash;
+    use massa_models::address::{Address, UserAddress, UserAddressV0};
+    use massa_models::execution::{EventCursor, EventFilter};
+    use massa_models::output_event::{EventExecutionContext, SCOutputEvent};
+    use massa_models::slot::Slot;
+    use std::collections::VecDeque;
+    use tempfile::TempDir;
+
+    fn address(name: &str) -> Address {
+        Address::User(UserAddress::UserAddressV0(UserAddressV0(
+            Hash::compute_from(name.as_bytes()),
+        )))
+    }
+
+    fn event(slot: Slot, index_in_slot: u64, emitter: Address, caller: Address) -> SCOutputEvent {
+        SCOutputEvent {
+            context: EventExecutionContext {
+                slot,
+                block: None,
+                read_only: false,
+                index_in_slot,
+                call_stack: VecDeque::from(vec![emitter, caller]),
+                origin_operation_id: None,
+                is_final: true,
+                is_error: false,
+            },
+            data: index_in_slot.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_event_db_range_and_index_queries() {
+        let temp_dir = TempDir::new().unwrap();
+        let emitter_1 = address("emitter_1");
+        let emitter_2 = address("emitter_2");
+        let caller = address("caller");
+
+        let db = EventDB::new(temp_dir.path().to_path_buf());
+        let mut events = EventStore::default();
+        for period in 1..=10 {
+            let slot = Slot::new(period, 0);
+            events.push(event(slot, 0, emitter_1, caller));
+            events.push(event(slot, 1, emitter_2, caller));
+        }
+        db.insert_events(&events);
+        // the events survive a reopening of the store
+        drop(db);
+        let db = EventDB::new(temp_dir.path().to_path_buf());
+
+        let range = db.get_filtered_sc_output_events(
+            &EventFilter {
+                start: Some(Slot::new(3, 0)),
+                end: Some(Slot::new(5, 0)),
+                ..Default::default()
+            },
+            None,
+            usize::MAX,
+        );
+        assert_eq!(
+            range
+                .iter()
+                .map(|e| (e.context.slot, e.context.index_in_slot))
+                .collect::<Vec<_>>(),
+            vec![
+                (Slot::new(3, 0), 0),
+                (Slot::new(3, 0), 1),
+                (Slot::new(4, 0), 0),
+                (Slot::new(4, 0), 1)
+            ]
+        );
+
+        let by_emitter = db.get_filtered_sc_output_events(
+            &EventFilter {
+                start: Some(Slot::new(8, 0)),
+                emitter_address: Some(emitter_2),
+                ..Default::default()
+            },
+            None,
+            usize::MAX,
+        );
+        assert_eq!(by_emitter.len(), 3);
+        assert!(by_emitter
+            .iter()
+            .all(|e| e.context.call_stack.front() == Some(&emitter_2)));
+
+        let by_caller = db.get_filtered_sc_output_events(
+            &EventFilter {
+                original_caller_address: Some(caller),
+                ..Default::default()
+            },
+            None,
+            usize::MAX,
+        );
+        assert_eq!(by_caller.len(), 20);
+        assert!(by_caller
+            .windows(2)
+            .all(|w| (w[0].context.slot, w[0].context.index_in_slot)
+                < (w[1].context.slot, w[1].context.index_in_slot)));
+
+        let unknown = db.get_filtered_sc_output_events(
+            &EventFilter {
+                emitter_address: Some(caller),
+                ..Default::default()
+            },
+            None,
+            usize::MAX,
+        );
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_event_db_pagination_and_data_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let emitter = address("emitter");
+        let caller = address("caller");
+
+        let db = EventDB::new(temp_dir.path().to_path_buf());
+        let mut events = EventStore::default();
+        for period in 1..=5 {
+            for index_in_slot in 0..3 {
+                let mut event = event(Slot::new(period, 0), index_in_slot, emitter, caller);
+                event.data = format!("transfer:{}:{}", period, index_in_slot);
+                events.push(event);
+            }
+        }
+        db.insert_events(&events);
+
+        // walk through all the events of the emitter, 4 by 4
+        let filter = EventFilter {
+            emitter_address: Some(emitter),
+            ..Default::default()
+        };
+        let mut cursor: Option<EventCursor> = None;
+        let mut pages = Vec::new();
+        loop {
+            let page = db.get_filtered_sc_output_events(&filter, cursor.as_ref(), 4);
+            let Some(last) = page.last() else {
+                break;
+            };
+            cursor = Some(EventCursor {
+                slot: last.context.slot,
+                index_in_slot: last.context.index_in_slot,
+            });
+            pages.push(page.len());
+        }
+        assert_eq!(pages, vec![4, 4, 4, 3]);
+
+        let by_prefix = db.get_filtered_sc_output_events(
+            &EventFilter {
+                data_prefix: Some("transfer:2:".to_string()),
+                ..Default::default()
+            },
+            None,
+            usize::MAX,
+        );
+        assert_eq!(by_prefix.len(), 3);
+
+        let by_substring = db.get_filtered_sc_output_events(
+            &EventFilter {
+                data_substring: Some(":1".to_string()),
+                call_stack_address: Some(caller),
+                ..Default::default()
+            },
+            None,
+            usize::MAX,
+        );
+        // "transfer:1:x" for 3 events and "transfer:y:1" for the 4 other periods
+        assert_eq!(by_substring.len(), 7);
+    }
+
+    #[test]
+    fn test_event_db_latest_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let emitter_1 = address("emitter_1");
+        let emitter_2 = address("emitter_2");
+        let caller = address("caller");
+
+        let db = EventDB::new(temp_dir.path().to_path_buf());
+        let mut events = EventStore::default();
+        for period in 1..=10 {
+            let slot = Slot::new(period, 0);
+            events.push(event(slot, 0, emitter_1, caller));
+            events.push(event(slot, 1, emitter_2, caller));
+        }
+        db.insert_events(&events);
+
+        // the newest events are returned, still in ascending order
+        let latest = db.get_latest_filtered_sc_output_events(&EventFilter::default(), 3);
+        assert_eq!(
+            latest
+                .iter()
+                .map(|e| (e.context.slot, e.context.index_in_slot))
+                .collect::<Vec<_>>(),
+            vec![
+                (Slot::new(9, 0), 1),
+                (Slot::new(10, 0), 0),
+                (Slot::new(10, 0), 1)
+            ]
+        );
+
+        // the end of the slot range is excluded and its start is included
+        let latest_in_range = db.get_latest_filtered_sc_output_events(
+            &EventFilter {
+                start: Some(Slot::new(3, 0)),
+                end: Some(Slot::new(5, 0)),
+                emitter_address: Some(emitter_1),
+                ..Default::default()
+            },
+            usize::MAX,
+        );
+        assert_eq!(
+            latest_in_range
+                .iter()
+                .map(|e| (e.context.slot, e.context.index_in_slot))
+                .collect::<Vec<_>>(),
+            vec![(Slot::new(3, 0), 0), (Slot::new(4, 0), 0)]
+        );
+    }
+
+    #[test]
+    fn test_event_db_pruning() {
+        let temp_dir = TempDir::new().unwrap();
+        let emitter = address("emitter");
+        let caller = address("caller");
+
+        let db = EventDB::new(temp_dir.path().to_path_buf());
+        let mut events = EventStore::default();
+        for period in 1..=10 {
+            events.push(event(Slot::new(period, 0), 0, emitter, caller));
+        }
+        db.insert_events(&events);
+        db.prune_events_before(&Slot::new(6, 0));
+
+        let periods = |events: Vec<SCOutputEvent>| {
+            events
+                .iter()
+                .map(|e| e.context.slot.period)
+                .collect::<Vec<_>>()
+        };
+        let remaining = db.get_filtered_sc_output_events(&EventFilter::default(), None, usize::MAX);
+        assert_eq!(periods(remaining), vec![6, 7, 8, 9, 10]);
+        // index queries no longer return the pruned events
+        let by_emitter = db.get_filtered_sc_output_events(
+            &EventFilter {
+                emitter_address: Some(emitter),
+                ..Default::default()
+            },
+            None,
+            usize::MAX,
+        );
+        assert_eq!(periods(by_emitter), vec![6, 7, 8, 9, 10]);
+        let by_caller = db.get_latest_filtered_sc_output_events(
+            &EventFilter {
+                original_caller_address: Some(caller),
+                ..Default::default()
+            },
+            usize::MAX,
+        );
+        assert_eq!(periods(by_caller), vec![6, 7, 8, 9, 10]);
+    }
+}
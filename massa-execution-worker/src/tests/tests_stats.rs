@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use crate::stats::ExecutionStatsCounter;
+    use massa_execution_exports::SlotGasProfile;
+    use massa_hash::Hash;
+    use massa_models::address::{Address, UserAddress, UserAddressV0};
+    use massa_models::amount::Amount;
+    use massa_models::operation::OperationType;
+    use massa_models::slot::Slot;
+    use massa_time::MassaTime;
+    use std::time::Duration;
+
+    fn call_operation(target_addr: Address, target_func: &str) -> OperationType {
+        OperationType::CallSC {
+            target_addr,
+            target_func: target_func.to_string(),
+            param: Vec::new(),
+            max_gas: 1_000_000,
+            coins: Amount::zero(),
+        }
+    }
+
+    #[test]
+    fn test_execution_stats_gas_profiling() {
+        let sc_a = Address::User(UserAddress::UserAddressV0(UserAddressV0(
+            Hash::compute_from("SC_A".as_bytes()),
+        )));
+        let sc_b = Address::User(UserAddress::UserAddressV0(UserAddressV0(
+            Hash::compute_from("SC_B".as_bytes()),
+        )));
+
+        let mut profile_1 = SlotGasProfile::default();
+        profile_1.record_operation(&call_operation(sc_a, "transfer"), 300);
+        profile_1.record_operation(&OperationType::RollBuy { roll_count: 1 }, 0);
+        profile_1.record_async_message(sc_b, "receive", 50);
+        profile_1.execution_time = Duration::from_millis(5);
+
+        let mut profile_2 = SlotGasProfile::default();
+        profile_2.record_operation(&call_operation(sc_a, "transfer"), 200);
+        profile_2.record_operation(&call_operation(sc_b, "mint"), 1000);
+        profile_2.execution_time = Duration::from_millis(20);
+
+        let mut counter = ExecutionStatsCounter::new(MassaTime::from_millis(60_000));
+        counter.register_final_gas_profile(Slot::new(1, 0), profile_1);
+        counter.register_final_gas_profile(Slot::new(1, 1), profile_2);
+        let stats = counter.get_stats(Slot::new(1, 1), Default::default());
+
+        assert_eq!(stats.gas_used_by_operation_type.get("CallSC"), Some(&1500));
+        assert_eq!(stats.gas_used_by_operation_type.get("RollBuy"), Some(&0));
+
+        // functions are ranked by gas used, calls of both slots being summed
+        let top_functions: Vec<_> = stats
+            .top_gas_functions
+            .iter()
+            .map(|f| (f.address, f.function.as_str(), f.call_count, f.gas_used))
+            .collect();
+        assert_eq!(
+            top_functions,
+            vec![
+                (sc_b, "mint", 1, 1000),
+                (sc_a, "transfer", 2, 500),
+                (sc_b, "receive", 1, 50)
+            ]
+        );
+
+        // slots are ranked by execution time
+        let slowest_slots: Vec<_> = stats
+            .slowest_slots
+            .iter()
+            .map(|s| (s.slot, s.execution_time.to_millis(), s.gas_used))
+            .collect();
+        assert_eq!(
+            slowest_slots,
+            vec![(Slot::new(1, 1), 20, 1200), (Slot::new(1, 0), 5, 350)]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! Recorder of the ABI calls made during a read-only execution.
+
+use massa_execution_exports::{ExecutionTraceFrame, ExecutionTraceStep};
+use massa_models::{address::Address, amount::Amount};
+
+/// Records the trace of a read-only execution as a tree of call frames
+pub(crate) struct ExecutionTracer {
+    /// frames currently open, the root frame first
+    frames: Vec<ExecutionTraceFrame>,
+}
+
+impl ExecutionTracer {
+    /// Creates a new tracer
+    ///
+    /// # Arguments
+    /// * `address`: address executing the root frame
+    /// * `coins`: coins available to the root frame
+    /// * `max_gas`: gas available to the whole execution
+    pub fn new(address: Address, coins: Amount, max_gas: u64) -> Self {
+        let mut root = ExecutionTraceFrame::new(address, coins);
+        root.gas_at_entry = Some(max_gas);
+        ExecutionTracer { frames: vec![root] }
+    }
+
+    /// Records a step in the current frame
+    pub fn record(&mut self, step: ExecutionTraceStep) {
+        self.current_frame().steps.push(step);
+    }
+
+    /// Opens a frame for a call to another smart contract
+    pub fn enter_frame(&mut self, address: Address, coins: Amount) {
+        self.frames.push(ExecutionTraceFrame::new(address, coins));
+    }
+
+    /// Records the gas given to the current frame when the VM loads its module.
+    /// Only the first report of a frame is kept: the next ones are local calls made within it.
+    pub fn record_entry_gas(&mut self, remaining_gas: u64) {
+        let frame = self.current_frame();
+        if frame.gas_at_entry.is_none() {
+            frame.gas_at_entry = Some(remaining_gas);
+        }
+    }
+
+    /// Closes the current frame, recording it as a call step of its parent.
+    /// The root frame is never closed.
+    pub fn exit_frame(&mut self) {
+        if self.frames.len() > 1 {
+            let frame = self.frames.pop().expect("tracer frames are never empty");
+            self.record(ExecutionTraceStep::Call(frame));
+        }
+    }
+
+    /// Closes the frames left open by an interrupted execution and returns the root frame
+    ///
+    /// # Arguments
+    /// * `remaining_gas`: gas remaining at the end of the execution, `None` if it failed
+    pub fn finish(mut self, remaining_gas: Option<u64>) -> ExecutionTraceFrame {
+        while self.frames.len() > 1 {
+            self.exit_frame();
+        }
+        let mut root = self.frames.pop().expect("tracer frames are never empty");
+        root.gas_at_exit = remaining_gas;
+        root
+    }
+
+    /// Returns the innermost open frame
+    fn current_frame(&mut self) -> &mut ExecutionTraceFrame {
+        self.frames
+            .last_mut()
+            .expect("tracer frames are never empty")
+    }
+}
@@ -220,6 +220,13 @@ impl ExecutionThread {
 
         // We are quitting the loop.
 
+        // Cancel pending slot determinism checks
+        self.execution_state
+            .write()
+            .cancel_determinism_checks(ExecutionError::ChannelError(
+                "slot determinism check cancelled because the execution worker is closing".into(),
+            ));
+
         // Cancel pending readonly requests
         let cancel_err = ExecutionError::ChannelError(
             "readonly execution cancelled because the execution worker is closing".into(),
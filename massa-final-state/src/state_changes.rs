@@ -10,7 +10,10 @@ use massa_executed_ops::{
     ExecutedDenunciationsChangesSerializer, ExecutedOpsChanges, ExecutedOpsChangesDeserializer,
     ExecutedOpsChangesSerializer,
 };
+use massa_hash::Hash;
 use massa_ledger_exports::{LedgerChanges, LedgerChangesDeserializer, LedgerChangesSerializer};
+use massa_models::denunciation::DenunciationIndexSerializer;
+use massa_models::prehash::{PreHashMap, PreHashed};
 use massa_pos_exports::{PoSChanges, PoSChangesDeserializer, PoSChangesSerializer};
 use massa_serialization::{Deserializer, SerializeError, Serializer};
 use nom::{
@@ -19,6 +22,7 @@ use nom::{
     IResult, Parser,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// represents changes that can be applied to the execution state
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
@@ -292,4 +296,54 @@ impl StateChanges {
         self.executed_ops_changes
             .extend(changes.executed_ops_changes);
     }
+
+    /// Computes a hash of the changes that does not depend on the iteration order of their hash maps,
+    /// allowing to compare the changes of two executions of the same slot
+    pub fn compute_hash(&self) -> Hash {
+        let mut canonical = self.clone();
+        canonical.ledger_changes.0 = sorted_map(&self.ledger_changes.0);
+        canonical.pos_changes.roll_changes = sorted_map(&self.pos_changes.roll_changes);
+        canonical.pos_changes.production_stats = sorted_map(&self.pos_changes.production_stats);
+        for credits in canonical.pos_changes.deferred_credits.credits.values_mut() {
+            *credits = sorted_map(credits);
+        }
+        canonical.executed_ops_changes = sorted_map(&self.executed_ops_changes);
+        // denunciation indexes are not ordered and their set is randomly seeded:
+        // they are serialized separately, sorted by their serialized form
+        canonical.executed_denunciations_changes = Default::default();
+
+        let mut buffer = Vec::new();
+        StateChangesSerializer::new()
+            .serialize(&canonical, &mut buffer)
+            .expect("state changes serialization failed");
+        let index_serializer = DenunciationIndexSerializer::new();
+        let mut indexes: Vec<Vec<u8>> = self
+            .executed_denunciations_changes
+            .iter()
+            .map(|index| {
+                let mut serialized = Vec::new();
+                index_serializer
+                    .serialize(index, &mut serialized)
+                    .expect("denunciation index serialization failed");
+                serialized
+            })
+            .collect();
+        indexes.sort_unstable();
+        buffer.extend(indexes.concat());
+        Hash::compute_from(&buffer)
+    }
+}
+
+/// Rebuilds a hash map by inserting its entries sorted by key,
+/// so that maps with the same entries are iterated in the same order
+fn sorted_map<K, V>(map: &PreHashMap<K, V>) -> PreHashMap<K, V>
+where
+    K: PreHashed + Ord + std::hash::Hash + Copy,
+    V: Clone,
+{
+    map.iter()
+        .map(|(key, value)| (*key, value.clone()))
+        .collect::<BTreeMap<K, V>>()
+        .into_iter()
+        .collect()
 }
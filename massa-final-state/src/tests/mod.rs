@@ -1,3 +1,4 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 mod scenarios;
+mod state_changes;
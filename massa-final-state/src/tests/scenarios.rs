@@ -38,6 +38,8 @@ fn create_final_state(temp_dir: &TempDir, reset_final_state: bool) -> Arc<RwLock
         max_history_length: 10,
         max_new_elements: 100,
         thread_count,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
 
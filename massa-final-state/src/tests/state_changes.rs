@@ -0,0 +1,103 @@
+//! Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::StateChanges;
+use massa_hash::Hash;
+use massa_ledger_exports::{LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete};
+use massa_models::address::Address;
+use massa_models::amount::Amount;
+use massa_models::denunciation::DenunciationIndex;
+use massa_models::operation::OperationId;
+use massa_models::prehash::PreHashMap;
+use massa_models::secure_share::Id;
+use massa_models::slot::Slot;
+use std::str::FromStr;
+
+const ADDRESSES: [&str; 5] = [
+    "AU12M3AQqs7JH7mSe1UZyEA5NQ7nGQHXaqqxe1TGEpkimcRhsQ4eF",
+    "AU12cMW9zRKFDS43Z2W88VCmdQFxmHjAo54XvuVV34UzJeXRLXW9M",
+    "AU12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x",
+    "AU12hgh5ULW9o8fJE9muLNXhQENaUUswQbxPyDSq8ridnDGu5gRiJ",
+    "AU12htxRWiEm8jDJpJptr6cwEhWNcCSFWstN1MLSa96DDkVM9Y42G",
+];
+
+/// Builds the same state changes with their entries inserted in the given order,
+/// in hash maps allocated with the given capacity
+fn state_changes(reverse: bool, capacity: usize) -> StateChanges {
+    let mut addresses: Vec<Address> = ADDRESSES
+        .iter()
+        .map(|address| Address::from_str(address).unwrap())
+        .collect();
+    let mut indexes: Vec<u64> = (0..200).collect();
+    if reverse {
+        addresses.reverse();
+        indexes.reverse();
+    }
+
+    let mut changes = StateChanges::default();
+    changes.ledger_changes.0 = PreHashMap::with_capacity_and_hasher(capacity, Default::default());
+    changes.pos_changes.roll_changes =
+        PreHashMap::with_capacity_and_hasher(capacity, Default::default());
+    changes.executed_ops_changes =
+        PreHashMap::with_capacity_and_hasher(capacity, Default::default());
+    for (index, address) in addresses.iter().enumerate() {
+        changes.ledger_changes.0.insert(
+            *address,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_raw(index as u64)),
+                ..Default::default()
+            }),
+        );
+        changes.pos_changes.roll_changes.insert(*address, 10);
+        changes.pos_changes.deferred_credits.insert(
+            Slot::new(5, 0),
+            *address,
+            Amount::from_raw(100),
+        );
+    }
+    for index in indexes {
+        changes.executed_ops_changes.insert(
+            OperationId::new(Hash::compute_from(&index.to_be_bytes())),
+            (index % 2 == 0, Slot::new(10, 0)),
+        );
+        changes
+            .executed_denunciations_changes
+            .insert(DenunciationIndex::Endorsement {
+                slot: Slot::new(index, 0),
+                index: 1,
+            });
+    }
+    changes
+}
+
+#[test]
+fn test_state_changes_hash_ignores_insertion_order() {
+    let changes = state_changes(false, 0);
+    let reversed = state_changes(true, 1_000);
+    assert_eq!(changes.compute_hash(), reversed.compute_hash());
+    assert_eq!(changes.compute_hash(), changes.clone().compute_hash());
+}
+
+#[test]
+fn test_state_changes_hash_depends_on_the_changes() {
+    let changes = state_changes(false, 0);
+    assert_ne!(
+        changes.compute_hash(),
+        StateChanges::default().compute_hash()
+    );
+
+    let mut other_roll_count = changes.clone();
+    let address = Address::from_str(ADDRESSES[0]).unwrap();
+    other_roll_count
+        .pos_changes
+        .roll_changes
+        .insert(address, 11);
+    assert_ne!(changes.compute_hash(), other_roll_count.compute_hash());
+
+    let mut other_denunciation = changes.clone();
+    other_denunciation
+        .executed_denunciations_changes
+        .insert(DenunciationIndex::BlockHeader {
+            slot: Slot::new(1, 1),
+        });
+    assert_ne!(changes.compute_hash(), other_denunciation.compute_hash());
+}
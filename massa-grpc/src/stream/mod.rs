@@ -1,5 +1,6 @@
 // Copyright (c) 2023 MASSA LABS <info@massa.net>
 
+/// subscribe new async message executions
 /// stream new blocks
 pub mod new_blocks;
 /// stream new blocks with operations content
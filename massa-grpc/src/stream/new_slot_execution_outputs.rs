@@ -53,10 +53,14 @@ pub(crate) async fn new_slot_execution_outputs(
                                 if !should_send(&filter, &massa_slot_execution_output) {
                                   continue;
                                 }
+                                // Canceled slots are filtered out above and have no execution output to send
+                                let Ok(output) = massa_slot_execution_output.try_into() else {
+                                    continue;
+                                };
                                 // Send the new slot execution output through the channel
                                 if let Err(e) = tx.send(Ok(grpc_api::NewSlotExecutionOutputsResponse {
                                         id: request_id.clone(),
-                                        output: Some(massa_slot_execution_output.into())
+                                        output: Some(output)
                                 })).await {
                                     error!("failed to send new slot execution output : {}", e);
                                     break;
@@ -131,7 +135,8 @@ fn should_send(
                 let id = grpc_model::ExecutionOutputStatus::Final as i32;
                 filter.status.contains(&id)
             }
+            SlotExecutionOutput::CanceledSlot(_) => false,
         },
-        None => true, // if user has no filter = All execution outputs status are sent
+        None => !matches!(exec_out_status, SlotExecutionOutput::CanceledSlot(_)), // if user has no filter = All execution outputs status are sent
     }
 }
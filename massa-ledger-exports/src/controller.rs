@@ -1,4 +1,4 @@
-use massa_models::{address::Address, amount::Amount, bytecode::Bytecode};
+use massa_models::{address::Address, amount::Amount, bytecode::Bytecode, slot::Slot};
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 
@@ -43,6 +43,32 @@ pub trait LedgerController: Send + Sync + Debug {
     /// A `BTreeSet` of the datastore keys
     fn get_datastore_keys(&self, addr: &Address) -> Option<BTreeSet<Vec<u8>>>;
 
+    /// Gets the balance of a ledger entry right after a past final slot, from the archive.
+    ///
+    /// # Returns
+    /// The balance, or None if the ledger entry was not found.
+    /// An error if the slot is not covered by the archive.
+    fn get_balance_at(&self, addr: &Address, slot: &Slot) -> Result<Option<Amount>, LedgerError>;
+
+    /// Gets a copy of the bytecode of a ledger entry right after a past final slot, from the archive.
+    fn get_bytecode_at(&self, addr: &Address, slot: &Slot)
+        -> Result<Option<Bytecode>, LedgerError>;
+
+    /// Gets a copy of the value of a datastore entry right after a past final slot, from the archive.
+    fn get_data_entry_at(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError>;
+
+    /// Get every key of the datastore of an address right after a past final slot, from the archive.
+    fn get_datastore_keys_at(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<BTreeSet<Vec<u8>>>, LedgerError>;
+
     /// Reset the ledger
     ///
     /// USED FOR BOOTSTRAP ONLY
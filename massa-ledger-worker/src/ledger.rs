@@ -11,6 +11,7 @@ use massa_models::{
     address::Address,
     amount::{Amount, AmountDeserializer},
     bytecode::{Bytecode, BytecodeDeserializer},
+    slot::Slot,
 };
 use massa_serialization::{DeserializeError, Deserializer};
 use parking_lot::RwLock;
@@ -147,6 +148,64 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_datastore_keys(addr)
     }
 
+    /// Gets the balance of a ledger entry right after a past final slot, from the archive.
+    ///
+    /// # Returns
+    /// The balance, or None if the ledger entry was not found.
+    /// An error if the slot is not covered by the archive.
+    fn get_balance_at(&self, addr: &Address, slot: &Slot) -> Result<Option<Amount>, LedgerError> {
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        Ok(self
+            .sorted_ledger
+            .get_sub_entry_at(addr, LedgerSubEntry::Balance, slot)?
+            .map(|bytes| {
+                amount_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .expect("critical: invalid balance format")
+                    .1
+            }))
+    }
+
+    /// Gets a copy of the bytecode of a ledger entry right after a past final slot, from the archive.
+    fn get_bytecode_at(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<Bytecode>, LedgerError> {
+        let bytecode_deserializer =
+            BytecodeDeserializer::new(self.config.max_datastore_value_length);
+        Ok(self
+            .sorted_ledger
+            .get_sub_entry_at(addr, LedgerSubEntry::Bytecode, slot)?
+            .map(|bytes| {
+                bytecode_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .expect("critical: invalid bytecode format")
+                    .1
+            }))
+    }
+
+    /// Gets a copy of the value of a datastore entry right after a past final slot, from the archive.
+    fn get_data_entry_at(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        self.sorted_ledger
+            .get_sub_entry_at(addr, LedgerSubEntry::Datastore(key.to_owned()), slot)
+    }
+
+    /// Get every key of the datastore of an address right after a past final slot, from the archive.
+    fn get_datastore_keys_at(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<BTreeSet<Vec<u8>>>, LedgerError> {
+        self.sorted_ledger.get_datastore_keys_at(addr, slot)
+    }
+
     /// Reset the disk ledger.
     ///
     /// USED FOR BOOTSTRAP ONLY
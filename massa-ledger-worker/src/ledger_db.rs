@@ -195,6 +195,62 @@ impl LedgerDB {
         Some(iter.collect())
     }
 
+    /// Get the given sub-entry of a given address right after the given final slot,
+    /// read from the archive of the final state.
+    pub fn get_sub_entry_at(
+        &self,
+        addr: &Address,
+        ty: LedgerSubEntry,
+        slot: &Slot,
+    ) -> Result<Option<Vec<u8>>, LedgerError> {
+        let key = ty.derive_key(addr);
+        let mut serialized_key = Vec::new();
+        self.key_serializer_db
+            .serialize(&key, &mut serialized_key)
+            .expect(KEY_SER_ERROR);
+        self.db
+            .read()
+            .get_value_at(&serialized_key, slot)
+            .map_err(|e| LedgerError::ArchiveError(e.to_string()))
+    }
+
+    /// Get every key of the datastore for a given address right after the given final slot,
+    /// read from the archive of the final state.
+    ///
+    /// # Returns
+    /// A `BTreeSet` of the datastore keys, or None if the datastore was empty
+    pub fn get_datastore_keys_at(
+        &self,
+        addr: &Address,
+        slot: &Slot,
+    ) -> Result<Option<BTreeSet<Vec<u8>>>, LedgerError> {
+        let serialized_keys = self
+            .db
+            .read()
+            .get_keys_with_prefix_at(&datastore_prefix_from_address(addr), slot)
+            .map_err(|e| LedgerError::ArchiveError(e.to_string()))?;
+        if serialized_keys.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            serialized_keys
+                .iter()
+                .map(|key| {
+                    let (_rest, key) = self
+                        .key_deserializer_db
+                        .deserialize::<DeserializeError>(key)
+                        .unwrap();
+                    match key.key_type {
+                        KeyType::DATASTORE(datastore_vec) => datastore_vec,
+                        _ => {
+                            vec![]
+                        }
+                    }
+                })
+                .collect(),
+        ))
+    }
+
     pub fn reset(&self, only_use_xor: bool) {
         self.db
             .write()
@@ -534,6 +590,8 @@ mod tests {
             max_history_length: 10,
             max_new_elements: 100,
             thread_count: 32,
+            enable_archive: false,
+            max_archive_periods: 1000,
         };
 
         let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
@@ -602,6 +660,259 @@ mod tests {
         assert!(ledger_db.get_entire_datastore(&addr).is_empty());
     }
 
+    /// Reads of `LedgerDB` entries at past slots, from the archive
+    #[test]
+    fn test_ledger_db_archive() {
+        use massa_db::MassaDBConfig;
+        use massa_ledger_exports::SetOrDelete;
+
+        let temp_dir = TempDir::new().unwrap();
+        let db_config = MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 32,
+            enable_archive: true,
+            max_archive_periods: 1000,
+        };
+        let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
+        let ledger_db = LedgerDB::new(db, 32, 255, 1000);
+        let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        let balance_at = |slot: Slot| {
+            ledger_db
+                .get_sub_entry_at(&addr, LedgerSubEntry::Balance, &slot)
+                .unwrap()
+                .map(|bytes| {
+                    amount_deserializer
+                        .deserialize::<DeserializeError>(&bytes)
+                        .unwrap()
+                        .1
+                })
+        };
+
+        // slot (0, 1): create the entry
+        let mut data = BTreeMap::new();
+        data.insert(b"1".to_vec(), b"a".to_vec());
+        data.insert(b"2".to_vec(), b"b".to_vec());
+        let mut batch = DBBatch::new();
+        ledger_db.put_entry(
+            &addr,
+            LedgerEntry {
+                balance: Amount::from_str("42").unwrap(),
+                datastore: data,
+                ..Default::default()
+            },
+            &mut batch,
+        );
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 1)), false);
+
+        // slot (0, 2): update the balance and the datastore
+        let mut datastore_updates = BTreeMap::new();
+        datastore_updates.insert(b"2".to_vec(), SetOrDelete::Delete);
+        datastore_updates.insert(b"3".to_vec(), SetOrDelete::Set(b"c".to_vec()));
+        let mut batch = DBBatch::new();
+        ledger_db.update_entry(
+            &addr,
+            LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_str("21").unwrap()),
+                bytecode: SetOrKeep::Keep,
+                datastore: datastore_updates,
+            },
+            &mut batch,
+        );
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 2)), false);
+
+        // slot (0, 3): delete the entry
+        let mut batch = DBBatch::new();
+        ledger_db.delete_entry(&addr, &mut batch);
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), Some(Slot::new(0, 3)), false);
+
+        // balances
+        assert_eq!(balance_at(Slot::new(0, 0)), None);
+        assert_eq!(
+            balance_at(Slot::new(0, 1)),
+            Some(Amount::from_str("42").unwrap())
+        );
+        assert_eq!(
+            balance_at(Slot::new(0, 2)),
+            Some(Amount::from_str("21").unwrap())
+        );
+        assert_eq!(balance_at(Slot::new(0, 3)), None);
+
+        // datastore keys and values
+        assert_eq!(
+            ledger_db
+                .get_datastore_keys_at(&addr, &Slot::new(0, 1))
+                .unwrap(),
+            Some(BTreeSet::from([b"1".to_vec(), b"2".to_vec()]))
+        );
+        assert_eq!(
+            ledger_db
+                .get_datastore_keys_at(&addr, &Slot::new(0, 2))
+                .unwrap(),
+            Some(BTreeSet::from([b"1".to_vec(), b"3".to_vec()]))
+        );
+        assert_eq!(
+            ledger_db
+                .get_datastore_keys_at(&addr, &Slot::new(0, 3))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            ledger_db
+                .get_sub_entry_at(
+                    &addr,
+                    LedgerSubEntry::Datastore(b"2".to_vec()),
+                    &Slot::new(0, 1)
+                )
+                .unwrap(),
+            Some(b"b".to_vec())
+        );
+        assert_eq!(
+            ledger_db
+                .get_sub_entry_at(
+                    &addr,
+                    LedgerSubEntry::Datastore(b"2".to_vec()),
+                    &Slot::new(0, 2)
+                )
+                .unwrap(),
+            None
+        );
+
+        // slots after the last final slot are not archived yet
+        assert!(ledger_db
+            .get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(0, 4))
+            .is_err());
+    }
+
+    /// Reads of archived keys that prefix each other, and pruning of the oldest archived slots
+    #[test]
+    fn test_ledger_db_archive_retention() {
+        use massa_db::MassaDBConfig;
+        use massa_ledger_exports::SetOrDelete;
+
+        let temp_dir = TempDir::new().unwrap();
+        let db_config = MassaDBConfig {
+            path: temp_dir.path().to_path_buf(),
+            max_history_length: 10,
+            max_new_elements: 100,
+            thread_count: 32,
+            enable_archive: true,
+            max_archive_periods: 2,
+        };
+        let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
+        let ledger_db = LedgerDB::new(db, 32, 255, 1000);
+        let addr = Address::from_public_key(&KeyPair::generate(0).unwrap().get_public_key());
+        let set_balance = |balance: &str, slot: Slot| {
+            let mut batch = DBBatch::new();
+            ledger_db.update_entry(
+                &addr,
+                LedgerEntryUpdate {
+                    balance: SetOrKeep::Set(Amount::from_str(balance).unwrap()),
+                    bytecode: SetOrKeep::Keep,
+                    datastore: BTreeMap::new(),
+                },
+                &mut batch,
+            );
+            ledger_db
+                .db
+                .write()
+                .write_batch(batch, Default::default(), Some(slot), false);
+        };
+        let datastore_at = |key: &[u8], slot: Slot| {
+            ledger_db
+                .get_sub_entry_at(&addr, LedgerSubEntry::Datastore(key.to_vec()), &slot)
+                .unwrap()
+        };
+
+        // slot (1, 0): create the entry with a datastore key prefixing another one
+        let mut batch = DBBatch::new();
+        ledger_db.put_entry(
+            &addr,
+            LedgerEntry {
+                balance: Amount::from_str("1").unwrap(),
+                datastore: BTreeMap::from([
+                    (b"1".to_vec(), b"a".to_vec()),
+                    (b"12".to_vec(), b"b".to_vec()),
+                ]),
+                ..Default::default()
+            },
+            &mut batch,
+        );
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), Some(Slot::new(1, 0)), false);
+
+        // slot (2, 0): update the longer key only
+        let mut batch = DBBatch::new();
+        ledger_db.update_entry(
+            &addr,
+            LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_str("2").unwrap()),
+                bytecode: SetOrKeep::Keep,
+                datastore: BTreeMap::from([(b"12".to_vec(), SetOrDelete::Set(b"c".to_vec()))]),
+            },
+            &mut batch,
+        );
+        ledger_db
+            .db
+            .write()
+            .write_batch(batch, Default::default(), Some(Slot::new(2, 0)), false);
+        set_balance("3", Slot::new(3, 0));
+
+        // the changes of the longer key are not read as changes of the shorter one
+        assert_eq!(datastore_at(b"1", Slot::new(1, 0)), Some(b"a".to_vec()));
+        assert_eq!(datastore_at(b"12", Slot::new(1, 0)), Some(b"b".to_vec()));
+        assert_eq!(datastore_at(b"12", Slot::new(2, 0)), Some(b"c".to_vec()));
+
+        // slot (4, 0): the slots more than 2 periods old are pruned
+        set_balance("4", Slot::new(4, 0));
+        assert!(ledger_db
+            .get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(1, 0))
+            .is_err());
+        for (period, balance) in [(2, "2"), (3, "3"), (4, "4")] {
+            assert_eq!(
+                ledger_db
+                    .get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(period, 0))
+                    .unwrap()
+                    .map(|bytes| {
+                        AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX))
+                            .deserialize::<DeserializeError>(&bytes)
+                            .unwrap()
+                            .1
+                    }),
+                Some(Amount::from_str(balance).unwrap())
+            );
+        }
+        assert_eq!(datastore_at(b"12", Slot::new(2, 0)), Some(b"c".to_vec()));
+
+        // slot (5, 0): the next pruning starts from the previous cutoff
+        set_balance("5", Slot::new(5, 0));
+        assert!(ledger_db
+            .get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(2, 0))
+            .is_err());
+        assert_eq!(datastore_at(b"12", Slot::new(3, 0)), Some(b"c".to_vec()));
+
+        // clearing the archive restarts it from the last final slot
+        ledger_db.db.read().clear_archive();
+        assert!(ledger_db
+            .get_sub_entry_at(&addr, LedgerSubEntry::Balance, &Slot::new(4, 0))
+            .is_err());
+        assert_eq!(datastore_at(b"1", Slot::new(5, 0)), Some(b"a".to_vec()));
+    }
+
     #[test]
     fn test_end_prefix() {
         assert_eq!(end_prefix(&[5, 6, 7]), Some(vec![5, 6, 8]));
@@ -0,0 +1,39 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+
+/// Light client configuration
+#[derive(Debug, Clone)]
+pub struct LightClientConfig {
+    /// public JSON-RPC urls of the full nodes queried for draw inputs and state proofs
+    pub full_nodes: Vec<String>,
+    /// minimum number of full nodes that must return the same draw inputs or prove the same final state values
+    pub min_agreeing_nodes: usize,
+    /// timeout of the requests sent to the full nodes
+    pub request_timeout: MassaTime,
+    /// maximum size in bytes of a request sent to the full nodes
+    pub max_request_body_size: u32,
+    /// maximum length of a datastore key
+    pub max_datastore_key_length: u8,
+    /// maximum number of block headers kept in memory
+    pub max_headers: usize,
+    /// interval at which the draw inputs of upcoming cycles are fetched
+    pub draw_refresh_interval: MassaTime,
+    /// number of cycles for which active rolls are kept in memory
+    pub active_rolls_cycle_count: usize,
+    /// whether to broadcast the verified block headers
+    pub broadcast_enabled: bool,
+    /// thread count
+    pub thread_count: u8,
+    /// periods per cycle
+    pub periods_per_cycle: u64,
+    /// `t0`
+    pub t0: MassaTime,
+    /// genesis timestamp
+    pub genesis_timestamp: MassaTime,
+    /// genesis key, used to compute the ids of the genesis blocks
+    pub genesis_key: KeyPair,
+    /// period of the genesis blocks
+    pub last_start_period: u64,
+}
@@ -0,0 +1,307 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::LightClientConfig;
+use massa_consensus_exports::{
+    block_graph_export::BlockGraphExport, bootstrapable_graph::BootstrapableGraph,
+    error::ConsensusError, ConsensusController, ConsensusManager,
+};
+use massa_hash::Hash;
+use massa_models::{
+    block::BlockGraphStatus,
+    block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
+    block_id::BlockId,
+    clique::Clique,
+    prehash::{PreHashMap, PreHashSet},
+    secure_share::{SecureShare, SecureShareContent},
+    slot::Slot,
+    stats::ConsensusStats,
+    streaming_step::StreamingStep,
+};
+use massa_pos_exports::SelectorController;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{debug, trace, warn};
+
+/// Block headers that passed the draw checks
+#[derive(Default)]
+struct HeaderStore {
+    /// verified headers
+    headers: PreHashMap<BlockId, SecuredHeader>,
+    /// verified headers by slot, used for pruning and slot lookups
+    by_slot: BTreeMap<Slot, PreHashSet<BlockId>>,
+    /// number of headers rejected because of a draw mismatch
+    rejected_count: u64,
+}
+
+/// Header-only consensus controller of a light node.
+///
+/// Headers received from protocol are kept if their producer and endorsers match the selector draws.
+/// No block is ever added to the protocol wishlist, so block contents are never downloaded.
+#[derive(Clone)]
+pub struct LightConsensusController {
+    config: LightClientConfig,
+    selector_controller: Box<dyn SelectorController>,
+    block_header_sender: broadcast::Sender<SecuredHeader>,
+    store: Arc<RwLock<HeaderStore>>,
+    /// ids of the genesis blocks, one per thread
+    genesis_block_ids: Arc<Vec<BlockId>>,
+}
+
+impl LightConsensusController {
+    /// Creates a controller with an empty header store
+    pub(crate) fn new(
+        config: LightClientConfig,
+        selector_controller: Box<dyn SelectorController>,
+        block_header_sender: broadcast::Sender<SecuredHeader>,
+    ) -> Self {
+        let genesis_block_ids = Arc::new(genesis_block_ids(&config));
+        LightConsensusController {
+            config,
+            selector_controller,
+            block_header_sender,
+            store: Default::default(),
+            genesis_block_ids,
+        }
+    }
+
+    /// Checks that the producer and the endorsers of a header were drawn for its slot.
+    ///
+    /// Signatures, endorsement indexes and endorsed slots are already checked by protocol.
+    ///
+    /// # Returns
+    /// * `Ok(true)` if the header matches the draws
+    /// * `Ok(false)` if it does not
+    /// * an error if the draws of the slot are not known yet
+    fn check_draws(&self, header: &SecuredHeader) -> Result<bool, ConsensusError> {
+        let selection = self
+            .selector_controller
+            .get_selection(header.content.slot)
+            .map_err(|err| ConsensusError::PosCycleUnavailable(err.to_string()))?;
+        if header.content_creator_address != selection.producer {
+            return Ok(false);
+        }
+        Ok(header.content.endorsements.iter().all(|endorsement| {
+            selection
+                .endorsements
+                .get(endorsement.content.index as usize)
+                .map_or(false, |draw| *draw == endorsement.content_creator_address)
+        }))
+    }
+
+    /// Latest verified header in each thread, as `(block_id, period)`
+    fn latest_headers(&self) -> Vec<(BlockId, u64)> {
+        let store = self.store.read();
+        (0..self.config.thread_count)
+            .filter_map(|thread| {
+                store
+                    .by_slot
+                    .iter()
+                    .rev()
+                    .find(|(slot, _)| slot.thread == thread)
+                    .and_then(|(slot, ids)| ids.iter().next().map(|id| (*id, slot.period)))
+            })
+            .collect()
+    }
+}
+
+impl ConsensusController for LightConsensusController {
+    /// Only the latest verified header of each thread is exported, as best parent.
+    fn get_block_graph_status(
+        &self,
+        _start_slot: Option<Slot>,
+        _end_slot: Option<Slot>,
+    ) -> Result<BlockGraphExport, ConsensusError> {
+        Ok(BlockGraphExport {
+            genesis_blocks: Vec::new(),
+            active_blocks: Default::default(),
+            discarded_blocks: Default::default(),
+            best_parents: self.latest_headers(),
+            latest_final_blocks_periods: Vec::new(),
+            gi_head: Default::default(),
+            max_cliques: Vec::new(),
+        })
+    }
+
+    /// Verified headers are reported as `Incoming`: their blocks are never processed.
+    fn get_block_statuses(&self, ids: &[BlockId]) -> Vec<BlockGraphStatus> {
+        let store = self.store.read();
+        ids.iter()
+            .map(|id| {
+                if store.headers.contains_key(id) {
+                    BlockGraphStatus::Incoming
+                } else {
+                    BlockGraphStatus::NotFound
+                }
+            })
+            .collect()
+    }
+
+    fn get_cliques(&self) -> Vec<Clique> {
+        Vec::new()
+    }
+
+    fn get_bootstrap_part(
+        &self,
+        _cursor: StreamingStep<PreHashSet<BlockId>>,
+        _execution_cursor: StreamingStep<Slot>,
+    ) -> Result<
+        (
+            BootstrapableGraph,
+            PreHashSet<BlockId>,
+            StreamingStep<PreHashSet<BlockId>>,
+        ),
+        ConsensusError,
+    > {
+        Err(ConsensusError::MissingBlock(
+            "a light node does not hold the block graph".to_string(),
+        ))
+    }
+
+    /// The stale block count is the number of headers rejected because of a draw mismatch.
+    fn get_stats(&self) -> Result<ConsensusStats, ConsensusError> {
+        let now = MassaTime::now()?;
+        Ok(ConsensusStats {
+            start_timespan: now,
+            end_timespan: now,
+            final_block_count: 0,
+            stale_block_count: self.store.read().rejected_count,
+            clique_count: 0,
+        })
+    }
+
+    /// Returns the latest verified header of each thread for which one was received.
+    fn get_best_parents(&self) -> Vec<(BlockId, u64)> {
+        self.latest_headers()
+    }
+
+    fn get_blockclique_block_at_slot(&self, slot: Slot) -> Option<BlockId> {
+        self.store
+            .read()
+            .by_slot
+            .get(&slot)
+            .and_then(|ids| ids.iter().next().copied())
+    }
+
+    /// Falls back to the genesis block of the thread when no header was verified before the slot.
+    fn get_latest_blockclique_block_at_slot(&self, slot: Slot) -> BlockId {
+        self.store
+            .read()
+            .by_slot
+            .range(..slot)
+            .rev()
+            .find(|(header_slot, _)| header_slot.thread == slot.thread)
+            .and_then(|(_, ids)| ids.iter().next().copied())
+            .unwrap_or_else(|| self.genesis_block_ids[slot.thread as usize])
+    }
+
+    fn register_block(
+        &self,
+        block_id: BlockId,
+        _slot: Slot,
+        _block_storage: Storage,
+        _created: bool,
+    ) {
+        debug!("light client: ignoring full block {}", block_id);
+    }
+
+    fn register_block_header(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
+        if self.store.read().headers.contains_key(&block_id) {
+            return;
+        }
+        match self.check_draws(&header) {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(
+                    "light client: header {} does not match the draws of slot {}",
+                    block_id, header.content.slot
+                );
+                self.store.write().rejected_count += 1;
+                return;
+            }
+            Err(err) => {
+                debug!(
+                    "light client: cannot check header {} yet: {}",
+                    block_id, err
+                );
+                return;
+            }
+        }
+
+        if self.config.broadcast_enabled {
+            if let Err(err) = self.block_header_sender.send(header.clone()) {
+                trace!(
+                    "error, failed to broadcast block header with block id {}: {}",
+                    block_id,
+                    err
+                );
+            }
+        }
+
+        let mut store = self.store.write();
+        store
+            .by_slot
+            .entry(header.content.slot)
+            .or_default()
+            .insert(block_id);
+        store.headers.insert(block_id, header);
+        // prune the oldest headers
+        while store.headers.len() > self.config.max_headers {
+            let Some((_, ids)) = store.by_slot.pop_first() else {
+                break;
+            };
+            for id in ids {
+                store.headers.remove(&id);
+            }
+        }
+    }
+
+    fn mark_invalid_block(&self, block_id: BlockId, header: SecureShare<BlockHeader, BlockId>) {
+        let mut store = self.store.write();
+        if store.headers.remove(&block_id).is_some() {
+            if let Some(ids) = store.by_slot.get_mut(&header.content.slot) {
+                ids.remove(&block_id);
+                if ids.is_empty() {
+                    store.by_slot.remove(&header.content.slot);
+                }
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn ConsensusController> {
+        Box::new(self.clone())
+    }
+}
+
+/// Ids of the genesis blocks, computed from the same headers as the ones created by the consensus worker
+fn genesis_block_ids(config: &LightClientConfig) -> Vec<BlockId> {
+    (0..config.thread_count)
+        .map(|thread| {
+            BlockHeader::new_verifiable(
+                BlockHeader {
+                    current_version: 0,
+                    announced_version: 0,
+                    slot: Slot::new(config.last_start_period, thread),
+                    parents: Vec::new(),
+                    operation_merkle_root: Hash::compute_from(&Vec::new()),
+                    endorsements: Vec::new(),
+                    denunciations: Vec::new(),
+                },
+                BlockHeaderSerializer::new(),
+                &config.genesis_key,
+            )
+            .expect("failed to compute the genesis block ids")
+            .id
+        })
+        .collect()
+}
+
+/// Nothing to stop: light headers are processed on the protocol threads
+pub struct LightConsensusManager;
+
+impl ConsensusManager for LightConsensusManager {
+    fn stop(&mut self) {}
+}
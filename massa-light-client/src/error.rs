@@ -0,0 +1,20 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use displaydoc::Display;
+use thiserror::Error;
+
+/// Light client errors
+#[non_exhaustive]
+#[derive(Display, Error, Debug, Clone)]
+pub enum LightClientError {
+    /// full node request error: {0}
+    RequestError(String),
+    /// full nodes disagree: {0}
+    NoAgreement(String),
+    /// channel error: {0}
+    ChannelError(String),
+    /// selector error: {0}
+    SelectorError(String),
+    /// time error: {0}
+    TimeError(String),
+}
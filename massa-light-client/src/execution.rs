@@ -0,0 +1,361 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::{
+    worker::{LightClientCommand, SharedActiveRolls},
+    LightClientConfig, LightClientError,
+};
+use crossbeam_channel::{bounded, RecvTimeoutError, Sender};
+use massa_api_exports::state_proof::{StateProofInput, StateProofOutput};
+use massa_db::StateProof;
+use massa_execution_exports::{
+    ExecutionAddressInfo, ExecutionController, ExecutionError, FinalStateProofs, OperationReceipt,
+    PendingAsyncMessage, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    SlotDeterminismCheckOutput,
+};
+use massa_hash::Hash;
+use massa_ledger_exports::{KeyDeserializer, KeyType};
+use massa_models::{
+    address::Address,
+    amount::{Amount, AmountDeserializer},
+    block_id::BlockId,
+    denunciation::DenunciationIndex,
+    execution::{
+        AddressHistoryCursor, AddressHistoryEntry, AsyncMessageFilter, EventCursor, EventFilter,
+    },
+    operation::OperationId,
+    output_event::SCOutputEvent,
+    prehash::PreHashMap,
+    slot::Slot,
+    stats::ExecutionStats,
+};
+use massa_serialization::{DeserializeError, Deserializer};
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Bound::Included;
+use tracing::warn;
+
+/// Execution controller of a light node.
+///
+/// Nothing is executed: final state queries are answered with entries
+/// fetched from the full nodes and checked against their proofs.
+/// As there is no speculative state, candidate values are the final ones.
+#[derive(Clone)]
+pub struct LightExecutionController {
+    config: LightClientConfig,
+    command_tx: Sender<LightClientCommand>,
+    active_rolls: SharedActiveRolls,
+}
+
+impl LightExecutionController {
+    pub(crate) fn new(
+        config: LightClientConfig,
+        command_tx: Sender<LightClientCommand>,
+        active_rolls: SharedActiveRolls,
+    ) -> Self {
+        LightExecutionController {
+            config,
+            command_tx,
+            active_rolls,
+        }
+    }
+
+    /// Fetches proven final state entries through the light client thread.
+    ///
+    /// The full nodes are queried concurrently, each request being bounded by `request_timeout`,
+    /// so the wait for the answer is bounded by twice that timeout to leave room for scheduling.
+    fn get_state_proof(
+        &self,
+        inputs: Vec<StateProofInput>,
+    ) -> Result<StateProofOutput, LightClientError> {
+        let (response_tx, response_rx) = bounded(1);
+        self.command_tx
+            .send(LightClientCommand::GetStateProof {
+                inputs,
+                response_tx,
+            })
+            .map_err(|_| {
+                LightClientError::ChannelError("light client thread is stopped".to_string())
+            })?;
+        let timeout = self.config.request_timeout.to_duration().saturating_mul(2);
+        match response_rx.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => Err(LightClientError::RequestError(
+                "timeout while waiting for the state proofs of the full nodes".to_string(),
+            )),
+            Err(RecvTimeoutError::Disconnected) => Err(LightClientError::ChannelError(
+                "light client thread is stopped".to_string(),
+            )),
+        }
+    }
+
+    /// Proven values of a batch of entries, `None` for absent entries or if they could not be proven
+    fn get_proven_values(&self, inputs: Vec<StateProofInput>) -> Vec<Option<Vec<u8>>> {
+        let count = inputs.len();
+        if count == 0 {
+            return Vec::new();
+        }
+        match self.get_state_proof(inputs) {
+            Ok(output) => output
+                .entries
+                .into_iter()
+                .map(|entry| entry.value)
+                .collect(),
+            Err(err) => {
+                warn!(
+                    "light client: could not get proven final state entries: {}",
+                    err
+                );
+                vec![None; count]
+            }
+        }
+    }
+
+    /// Proven final balances of a batch of addresses
+    fn get_balances(&self, addresses: &[Address]) -> Vec<Option<Amount>> {
+        let amount_deserializer =
+            AmountDeserializer::new(Included(Amount::MIN), Included(Amount::MAX));
+        self.get_proven_values(
+            addresses
+                .iter()
+                .map(|address| StateProofInput::Balance(*address))
+                .collect(),
+        )
+        .into_iter()
+        .map(|value| {
+            value.and_then(|bytes| {
+                amount_deserializer
+                    .deserialize::<DeserializeError>(&bytes)
+                    .ok()
+                    .map(|(_, amount)| amount)
+            })
+        })
+        .collect()
+    }
+}
+
+impl ExecutionController for LightExecutionController {
+    /// No block is executed by a light node
+    fn update_blockclique_status(
+        &self,
+        _finalized_blocks: HashMap<Slot, BlockId>,
+        _new_blockclique: Option<HashMap<Slot, BlockId>>,
+        _block_storage: PreHashMap<BlockId, Storage>,
+    ) {
+    }
+
+    fn get_filtered_sc_output_event(&self, _filter: EventFilter) -> Vec<SCOutputEvent> {
+        Vec::new()
+    }
+
+    fn get_filtered_sc_output_event_page(
+        &self,
+        _filter: EventFilter,
+        _cursor: Option<EventCursor>,
+        _limit: usize,
+    ) -> Vec<SCOutputEvent> {
+        Vec::new()
+    }
+
+    fn get_final_and_candidate_balance(
+        &self,
+        addresses: &[Address],
+    ) -> Vec<(Option<Amount>, Option<Amount>)> {
+        self.get_balances(addresses)
+            .into_iter()
+            .map(|balance| (balance, balance))
+            .collect()
+    }
+
+    fn get_ops_exec_status(&self, batch: &[OperationId]) -> Vec<(Option<bool>, Option<bool>)> {
+        vec![(None, None); batch.len()]
+    }
+
+    /// Operations are not executed by a light node
+    fn get_operation_receipts(&self, batch: &[OperationId]) -> Vec<Option<OperationReceipt>> {
+        vec![None; batch.len()]
+    }
+
+    /// The asynchronous pool is not held by a light node
+    fn get_async_messages(&self, _filter: &AsyncMessageFilter) -> Vec<PendingAsyncMessage> {
+        Vec::new()
+    }
+
+    /// The address history is not indexed by a light node
+    fn get_address_history(
+        &self,
+        _address: &Address,
+        _cursor: Option<AddressHistoryCursor>,
+        _limit: usize,
+    ) -> Option<Vec<AddressHistoryEntry>> {
+        None
+    }
+
+    /// A light node does not hold the final state, let alone its archive
+    fn get_archived_balances(
+        &self,
+        _addresses: &[Address],
+        _slot: Slot,
+    ) -> Result<Vec<Option<Amount>>, ExecutionError> {
+        Err(ExecutionError::ArchiveError(
+            "a light node does not archive the final state".to_string(),
+        ))
+    }
+
+    fn get_archived_datastore_keys(
+        &self,
+        _addresses: &[Address],
+        _slot: Slot,
+    ) -> Result<Vec<Option<BTreeSet<Vec<u8>>>>, ExecutionError> {
+        Err(ExecutionError::ArchiveError(
+            "a light node does not archive the final state".to_string(),
+        ))
+    }
+
+    fn get_archived_data_entries(
+        &self,
+        _input: Vec<(Address, Vec<u8>)>,
+        _slot: Slot,
+    ) -> Result<Vec<Option<Vec<u8>>>, ExecutionError> {
+        Err(ExecutionError::ArchiveError(
+            "a light node does not archive the final state".to_string(),
+        ))
+    }
+
+    fn get_final_and_active_data_entry(
+        &self,
+        input: Vec<(Address, Vec<u8>)>,
+    ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+        self.get_proven_values(
+            input
+                .into_iter()
+                .map(|(address, key)| StateProofInput::Datastore { address, key })
+                .collect(),
+        )
+        .into_iter()
+        .map(|value| (value.clone(), value))
+        .collect()
+    }
+
+    /// Proofs are forwarded from the full nodes, once they agreed on the final state hash.
+    fn get_final_state_proofs(&self, keys: &[Vec<u8>]) -> Result<FinalStateProofs, ExecutionError> {
+        let key_deserializer = KeyDeserializer::new(self.config.max_datastore_key_length, false);
+        let inputs = keys
+            .iter()
+            .map(|key| {
+                let (_, key) = key_deserializer
+                    .deserialize::<DeserializeError>(key)
+                    .map_err(|err| ExecutionError::StateProofError(err.to_string()))?;
+                Ok(match key.key_type {
+                    KeyType::BALANCE => StateProofInput::Balance(key.address),
+                    KeyType::BYTECODE => StateProofInput::Bytecode(key.address),
+                    KeyType::DATASTORE(datastore_key) => StateProofInput::Datastore {
+                        address: key.address,
+                        key: datastore_key,
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, ExecutionError>>()?;
+        let output = self
+            .get_state_proof(inputs)
+            .map_err(|err| ExecutionError::StateProofError(err.to_string()))?;
+        Ok(FinalStateProofs {
+            slot: output.slot,
+            state_hash: output.state_hash,
+            entries: output
+                .entries
+                .into_iter()
+                .map(|entry| {
+                    (
+                        entry.value,
+                        StateProof {
+                            side_nodes: entry.side_nodes,
+                            non_membership_leaf_data: entry.non_membership_leaf_data,
+                        },
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Only the cycles fed to the selector are known
+    fn get_cycle_active_rolls(&self, cycle: u64) -> BTreeMap<Address, u64> {
+        self.active_rolls
+            .read()
+            .get(&cycle)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn get_selector_draw_inputs(
+        &self,
+        _cycle: u64,
+    ) -> Result<(BTreeMap<Address, u64>, Hash), ExecutionError> {
+        Err(ExecutionError::DrawInputsError(
+            "a light node does not hold the PoS state".to_string(),
+        ))
+    }
+
+    fn execute_readonly_request(
+        &self,
+        _req: ReadOnlyExecutionRequest,
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
+        Err(ExecutionError::RuntimeError(
+            "read-only executions are not available on a light node".to_string(),
+        ))
+    }
+
+    fn check_slot_determinism(
+        &self,
+        _slot: Slot,
+        _trace: bool,
+        _timeout: MassaTime,
+    ) -> Result<SlotDeterminismCheckOutput, ExecutionError> {
+        Err(ExecutionError::RuntimeError(
+            "a light node does not execute slots".to_string(),
+        ))
+    }
+
+    fn is_denunciation_executed(&self, _denunciation_index: &DenunciationIndex) -> bool {
+        false
+    }
+
+    /// Only balances are filled, from proven final state entries
+    fn get_addresses_infos(&self, addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
+        self.get_balances(addresses)
+            .into_iter()
+            .map(|balance| {
+                let balance = balance.unwrap_or_default();
+                ExecutionAddressInfo {
+                    candidate_balance: balance,
+                    final_balance: balance,
+                    final_roll_count: 0,
+                    final_datastore_keys: Default::default(),
+                    candidate_roll_count: 0,
+                    candidate_datastore_keys: Default::default(),
+                    future_deferred_credits: Default::default(),
+                    cycle_infos: Vec::new(),
+                }
+            })
+            .collect()
+    }
+
+    fn get_stats(&self) -> ExecutionStats {
+        let now = MassaTime::now().unwrap_or_else(|_| MassaTime::from_millis(0));
+        ExecutionStats {
+            time_window_start: now,
+            time_window_end: now,
+            final_block_count: 0,
+            final_executed_operations_count: 0,
+            active_cursor: Slot::new(0, 0),
+            gas_used_by_operation_type: Default::default(),
+            top_gas_functions: Vec::new(),
+            slowest_slots: Vec::new(),
+            module_cache: Default::default(),
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn ExecutionController> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::{LightClientConfig, LightClientError};
+use futures::future::join_all;
+use massa_api_exports::{
+    rolls::DrawInputs,
+    state_proof::{StateProofInput, StateProofOutput},
+};
+use massa_sdk::{ClientConfig, HttpConfig, RpcClient};
+use tracing::{debug, warn};
+
+/// Maximum number of requests in flight to a single full node
+const MAX_CONCURRENT_REQUESTS: usize = 10;
+
+/// Public JSON-RPC clients of the full nodes the light client relies on
+pub(crate) struct FullNodes {
+    /// (url, client) of each full node
+    clients: Vec<(String, RpcClient)>,
+    /// minimum number of full nodes that must agree on an answer
+    min_agreeing_nodes: usize,
+}
+
+impl FullNodes {
+    /// Creates a client for each full node of the configuration
+    pub(crate) async fn new(config: &LightClientConfig) -> Self {
+        let http_config = HttpConfig {
+            client_config: ClientConfig {
+                max_request_body_size: config.max_request_body_size,
+                request_timeout: config.request_timeout,
+                max_concurrent_requests: MAX_CONCURRENT_REQUESTS,
+                certificate_store: "Native".to_string(),
+                id_kind: "Number".to_string(),
+                max_log_length: 1024,
+                headers: Vec::new(),
+            },
+            enabled: true,
+        };
+        let mut clients = Vec::with_capacity(config.full_nodes.len());
+        for url in config.full_nodes.iter() {
+            clients.push((url.clone(), RpcClient::from_url(url, &http_config).await));
+        }
+        FullNodes {
+            clients,
+            min_agreeing_nodes: config.min_agreeing_nodes,
+        }
+    }
+
+    /// Fetches the draw inputs of a cycle from every full node at once.
+    ///
+    /// Returns them only if enough full nodes returned the same ones.
+    pub(crate) async fn get_draw_inputs(&self, cycle: u64) -> Result<DrawInputs, LightClientError> {
+        let answers = join_all(self.clients.iter().map(|(url, client)| async move {
+            match client.get_draw_inputs(vec![cycle]).await {
+                Ok(mut draw_inputs) if draw_inputs.len() == 1 && draw_inputs[0].cycle == cycle => {
+                    draw_inputs.pop()
+                }
+                Ok(_) => {
+                    warn!(
+                        "full node {} returned unexpected draw inputs for cycle {}",
+                        url, cycle
+                    );
+                    None
+                }
+                Err(err) => {
+                    debug!(
+                        "could not get draw inputs of cycle {} from full node {}: {}",
+                        cycle, url, err
+                    );
+                    None
+                }
+            }
+        }))
+        .await;
+        agreed_answer(
+            answers.into_iter().flatten().collect(),
+            self.min_agreeing_nodes,
+            |draw_inputs| draw_inputs.clone(),
+        )
+        .ok_or_else(|| {
+            LightClientError::NoAgreement(format!(
+                "less than {} full nodes returned the same draw inputs for cycle {}",
+                self.min_agreeing_nodes, cycle
+            ))
+        })
+    }
+
+    /// Fetches proven final state entries from every full node at once.
+    ///
+    /// Answers whose proofs do not match the requested keys or do not verify against their state hash are dropped.
+    /// See `agreed_state_proof` for how the remaining ones are combined.
+    pub(crate) async fn get_state_proof(
+        &self,
+        inputs: &[StateProofInput],
+    ) -> Result<StateProofOutput, LightClientError> {
+        let keys: Vec<Vec<u8>> = inputs.iter().map(|input| input.to_db_key()).collect();
+        let keys = &keys;
+        let answers = join_all(self.clients.iter().map(|(url, client)| async move {
+            let output = match client.get_state_proof(inputs.to_vec()).await {
+                Ok(output) => output,
+                Err(err) => {
+                    debug!("could not get state proof from full node {}: {}", url, err);
+                    return None;
+                }
+            };
+            if output.entries.len() != keys.len()
+                || output
+                    .entries
+                    .iter()
+                    .zip(keys.iter())
+                    .any(|(entry, key)| &entry.key != key)
+            {
+                warn!("full node {} returned proofs of unrequested keys", url);
+                return None;
+            }
+            // no state hash is trusted yet: this only drops malformed answers,
+            // the values are trusted once enough full nodes agree on them
+            if !output.verify(&output.state_hash) {
+                warn!("full node {} returned invalid state proofs", url);
+                return None;
+            }
+            Some(output)
+        }))
+        .await;
+        agreed_state_proof(
+            answers.into_iter().flatten().collect(),
+            self.min_agreeing_nodes,
+        )
+        .ok_or_else(|| {
+            LightClientError::NoAgreement(format!(
+                "less than {} full nodes proved the same final state values",
+                self.min_agreeing_nodes
+            ))
+        })
+    }
+}
+
+/// Combines valid state proofs returned by different full nodes.
+///
+/// Full nodes finalize slots at slightly different times, so their proofs are rarely
+/// against the same final state hash. Instead, the proven values must be the same for
+/// at least `min_agreeing_nodes` of them, and the answer computed at the latest final slot
+/// among those is returned, so that the output stays consistent with its own state hash.
+pub(crate) fn agreed_state_proof(
+    mut answers: Vec<StateProofOutput>,
+    min_agreeing_nodes: usize,
+) -> Option<StateProofOutput> {
+    // the first answer of each group is kept: put the latest ones first
+    answers.sort_by(|a, b| b.slot.cmp(&a.slot));
+    agreed_answer(answers, min_agreeing_nodes, |output| {
+        output
+            .entries
+            .iter()
+            .map(|entry| entry.value.clone())
+            .collect::<Vec<_>>()
+    })
+}
+
+/// Returns the answer shared by the largest number of full nodes,
+/// if at least `min_agreeing_nodes` of them returned it.
+///
+/// Answers are compared by `key`, and the first answer of the largest group is returned.
+pub(crate) fn agreed_answer<T, K: PartialEq>(
+    answers: Vec<T>,
+    min_agreeing_nodes: usize,
+    key: impl Fn(&T) -> K,
+) -> Option<T> {
+    let mut groups: Vec<(K, T, usize)> = Vec::new();
+    for answer in answers {
+        let answer_key = key(&answer);
+        match groups.iter_mut().find(|(k, _, _)| k == &answer_key) {
+            Some((_, _, count)) => *count += 1,
+            None => groups.push((answer_key, answer, 1)),
+        }
+    }
+    groups
+        .into_iter()
+        .max_by_key(|(_, _, count)| *count)
+        .filter(|(_, _, count)| *count >= min_agreeing_nodes)
+        .map(|(_, answer, _)| answer)
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+//! # Light client mode
+//!
+//! A light node does not bootstrap the final state and does not execute slots.
+//! It only follows the block headers announced on the network:
+//! * protocol checks the signatures of the headers and of their endorsements,
+//!   and forwards them to `LightConsensusController`;
+//! * `LightConsensusController` checks that the block producer and the endorsers
+//!   were drawn by the selector for the slot of the header,
+//!   and never asks protocol for block contents;
+//! * the selector is fed with draw inputs (look back rolls and seed) fetched from full nodes;
+//! * balance and datastore queries are answered by `LightExecutionController`
+//!   with final state entries fetched from full nodes and checked against their merkle proofs.
+//!
+//! Block headers do not commit to the final state hash, so a proof alone does not tie a value to the chain.
+//! Draw inputs are therefore only accepted when at least `min_agreeing_nodes` of the configured full nodes
+//! return the same ones, and proven entries when at least `min_agreeing_nodes` of them prove the same values.
+//! As full nodes finalize slots at slightly different times, the proofs are not required to be
+//! against the same final state hash.
+//!
+//! ## `config.rs`
+//! Configuration of the light client.
+//!
+//! ## `consensus.rs`
+//! Header-only consensus controller.
+//!
+//! ## `execution.rs`
+//! Execution controller answering final state queries with proven entries.
+//!
+//! ## `full_nodes.rs`
+//! Queries to the full nodes and agreement between their answers.
+//!
+//! ## `worker.rs`
+//! Thread feeding the selector and forwarding state queries to the full nodes.
+
+#![warn(missing_docs)]
+
+mod config;
+mod consensus;
+mod error;
+mod execution;
+mod full_nodes;
+mod worker;
+
+pub use config::LightClientConfig;
+pub use consensus::{LightConsensusController, LightConsensusManager};
+pub use error::LightClientError;
+pub use execution::LightExecutionController;
+pub use worker::{start_light_client, LightClientManager};
+
+#[cfg(test)]
+mod tests;
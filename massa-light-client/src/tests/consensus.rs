@@ -0,0 +1,176 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use super::tools::{create_header, light_client_config};
+use crate::LightConsensusController;
+use massa_consensus_exports::ConsensusController;
+use massa_models::{address::Address, block::BlockGraphStatus, slot::Slot};
+use massa_pos_exports::{MockSelectorController, PosError, Selection};
+use massa_signature::KeyPair;
+use tokio::sync::broadcast;
+
+/// Selector drawing `producer` for every slot up to period 9, and `endorsers` in this order
+fn selector(producer: &KeyPair, endorsers: &[&KeyPair]) -> Box<MockSelectorController> {
+    let selection = Selection {
+        producer: Address::from_public_key(&producer.get_public_key()),
+        endorsements: endorsers
+            .iter()
+            .map(|endorser| Address::from_public_key(&endorser.get_public_key()))
+            .collect(),
+    };
+    let mut selector = Box::new(MockSelectorController::new());
+    selector.expect_get_selection().returning(move |slot| {
+        if slot.period < 10 {
+            Ok(selection.clone())
+        } else {
+            Err(PosError::CycleUnavailable(0))
+        }
+    });
+    selector
+}
+
+#[test]
+fn test_header_draw_checks() {
+    let producer = KeyPair::generate(0).unwrap();
+    let endorser_0 = KeyPair::generate(0).unwrap();
+    let endorser_1 = KeyPair::generate(0).unwrap();
+    let controller = LightConsensusController::new(
+        light_client_config(100),
+        selector(&producer, &[&endorser_0, &endorser_1]),
+        broadcast::channel(10).0,
+    );
+
+    // producer and endorsers match the draws
+    let valid = create_header(
+        &producer,
+        Slot::new(1, 0),
+        &[(0, &endorser_0), (1, &endorser_1)],
+    );
+    // drawn producer, but the endorsements are swapped
+    let wrong_endorsers = create_header(
+        &producer,
+        Slot::new(1, 1),
+        &[(0, &endorser_1), (1, &endorser_0)],
+    );
+    // endorsement index out of the draws
+    let wrong_index = create_header(&producer, Slot::new(2, 0), &[(2, &endorser_0)]);
+    // producer not drawn
+    let wrong_producer = create_header(&endorser_0, Slot::new(2, 1), &[]);
+    // draws not known yet: neither kept nor counted as rejected
+    let unknown_draws = create_header(&producer, Slot::new(10, 0), &[]);
+
+    for header in [
+        &valid,
+        &wrong_endorsers,
+        &wrong_index,
+        &wrong_producer,
+        &unknown_draws,
+    ] {
+        controller.register_block_header(header.id, header.clone());
+    }
+
+    assert_eq!(
+        controller.get_block_statuses(&[
+            valid.id,
+            wrong_endorsers.id,
+            wrong_index.id,
+            wrong_producer.id,
+            unknown_draws.id
+        ]),
+        vec![
+            BlockGraphStatus::Incoming,
+            BlockGraphStatus::NotFound,
+            BlockGraphStatus::NotFound,
+            BlockGraphStatus::NotFound,
+            BlockGraphStatus::NotFound,
+        ]
+    );
+    assert_eq!(controller.get_stats().unwrap().stale_block_count, 3);
+    assert_eq!(controller.get_best_parents(), vec![(valid.id, 1)]);
+}
+
+#[test]
+fn test_header_pruning() {
+    let producer = KeyPair::generate(0).unwrap();
+    let controller = LightConsensusController::new(
+        light_client_config(3),
+        selector(&producer, &[]),
+        broadcast::channel(10).0,
+    );
+
+    let headers: Vec<_> = [
+        Slot::new(1, 0),
+        Slot::new(1, 1),
+        Slot::new(2, 0),
+        Slot::new(2, 1),
+        Slot::new(3, 0),
+    ]
+    .into_iter()
+    .map(|slot| create_header(&producer, slot, &[]))
+    .collect();
+    for header in headers.iter() {
+        controller.register_block_header(header.id, header.clone());
+    }
+
+    // only the 3 most recent headers are kept
+    let ids: Vec<_> = headers.iter().map(|header| header.id).collect();
+    assert_eq!(
+        controller.get_block_statuses(&ids),
+        vec![
+            BlockGraphStatus::NotFound,
+            BlockGraphStatus::NotFound,
+            BlockGraphStatus::Incoming,
+            BlockGraphStatus::Incoming,
+            BlockGraphStatus::Incoming,
+        ]
+    );
+    assert_eq!(
+        controller.get_blockclique_block_at_slot(Slot::new(1, 0)),
+        None
+    );
+    assert_eq!(
+        controller.get_blockclique_block_at_slot(Slot::new(2, 1)),
+        Some(headers[3].id)
+    );
+    assert_eq!(
+        controller.get_best_parents(),
+        vec![(headers[4].id, 3), (headers[3].id, 2)]
+    );
+
+    // a header marked invalid is forgotten
+    controller.mark_invalid_block(headers[4].id, headers[4].clone());
+    assert_eq!(
+        controller.get_best_parents(),
+        vec![(headers[2].id, 2), (headers[3].id, 2)]
+    );
+}
+
+#[test]
+fn test_latest_block_at_slot_fallback() {
+    let producer = KeyPair::generate(0).unwrap();
+    let controller = LightConsensusController::new(
+        light_client_config(100),
+        selector(&producer, &[]),
+        broadcast::channel(10).0,
+    );
+
+    // without any verified header, the genesis blocks are returned
+    let genesis_0 = controller.get_latest_blockclique_block_at_slot(Slot::new(5, 0));
+    let genesis_1 = controller.get_latest_blockclique_block_at_slot(Slot::new(5, 1));
+    assert_ne!(genesis_0, genesis_1);
+
+    let header = create_header(&producer, Slot::new(2, 0), &[]);
+    controller.register_block_header(header.id, header.clone());
+    assert_eq!(
+        controller.get_latest_blockclique_block_at_slot(Slot::new(5, 0)),
+        header.id
+    );
+    // only the headers strictly before the slot, in its thread, are considered
+    assert_eq!(
+        controller.get_latest_blockclique_block_at_slot(Slot::new(2, 0)),
+        genesis_0
+    );
+    assert_eq!(
+        controller.get_latest_blockclique_block_at_slot(Slot::new(5, 1)),
+        genesis_1
+    );
+}
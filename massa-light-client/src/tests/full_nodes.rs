@@ -0,0 +1,90 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::full_nodes::{agreed_answer, agreed_state_proof};
+use massa_api_exports::state_proof::{StateProofEntry, StateProofOutput};
+use massa_hash::Hash;
+use massa_models::slot::Slot;
+
+/// Answer of a full node proving `values` at `slot`
+fn state_proof_output(period: u64, values: &[Option<&str>]) -> StateProofOutput {
+    StateProofOutput {
+        slot: Slot::new(period, 0),
+        state_hash: Hash::compute_from(&period.to_be_bytes()),
+        entries: values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| StateProofEntry {
+                key: vec![index as u8],
+                value: value.map(|value| value.as_bytes().to_vec()),
+                side_nodes: Vec::new(),
+                non_membership_leaf_data: None,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_agreed_answer() {
+    assert_eq!(agreed_answer(vec![1, 2, 1, 3], 2, |n| *n), Some(1));
+    // not enough full nodes agree
+    assert_eq!(agreed_answer(vec![1, 2, 1, 3], 3, |n| *n), None);
+    assert_eq!(agreed_answer(Vec::<u8>::new(), 1, |n| *n), None);
+    // the first answer of the largest group is returned
+    assert_eq!(
+        agreed_answer(vec![(1, 'a'), (2, 'b'), (1, 'c')], 2, |(n, _)| *n),
+        Some((1, 'a'))
+    );
+}
+
+#[test]
+fn test_state_proof_agreement_across_final_slots() {
+    // full nodes that finalized different slots, but prove the same values
+    let output = agreed_state_proof(
+        vec![
+            state_proof_output(10, &[Some("a"), None]),
+            state_proof_output(12, &[Some("a"), None]),
+            state_proof_output(11, &[Some("a"), None]),
+        ],
+        3,
+    )
+    .expect("the proven values are the same");
+    // the most recent answer is kept, with its own state hash
+    assert_eq!(output.slot, Slot::new(12, 0));
+    assert_eq!(output.state_hash, Hash::compute_from(&12u64.to_be_bytes()));
+}
+
+#[test]
+fn test_state_proof_disagreement() {
+    // no set of values is proven by 3 full nodes
+    let output = agreed_state_proof(
+        vec![
+            state_proof_output(10, &[Some("a"), None]),
+            state_proof_output(11, &[Some("b"), None]),
+            state_proof_output(12, &[Some("a"), Some("c")]),
+            state_proof_output(13, &[Some("b"), None]),
+        ],
+        3,
+    );
+    assert!(output.is_none());
+
+    // the values proven by the most full nodes win, even if another node is more recent
+    let output = agreed_state_proof(
+        vec![
+            state_proof_output(10, &[Some("a"), None]),
+            state_proof_output(11, &[Some("a"), None]),
+            state_proof_output(12, &[Some("b"), None]),
+        ],
+        2,
+    )
+    .expect("two full nodes agree");
+    assert_eq!(output.slot, Slot::new(11, 0));
+    assert_eq!(output.entries[0].value, Some(b"a".to_vec()));
+}
+
+#[test]
+fn test_state_proof_requires_the_trusted_hash() {
+    let output = state_proof_output(10, &[]);
+    assert!(output.verify(&Hash::compute_from(&10u64.to_be_bytes())));
+    // the state hash supplied by the full node is not trusted by itself
+    assert!(!output.verify(&Hash::compute_from(&11u64.to_be_bytes())));
+}
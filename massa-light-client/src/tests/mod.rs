@@ -0,0 +1,5 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+mod consensus;
+mod full_nodes;
+mod tools;
@@ -0,0 +1,73 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::LightClientConfig;
+use massa_hash::Hash;
+use massa_models::{
+    block_header::{BlockHeader, BlockHeaderSerializer, SecuredHeader},
+    block_id::BlockId,
+    endorsement::{Endorsement, EndorsementSerializer},
+    secure_share::SecureShareContent,
+    slot::Slot,
+};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+
+/// Light client configuration with two threads and no full node
+pub fn light_client_config(max_headers: usize) -> LightClientConfig {
+    LightClientConfig {
+        full_nodes: Vec::new(),
+        min_agreeing_nodes: 2,
+        request_timeout: MassaTime::from_millis(1000),
+        max_request_body_size: 52428800,
+        max_datastore_key_length: 255,
+        max_headers,
+        draw_refresh_interval: MassaTime::from_millis(1000),
+        active_rolls_cycle_count: 6,
+        broadcast_enabled: false,
+        thread_count: 2,
+        periods_per_cycle: 128,
+        t0: MassaTime::from_millis(16000),
+        genesis_timestamp: MassaTime::from_millis(0),
+        genesis_key: KeyPair::generate(0).unwrap(),
+        last_start_period: 0,
+    }
+}
+
+/// Creates a header produced by `producer` at `slot`,
+/// endorsed by the given `(index, endorser)` pairs
+pub fn create_header(
+    producer: &KeyPair,
+    slot: Slot,
+    endorsers: &[(u32, &KeyPair)],
+) -> SecuredHeader {
+    let parent = BlockId(Hash::compute_from("parent".as_bytes()));
+    let endorsements = endorsers
+        .iter()
+        .map(|(index, endorser)| {
+            Endorsement::new_verifiable(
+                Endorsement {
+                    slot,
+                    index: *index,
+                    endorsed_block: parent,
+                },
+                EndorsementSerializer::new(),
+                endorser,
+            )
+            .unwrap()
+        })
+        .collect();
+    BlockHeader::new_verifiable(
+        BlockHeader {
+            current_version: 0,
+            announced_version: 0,
+            slot,
+            parents: vec![parent, parent],
+            operation_merkle_root: Hash::compute_from(&Vec::new()),
+            endorsements,
+            denunciations: Vec::new(),
+        },
+        BlockHeaderSerializer::new(),
+        producer,
+    )
+    .unwrap()
+}
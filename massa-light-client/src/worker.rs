@@ -0,0 +1,211 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use crate::{
+    full_nodes::FullNodes, LightClientConfig, LightClientError, LightConsensusController,
+    LightExecutionController,
+};
+use crossbeam_channel::{select, tick, unbounded, Receiver, Sender};
+use massa_api_exports::state_proof::{StateProofInput, StateProofOutput};
+use massa_execution_exports::ExecutionManager;
+use massa_models::{
+    address::Address, block_header::SecuredHeader, timeslots::get_latest_block_slot_at_timestamp,
+};
+use massa_pos_exports::SelectorController;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Active rolls of the cycles fed to the selector, shared with `LightExecutionController`
+pub(crate) type SharedActiveRolls = Arc<RwLock<BTreeMap<u64, BTreeMap<Address, u64>>>>;
+
+/// Commands sent to the light client thread
+pub(crate) enum LightClientCommand {
+    /// fetch proven final state entries from the full nodes
+    GetStateProof {
+        inputs: Vec<StateProofInput>,
+        response_tx: Sender<Result<StateProofOutput, LightClientError>>,
+    },
+    /// stop the thread
+    Stop,
+}
+
+/// Thread feeding the selector with the draw inputs agreed on by the full nodes,
+/// and forwarding final state queries to them
+struct LightClientWorker {
+    config: LightClientConfig,
+    command_rx: Receiver<LightClientCommand>,
+    selector_controller: Box<dyn SelectorController>,
+    full_nodes: Arc<FullNodes>,
+    runtime: tokio::runtime::Runtime,
+    /// next cycle to feed to the selector, `None` until the first cycle is fed
+    next_draw_cycle: Option<u64>,
+    active_rolls: SharedActiveRolls,
+}
+
+impl LightClientWorker {
+    fn run(&mut self) {
+        let refresh_tick = tick(self.config.draw_refresh_interval.to_duration());
+        self.refresh_draws();
+        loop {
+            select! {
+                recv(self.command_rx) -> command => match command {
+                    Ok(LightClientCommand::GetStateProof { inputs, response_tx }) => {
+                        // answered on the runtime so that queries do not wait for each other
+                        let full_nodes = self.full_nodes.clone();
+                        self.runtime.spawn(async move {
+                            let result = full_nodes.get_state_proof(&inputs).await;
+                            if response_tx.send(result).is_err() {
+                                debug!("light client: state proof requester is gone");
+                            }
+                        });
+                    }
+                    Ok(LightClientCommand::Stop) | Err(_) => break,
+                },
+                recv(refresh_tick) -> _ => self.refresh_draws(),
+            }
+        }
+    }
+
+    /// Feeds the selector with the draws of every cycle up to two cycles after the current one,
+    /// which is as far as the full nodes know them.
+    fn refresh_draws(&mut self) {
+        let current_cycle = match MassaTime::now()
+            .map_err(|err| LightClientError::TimeError(err.to_string()))
+            .and_then(|now| {
+                get_latest_block_slot_at_timestamp(
+                    self.config.thread_count,
+                    self.config.t0,
+                    self.config.genesis_timestamp,
+                    now,
+                )
+                .map_err(|err| LightClientError::TimeError(err.to_string()))
+            }) {
+            Ok(slot) => slot.map_or(0, |slot| slot.get_cycle(self.config.periods_per_cycle)),
+            Err(err) => {
+                warn!("light client: could not compute the current cycle: {}", err);
+                return;
+            }
+        };
+
+        // the selector requires the cycles to be fed without gaps
+        let mut cycle = self.next_draw_cycle.unwrap_or(current_cycle);
+        while cycle <= current_cycle.saturating_add(2) {
+            let draw_inputs = match self
+                .runtime
+                .block_on(self.full_nodes.get_draw_inputs(cycle))
+            {
+                Ok(draw_inputs) => draw_inputs,
+                Err(err) => {
+                    debug!(
+                        "light client: draws of cycle {} unavailable: {}",
+                        cycle, err
+                    );
+                    return;
+                }
+            };
+            let rolls: BTreeMap<Address, u64> = draw_inputs.rolls.into_iter().collect();
+            if let Err(err) =
+                self.selector_controller
+                    .feed_cycle(cycle, rolls.clone(), draw_inputs.seed)
+            {
+                warn!(
+                    "light client: could not feed cycle {} to the selector: {}",
+                    cycle, err
+                );
+                return;
+            }
+            {
+                let mut active_rolls = self.active_rolls.write();
+                active_rolls.insert(cycle, rolls);
+                while active_rolls.len() > self.config.active_rolls_cycle_count {
+                    active_rolls.pop_first();
+                }
+            }
+            debug!("light client: fed draws of cycle {} to the selector", cycle);
+            cycle = cycle.saturating_add(1);
+            self.next_draw_cycle = Some(cycle);
+        }
+    }
+}
+
+/// Stops the light client thread
+pub struct LightClientManager {
+    thread: Option<JoinHandle<()>>,
+    command_tx: Sender<LightClientCommand>,
+}
+
+impl ExecutionManager for LightClientManager {
+    fn stop(&mut self) {
+        info!("stopping light client worker...");
+        if let Some(join_handle) = self.thread.take() {
+            if self.command_tx.send(LightClientCommand::Stop).is_err() {
+                warn!("light client thread is already stopped");
+            }
+            join_handle
+                .join()
+                .expect("light client thread panicked on try to join");
+        }
+        info!("light client worker stopped");
+    }
+}
+
+/// Launches the light client thread and returns:
+/// * the manager stopping it
+/// * the header-only consensus controller
+/// * the execution controller answering final state queries with proven entries
+///
+/// # Arguments
+/// * `config`: light client configuration
+/// * `selector_controller`: selector fed with the draws fetched from the full nodes
+/// * `block_header_sender`: channel used to broadcast the verified headers
+pub fn start_light_client(
+    config: LightClientConfig,
+    selector_controller: Box<dyn SelectorController>,
+    block_header_sender: broadcast::Sender<SecuredHeader>,
+) -> (
+    LightClientManager,
+    LightConsensusController,
+    LightExecutionController,
+) {
+    let (command_tx, command_rx) = unbounded();
+    let active_rolls = SharedActiveRolls::default();
+
+    let worker_config = config.clone();
+    let worker_selector = selector_controller.clone();
+    let worker_active_rolls = active_rolls.clone();
+    let thread = std::thread::Builder::new()
+        .name("light-client".into())
+        .spawn(move || {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .thread_name("light-client-runtime")
+                .enable_all()
+                .build()
+                .expect("failed to create the light client runtime");
+            let full_nodes = Arc::new(runtime.block_on(FullNodes::new(&worker_config)));
+            let mut worker = LightClientWorker {
+                config: worker_config,
+                command_rx,
+                selector_controller: worker_selector,
+                full_nodes,
+                runtime,
+                next_draw_cycle: None,
+                active_rolls: worker_active_rolls,
+            };
+            worker.run();
+        })
+        .expect("failed to spawn thread : light-client");
+
+    let consensus_controller =
+        LightConsensusController::new(config.clone(), selector_controller, block_header_sender);
+    let execution_controller =
+        LightExecutionController::new(config, command_tx.clone(), active_rolls);
+    let manager = LightClientManager {
+        thread: Some(thread),
+        command_tx,
+    };
+    (manager, consensus_controller, execution_controller)
+}
@@ -1,5 +1,8 @@
 use lazy_static::lazy_static;
-use prometheus::{register_int_gauge, Gauge, IntCounter, IntGauge};
+use prometheus::{
+    register_int_gauge, Gauge, GaugeVec, Histogram, HistogramOpts, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec, Opts,
+};
 
 #[cfg(not(feature = "testing"))]
 mod server;
@@ -86,6 +89,27 @@ pub struct MassaMetrics {
 
     final_cursor_thread: IntGauge,
     final_cursor_period: IntGauge,
+
+    // bootstrap server
+    bootstrap_active_sessions: IntGauge,
+    bootstrap_queued_clients: IntGauge,
+    bootstrap_bytes_sent: IntCounter,
+    bootstrap_session_bytes_sent: IntGaugeVec,
+    bootstrap_session_bandwidth: GaugeVec,
+
+    // execution profiling
+    execution_operation_gas_used: IntCounterVec,
+    execution_async_message_gas_used: IntCounter,
+    execution_final_slot_duration: Histogram,
+    execution_top_function_gas_used: IntGaugeVec,
+
+    // module cache
+    module_cache_lru_hits: IntGauge,
+    module_cache_hd_hits: IntGauge,
+    module_cache_misses: IntGauge,
+    module_cache_hit_rate: Gauge,
+    module_cache_compilations: IntGauge,
+    module_cache_compilation_seconds: Gauge,
 }
 
 impl MassaMetrics {
@@ -232,6 +256,93 @@ impl MassaMetrics {
         )
         .unwrap();
 
+        let bootstrap_active_sessions = IntGauge::new(
+            "bootstrap_active_sessions",
+            "bootstrap server running sessions",
+        )
+        .unwrap();
+        let bootstrap_queued_clients = IntGauge::new(
+            "bootstrap_queued_clients",
+            "bootstrap server clients waiting for a session",
+        )
+        .unwrap();
+        let bootstrap_bytes_sent = IntCounter::new(
+            "bootstrap_bytes_sent",
+            "total bytes sent by the bootstrap server",
+        )
+        .unwrap();
+        let bootstrap_session_bytes_sent = IntGaugeVec::new(
+            Opts::new(
+                "bootstrap_session_bytes_sent",
+                "bytes sent during a running bootstrap session",
+            ),
+            &["peer"],
+        )
+        .unwrap();
+        let bootstrap_session_bandwidth = GaugeVec::new(
+            Opts::new(
+                "bootstrap_session_bandwidth",
+                "bandwidth allowed to a running bootstrap session in bytes per second",
+            ),
+            &["peer"],
+        )
+        .unwrap();
+
+        let execution_operation_gas_used = IntCounterVec::new(
+            Opts::new(
+                "execution_operation_gas_used",
+                "gas used by the final executed operations, by operation type",
+            ),
+            &["operation_type"],
+        )
+        .unwrap();
+        let execution_async_message_gas_used = IntCounter::new(
+            "execution_async_message_gas_used",
+            "gas used by the final executed asynchronous messages",
+        )
+        .unwrap();
+        let execution_final_slot_duration = Histogram::with_opts(HistogramOpts::new(
+            "execution_final_slot_duration",
+            "time taken to execute the final slots in seconds",
+        ))
+        .unwrap();
+        let execution_top_function_gas_used = IntGaugeVec::new(
+            Opts::new(
+                "execution_top_function_gas_used",
+                "gas used in the stats time window by the smart contract functions consuming the most gas",
+            ),
+            &["address", "function"],
+        )
+        .unwrap();
+
+        let module_cache_lru_hits = IntGauge::new(
+            "module_cache_lru_hits",
+            "modules loaded from the in-memory module cache",
+        )
+        .unwrap();
+        let module_cache_hd_hits = IntGauge::new(
+            "module_cache_hd_hits",
+            "modules loaded from the disk module cache",
+        )
+        .unwrap();
+        let module_cache_misses = IntGauge::new(
+            "module_cache_misses",
+            "modules missing from the module cache and compiled",
+        )
+        .unwrap();
+        let module_cache_hit_rate = Gauge::new(
+            "module_cache_hit_rate",
+            "ratio of module loads served by the module cache",
+        )
+        .unwrap();
+        let module_cache_compilations =
+            IntGauge::new("module_cache_compilations", "number of module compilations").unwrap();
+        let module_cache_compilation_seconds = Gauge::new(
+            "module_cache_compilation_seconds",
+            "total time spent compiling modules in seconds",
+        )
+        .unwrap();
+
         if enabled {
             // TODO addr from config
             #[cfg(not(feature = "testing"))]
@@ -266,6 +377,21 @@ impl MassaMetrics {
                 let _ = prometheus::register(Box::new(endorsement_cache_known_by_peer.clone()));
                 let _ = prometheus::register(Box::new(block_graph_counter.clone()));
                 let _ = prometheus::register(Box::new(block_graph_ms.clone()));
+                let _ = prometheus::register(Box::new(bootstrap_active_sessions.clone()));
+                let _ = prometheus::register(Box::new(bootstrap_queued_clients.clone()));
+                let _ = prometheus::register(Box::new(bootstrap_bytes_sent.clone()));
+                let _ = prometheus::register(Box::new(bootstrap_session_bytes_sent.clone()));
+                let _ = prometheus::register(Box::new(bootstrap_session_bandwidth.clone()));
+                let _ = prometheus::register(Box::new(execution_operation_gas_used.clone()));
+                let _ = prometheus::register(Box::new(execution_async_message_gas_used.clone()));
+                let _ = prometheus::register(Box::new(execution_final_slot_duration.clone()));
+                let _ = prometheus::register(Box::new(execution_top_function_gas_used.clone()));
+                let _ = prometheus::register(Box::new(module_cache_lru_hits.clone()));
+                let _ = prometheus::register(Box::new(module_cache_hd_hits.clone()));
+                let _ = prometheus::register(Box::new(module_cache_misses.clone()));
+                let _ = prometheus::register(Box::new(module_cache_hit_rate.clone()));
+                let _ = prometheus::register(Box::new(module_cache_compilations.clone()));
+                let _ = prometheus::register(Box::new(module_cache_compilation_seconds.clone()));
             }
         }
 
@@ -295,6 +421,21 @@ impl MassaMetrics {
             active_cursor_period,
             final_cursor_thread,
             final_cursor_period,
+            bootstrap_active_sessions,
+            bootstrap_queued_clients,
+            bootstrap_bytes_sent,
+            bootstrap_session_bytes_sent,
+            bootstrap_session_bandwidth,
+            execution_operation_gas_used,
+            execution_async_message_gas_used,
+            execution_final_slot_duration,
+            execution_top_function_gas_used,
+            module_cache_lru_hits,
+            module_cache_hd_hits,
+            module_cache_misses,
+            module_cache_hit_rate,
+            module_cache_compilations,
+            module_cache_compilation_seconds,
         }
     }
 
@@ -405,6 +546,76 @@ impl MassaMetrics {
     pub fn inc_block_graph_counter(&self) {
         self.block_graph_counter.inc();
     }
+
+    pub fn set_bootstrap_scheduler(&self, active_sessions: usize, queued_clients: usize) {
+        self.bootstrap_active_sessions.set(active_sessions as i64);
+        self.bootstrap_queued_clients.set(queued_clients as i64);
+    }
+
+    pub fn inc_bootstrap_bytes_sent(&self, bytes: u64) {
+        self.bootstrap_bytes_sent.inc_by(bytes);
+    }
+
+    pub fn set_bootstrap_session(&self, peer: &str, bytes_sent: u64, bandwidth: f64) {
+        self.bootstrap_session_bytes_sent
+            .with_label_values(&[peer])
+            .set(bytes_sent as i64);
+        self.bootstrap_session_bandwidth
+            .with_label_values(&[peer])
+            .set(bandwidth);
+    }
+
+    pub fn remove_bootstrap_session(&self, peer: &str) {
+        let _ = self
+            .bootstrap_session_bytes_sent
+            .remove_label_values(&[peer]);
+        let _ = self
+            .bootstrap_session_bandwidth
+            .remove_label_values(&[peer]);
+    }
+
+    pub fn inc_execution_operation_gas(&self, operation_type: &str, gas_used: u64) {
+        self.execution_operation_gas_used
+            .with_label_values(&[operation_type])
+            .inc_by(gas_used);
+    }
+
+    pub fn inc_execution_async_message_gas(&self, gas_used: u64) {
+        self.execution_async_message_gas_used.inc_by(gas_used);
+    }
+
+    pub fn observe_final_slot_execution_time(&self, seconds: f64) {
+        self.execution_final_slot_duration.observe(seconds);
+    }
+
+    /// Replaces the listed top gas consuming functions, given as (address, function, gas used)
+    pub fn set_execution_top_gas_functions(&self, functions: &[(String, String, u64)]) {
+        // functions leaving the top are removed to keep the label count bounded
+        self.execution_top_function_gas_used.reset();
+        for (address, function, gas_used) in functions {
+            self.execution_top_function_gas_used
+                .with_label_values(&[address, function])
+                .set(*gas_used as i64);
+        }
+    }
+
+    pub fn set_module_cache_metrics(
+        &self,
+        lru_hits: u64,
+        hd_hits: u64,
+        misses: u64,
+        compilations: u64,
+        compilation_seconds: f64,
+        hit_rate: f64,
+    ) {
+        self.module_cache_lru_hits.set(lru_hits as i64);
+        self.module_cache_hd_hits.set(hd_hits as i64);
+        self.module_cache_misses.set(misses as i64);
+        self.module_cache_compilations.set(compilations as i64);
+        self.module_cache_compilation_seconds
+            .set(compilation_seconds);
+        self.module_cache_hit_rate.set(hit_rate);
+    }
 }
 // mod test {
 //     use massa_channel::MassaChannel;
@@ -78,9 +78,9 @@ lazy_static::lazy_static! {
     /// node version
     pub static ref VERSION: Version = {
         if cfg!(feature = "sandbox") {
-            "SAND.23.2"
+            "SAND.23.3"
         } else {
-            "TEST.23.2"
+            "TEST.23.3"
         }
         .parse()
         .unwrap()
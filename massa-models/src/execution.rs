@@ -30,6 +30,81 @@ pub struct EventFilter {
     /// Some(false) means events coming from a succeeded sc execution
     /// None means both
     pub is_error: Option<bool>,
+    /// optional prefix of the event data
+    pub data_prefix: Option<String>,
+    /// optional substring of the event data
+    pub data_substring: Option<String>,
+    /// optional address that must be in the call stack of the event
+    pub call_stack_address: Option<Address>,
+}
+
+/// Position of an event in the list of events ordered by slot and index in slot.
+///
+/// Used to resume a paginated event query right after the last returned event.
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventCursor {
+    /// slot of the event
+    pub slot: Slot,
+    /// index of the event in its slot
+    pub index_in_slot: u64,
+}
+
+/// Role of an address in an operation of its history
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum AddressHistoryDirection {
+    /// the address sent the operation
+    Sent,
+    /// the operation was sent by another address and changed the balance or datastore of this one
+    Received,
+}
+
+/// Operation that affected an address
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct AddressHistoryEntry {
+    /// operation id
+    pub operation_id: OperationId,
+    /// slot at which the operation was executed
+    pub slot: Slot,
+    /// role of the address in the operation
+    pub direction: AddressHistoryDirection,
+}
+
+impl std::fmt::Display for AddressHistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            AddressHistoryDirection::Sent => "sent",
+            AddressHistoryDirection::Received => "received",
+        };
+        write!(
+            f,
+            "{} {} at slot {}",
+            direction, self.operation_id, self.slot
+        )
+    }
+}
+
+/// Position of an entry in the history of an address, ordered from the most recent slot.
+///
+/// Used to resume a paginated history query right after the last returned entry.
+#[derive(Debug, Deserialize, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct AddressHistoryCursor {
+    /// slot of the entry
+    pub slot: Slot,
+    /// operation id of the entry
+    pub operation_id: OperationId,
+}
+
+/// filter used when retrieving pending asynchronous messages
+#[derive(Default, Debug, Deserialize, Clone, Serialize)]
+pub struct AsyncMessageFilter {
+    /// optional sender address
+    pub sender: Option<Address>,
+    /// optional destination address
+    pub destination: Option<Address>,
+    /// optional handler function name
+    pub handler: Option<String>,
+    /// optional slot that must be in the validity range of the messages
+    pub valid_at_slot: Option<Slot>,
 }
 
 /// Used for Deserialize
@@ -266,6 +266,9 @@ impl TryFrom<grpc_api::GetScExecutionEventsFilter> for EventFilter {
                 .transpose()?,
             is_final: Some(filter.status.contains(&status_final)),
             is_error: Some(filter.status.contains(&status_error)),
+            data_prefix: None,
+            data_substring: None,
+            call_stack_address: None,
         })
     }
 }
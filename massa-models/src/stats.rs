@@ -1,8 +1,10 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::address::Address;
 use crate::slot::Slot;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Formatter;
 
 /// execution statistics
@@ -18,6 +20,101 @@ pub struct ExecutionStats {
     pub final_executed_operations_count: usize,
     /// active execution cursor slot
     pub active_cursor: Slot,
+    /// gas used by the final executed operations in the time window, by operation type
+    pub gas_used_by_operation_type: BTreeMap<String, u64>,
+    /// smart contract functions that used the most gas in the final slots of the time window
+    pub top_gas_functions: Vec<FunctionGasStats>,
+    /// final slots of the time window that took the longest to execute
+    pub slowest_slots: Vec<SlotExecutionTime>,
+    /// module cache statistics
+    pub module_cache: ModuleCacheStats,
+}
+
+/// gas used by the calls to a smart contract function, made by operations or asynchronous messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionGasStats {
+    /// address of the called smart contract
+    pub address: Address,
+    /// name of the called function
+    pub function: String,
+    /// number of calls
+    pub call_count: u64,
+    /// total gas used by the calls
+    pub gas_used: u64,
+}
+
+/// execution time of a slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotExecutionTime {
+    /// executed slot
+    pub slot: Slot,
+    /// time taken to execute the slot
+    pub execution_time: MassaTime,
+    /// gas used by the operations and asynchronous messages executed at the slot
+    pub gas_used: u64,
+}
+
+/// statistics of the cache of compiled smart contract modules, counted since the node started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleCacheStats {
+    /// number of modules loaded from the in-memory cache
+    pub lru_hits: u64,
+    /// number of modules loaded from the disk cache
+    pub hd_hits: u64,
+    /// number of modules missing from both caches
+    pub misses: u64,
+    /// number of module compilations, temporary modules included
+    pub compilation_count: u64,
+    /// total time spent compiling modules
+    pub compilation_time: MassaTime,
+    /// longest module compilation time
+    pub max_compilation_time: MassaTime,
+}
+
+impl ModuleCacheStats {
+    /// ratio of module loads served by one of the caches, `None` if no module was loaded
+    pub fn hit_rate(&self) -> Option<f64> {
+        let hits = self.lru_hits + self.hd_hits;
+        let loads = hits + self.misses;
+        (loads > 0).then(|| hits as f64 / loads as f64)
+    }
+}
+
+impl Default for ModuleCacheStats {
+    fn default() -> Self {
+        ModuleCacheStats {
+            lru_hits: 0,
+            hd_hits: 0,
+            misses: 0,
+            compilation_count: 0,
+            compilation_time: MassaTime::from_millis(0),
+            max_compilation_time: MassaTime::from_millis(0),
+        }
+    }
+}
+
+impl std::fmt::Display for ModuleCacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Module cache stats:")?;
+        writeln!(f, "	In-memory cache hits: {}", self.lru_hits)?;
+        writeln!(f, "	Disk cache hits: {}", self.hd_hits)?;
+        writeln!(f, "	Misses: {}", self.misses)?;
+        if let Some(hit_rate) = self.hit_rate() {
+            writeln!(f, "	Hit rate: {:.2}%", hit_rate * 100.0)?;
+        }
+        writeln!(f, "	Compilations: {}", self.compilation_count)?;
+        writeln!(
+            f,
+            "	Total compilation time: {} ms",
+            self.compilation_time.to_millis()
+        )?;
+        writeln!(
+            f,
+            "	Longest compilation time: {} ms",
+            self.max_compilation_time.to_millis()
+        )?;
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for ExecutionStats {
@@ -44,6 +141,29 @@ impl std::fmt::Display for ExecutionStats {
             self.final_executed_operations_count
         )?;
         writeln!(f, "\tActive cursor: {}", self.active_cursor)?;
+        writeln!(f, "\tFinal gas used by operation type:")?;
+        for (operation_type, gas_used) in &self.gas_used_by_operation_type {
+            writeln!(f, "\t\t{}: {}", operation_type, gas_used)?;
+        }
+        writeln!(f, "\tTop gas consuming functions:")?;
+        for function in &self.top_gas_functions {
+            writeln!(
+                f,
+                "\t\t{}::{}: {} gas over {} calls",
+                function.address, function.function, function.gas_used, function.call_count
+            )?;
+        }
+        writeln!(f, "\tSlowest final slots:")?;
+        for slot in &self.slowest_slots {
+            writeln!(
+                f,
+                "\t\t{}: {} ms, {} gas",
+                slot.slot,
+                slot.execution_time.to_millis(),
+                slot.gas_used
+            )?;
+        }
+        write!(f, "{}", self.module_cache)?;
         Ok(())
     }
 }
@@ -196,6 +196,20 @@ impl Version {
             && self.minor > 0
             && other.minor > 0
     }
+
+    /// true if the version is `major`.`minor` or a later one, whatever the instance
+    /// ```rust
+    /// # use massa_models::*;
+    /// # use std::str::FromStr;
+    /// let v: version::Version = version::Version::from_str("TEST.1.10").unwrap();
+    /// assert!(v.is_at_least(1, 9));
+    /// assert!(v.is_at_least(1, 10));
+    /// assert!(!v.is_at_least(1, 11));
+    /// assert!(!v.is_at_least(2, 0));
+    /// ```
+    pub fn is_at_least(&self, major: u32, minor: u32) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
 }
 
 impl fmt::Display for Version {
@@ -1,7 +1,11 @@
 use massa_hash::Hash;
 use massa_models::prehash::BuildHashMapper;
+use massa_models::stats::ModuleCacheStats;
 use massa_sc_runtime::{Compiler, RuntimeModule};
+use massa_time::MassaTime;
 use schnellru::{ByLength, LruMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 use tracing::{debug, warn};
 
 use crate::{
@@ -23,6 +27,20 @@ pub struct ModuleCache {
     /// Disk stored cache.
     /// See the `HDCache` documentation for more information.
     hd_cache: HDCache,
+    /// Usage counters, see `ModuleCacheStats`.
+    /// Atomic so that temporary modules can be counted under a read lock of the cache.
+    stats: CacheCounters,
+}
+
+/// Atomic counterpart of `ModuleCacheStats`, with durations in microseconds
+#[derive(Default)]
+struct CacheCounters {
+    lru_hits: AtomicU64,
+    hd_hits: AtomicU64,
+    misses: AtomicU64,
+    compilation_count: AtomicU64,
+    compilation_time: AtomicU64,
+    max_compilation_time: AtomicU64,
 }
 
 impl ModuleCache {
@@ -36,17 +54,50 @@ impl ModuleCache {
                 cfg.snip_amount,
             ),
             cfg,
+            stats: Default::default(),
         }
     }
 
+    /// Get the usage statistics of the cache
+    pub fn get_stats(&self) -> ModuleCacheStats {
+        let stats = &self.stats;
+        ModuleCacheStats {
+            lru_hits: stats.lru_hits.load(Ordering::Relaxed),
+            hd_hits: stats.hd_hits.load(Ordering::Relaxed),
+            misses: stats.misses.load(Ordering::Relaxed),
+            compilation_count: stats.compilation_count.load(Ordering::Relaxed),
+            compilation_time: MassaTime::from_millis(
+                stats.compilation_time.load(Ordering::Relaxed) / 1000,
+            ),
+            max_compilation_time: MassaTime::from_millis(
+                stats.max_compilation_time.load(Ordering::Relaxed) / 1000,
+            ),
+        }
+    }
+
+    /// Internal function to count a module compilation started at `start`
+    fn register_compilation(&self, start: Instant) {
+        let duration = start.elapsed().as_micros() as u64;
+        self.stats.compilation_count.fetch_add(1, Ordering::Relaxed);
+        self.stats
+            .compilation_time
+            .fetch_add(duration, Ordering::Relaxed);
+        self.stats
+            .max_compilation_time
+            .fetch_max(duration, Ordering::Relaxed);
+    }
+
     /// Internal function to compile and build `ModuleInfo`
     fn compile_cached(&mut self, bytecode: &[u8], hash: Hash) -> ModuleInfo {
-        match RuntimeModule::new(
+        let start = Instant::now();
+        let compilation_result = RuntimeModule::new(
             bytecode,
             self.cfg.compilation_gas,
             self.cfg.gas_costs.clone(),
             Compiler::CL,
-        ) {
+        );
+        self.register_compilation(start);
+        match compilation_result {
             Ok(module) => {
                 debug!("compilation of module {} succeeded", hash);
                 ModuleInfo::Module(module)
@@ -97,16 +148,19 @@ impl ModuleCache {
         let hash = Hash::compute_from(bytecode);
         if let Some(lru_module_info) = self.lru_cache.get(hash) {
             debug!("load_module: {} present in lru", hash);
+            self.stats.lru_hits.fetch_add(1, Ordering::Relaxed);
             lru_module_info
         } else if let Some(hd_module_info) =
             self.hd_cache
                 .get(hash, self.cfg.compilation_gas, self.cfg.gas_costs.clone())
         {
             debug!("load_module: {} missing in lru but present in hd", hash);
+            self.stats.hd_hits.fetch_add(1, Ordering::Relaxed);
             self.lru_cache.insert(hash, hd_module_info.clone());
             hd_module_info
         } else {
             debug!("load_module: {} missing", hash);
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
             let module_info = self.compile_cached(bytecode, hash);
             self.hd_cache.insert(hash, module_info.clone());
             self.lru_cache.insert(hash, module_info.clone());
@@ -146,11 +200,54 @@ impl ModuleCache {
         limit: u64,
     ) -> Result<RuntimeModule, CacheError> {
         debug!("load_tmp_module");
-        Ok(RuntimeModule::new(
-            bytecode,
-            limit,
-            self.cfg.gas_costs.clone(),
-            Compiler::SP,
-        )?)
+        let start = Instant::now();
+        let module = RuntimeModule::new(bytecode, limit, self.cfg.gas_costs.clone(), Compiler::SP);
+        self.register_compilation(start);
+        Ok(module?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_sc_runtime::GasCosts;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    const BYTECODE: [u8; 72] = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7f, 0x01,
+        0x7f, 0x03, 0x02, 0x01, 0x00, 0x07, 0x0b, 0x01, 0x07, 0x61, 0x64, 0x64, 0x5f, 0x6f, 0x6e,
+        0x65, 0x00, 0x00, 0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x41, 0x01, 0x6a, 0x0b, 0x00,
+        0x1a, 0x04, 0x6e, 0x61, 0x6d, 0x65, 0x01, 0x0a, 0x01, 0x00, 0x07, 0x61, 0x64, 0x64, 0x5f,
+        0x6f, 0x6e, 0x65, 0x02, 0x07, 0x01, 0x00, 0x01, 0x00, 0x02, 0x70, 0x30,
+    ];
+
+    #[test]
+    #[serial]
+    fn test_stats() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut cache = ModuleCache::new(ModuleCacheConfig {
+            hd_cache_path: tmp_dir.path().to_path_buf(),
+            gas_costs: GasCosts::default(),
+            compilation_gas: 10,
+            lru_cache_size: 10,
+            hd_cache_size: 10,
+            snip_amount: 1,
+        });
+
+        // the first load compiles the module, the second one finds it in the LRU cache
+        cache.load_module(&BYTECODE, 10).unwrap();
+        cache.load_module(&BYTECODE, 10).unwrap();
+        // temporary modules are compiled and counted through a shared reference to the cache
+        let shared_cache: &ModuleCache = &cache;
+        shared_cache.load_tmp_module(&BYTECODE, 10).unwrap();
+
+        let stats = cache.get_stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.lru_hits, 1);
+        assert_eq!(stats.hd_hits, 0);
+        assert_eq!(stats.compilation_count, 2);
+        assert!(stats.max_compilation_time <= stats.compilation_time);
+        assert_eq!(stats.hit_rate(), Some(0.5));
     }
 }
@@ -18,18 +18,21 @@ use massa_api_exports::config::APIConfig;
 use massa_async_pool::AsyncPoolConfig;
 use massa_bootstrap::BootstrapError;
 use massa_bootstrap::{
-    get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager, BootstrapTcpListener,
-    DefaultConnector,
+    export_snapshot, get_state, get_state_from_snapshot, start_bootstrap_server, BootstrapConfig,
+    BootstrapManager, BootstrapTcpListener, DefaultConnector, GlobalBootstrapState,
 };
 use massa_channel::receiver::MassaReceiver;
 use massa_channel::MassaChannel;
 use massa_consensus_exports::events::ConsensusEvent;
-use massa_consensus_exports::{ConsensusChannels, ConsensusConfig, ConsensusManager};
+use massa_consensus_exports::{
+    ConsensusChannels, ConsensusConfig, ConsensusController, ConsensusManager,
+};
 use massa_consensus_worker::start_consensus_worker;
-use massa_db::{MassaDB, MassaDBConfig};
+use massa_db::{has_pending_bootstrap, MassaDB, MassaDBConfig};
 use massa_executed_ops::{ExecutedDenunciationsConfig, ExecutedOpsConfig};
 use massa_execution_exports::{
-    ExecutionChannels, ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants,
+    ExecutionChannels, ExecutionConfig, ExecutionController, ExecutionManager, GasCosts,
+    StorageCostsConstants,
 };
 use massa_execution_worker::start_execution_worker;
 use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
@@ -39,6 +42,9 @@ use massa_grpc::config::GrpcConfig;
 use massa_grpc::server::MassaGrpc;
 use massa_ledger_exports::LedgerConfig;
 use massa_ledger_worker::FinalLedger;
+use massa_light_client::{
+    start_light_client, LightClientConfig, LightConsensusController, LightConsensusManager,
+};
 use massa_logging::massa_trace;
 use massa_metrics::MassaMetrics;
 use massa_models::address::Address;
@@ -118,12 +124,14 @@ async fn launch(
     Box<dyn SelectorManager>,
     Box<dyn PoolManager>,
     Box<dyn ProtocolManager>,
-    Box<dyn FactoryManager>,
+    Option<Box<dyn FactoryManager>>,
     mpsc::Receiver<()>,
     StopHandle,
     StopHandle,
     StopHandle,
     Option<massa_grpc::server::StopHandle>,
+    Arc<RwLock<FinalState>>,
+    Box<dyn ConsensusController>,
 ) {
     info!("Node version : {}", *VERSION);
     let now = MassaTime::now().expect("could not get now time");
@@ -233,10 +241,19 @@ async fn launch(
     // Start massa metrics
     let metrics = MassaMetrics::new(SETTINGS.metrics.enabled, THREAD_COUNT);
 
+    // An interrupted bootstrap resumes from the partial ledger it left on disk
+    let resume_bootstrap = !args.keep_ledger
+        && args.restart_from_snapshot_at_period.is_none()
+        && args.bootstrap_from_snapshot.is_none()
+        && !SETTINGS.light_client.enabled
+        && has_pending_bootstrap(&SETTINGS.ledger.disk_ledger_path);
+
     // Remove current disk ledger if there is one and we don't want to restart from snapshot
     // NOTE: this is temporary, since we cannot currently handle bootstrap from remaining ledger
     if args.keep_ledger || args.restart_from_snapshot_at_period.is_some() {
         info!("Loading old ledger for next episode");
+    } else if resume_bootstrap {
+        info!("Keeping the partial ledger of the interrupted bootstrap");
     } else {
         if SETTINGS.ledger.disk_ledger_path.exists() {
             std::fs::remove_dir_all(SETTINGS.ledger.disk_ledger_path.clone())
@@ -253,6 +270,8 @@ async fn launch(
         max_history_length: SETTINGS.ledger.final_history_length,
         max_new_elements: MAX_BOOTSTRAPPED_NEW_ELEMENTS as usize,
         thread_count: THREAD_COUNT,
+        enable_archive: SETTINGS.ledger.archive_mode,
+        max_archive_periods: SETTINGS.ledger.archive_max_periods,
     };
     let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
 
@@ -331,7 +350,7 @@ async fn launch(
                 Box::new(ledger),
                 selector_controller.clone(),
                 mip_store.clone(),
-                true,
+                !resume_bootstrap,
             )
             .expect("could not init final state"),
         },
@@ -356,9 +375,14 @@ async fn launch(
         keep_ledger: args.keep_ledger,
         max_listeners_per_peer: MAX_LISTENERS_PER_PEER as u32,
         max_simultaneous_bootstraps: SETTINGS.bootstrap.max_simultaneous_bootstraps,
+        max_bootstrap_queue_length: SETTINGS.bootstrap.max_bootstrap_queue_length,
+        max_queue_wait: SETTINGS.bootstrap.max_queue_wait,
         per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
         ip_list_max_size: SETTINGS.bootstrap.ip_list_max_size,
         max_bytes_read_write: SETTINGS.bootstrap.max_bytes_read_write,
+        max_bootstrap_bandwidth: SETTINGS.bootstrap.max_bootstrap_bandwidth,
+        compression: SETTINGS.bootstrap.compression,
+        parallel_servers: SETTINGS.bootstrap.parallel_servers,
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
         randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
         thread_count: THREAD_COUNT,
@@ -394,22 +418,44 @@ async fn launch(
         max_denunciation_changes_length: MAX_DENUNCIATION_CHANGES_LENGTH,
     };
 
-    let bootstrap_state = match get_state(
-        &bootstrap_config,
-        final_state.clone(),
-        DefaultConnector,
-        *VERSION,
-        *GENESIS_TIMESTAMP,
-        *END_TIMESTAMP,
-        args.restart_from_snapshot_at_period,
-        sig_int_toggled,
-    ) {
-        Ok(vals) => vals,
-        Err(BootstrapError::Interupted(msg)) => {
-            info!("{}", msg);
-            process::exit(0);
+    // a light node does not hold the final state: it skips the bootstrap
+    // and connects to the initial peers
+    let bootstrap_state = if SETTINGS.light_client.enabled {
+        info!("Light client mode: skipping bootstrap");
+        GlobalBootstrapState {
+            final_state: final_state.clone(),
+            graph: None,
+            peers: None,
+        }
+    } else {
+        let bootstrap_result = match &args.bootstrap_from_snapshot {
+            // start from a local snapshot file instead of a bootstrap server,
+            // the following slots are then caught up from peers
+            Some(snapshot_path) => get_state_from_snapshot(
+                snapshot_path,
+                &bootstrap_config,
+                final_state.clone(),
+                *VERSION,
+            ),
+            None => get_state(
+                &bootstrap_config,
+                final_state.clone(),
+                DefaultConnector,
+                *VERSION,
+                *GENESIS_TIMESTAMP,
+                *END_TIMESTAMP,
+                args.restart_from_snapshot_at_period,
+                sig_int_toggled,
+            ),
+        };
+        match bootstrap_result {
+            Ok(vals) => vals,
+            Err(BootstrapError::Interupted(msg)) => {
+                info!("{}", msg);
+                process::exit(0);
+            }
+            Err(err) => panic!("critical error detected in the bootstrap process: {}", err),
         }
-        Err(err) => panic!("critical error detected in the bootstrap process: {}", err),
     };
 
     if !final_state.read().is_db_valid() {
@@ -417,7 +463,8 @@ async fn launch(
         panic!("critical: db is not valid after bootstrap");
     }
 
-    if args.restart_from_snapshot_at_period.is_none() {
+    // a light node feeds its selector with the draws fetched from full nodes instead
+    if args.restart_from_snapshot_at_period.is_none() && !SETTINGS.light_client.enabled {
         final_state.write().recompute_caches();
 
         // give the controller to final state in order for it to feed the cycles
@@ -466,6 +513,12 @@ async fn launch(
     // launch execution module
     let execution_config = ExecutionConfig {
         max_final_events: SETTINGS.execution.max_final_events,
+        event_store_enabled: SETTINGS.execution.event_store_enabled,
+        event_store_path: SETTINGS.execution.event_store_path.clone(),
+        event_store_retention_periods: SETTINGS.execution.event_store_retention_periods,
+        max_final_receipts: SETTINGS.execution.max_final_receipts,
+        address_history_enabled: SETTINGS.execution.address_history_enabled,
+        address_history_path: SETTINGS.execution.address_history_path.clone(),
         readonly_queue_length: SETTINGS.execution.readonly_queue_length,
         cursor_delay: SETTINGS.execution.cursor_delay,
         max_async_gas: MAX_ASYNC_GAS,
@@ -504,6 +557,7 @@ async fn launch(
             .broadcast_slot_execution_output_channel_capacity,
     };
 
+    let sp_compilation_cost = execution_config.gas_costs.sp_compilation_cost;
     let execution_channels = ExecutionChannels {
         slot_execution_output_sender: broadcast::channel(
             execution_config.broadcast_slot_execution_output_channel_capacity,
@@ -511,14 +565,55 @@ async fn launch(
         .0,
     };
 
-    let (execution_manager, execution_controller) = start_execution_worker(
-        execution_config,
-        final_state.clone(),
-        selector_controller.clone(),
-        mip_store.clone(),
-        execution_channels.clone(),
-        metrics.clone(),
-    );
+    // channel used to broadcast the new block headers, by the consensus worker or the light client
+    let block_header_sender =
+        broadcast::channel(SETTINGS.consensus.broadcast_blocks_headers_channel_capacity).0;
+
+    // a light node replaces the execution and consensus workers by the light client
+    let (execution_manager, execution_controller, light_consensus_controller): (
+        Box<dyn ExecutionManager>,
+        Box<dyn ExecutionController>,
+        Option<LightConsensusController>,
+    ) = if SETTINGS.light_client.enabled {
+        let light_client_config = LightClientConfig {
+            full_nodes: SETTINGS.light_client.full_nodes.clone(),
+            min_agreeing_nodes: SETTINGS.light_client.min_agreeing_nodes,
+            request_timeout: SETTINGS.light_client.request_timeout,
+            max_request_body_size: SETTINGS.api.max_request_body_size,
+            max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
+            max_headers: SETTINGS.light_client.max_headers,
+            draw_refresh_interval: SETTINGS.light_client.draw_refresh_interval,
+            active_rolls_cycle_count: POS_SAVED_CYCLES,
+            broadcast_enabled: SETTINGS.api.enable_broadcast,
+            thread_count: THREAD_COUNT,
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            t0: T0,
+            genesis_timestamp: *GENESIS_TIMESTAMP,
+            genesis_key: GENESIS_KEY.clone(),
+            last_start_period: final_state.read().last_start_period,
+        };
+        let (light_client_manager, light_consensus_controller, light_execution_controller) =
+            start_light_client(
+                light_client_config,
+                selector_controller.clone(),
+                block_header_sender.clone(),
+            );
+        (
+            Box::new(light_client_manager),
+            Box::new(light_execution_controller),
+            Some(light_consensus_controller),
+        )
+    } else {
+        let (execution_manager, execution_controller) = start_execution_worker(
+            execution_config,
+            final_state.clone(),
+            selector_controller.clone(),
+            mip_store.clone(),
+            execution_channels.clone(),
+            metrics.clone(),
+        );
+        (execution_manager, execution_controller, None)
+    };
 
     // launch pool controller
     let pool_config = PoolConfig {
@@ -570,6 +665,7 @@ async fn launch(
     let protocol_config = ProtocolConfig {
         thread_count: THREAD_COUNT,
         ask_block_timeout: SETTINGS.protocol.ask_block_timeout,
+        compact_block_relay: SETTINGS.protocol.compact_block_relay,
         max_known_blocks_size: SETTINGS.protocol.max_known_blocks_size,
         max_node_known_blocks_size: SETTINGS.protocol.max_node_known_blocks_size,
         max_node_wanted_blocks_size: SETTINGS.protocol.max_node_wanted_blocks_size,
@@ -597,12 +693,17 @@ async fn launch(
         t0: T0,
         endorsement_count: ENDORSEMENT_COUNT,
         max_message_size: MAX_MESSAGE_SIZE as usize,
+        compression: SETTINGS.protocol.compression,
+        compression_min_message_size: SETTINGS.protocol.compression_min_message_size,
         max_operations_propagation_time: SETTINGS.protocol.max_operations_propagation_time,
         max_endorsements_propagation_time: SETTINGS.protocol.max_endorsements_propagation_time,
         last_start_period: final_state.read().last_start_period,
         max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE as u64,
         max_denunciations_in_block_header: MAX_DENUNCIATIONS_PER_BLOCK_HEADER,
         initial_peers: SETTINGS.protocol.initial_peers_file.clone(),
+        peers_file: SETTINGS.protocol.peers_file.clone(),
+        peers_file_dump_interval: SETTINGS.protocol.peers_file_dump_interval,
+        peers_max_age: SETTINGS.protocol.peers_max_age,
         listeners,
         keypair_file: SETTINGS.protocol.keypair_file.clone(),
         max_known_blocks_saved_size: SETTINGS.protocol.max_known_blocks_size,
@@ -641,6 +742,9 @@ async fn launch(
             as u128,
         try_connection_timer: SETTINGS.protocol.try_connection_timer,
         max_in_connections: SETTINGS.protocol.max_in_connections,
+        peer_score_half_life: SETTINGS.protocol.peer_score_half_life,
+        peer_score_ban_threshold: SETTINGS.protocol.peer_score_ban_threshold,
+        peer_score_ban_duration: SETTINGS.protocol.peer_score_ban_duration,
         timeout_connection: SETTINGS.protocol.timeout_connection,
         routable_ip: SETTINGS
             .protocol
@@ -696,10 +800,7 @@ async fn launch(
         pool_controller: pool_controller.clone(),
         controller_event_tx: consensus_event_sender,
         protocol_controller: protocol_controller.clone(),
-        block_header_sender: broadcast::channel(
-            consensus_config.broadcast_blocks_headers_channel_capacity,
-        )
-        .0,
+        block_header_sender,
         block_sender: broadcast::channel(consensus_config.broadcast_blocks_channel_capacity).0,
         filled_block_sender: broadcast::channel(
             consensus_config.broadcast_filled_blocks_channel_capacity,
@@ -707,13 +808,22 @@ async fn launch(
         .0,
     };
 
-    let (consensus_controller, consensus_manager) = start_consensus_worker(
-        consensus_config,
-        consensus_channels.clone(),
-        bootstrap_state.graph,
-        shared_storage.clone(),
-        metrics.clone(),
-    );
+    let (consensus_controller, consensus_manager): (
+        Box<dyn ConsensusController>,
+        Box<dyn ConsensusManager>,
+    ) = match light_consensus_controller {
+        Some(light_consensus_controller) => (
+            Box::new(light_consensus_controller),
+            Box::new(LightConsensusManager),
+        ),
+        None => start_consensus_worker(
+            consensus_config,
+            consensus_channels.clone(),
+            bootstrap_state.graph,
+            shared_storage.clone(),
+            metrics.clone(),
+        ),
+    };
 
     let (protocol_manager, keypair, node_id) = start_protocol_controller(
         protocol_config.clone(),
@@ -724,7 +834,7 @@ async fn launch(
         shared_storage.clone(),
         protocol_channels,
         mip_store.clone(),
-        metrics,
+        metrics.clone(),
     )
     .expect("could not start protocol controller");
 
@@ -748,14 +858,21 @@ async fn launch(
         protocol: protocol_controller.clone(),
         storage: shared_storage.clone(),
     };
-    let factory_manager = start_factory(
-        factory_config,
-        node_wallet.clone(),
-        factory_channels,
-        mip_store.clone(),
-    );
+    // a light node does not know the blockclique, so it can't produce blocks nor endorsements
+    let factory_manager = (!SETTINGS.light_client.enabled).then(|| {
+        start_factory(
+            factory_config,
+            node_wallet.clone(),
+            factory_channels,
+            mip_store.clone(),
+        )
+    });
 
-    let bootstrap_manager = bootstrap_config.listen_addr.map(|addr| {
+    // a light node has no final state to serve
+    let bootstrap_listen_addr = bootstrap_config
+        .listen_addr
+        .filter(|_| !SETTINGS.light_client.enabled);
+    let bootstrap_manager = bootstrap_listen_addr.map(|addr| {
         let (waker, listener) = BootstrapTcpListener::new(&addr).unwrap_or_else(|_| {
             panic!(
                 "{}",
@@ -770,6 +887,7 @@ async fn launch(
             bootstrap_config,
             keypair.clone(),
             *VERSION,
+            metrics,
         )
         .expect("Could not start bootstrap server");
         manager.set_listener_stopper(waker);
@@ -806,6 +924,11 @@ async fn launch(
         genesis_timestamp: *GENESIS_TIMESTAMP,
         t0: T0,
         periods_per_cycle: PERIODS_PER_CYCLE,
+        gas_estimation_margin_percent: SETTINGS.api.gas_estimation_margin_percent,
+        max_gas_per_block: MAX_GAS_PER_BLOCK,
+        max_read_only_gas: SETTINGS.execution.max_read_only_gas,
+        slot_determinism_check_timeout: SETTINGS.api.slot_determinism_check_timeout,
+        sp_compilation_cost,
     };
 
     // spawn Massa API
@@ -1019,6 +1142,8 @@ async fn launch(
         api_public_handle,
         api_handle,
         grpc_handle,
+        final_state,
+        consensus_controller,
     )
 }
 
@@ -1029,7 +1154,7 @@ struct Managers {
     selector_manager: Box<dyn SelectorManager>,
     pool_manager: Box<dyn PoolManager>,
     protocol_manager: Box<dyn ProtocolManager>,
-    factory_manager: Box<dyn FactoryManager>,
+    factory_manager: Option<Box<dyn FactoryManager>>,
 }
 
 async fn stop(
@@ -1041,7 +1166,7 @@ async fn stop(
         mut selector_manager,
         mut pool_manager,
         mut protocol_manager,
-        mut factory_manager,
+        factory_manager,
     }: Managers,
     api_private_handle: StopHandle,
     api_public_handle: StopHandle,
@@ -1075,7 +1200,9 @@ async fn stop(
     info!("API | PRIVATE JsonRPC | stopped");
 
     // stop factory
-    factory_manager.stop();
+    if let Some(mut factory_manager) = factory_manager {
+        factory_manager.stop();
+    }
 
     // stop protocol controller
     protocol_manager.stop();
@@ -1111,6 +1238,14 @@ struct Args {
     #[structopt(long = "restart-from-snapshot-at-period")]
     restart_from_snapshot_at_period: Option<u64>,
 
+    /// Bootstrap from a state snapshot file instead of a bootstrap server
+    #[structopt(long = "bootstrap-from-snapshot", parse(from_os_str))]
+    bootstrap_from_snapshot: Option<PathBuf>,
+
+    /// Export a state snapshot file when the node stops
+    #[structopt(long = "export-snapshot", parse(from_os_str))]
+    export_snapshot: Option<PathBuf>,
+
     #[cfg(feature = "op_spammer")]
     /// number of operations
     #[structopt(
@@ -1239,6 +1374,8 @@ async fn run(args: Args) -> anyhow::Result<()> {
             api_public_handle,
             api_handle,
             grpc_handle,
+            final_state,
+            consensus_controller,
         ) = launch(&cur_args, node_wallet.clone(), Arc::clone(&sig_int_toggled)).await;
 
         // interrupt signal listener
@@ -1292,6 +1429,18 @@ async fn run(args: Args) -> anyhow::Result<()> {
             }
             sleep(Duration::from_millis(100));
         };
+
+        // export the state while consensus is still running, to include its final blocks
+        if !restart {
+            if let Some(snapshot_path) = &cur_args.export_snapshot {
+                if let Err(err) =
+                    export_snapshot(snapshot_path, final_state, consensus_controller, *VERSION)
+                {
+                    error!("could not export the state snapshot: {}", err);
+                }
+            }
+        }
+
         stop(
             consensus_event_receiver,
             Managers {
@@ -1315,6 +1464,7 @@ async fn run(args: Args) -> anyhow::Result<()> {
         }
         // If we restart because of a desync, then we do not want to restart from a snapshot
         cur_args.restart_from_snapshot_at_period = None;
+        cur_args.bootstrap_from_snapshot = None;
         interrupt_signal_listener.abort();
     }
     Ok(())
@@ -3,9 +3,9 @@
 //! Build here the default node settings from the configuration file toml
 use std::{collections::HashMap, path::PathBuf};
 
-use massa_bootstrap::IpType;
+use massa_bootstrap::{BootstrapCompression, IpType};
 use massa_models::{config::build_massa_settings, node::NodeId};
-use massa_protocol_exports::PeerCategoryInfo;
+use massa_protocol_exports::{PeerCategoryInfo, ProtocolCompression};
 use massa_time::MassaTime;
 use serde::Deserialize;
 use std::net::{IpAddr, SocketAddr};
@@ -22,6 +22,12 @@ pub struct LoggingSettings {
 #[derive(Clone, Debug, Deserialize)]
 pub struct ExecutionSettings {
     pub max_final_events: usize,
+    pub event_store_enabled: bool,
+    pub event_store_path: PathBuf,
+    pub event_store_retention_periods: u64,
+    pub max_final_receipts: usize,
+    pub address_history_enabled: bool,
+    pub address_history_path: PathBuf,
     pub readonly_queue_length: usize,
     pub cursor_delay: MassaTime,
     pub stats_time_window_duration: MassaTime,
@@ -47,6 +53,8 @@ pub struct LedgerSettings {
     pub initial_ledger_path: PathBuf,
     pub disk_ledger_path: PathBuf,
     pub final_history_length: usize,
+    pub archive_mode: bool,
+    pub archive_max_periods: u64,
 }
 
 /// Bootstrap configuration.
@@ -67,9 +75,19 @@ pub struct BootstrapSettings {
     pub max_clock_delta: MassaTime,
     pub cache_duration: MassaTime,
     pub max_simultaneous_bootstraps: u32,
+    /// Max number of clients waiting for a bootstrap session once all are taken
+    pub max_bootstrap_queue_length: usize,
+    /// Max estimated wait in the queue of a bootstrap server before trying another server
+    pub max_queue_wait: MassaTime,
     pub per_ip_min_interval: MassaTime,
     pub ip_list_max_size: usize,
     pub max_bytes_read_write: f64,
+    /// Bandwidth limitation of the bootstrap server in bytes per seconds, shared fairly between sessions
+    pub max_bootstrap_bandwidth: f64,
+    /// Compression codec of the bootstrap state parts
+    pub compression: BootstrapCompression,
+    /// Number of servers the state is downloaded from at once
+    pub parallel_servers: usize,
     /// Allocated time with which to manage the bootstrap process
     pub bootstrap_timeout: MassaTime,
 }
@@ -118,6 +136,8 @@ pub struct APISettings {
     pub enable_ws: bool,
     // whether to broadcast for blocks, endorsement and operations
     pub enable_broadcast: bool,
+    pub gas_estimation_margin_percent: u64,
+    pub slot_determinism_check_timeout: MassaTime,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -135,6 +155,7 @@ pub struct Settings {
     pub factory: FactorySettings,
     pub grpc: GrpcSettings,
     pub metrics: MetricsSettings,
+    pub light_client: LightClientSettings,
 }
 
 /// Consensus configuration
@@ -180,6 +201,8 @@ pub struct MetricsSettings {
 pub struct ProtocolSettings {
     /// after `ask_block_timeout` milliseconds we try to ask a block to another node
     pub ask_block_timeout: MassaTime,
+    /// propagate blocks as headers with short salted operation ids
+    pub compact_block_relay: bool,
     /// max known blocks of current nodes we keep in memory (by node)
     pub max_known_blocks_size: usize,
     /// max known blocks of foreign nodes we keep in memory (by node)
@@ -218,6 +241,12 @@ pub struct ProtocolSettings {
     pub max_endorsements_propagation_time: MassaTime,
     /// Path for initial peers
     pub initial_peers_file: PathBuf,
+    /// Path of the file where the known peers are saved
+    pub peers_file: PathBuf,
+    /// Interval at which the known peers are saved
+    pub peers_file_dump_interval: MassaTime,
+    /// Known peers not seen for this time are forgotten
+    pub peers_max_age: MassaTime,
     /// Keypair
     pub keypair_file: PathBuf,
     /// Ip we are bind to listen to
@@ -230,12 +259,22 @@ pub struct ProtocolSettings {
     pub thread_tester_count: u8,
     /// Number of bytes we can read/write by seconds in a connection (must be a 10 multiple)
     pub read_write_limit_bytes_per_second: u64,
+    /// Compression codec of the messages carrying operations and block bodies
+    pub compression: ProtocolCompression,
+    /// Messages smaller than this size in bytes are sent uncompressed
+    pub compression_min_message_size: usize,
     /// try connection timer
     pub try_connection_timer: MassaTime,
     /// Timeout connection
     pub timeout_connection: MassaTime,
     /// Nb in connections
     pub max_in_connections: usize,
+    /// Time after which the reputation score of a peer is halved
+    pub peer_score_half_life: MassaTime,
+    /// Score under which a peer is banned temporarily
+    pub peer_score_ban_threshold: f64,
+    /// Duration of the temporary bans of peers with a low score
+    pub peer_score_ban_duration: MassaTime,
     /// Peers limits per category
     pub peers_categories: HashMap<String, PeerCategoryInfo>,
     /// Limits for default category
@@ -306,6 +345,23 @@ pub struct GrpcSettings {
     pub client_certificate_authority_root_path: PathBuf,
 }
 
+/// Light client settings
+#[derive(Debug, Deserialize, Clone)]
+pub struct LightClientSettings {
+    /// whether to run the node as a light client, following block headers only
+    pub enabled: bool,
+    /// public JSON-RPC urls of the full nodes queried for draw inputs and state proofs
+    pub full_nodes: Vec<String>,
+    /// minimum number of full nodes that must return the same draw inputs or prove the same final state values
+    pub min_agreeing_nodes: usize,
+    /// timeout of the requests sent to the full nodes
+    pub request_timeout: MassaTime,
+    /// maximum number of block headers kept in memory
+    pub max_headers: usize,
+    /// interval at which the draw inputs of upcoming cycles are fetched
+    pub draw_refresh_interval: MassaTime,
+}
+
 #[cfg(test)]
 #[test]
 fn test_load_node_config() {
@@ -490,6 +490,19 @@ impl PoSFinalState {
 
     /// Feeds the selector targeting a given draw cycle
     pub fn feed_selector(&self, draw_cycle: u64) -> PosResult<()> {
+        let (lookback_rolls, lookback_seed) = self.get_draw_inputs(draw_cycle)?;
+
+        // feed selector
+        self.selector
+            .as_ref()
+            .feed_cycle(draw_cycle, lookback_rolls, lookback_seed)
+    }
+
+    /// Gets the inputs of the selector draws of a given cycle:
+    /// the look back rolls (cycle - 3) and the look back seed (cycle - 2).
+    ///
+    /// Feeding them to a selector reproduces the draws of `draw_cycle`.
+    pub fn get_draw_inputs(&self, draw_cycle: u64) -> PosResult<(BTreeMap<Address, u64>, Hash)> {
         // get roll lookback
 
         let (lookback_rolls, lookback_state_hash) = match draw_cycle.checked_sub(3) {
@@ -541,10 +554,7 @@ impl PoSFinalState {
             None => self.initial_seeds[draw_cycle as usize],
         };
 
-        // feed selector
-        self.selector
-            .as_ref()
-            .feed_cycle(draw_cycle, lookback_rolls, lookback_seed)
+        Ok((lookback_rolls, lookback_seed))
     }
 
     /// Feeds the selector targeting a given draw cycle
@@ -1493,6 +1503,8 @@ fn test_pos_final_state_hash_computation() {
         max_history_length: 10,
         max_new_elements: 100,
         thread_count: 2,
+        enable_archive: false,
+        max_archive_periods: 1000,
     };
     let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
     let (selector_controller, _) = MockSelectorController::new_with_receiver();
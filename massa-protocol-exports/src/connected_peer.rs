@@ -0,0 +1,29 @@
+use std::net::SocketAddr;
+
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Live state of a connection with a peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectedPeerInfo {
+    /// Address of the peer
+    pub address: SocketAddr,
+    /// True if we opened the connection, false if the peer did
+    pub is_outgoing: bool,
+    /// Category of the peer IP, if any
+    pub category: Option<String>,
+    /// Time at which the connection was established
+    pub connected_since: Option<MassaTime>,
+    /// Number of bytes received from the peer on this connection
+    pub bytes_received: u64,
+    /// Number of bytes sent to the peer on this connection
+    pub bytes_sent: u64,
+    /// Time of the last message received from the peer
+    pub last_message_time: Option<MassaTime>,
+    /// Number of blocks we know the peer knows about
+    pub known_blocks: usize,
+    /// Number of operations we know the peer knows about
+    pub known_operations: usize,
+    /// Number of endorsements we know the peer knows about
+    pub known_endorsements: usize,
+}
@@ -5,6 +5,8 @@ use std::net::SocketAddr;
 
 use crate::error::ProtocolError;
 use crate::BootstrapPeers;
+use crate::ConnectedPeerInfo;
+use crate::PeerScore;
 
 use crate::PeerId;
 use massa_models::prehash::{PreHashMap, PreHashSet};
@@ -78,6 +80,18 @@ pub trait ProtocolController: Send + Sync {
     /// Unban a list of Peer Id
     fn unban_peers(&self, peer_ids: Vec<PeerId>) -> Result<(), ProtocolError>;
 
+    /// Get the reputation scores of the peers we interacted with
+    fn get_peer_scores(&self) -> Result<HashMap<PeerId, PeerScore>, ProtocolError>;
+
+    /// Get the peers we are connected to, with the live state of their connection
+    fn get_connected_peers(&self) -> Result<HashMap<PeerId, ConnectedPeerInfo>, ProtocolError>;
+
+    /// Try to connect to a peer listening at `addr`
+    fn connect_peer(&self, addr: SocketAddr) -> Result<(), ProtocolError>;
+
+    /// Close the connection with a peer, without banning it
+    fn disconnect_peer(&self, peer_id: PeerId) -> Result<(), ProtocolError>;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ProtocolController>`.
     fn clone_box(&self) -> Box<dyn ProtocolController>;
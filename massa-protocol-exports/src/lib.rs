@@ -1,18 +1,22 @@
 mod bootstrap_peers;
+mod connected_peer;
 mod controller_trait;
 mod error;
 mod peer_id;
+mod peer_score;
 mod settings;
 
 pub use bootstrap_peers::{
     BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, PeerData,
 };
+pub use connected_peer::ConnectedPeerInfo;
 pub use controller_trait::{ProtocolController, ProtocolManager};
 pub use error::ProtocolError;
 pub use peer_id::{PeerId, PeerIdDeserializer, PeerIdSerializer};
+pub use peer_score::PeerScore;
 pub use peernet::peer::PeerConnectionType;
 pub use peernet::transports::TransportType;
-pub use settings::{PeerCategoryInfo, ProtocolConfig};
+pub use settings::{PeerCategoryInfo, ProtocolCompression, ProtocolConfig};
 
 #[cfg(feature = "testing")]
 pub mod test_exports;
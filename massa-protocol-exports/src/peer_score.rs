@@ -0,0 +1,21 @@
+use massa_time::MassaTime;
+use serde::{Deserialize, Serialize};
+
+/// Reputation of a peer, built from the data it sent us and how it answered our requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerScore {
+    /// Current score, decaying towards 0 over time: positive for useful peers, negative for faulty ones
+    pub score: f64,
+    /// Average time taken by the peer to deliver the blocks we asked it
+    pub average_latency: Option<MassaTime>,
+    /// Number of blocks the peer delivered to us
+    pub useful_blocks: u64,
+    /// Number of new operations the peer sent us
+    pub useful_operations: u64,
+    /// Number of our block requests the peer didn't answer in time
+    pub timeouts: u64,
+    /// Number of times the peer sent us invalid data
+    pub invalid_data: u64,
+    /// End of the temporary ban of the peer, if it is banned because of its score
+    pub banned_until: Option<MassaTime>,
+}
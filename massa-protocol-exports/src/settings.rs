@@ -8,6 +8,7 @@ use std::{
 
 use massa_models::version::Version;
 use massa_time::MassaTime;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 use peernet::transports::TransportType;
 use serde::Deserialize;
 
@@ -19,6 +20,16 @@ pub struct PeerCategoryInfo {
     pub max_in_connections_per_ip: usize,
 }
 
+/// Compression codec of the messages carrying operations and block bodies, negotiated per connection.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum ProtocolCompression {
+    /// Send and accept uncompressed messages only.
+    None = 0u8,
+    /// Compress messages with zstd when the peer supports it (default).
+    Zstd = 1u8,
+}
+
 /// Dynamic protocol configuration mix in static settings and constants configurations.
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProtocolConfig {
@@ -28,8 +39,17 @@ pub struct ProtocolConfig {
     pub listeners: HashMap<SocketAddr, TransportType>,
     /// initial peers path
     pub initial_peers: PathBuf,
+    /// path of the file where the known peers are saved to be reloaded at restart
+    pub peers_file: PathBuf,
+    /// interval at which the known peers are saved
+    pub peers_file_dump_interval: MassaTime,
+    /// known peers not seen for this time are forgotten
+    pub peers_max_age: MassaTime,
     /// after `ask_block_timeout` milliseconds we try to ask a block to another node
     pub ask_block_timeout: MassaTime,
+    /// propagate blocks as headers with short salted operation ids, rebuilt by the receivers from the operations they know.
+    /// Negotiated during the handshake: only the peers relaying compact blocks too are sent them
+    pub compact_block_relay: bool,
     /// Max known blocks we keep in block_handler
     pub max_known_blocks_saved_size: usize,
     /// max known blocks of current nodes we keep in memory
@@ -86,6 +106,10 @@ pub struct ProtocolConfig {
     pub max_endorsements_propagation_time: MassaTime,
     /// Max message size
     pub max_message_size: usize,
+    /// Compression codec of the messages carrying operations and block bodies
+    pub compression: ProtocolCompression,
+    /// Messages smaller than this size in bytes are sent uncompressed
+    pub compression_min_message_size: usize,
     /// number of thread tester
     pub thread_tester_count: u8,
     /// Max size of the channel for command to the connectivity thread
@@ -146,6 +170,12 @@ pub struct ProtocolConfig {
     pub try_connection_timer: MassaTime,
     /// Max in connections
     pub max_in_connections: usize,
+    /// Time after which the reputation score of a peer is halved
+    pub peer_score_half_life: MassaTime,
+    /// Score under which a peer is banned temporarily
+    pub peer_score_ban_threshold: f64,
+    /// Duration of the temporary bans of peers with a low score
+    pub peer_score_ban_duration: MassaTime,
     /// Timeout connection
     pub timeout_connection: MassaTime,
     /// Number of bytes per second that can be read/write in a connection (should be a 10 multiplier)
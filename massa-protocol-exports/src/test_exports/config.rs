@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::{settings::PeerCategoryInfo, ProtocolConfig};
+use crate::{settings::PeerCategoryInfo, ProtocolCompression, ProtocolConfig};
 use massa_models::config::{ENDORSEMENT_COUNT, MAX_MESSAGE_SIZE};
 use massa_time::MassaTime;
 use tempfile::NamedTempFile;
@@ -13,6 +13,7 @@ impl Default for ProtocolConfig {
                 .path()
                 .to_path_buf(),
             ask_block_timeout: MassaTime::from_millis(500),
+            compact_block_relay: false,
             max_known_blocks_saved_size: 300,
             max_known_blocks_size: 100,
             max_node_known_blocks_size: 100,
@@ -44,6 +45,12 @@ impl Default for ProtocolConfig {
                 .expect("cannot create temp file")
                 .path()
                 .to_path_buf(),
+            peers_file: NamedTempFile::new()
+                .expect("cannot create temp file")
+                .path()
+                .to_path_buf(),
+            peers_file_dump_interval: MassaTime::from_millis(10_000),
+            peers_max_age: MassaTime::from_millis(604_800_000),
             listeners: HashMap::default(),
             thread_tester_count: 2,
             max_size_channel_commands_connectivity: 1000,
@@ -60,6 +67,8 @@ impl Default for ProtocolConfig {
             max_size_channel_commands_peer_testers: 10000,
             max_size_channel_commands_peers: 300,
             max_message_size: MAX_MESSAGE_SIZE as usize,
+            compression: ProtocolCompression::Zstd,
+            compression_min_message_size: 1024,
             endorsement_count: ENDORSEMENT_COUNT,
             max_size_block_infos: 200,
             max_size_value_datastore: 1_000_000,
@@ -78,6 +87,9 @@ impl Default for ProtocolConfig {
             try_connection_timer: MassaTime::from_millis(5000),
             routable_ip: None,
             max_in_connections: 10,
+            peer_score_half_life: MassaTime::from_millis(3_600_000),
+            peer_score_ban_threshold: -100.0,
+            peer_score_ban_duration: MassaTime::from_millis(3_600_000),
             debug: true,
             peers_categories: HashMap::default(),
             default_category_info: PeerCategoryInfo {
@@ -5,22 +5,30 @@ use massa_metrics::MassaMetrics;
 use massa_models::stats::NetworkStats;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
-use massa_protocol_exports::{PeerCategoryInfo, PeerId, ProtocolConfig, ProtocolError};
+use massa_protocol_exports::{
+    ConnectedPeerInfo, PeerCategoryInfo, PeerId, ProtocolConfig, ProtocolError,
+};
 use massa_storage::Storage;
 use massa_versioning::versioning::MipStore;
 use parking_lot::RwLock;
 use peernet::peer::PeerConnectionType;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::{collections::HashMap, net::IpAddr};
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+};
 use std::{thread::JoinHandle, time::Duration};
 use tracing::{info, warn};
 
+use crate::{handlers::peer_handler::PeerManagementHandler, messages::MessagesHandler};
 use crate::{
-    handlers::peer_handler::models::{InitialPeers, PeerState, SharedPeerDB},
+    handlers::peer_handler::{
+        models::{InitialPeers, PeerState, SharedPeerDB},
+        scores::PeerScores,
+    },
     worker::ProtocolChannels,
 };
-use crate::{handlers::peer_handler::PeerManagementHandler, messages::MessagesHandler};
 use crate::{
     handlers::{
         block_handler::{cache::BlockCache, BlockHandler},
@@ -41,6 +49,11 @@ pub enum ConnectivityCommand {
             HashMap<PeerId, (SocketAddr, PeerConnectionType)>,
         )>,
     },
+    GetConnectedPeers {
+        responder: MassaSender<HashMap<PeerId, ConnectedPeerInfo>>,
+    },
+    ConnectPeer(SocketAddr),
+    DisconnectPeer(PeerId),
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -116,11 +129,15 @@ pub(crate) fn start_connectivity_thread(
                 config.max_node_known_blocks_size.try_into().unwrap(),
             )));
 
+            let peer_scores = Arc::new(RwLock::new(PeerScores::new(&config)));
+            let connection_stats = messages_handler.connection_stats.clone();
+
             // Start handlers
             let mut peer_management_handler = PeerManagementHandler::new(
                 initial_peers,
                 peer_id,
                 peer_db.clone(),
+                peer_scores.clone(),
                 channel_peers,
                 protocol_channels.peer_management_handler,
                 messages_handler,
@@ -142,6 +159,7 @@ pub(crate) fn start_connectivity_thread(
                 sender_operations_propagation_ext.clone(),
                 protocol_channels.operation_handler_propagation.1.clone(),
                 peer_management_handler.sender.command_sender.clone(),
+                peer_scores.clone(),
                 massa_metrics.clone(),
             );
             let mut endorsement_handler = EndorsementHandler::new(
@@ -173,14 +191,18 @@ pub(crate) fn start_connectivity_thread(
                 sender_endorsements_propagation_ext,
                 peer_management_handler.sender.command_sender.clone(),
                 config.clone(),
-                endorsement_cache,
-                operation_cache,
-                block_cache,
+                endorsement_cache.clone(),
+                operation_cache.clone(),
+                block_cache.clone(),
+                peer_scores.clone(),
                 storage.clone_without_refs(),
                 mip_store,
                 massa_metrics.clone(),
             );
 
+            // peers with a state left after their connection closed, forgotten at the next tick
+            let mut unconnected_peers = HashSet::new();
+
             //Try to connect to peers
             loop {
                 select! {
@@ -220,6 +242,40 @@ pub(crate) fn start_connectivity_thread(
                                     }).collect();
                                     responder.try_send((stats, peers)).unwrap_or_else(|_| warn!("Failed to send stats to responder"));
                                 }
+                                Ok(ConnectivityCommand::GetConnectedPeers { responder }) => {
+                                    let peers_connected = network_controller.get_active_connections().get_peers_connected();
+                                    let connection_stats_read = connection_stats.read();
+                                    let block_cache_read = block_cache.read();
+                                    let operation_cache_read = operation_cache.read();
+                                    let endorsement_cache_read = endorsement_cache.read();
+                                    let peers: HashMap<PeerId, ConnectedPeerInfo> = peers_connected.into_iter().map(|(peer_id, (address, connection_type, category))| {
+                                        let stats = connection_stats_read.get(&peer_id);
+                                        let info = ConnectedPeerInfo {
+                                            address,
+                                            is_outgoing: connection_type == PeerConnectionType::OUT,
+                                            category,
+                                            connected_since: stats.and_then(|stats| stats.connected_since),
+                                            bytes_received: stats.map_or(0, |stats| stats.bytes_received),
+                                            bytes_sent: stats.map_or(0, |stats| stats.bytes_sent),
+                                            last_message_time: stats.and_then(|stats| stats.last_message_time),
+                                            known_blocks: block_cache_read.blocks_known_by_peer.peek(&peer_id).map_or(0, |(blocks, _)| blocks.len()),
+                                            known_operations: operation_cache_read.ops_known_by_peer.peek(&peer_id).map_or(0, |ops| ops.len()),
+                                            known_endorsements: endorsement_cache_read.endorsements_known_by_peer.peek(&peer_id).map_or(0, |endorsements| endorsements.len()),
+                                        };
+                                        (peer_id, info)
+                                    }).collect();
+                                    responder.try_send(peers).unwrap_or_else(|_| warn!("Failed to send connected peers to responder"));
+                                }
+                                Ok(ConnectivityCommand::ConnectPeer(addr)) => {
+                                    info!("Trying to connect to addr {} on request", addr);
+                                    if let Err(err) = network_controller.try_connect(addr, config.timeout_connection.to_duration()) {
+                                        warn!("Failed to connect to peer {:?}: {:?}", addr, err);
+                                    }
+                                }
+                                Ok(ConnectivityCommand::DisconnectPeer(peer_id)) => {
+                                    info!("Disconnecting peer {} on request", peer_id);
+                                    network_controller.get_active_connections().shutdown_connection(&peer_id);
+                                }
                                 Err(_) => {
                                     warn!("Channel to connectivity thread is closed. Stopping the protocol");
                                     break;
@@ -229,6 +285,8 @@ pub(crate) fn start_connectivity_thread(
                     default(config.try_connection_timer.to_duration()) => {
                         let active_conn = network_controller.get_active_connections();
                         let peers_connected = active_conn.get_peers_connected();
+                        // forget the codecs, compact block support and traffic of the closed connections
+                        active_conn.forget_closed_connections(&mut unconnected_peers);
                         // update massa metrics
                         massa_metrics.set_active_connections(active_conn.get_nb_in_connections(), active_conn.get_nb_out_connections());
 
@@ -247,7 +305,13 @@ pub(crate) fn start_connectivity_thread(
                         let mut addresses_to_connect: Vec<SocketAddr> = Vec::new();
                         {
                             let peer_db_read = peer_db.read();
-                            for (_, peer_id) in &peer_db_read.index_by_newest {
+                            // try the peers with the best scores first, then the newest ones
+                            let mut candidates: Vec<&PeerId> = peer_db_read.index_by_newest.iter().map(|(_, peer_id)| peer_id).collect();
+                            {
+                                let peer_scores_read = peer_scores.read();
+                                candidates.sort_by(|a, b| peer_scores_read.score(b).total_cmp(&peer_scores_read.score(a)));
+                            }
+                            for peer_id in candidates {
                                 if peers_connected.contains_key(peer_id) {
                                     continue;
                                 }
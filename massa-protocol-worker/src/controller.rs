@@ -7,7 +7,9 @@ use massa_models::{
     prehash::{PreHashMap, PreHashSet},
     stats::NetworkStats,
 };
-use massa_protocol_exports::{BootstrapPeers, PeerId, ProtocolController, ProtocolError};
+use massa_protocol_exports::{
+    BootstrapPeers, ConnectedPeerInfo, PeerId, PeerScore, ProtocolController, ProtocolError,
+};
 use massa_storage::Storage;
 use peernet::peer::PeerConnectionType;
 
@@ -188,6 +190,50 @@ impl ProtocolController for ProtocolControllerImpl {
         })
     }
 
+    fn get_peer_scores(&self) -> Result<HashMap<PeerId, PeerScore>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_peer_scores".to_string(), Some(1));
+        self.sender_peer_management_thread
+            .as_ref()
+            .unwrap()
+            .try_send(PeerManagementCmd::GetPeerScores { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_peer_scores command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_peer_scores command receive error".into())
+        })
+    }
+
+    fn get_connected_peers(&self) -> Result<HashMap<PeerId, ConnectedPeerInfo>, ProtocolError> {
+        let (sender, receiver) = MassaChannel::new("get_connected_peers".to_string(), Some(1));
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::GetConnectedPeers { responder: sender })
+            .map_err(|_| {
+                ProtocolError::ChannelError("get_connected_peers command send error".into())
+            })?;
+        receiver.recv_timeout(Duration::from_secs(10)).map_err(|_| {
+            ProtocolError::ChannelError("get_connected_peers command receive error".into())
+        })
+    }
+
+    fn connect_peer(&self, addr: SocketAddr) -> Result<(), ProtocolError> {
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::ConnectPeer(addr))
+            .map_err(|_| ProtocolError::ChannelError("connect_peer command send error".into()))
+    }
+
+    fn disconnect_peer(&self, peer_id: PeerId) -> Result<(), ProtocolError> {
+        self.sender_connectivity_thread
+            .as_ref()
+            .unwrap()
+            .try_send(ConnectivityCommand::DisconnectPeer(peer_id))
+            .map_err(|_| ProtocolError::ChannelError("disconnect_peer command send error".into()))
+    }
+
     fn clone_box(&self) -> Box<dyn ProtocolController> {
         Box::new(self.clone())
     }
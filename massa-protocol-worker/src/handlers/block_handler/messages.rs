@@ -1,4 +1,4 @@
-use massa_hash::HashDeserializer;
+use massa_hash::{Hash, HashDeserializer, HASH_SIZE_BYTES};
 use massa_models::{
     block_header::{BlockHeader, BlockHeaderDeserializer, SecuredHeader},
     block_id::{BlockId, BlockIdSerializer},
@@ -6,12 +6,14 @@ use massa_models::{
         OperationId, OperationIdSerializer, OperationIdsDeserializer, OperationsDeserializer,
         SecureShareOperation,
     },
-    secure_share::{SecureShareDeserializer, SecureShareSerializer},
+    secure_share::{Id, SecureShareDeserializer, SecureShareSerializer},
 };
 use massa_serialization::{
-    Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
+    Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
 };
 use nom::{
+    bytes::complete::take,
     error::{context, ContextError, ParseError},
     multi::length_count,
     sequence::tuple,
@@ -20,6 +22,36 @@ use nom::{
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use std::ops::Bound::Included;
 
+/// Size in bytes of the short operation ids of a compact block
+pub const SHORT_OPERATION_ID_SIZE: usize = 6;
+
+/// Operation id shortened and salted for a compact block
+pub type ShortOperationId = [u8; SHORT_OPERATION_ID_SIZE];
+
+/// Computes the short id of an operation in a compact block salted with `salt`.
+///
+/// The salt is drawn by the sender of each compact block so that no one can craft colliding operations in advance.
+pub fn short_operation_id(salt: u64, operation_id: &OperationId) -> ShortOperationId {
+    let mut bytes = Vec::with_capacity(8 + HASH_SIZE_BYTES);
+    bytes.extend(salt.to_le_bytes());
+    bytes.extend(operation_id.get_hash().to_bytes());
+    let mut short_id = [0u8; SHORT_OPERATION_ID_SIZE];
+    short_id.copy_from_slice(&Hash::compute_from(&bytes).to_bytes()[..SHORT_OPERATION_ID_SIZE]);
+    short_id
+}
+
+/// Block header sent along with the short ids of the block operations,
+/// so that the receiver can rebuild the block from the operations it already has.
+#[derive(Debug, Clone)]
+pub struct CompactBlock {
+    /// Block header
+    pub header: SecuredHeader,
+    /// Salt of the short operation ids
+    pub salt: u64,
+    /// Short ids of the block operations, in the block order
+    pub short_operation_ids: Vec<ShortOperationId>,
+}
+
 /// Ask for the info about a block.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum AskForBlocksInfo {
@@ -30,6 +62,9 @@ pub enum AskForBlocksInfo {
     Info,
     /// The actual operations are required.
     Operations(Vec<OperationId>),
+    /// The operations at these positions in the block are required
+    /// (the ones missing to rebuild a compact block).
+    OperationsAt(Vec<u32>),
 }
 
 #[derive(Debug, Clone)]
@@ -45,7 +80,7 @@ pub enum BlockInfoReply {
     NotFound,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 //TODO: Fix this clippy warning
 #[allow(clippy::large_enum_variant)]
 pub enum BlockMessage {
@@ -55,6 +90,8 @@ pub enum BlockMessage {
     AskForBlocks(Vec<(BlockId, AskForBlocksInfo)>),
     /// Message replying with info on a list of blocks.
     ReplyForBlocks(Vec<(BlockId, BlockInfoReply)>),
+    /// Block header with the short ids of the block operations
+    CompactBlock(CompactBlock),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -63,6 +100,7 @@ pub enum MessageTypeId {
     BlockHeader,
     AskForBlocks,
     ReplyForBlocks,
+    CompactBlock,
 }
 
 impl From<&BlockMessage> for MessageTypeId {
@@ -71,6 +109,7 @@ impl From<&BlockMessage> for MessageTypeId {
             BlockMessage::BlockHeader(_) => MessageTypeId::BlockHeader,
             BlockMessage::AskForBlocks(_) => MessageTypeId::AskForBlocks,
             BlockMessage::ReplyForBlocks(_) => MessageTypeId::ReplyForBlocks,
+            BlockMessage::CompactBlock(_) => MessageTypeId::CompactBlock,
         }
     }
 }
@@ -82,6 +121,7 @@ pub enum BlockInfoType {
     Info = 1,
     Operations = 2,
     NotFound = 3,
+    OperationsAt = 4,
 }
 
 #[derive(Default, Clone)]
@@ -91,6 +131,7 @@ pub struct BlockMessageSerializer {
     length_serializer: U64VarIntSerializer,
     block_id_serializer: BlockIdSerializer,
     operation_id_serializer: OperationIdSerializer,
+    operation_index_serializer: U32VarIntSerializer,
 }
 
 impl BlockMessageSerializer {
@@ -101,6 +142,7 @@ impl BlockMessageSerializer {
             length_serializer: U64VarIntSerializer::new(),
             block_id_serializer: BlockIdSerializer::new(),
             operation_id_serializer: OperationIdSerializer::new(),
+            operation_index_serializer: U32VarIntSerializer::new(),
         }
     }
 }
@@ -146,6 +188,15 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
                                     .serialize(operation_id, buffer)?;
                             }
                         }
+                        AskForBlocksInfo::OperationsAt(indexes) => {
+                            self.id_serializer
+                                .serialize(&(BlockInfoType::OperationsAt as u64), buffer)?;
+                            self.length_serializer
+                                .serialize(&(indexes.len() as u64), buffer)?;
+                            for index in indexes {
+                                self.operation_index_serializer.serialize(index, buffer)?;
+                            }
+                        }
                     }
                 }
             }
@@ -186,6 +237,17 @@ impl Serializer<BlockMessage> for BlockMessageSerializer {
                     }
                 }
             }
+            BlockMessage::CompactBlock(compact_block) => {
+                self.secure_share_serializer
+                    .serialize(&compact_block.header, buffer)?;
+                self.length_serializer
+                    .serialize(&compact_block.salt, buffer)?;
+                self.length_serializer
+                    .serialize(&(compact_block.short_operation_ids.len() as u64), buffer)?;
+                for short_id in &compact_block.short_operation_ids {
+                    buffer.extend(short_id);
+                }
+            }
         }
         Ok(())
     }
@@ -198,6 +260,8 @@ pub struct BlockMessageDeserializer {
     hash_deserializer: HashDeserializer,
     operation_ids_deserializer: OperationIdsDeserializer,
     operations_deserializer: OperationsDeserializer,
+    operations_length_deserializer: U32VarIntDeserializer,
+    operation_index_deserializer: U32VarIntDeserializer,
 }
 
 pub struct BlockMessageDeserializerArgs {
@@ -233,6 +297,14 @@ impl BlockMessageDeserializer {
             operation_ids_deserializer: OperationIdsDeserializer::new(
                 args.max_operations_per_block,
             ),
+            operations_length_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(args.max_operations_per_block),
+            ),
+            operation_index_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(args.max_operations_per_block.saturating_sub(1)),
+            ),
             operations_deserializer: OperationsDeserializer::new(
                 args.max_operations_per_block,
                 args.max_datastore_value_length,
@@ -301,6 +373,17 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                                             .map(|(rest, operation_ids)| {
                                                 (rest, AskForBlocksInfo::Operations(operation_ids))
                                             }),
+                                        BlockInfoType::OperationsAt => length_count(
+                                            |input| {
+                                                self.operations_length_deserializer
+                                                    .deserialize(input)
+                                            },
+                                            |input| {
+                                                self.operation_index_deserializer.deserialize(input)
+                                            },
+                                        )
+                                        .map(AskForBlocksInfo::OperationsAt)
+                                        .parse(rest),
                                         BlockInfoType::NotFound => {
                                             Err(nom::Err::Error(ParseError::from_error_kind(
                                                 buffer,
@@ -360,6 +443,12 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                                         BlockInfoType::NotFound => {
                                             Ok((rest, BlockInfoReply::NotFound))
                                         }
+                                        BlockInfoType::OperationsAt => {
+                                            Err(nom::Err::Error(ParseError::from_error_kind(
+                                                buffer,
+                                                nom::error::ErrorKind::Digit,
+                                            )))
+                                        }
                                     }
                                 }),
                             )),
@@ -368,6 +457,40 @@ impl Deserializer<BlockMessage> for BlockMessageDeserializer {
                 )
                 .map(BlockMessage::ReplyForBlocks)
                 .parse(buffer),
+                MessageTypeId::CompactBlock => context(
+                    "Failed CompactBlock deserialization",
+                    tuple((
+                        context("Failed header deserialization", |input| {
+                            self.block_header_deserializer.deserialize(input)
+                        }),
+                        context("Failed salt deserialization", |input| {
+                            self.id_deserializer.deserialize(input)
+                        }),
+                        context(
+                            "Failed short operation ids deserialization",
+                            length_count(
+                                |input| self.operations_length_deserializer.deserialize(input),
+                                |input| {
+                                    take(SHORT_OPERATION_ID_SIZE)
+                                        .map(|bytes: &[u8]| {
+                                            let mut short_id = [0u8; SHORT_OPERATION_ID_SIZE];
+                                            short_id.copy_from_slice(bytes);
+                                            short_id
+                                        })
+                                        .parse(input)
+                                },
+                            ),
+                        ),
+                    )),
+                )
+                .map(|(header, salt, short_operation_ids)| {
+                    BlockMessage::CompactBlock(CompactBlock {
+                        header,
+                        salt,
+                        short_operation_ids,
+                    })
+                })
+                .parse(buffer),
             }
         })
         .parse(buffer)
@@ -24,7 +24,7 @@ mod messages;
 mod propagation;
 mod retrieval;
 
-pub(crate) use messages::{BlockMessage, BlockMessageSerializer};
+pub(crate) use messages::{short_operation_id, BlockMessage, BlockMessageSerializer, CompactBlock};
 
 #[cfg(feature = "testing")]
 pub use messages::{
@@ -38,7 +38,10 @@ use super::{
     operation_handler::{
         cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
     },
-    peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    peer_handler::{
+        models::{PeerManagementCmd, PeerMessageTuple},
+        scores::SharedPeerScores,
+    },
 };
 
 pub struct BlockHandler {
@@ -66,6 +69,7 @@ impl BlockHandler {
         endorsement_cache: SharedEndorsementCache,
         operation_cache: SharedOperationCache,
         cache: SharedBlockCache,
+        peer_scores: SharedPeerScores,
         storage: Storage,
         mip_store: MipStore,
         massa_metrics: MassaMetrics,
@@ -85,6 +89,7 @@ impl BlockHandler {
             endorsement_cache,
             operation_cache,
             cache.clone(),
+            peer_scores,
             storage.clone_without_refs(),
             mip_store,
             massa_metrics,
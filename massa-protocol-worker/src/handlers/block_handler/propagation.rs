@@ -6,10 +6,14 @@ use massa_models::{block_id::BlockId, prehash::PreHashSet};
 use massa_protocol_exports::PeerId;
 use massa_protocol_exports::{ProtocolConfig, ProtocolError};
 use massa_storage::Storage;
+use rand::Rng;
 use tracing::{debug, info, warn};
 
 use crate::{
-    handlers::{block_handler::BlockMessage, peer_handler::models::PeerManagementCmd},
+    handlers::{
+        block_handler::{short_operation_id, BlockMessage, CompactBlock},
+        peer_handler::models::PeerManagementCmd,
+    },
     messages::MessagesSerializer,
     wrap_network::ActiveConnectionsTrait,
 };
@@ -41,7 +45,7 @@ impl PropagationThread {
                                 "protocol.protocol_worker.process_command.integrated_block.begin",
                                 { "block_id": block_id }
                             );
-                            let header = {
+                            let message = {
                                 let block = {
                                     let blocks = storage.read_blocks();
                                     blocks.get(&block_id).cloned()
@@ -57,7 +61,22 @@ impl PropagationThread {
                                         ids_to_delete.insert(block_id);
                                         self.storage.drop_block_refs(&ids_to_delete);
                                     }
-                                    block.content.header.clone()
+                                    if self.config.compact_block_relay {
+                                        // a fresh salt for each block prevents crafting colliding operations
+                                        let salt = rand::thread_rng().gen::<u64>();
+                                        BlockMessage::CompactBlock(CompactBlock {
+                                            header: block.content.header.clone(),
+                                            salt,
+                                            short_operation_ids: block
+                                                .content
+                                                .operations
+                                                .iter()
+                                                .map(|id| short_operation_id(salt, id))
+                                                .collect(),
+                                        })
+                                    } else {
+                                        BlockMessage::BlockHeader(block.content.header.clone())
+                                    }
                                 } else {
                                     warn!("Block {} not found in storage", &block_id);
                                     continue;
@@ -82,13 +101,13 @@ impl PropagationThread {
                                     if !cond.map_or_else(|| false, |v| v.0) {
                                         massa_trace!("protocol.protocol_worker.process_command.integrated_block.send_header", { "peer_id": peer_id, "block_id": block_id});
                                         debug!(
-                                            "Send block header for slot {} to peer {}",
-                                            peer_id, header.content.slot
+                                            "Send block header of block {} to peer {}",
+                                            block_id, peer_id
                                         );
                                         if let Err(err) = self.active_connections.send_to_peer(
                                             peer_id,
                                             &self.block_serializer,
-                                            BlockMessage::BlockHeader(header.clone()).into(),
+                                            message.clone().into(),
                                             true,
                                         ) {
                                             warn!("Error while sending block header to peer {} err: {:?}", peer_id, err);
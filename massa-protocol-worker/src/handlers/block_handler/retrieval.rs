@@ -13,7 +13,10 @@ use crate::{
         operation_handler::{
             cache::SharedOperationCache, commands_propagation::OperationHandlerPropagationCommand,
         },
-        peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+        peer_handler::{
+            models::{PeerManagementCmd, PeerMessageTuple},
+            scores::{PeerScoreEvent, SharedPeerScores},
+        },
     },
     messages::MessagesSerializer,
     sig_verifier::verify_sigs_batch,
@@ -55,14 +58,97 @@ use super::{
     commands_propagation::BlockHandlerPropagationCommand,
     commands_retrieval::BlockHandlerRetrievalCommand,
     messages::{
-        AskForBlocksInfo, BlockInfoReply, BlockMessage, BlockMessageDeserializer,
-        BlockMessageDeserializerArgs,
+        short_operation_id, AskForBlocksInfo, BlockInfoReply, BlockMessage,
+        BlockMessageDeserializer, BlockMessageDeserializerArgs, CompactBlock, ShortOperationId,
     },
     BlockMessageSerializer,
 };
 
 static BLOCK_HEADER: &str = "protocol.protocol_worker.on_network_event.received_block_header";
 
+/// Max number of compact blocks kept until their block enters the wishlist
+const MAX_PENDING_COMPACT_BLOCKS: u32 = 256;
+
+/// Operations of a block received as a compact block, matched with the operations we know
+#[derive(Debug, Clone)]
+pub(crate) struct CompactBlockOperations {
+    /// Peer that sent the compact block
+    peer_id: PeerId,
+    /// Salt of the short operation ids
+    salt: u64,
+    /// Positions in the block of each short id
+    positions: HashMap<ShortOperationId, Vec<usize>>,
+    /// Operation matched with each position, None if not found yet
+    operation_ids: Vec<Option<OperationId>>,
+    /// Operations of the storage already compared with the short ids
+    checked: PreHashSet<OperationId>,
+}
+
+impl CompactBlockOperations {
+    fn new(peer_id: PeerId, salt: u64, short_ids: Vec<ShortOperationId>) -> Self {
+        let mut positions: HashMap<ShortOperationId, Vec<usize>> = HashMap::new();
+        for (index, short_id) in short_ids.iter().enumerate() {
+            positions.entry(*short_id).or_default().push(index);
+        }
+        CompactBlockOperations {
+            peer_id,
+            salt,
+            positions,
+            operation_ids: vec![None; short_ids.len()],
+            checked: PreHashSet::default(),
+        }
+    }
+
+    /// Whether the short id of `operation_id` is one of the short ids of the block
+    fn contains(&self, operation_id: &OperationId) -> bool {
+        self.positions
+            .contains_key(&short_operation_id(self.salt, operation_id))
+    }
+
+    /// Matches `operation_id` with the positions of its short id not found yet
+    fn match_operation(&mut self, operation_id: OperationId) {
+        let short_id = short_operation_id(self.salt, &operation_id);
+        if let Some(indexes) = self.positions.get(&short_id) {
+            for index in indexes {
+                self.operation_ids[*index].get_or_insert(operation_id);
+            }
+        }
+    }
+
+    /// Matches the short ids not found yet with the operations in `storage`,
+    /// which holds the operations of the pool as well as the ones received for blocks.
+    /// Each operation is hashed only once, the first time it is seen in `storage`.
+    fn resolve(&mut self, storage: &Storage) {
+        if self.operation_ids.iter().all(Option::is_some) {
+            return;
+        }
+        let new_ids: Vec<OperationId> = storage
+            .read_operations()
+            .ids()
+            .filter(|operation_id| self.checked.insert(**operation_id))
+            .copied()
+            .collect();
+        for operation_id in new_ids {
+            self.match_operation(operation_id);
+        }
+    }
+
+    /// Positions in the block of the operations not found yet
+    fn missing_indexes(&self) -> Vec<u32> {
+        self.operation_ids
+            .iter()
+            .enumerate()
+            .filter(|(_, operation_id)| operation_id.is_none())
+            .map(|(index, _)| index as u32)
+            .collect()
+    }
+
+    /// Operations of the block, if they were all found
+    fn operation_ids(&self) -> Option<Vec<OperationId>> {
+        self.operation_ids.iter().copied().collect()
+    }
+}
+
 /// Info about a block we've seen
 #[derive(Debug, Clone)]
 pub(crate) struct BlockInfo {
@@ -75,6 +161,8 @@ pub(crate) struct BlockInfo {
     pub(crate) storage: Storage,
     /// Full operations size in bytes
     pub(crate) operations_size: usize,
+    /// Operations announced by a compact block, until the operation ids are known
+    pub(crate) compact_operations: Option<CompactBlockOperations>,
 }
 
 impl BlockInfo {
@@ -84,6 +172,7 @@ impl BlockInfo {
             operation_ids: None,
             storage,
             operations_size: 0,
+            compact_operations: None,
         }
     }
 }
@@ -98,6 +187,8 @@ pub struct RetrievalThread {
     receiver: MassaReceiver<BlockHandlerRetrievalCommand>,
     block_message_serializer: MessagesSerializer,
     block_wishlist: PreHashMap<BlockId, BlockInfo>,
+    /// Compact blocks received before their block entered the wishlist
+    pending_compact_blocks: LruMap<BlockId, CompactBlockOperations>,
     asked_blocks: HashMap<PeerId, PreHashMap<BlockId, Instant>>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
     sender_propagation_ops: MassaSender<OperationHandlerPropagationCommand>,
@@ -106,6 +197,7 @@ pub struct RetrievalThread {
     operation_cache: SharedOperationCache,
     next_timer_ask_block: Instant,
     cache: SharedBlockCache,
+    peer_scores: SharedPeerScores,
     config: ProtocolConfig,
     storage: Storage,
     mip_store: MipStore,
@@ -189,6 +281,12 @@ impl RetrievalThread {
                                         }
                                     }
                                 }
+                                BlockMessage::CompactBlock(compact_block) => {
+                                    massa_trace!(BLOCK_HEADER, { "peer_id": peer_id, "header": compact_block.header});
+                                    if let Err(err) = self.on_compact_block_received(peer_id, compact_block) {
+                                        warn!("Error in on_compact_block_received: {:?}", err);
+                                    }
+                                }
                             }
                         },
                         Err(_) => {
@@ -204,11 +302,12 @@ impl RetrievalThread {
                             match command {
                                 BlockHandlerRetrievalCommand::WishlistDelta { new, remove } => {
                                     massa_trace!("protocol.protocol_worker.process_command.wishlist_delta.begin", { "new": new, "remove": remove });
+                                    let mut new_block_ids = Vec::with_capacity(new.len());
                                     for (block_id, header) in new.into_iter() {
-                                        self.block_wishlist.insert(
-                                            block_id,
-                                            BlockInfo::new(header, self.storage.clone_without_refs()),
-                                        );
+                                        let mut info = BlockInfo::new(header, self.storage.clone_without_refs());
+                                        info.compact_operations = self.pending_compact_blocks.remove(&block_id);
+                                        self.block_wishlist.insert(block_id, info);
+                                        new_block_ids.push(block_id);
                                     }
                                     // Remove the knowledge that we asked this block to nodes.
                                     self.remove_asked_blocks_of_node(&remove);
@@ -217,6 +316,11 @@ impl RetrievalThread {
                                     for block_id in remove.iter() {
                                         self.block_wishlist.remove(block_id);
                                     }
+                                    for block_id in new_block_ids {
+                                        if let Err(err) = self.try_rebuild_compact_block(block_id) {
+                                            warn!("Error in try_rebuild_compact_block: {:?}", err);
+                                        }
+                                    }
                                     if let Err(err) = self.update_ask_block() {
                                         warn!("Error in update_ask_blocks: {:?}", err);
                                     }
@@ -323,6 +427,27 @@ impl RetrievalThread {
                     };
                     BlockInfoReply::Operations(needed_ops)
                 }
+                AskForBlocksInfo::OperationsAt(indexes) => {
+                    {
+                        let mut cache_write = self.cache.write();
+                        cache_write.insert_blocks_known(
+                            &from_peer_id,
+                            &[*hash],
+                            true,
+                            Instant::now(),
+                        );
+                    }
+                    let needed_ops = {
+                        let operations = self.storage.read_operations();
+                        indexes
+                            .iter()
+                            .filter_map(|index| operations_ids.get(*index as usize))
+                            .filter_map(|id| operations.get(id))
+                            .cloned()
+                            .collect()
+                    };
+                    BlockInfoReply::Operations(needed_ops)
+                }
             };
             all_blocks_info.push((*hash, block_info));
         }
@@ -366,10 +491,19 @@ impl RetrievalThread {
                 self.on_block_operation_list_received(from_peer_id, block_id, operation_list)
             }
             BlockInfoReply::Operations(operations) => {
-                // Send operations to pool,
-                // before performing the below checks,
-                // and wait for them to have been procesed(i.e. added to storage).
-                self.on_block_full_operations_received(from_peer_id, block_id, operations)
+                let rebuilding_compact_block =
+                    self.block_wishlist.get(&block_id).map_or(false, |info| {
+                        info.operation_ids.is_none() && info.compact_operations.is_some()
+                    });
+                if rebuilding_compact_block {
+                    // The operations missing to rebuild a compact block
+                    self.on_compact_block_operations_received(from_peer_id, block_id, operations)
+                } else {
+                    // Send operations to pool,
+                    // before performing the below checks,
+                    // and wait for them to have been procesed(i.e. added to storage).
+                    self.on_block_full_operations_received(from_peer_id, block_id, operations)
+                }
             }
             BlockInfoReply::NotFound => {
                 {
@@ -441,9 +575,157 @@ impl RetrievalThread {
         let mut set = PreHashSet::<BlockId>::with_capacity(1);
         set.insert(block_id);
         self.remove_asked_blocks_of_node(&set);
+        self.try_rebuild_compact_block(block_id)
+    }
+
+    /// On compact block received from a node.
+    /// The header is processed like a header announcement, and the short operation ids are kept
+    /// to rebuild the block from the operations we know once it is in `block_wishlist`.
+    fn on_compact_block_received(
+        &mut self,
+        from_peer_id: PeerId,
+        compact_block: CompactBlock,
+    ) -> Result<(), ProtocolError> {
+        let CompactBlock {
+            header,
+            salt,
+            short_operation_ids,
+        } = compact_block;
+        let Ok(Some((block_id, is_new))) = self.note_header_from_peer(&header, &from_peer_id) else {
+            warn!(
+                "peer {} sent us critically incorrect compact block header, \
+                which may be an attack attempt by the remote peer \
+                or a loss of sync between us and the remote peer",
+                from_peer_id,
+            );
+            if let Err(err) = self.ban_node(&from_peer_id) {
+                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+            }
+            return Ok(());
+        };
+        let compact_operations =
+            CompactBlockOperations::new(from_peer_id, salt, short_operation_ids);
+        if let Some(info) = self.block_wishlist.get_mut(&block_id) {
+            if info.operation_ids.is_none() && info.compact_operations.is_none() {
+                info.compact_operations = Some(compact_operations);
+            }
+        } else {
+            self.pending_compact_blocks
+                .insert(block_id, compact_operations);
+        }
+        if is_new {
+            self.consensus_controller
+                .register_block_header(block_id, header);
+        }
+        self.try_rebuild_compact_block(block_id)?;
+        self.update_ask_block()
+    }
+
+    /// Tries to rebuild a block of the wishlist announced by a compact block from the operations we know.
+    ///
+    /// Once all the short ids are matched, the operation list is checked against the header
+    /// and processed as if it was received from the sender of the compact block.
+    /// If it doesn't match (short ids collision), we fall back to asking the operation list.
+    /// Otherwise, the operations still missing are asked by `update_ask_block`.
+    fn try_rebuild_compact_block(&mut self, block_id: BlockId) -> Result<(), ProtocolError> {
+        let Some(info) = self.block_wishlist.get_mut(&block_id) else {
+            return Ok(());
+        };
+        if info.operation_ids.is_some() {
+            return Ok(());
+        }
+        let (Some(header), Some(compact_operations)) =
+            (&info.header, info.compact_operations.as_mut()) else {
+            return Ok(());
+        };
+        compact_operations.resolve(&self.storage);
+        let Some(operation_ids) = compact_operations.operation_ids() else {
+            return Ok(());
+        };
+        let from_peer_id = compact_operations.peer_id.clone();
+        let mut total_hash: Vec<u8> =
+            Vec::with_capacity(operation_ids.len().saturating_mul(HASH_SIZE_BYTES));
+        operation_ids.iter().for_each(|op_id| {
+            total_hash.extend(op_id.get_hash().to_bytes());
+        });
+        if header.content.operation_merkle_root != Hash::compute_from(&total_hash) {
+            debug!(
+                "Compact block {} from peer {} doesn't match its header, asking the operation list",
+                block_id, from_peer_id
+            );
+            info.compact_operations = None;
+            return Ok(());
+        }
+
+        self.on_block_operation_list_received(from_peer_id.clone(), block_id, operation_ids)?;
+
+        // all the operations are known: the block can be processed without asking anything
+        let all_operations_known = self.block_wishlist.get(&block_id).map_or(false, |info| {
+            info.operation_ids.as_ref().map_or(false, |operation_ids| {
+                !operation_ids.is_empty()
+                    && operation_ids
+                        .iter()
+                        .all(|id| info.storage.get_op_refs().contains(id))
+            })
+        });
+        if all_operations_known {
+            self.on_block_full_operations_received(from_peer_id, block_id, Vec::new())?;
+        }
         Ok(())
     }
 
+    /// On operations received to rebuild a compact block
+    fn on_compact_block_operations_received(
+        &mut self,
+        from_peer_id: PeerId,
+        block_id: BlockId,
+        operations: Vec<SecureShareOperation>,
+    ) -> Result<(), ProtocolError> {
+        // only the operations of the block were asked
+        let requested = self
+            .block_wishlist
+            .get(&block_id)
+            .and_then(|info| info.compact_operations.as_ref())
+            .map_or(false, |compact_operations| {
+                operations
+                    .iter()
+                    .all(|operation| compact_operations.contains(&operation.id))
+            });
+        if !requested {
+            warn!(
+                "Peer id {} sent us operations that are not in compact block id {}",
+                from_peer_id, block_id
+            );
+            if let Err(err) = self.ban_node(&from_peer_id) {
+                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+            }
+            return Ok(());
+        }
+        if let Err(err) = self.note_operations_from_peer(operations.clone(), &from_peer_id) {
+            warn!(
+                "Peer id {} sent us operations for compact block id {} but they failed at verifications. Err = {}",
+                from_peer_id, block_id, err
+            );
+            if let Err(err) = self.ban_node(&from_peer_id) {
+                warn!("Error while banning peer {} err: {:?}", from_peer_id, err);
+            }
+            return Ok(());
+        }
+        if let Some(info) = self.block_wishlist.get_mut(&block_id) {
+            if let Some(compact_operations) = info.compact_operations.as_mut() {
+                for operation in operations.iter() {
+                    compact_operations.match_operation(operation.id);
+                }
+            }
+            // keep the operations until the block is rebuilt
+            info.storage.store_operations(operations);
+        }
+        let mut set = PreHashSet::<BlockId>::with_capacity(1);
+        set.insert(block_id);
+        self.remove_asked_blocks_of_node(&set);
+        self.try_rebuild_compact_block(block_id)
+    }
+
     /// Check if the incoming header network version is compatible with the current node
     fn check_network_version_compatibility(
         &self,
@@ -611,6 +893,9 @@ impl RetrievalThread {
     /// send a ban peer command to the peer handler
     fn ban_node(&mut self, peer_id: &PeerId) -> Result<(), ProtocolError> {
         massa_trace!("ban node from retrieval thread", { "peer_id": peer_id.to_string() });
+        self.peer_scores
+            .write()
+            .record(peer_id, PeerScoreEvent::InvalidData);
         self.peer_cmd_sender
             .try_send(PeerManagementCmd::Ban(vec![peer_id.clone()]))
             .map_err(|err| ProtocolError::SendError(err.to_string()))
@@ -1072,6 +1357,16 @@ impl RetrievalThread {
                     // add block to local storage and claim ref
                     block_storage.store_block(signed_block);
 
+                    // reward the peer for the delivered block
+                    let latency = self
+                        .asked_blocks
+                        .get(&from_peer_id)
+                        .and_then(|asked_blocks| asked_blocks.get(&block_id))
+                        .map(|ask_time| ask_time.elapsed());
+                    self.peer_scores
+                        .write()
+                        .record(&from_peer_id, PeerScoreEvent::Block { latency });
+
                     // Send to consensus
                     self.consensus_controller
                         .register_block(block_id, slot, block_storage, false);
@@ -1236,7 +1531,13 @@ impl RetrievalThread {
                 let required_info = if block_info.header.is_none() {
                     AskForBlocksInfo::Header
                 } else if block_info.operation_ids.is_none() {
-                    AskForBlocksInfo::Info
+                    match &block_info.compact_operations {
+                        // ask only the operations missing to rebuild the compact block
+                        Some(compact_operations) => {
+                            AskForBlocksInfo::OperationsAt(compact_operations.missing_indexes())
+                        }
+                        None => AskForBlocksInfo::Info,
+                    }
                 } else {
                     let already_stored_operations = block_info.storage.get_op_refs();
                     // Unwrap safety: Check if `operation_ids` is none just above
@@ -1328,6 +1629,9 @@ impl RetrievalThread {
                             if info_time < &mut timeout_at {
                                 // info less recent than timeout: mark as not having it
                                 blocks_known.insert(*hash, (false, timeout_at));
+                                self.peer_scores
+                                    .write()
+                                    .record(peer_id, PeerScoreEvent::Timeout);
                                 (2u8, ask_time_opt)
                             } else {
                                 // told us it has it after a timeout: good candidate again
@@ -1339,21 +1643,38 @@ impl RetrievalThread {
                             if info_time < &mut timeout_at {
                                 // info less recent than timeout: update info time
                                 blocks_known.insert(*hash, (false, timeout_at));
+                                self.peer_scores
+                                    .write()
+                                    .record(peer_id, PeerScoreEvent::Timeout);
                             }
                             (2u8, ask_time_opt)
                         }
                         // timed out but don't know if has it: mark as not having it
                         (true, Some(timeout_at), None) => {
                             blocks_known.insert(*hash, (false, timeout_at));
+                            self.peer_scores
+                                .write()
+                                .record(peer_id, PeerScoreEvent::Timeout);
                             (2u8, ask_time_opt)
                         }
                     };
 
+                    // the peers without the compact block relay can't answer the operations
+                    // at some indexes: they are asked for the operation list instead
+                    let peer_required_info = match &required_info {
+                        AskForBlocksInfo::OperationsAt(_)
+                            if !self.active_connections.has_compact_blocks(peer_id) =>
+                        {
+                            AskForBlocksInfo::Info
+                        }
+                        required_info => required_info.clone(),
+                    };
+
                     // add candidate peer
                     candidate_nodes.entry(*hash).or_insert_with(Vec::new).push((
                         candidate,
                         peer_id.clone(),
-                        required_info.clone(),
+                        peer_required_info,
                     ));
                 }
 
@@ -1384,6 +1705,7 @@ impl RetrievalThread {
             .collect();
         {
             let cache_read = self.cache.read();
+            let peer_scores_read = self.peer_scores.read();
             for (hash, criteria) in candidate_nodes.into_iter() {
                 // find the best node
                 if let Some((_knowledge, best_node, required_info, _)) = criteria
@@ -1402,8 +1724,12 @@ impl RetrievalThread {
                         }
                     })
                     .min_by_key(|(knowledge, peer_id, _, instant)| {
+                        // best scores first, compared with a 0.01 precision
+                        let score_rank =
+                            std::cmp::Reverse((peer_scores_read.score(peer_id) * 100.0) as i64);
                         (
                             *knowledge,                                         // block knowledge
+                            score_rank,                                         // peer score
                             *active_block_req_count.get(peer_id).unwrap_or(&0), // active requests
                             *instant,                                           // node age
                             peer_id.clone(),                                    // node ID
@@ -1471,6 +1797,7 @@ pub fn start_retrieval_thread(
     endorsement_cache: SharedEndorsementCache,
     operation_cache: SharedOperationCache,
     cache: SharedBlockCache,
+    peer_scores: SharedPeerScores,
     storage: Storage,
     mip_store: MipStore,
     massa_metrics: MassaMetrics,
@@ -1487,6 +1814,7 @@ pub fn start_retrieval_thread(
                 pool_controller,
                 next_timer_ask_block: Instant::now() + config.ask_block_timeout.to_duration(),
                 block_wishlist: PreHashMap::default(),
+                pending_compact_blocks: LruMap::new(ByLength::new(MAX_PENDING_COMPACT_BLOCKS)),
                 asked_blocks: HashMap::default(),
                 peer_cmd_sender,
                 sender_propagation_ops,
@@ -1496,6 +1824,7 @@ pub fn start_retrieval_thread(
                 receiver,
                 _internal_sender,
                 cache,
+                peer_scores,
                 endorsement_cache,
                 operation_cache,
                 config,
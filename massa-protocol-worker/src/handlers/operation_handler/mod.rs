@@ -23,7 +23,10 @@ mod retrieval;
 
 pub(crate) use messages::{OperationMessage, OperationMessageSerializer};
 
-use super::peer_handler::models::{PeerManagementCmd, PeerMessageTuple};
+use super::peer_handler::{
+    models::{PeerManagementCmd, PeerMessageTuple},
+    scores::SharedPeerScores,
+};
 
 pub struct OperationHandler {
     pub operation_retrieval_thread: Option<(
@@ -50,6 +53,7 @@ impl OperationHandler {
         local_sender: MassaSender<OperationHandlerPropagationCommand>,
         local_receiver: MassaReceiver<OperationHandlerPropagationCommand>,
         peer_cmd_sender: MassaSender<PeerManagementCmd>,
+        peer_scores: SharedPeerScores,
         massa_metrics: MassaMetrics,
     ) -> Self {
         let operation_retrieval_thread = start_retrieval_thread(
@@ -62,6 +66,7 @@ impl OperationHandler {
             receiver_retrieval_ext,
             local_sender.clone(),
             peer_cmd_sender,
+            peer_scores,
             massa_metrics,
         );
 
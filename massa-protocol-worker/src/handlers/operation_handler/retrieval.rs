@@ -24,7 +24,10 @@ use massa_time::{MassaTime, TimeError};
 use schnellru::{ByLength, LruMap};
 
 use crate::{
-    handlers::peer_handler::models::{PeerManagementCmd, PeerMessageTuple},
+    handlers::peer_handler::{
+        models::{PeerManagementCmd, PeerMessageTuple},
+        scores::{PeerScoreEvent, SharedPeerScores},
+    },
     messages::MessagesSerializer,
     sig_verifier::verify_sigs_batch,
     wrap_network::ActiveConnectionsTrait,
@@ -65,6 +68,7 @@ pub struct RetrievalThread {
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     operation_message_serializer: MessagesSerializer,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_scores: SharedPeerScores,
     massa_metrics: MassaMetrics,
 }
 
@@ -251,6 +255,11 @@ impl RetrievalThread {
         }
 
         if !new_operations.is_empty() {
+            self.peer_scores.write().record(
+                source_peer_id,
+                PeerScoreEvent::Operations(new_operations.len()),
+            );
+
             // Store operation, claim locally
             let mut ops = self.storage.clone_without_refs();
             ops.store_operations(new_operations.into_values().collect());
@@ -504,6 +513,9 @@ impl RetrievalThread {
     /// send a ban peer command to the peer handler
     fn ban_node(&mut self, peer_id: &PeerId) -> Result<(), ProtocolError> {
         massa_trace!("ban node from retrieval thread", { "peer_id": peer_id.to_string() });
+        self.peer_scores
+            .write()
+            .record(peer_id, PeerScoreEvent::InvalidData);
         self.peer_cmd_sender
             .try_send(PeerManagementCmd::Ban(vec![peer_id.clone()]))
             .map_err(|err| ProtocolError::SendError(err.to_string()))
@@ -521,6 +533,7 @@ pub fn start_retrieval_thread(
     receiver_ext: MassaReceiver<OperationHandlerRetrievalCommand>,
     internal_sender: MassaSender<OperationHandlerPropagationCommand>,
     peer_cmd_sender: MassaSender<PeerManagementCmd>,
+    peer_scores: SharedPeerScores,
     massa_metrics: MassaMetrics,
 ) -> JoinHandle<()> {
     std::thread::Builder::new()
@@ -546,6 +559,7 @@ pub fn start_retrieval_thread(
                     .with_operation_message_serializer(OperationMessageSerializer::new()),
                 op_batch_buffer: VecDeque::new(),
                 peer_cmd_sender,
+                peer_scores,
                 massa_metrics,
             };
             retrieval_thread.run();
@@ -9,7 +9,8 @@ use massa_hash::Hash;
 use massa_models::config::SIGNATURE_DESER_SIZE;
 use massa_models::version::{VersionDeserializer, VersionSerializer};
 use massa_protocol_exports::{
-    BootstrapPeers, PeerId, PeerIdDeserializer, PeerIdSerializer, ProtocolConfig,
+    BootstrapPeers, PeerId, PeerIdDeserializer, PeerIdSerializer, ProtocolCompression,
+    ProtocolConfig,
 };
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_signature::Signature;
@@ -27,10 +28,14 @@ use tracing::log::{debug, error, info, warn};
 
 use crate::context::Context;
 use crate::handlers::peer_handler::models::PeerState;
-use crate::messages::{Message, MessagesHandler, MessagesSerializer};
+use crate::messages::{
+    ConnectionStats, Message, MessagesHandler, MessagesSerializer, SharedCompactBlockPeers,
+    SharedPeerCompressions,
+};
 use crate::wrap_network::ActiveConnectionsTrait;
 
 use self::models::PeerInfo;
+use self::scores::SharedPeerScores;
 use self::{
     models::{
         InitialPeers, PeerManagementChannel, PeerManagementCmd, PeerMessageTuple, SharedPeerDB,
@@ -49,9 +54,10 @@ use self::{
 /// This file contains the definition of the peer management handler
 /// This handler is here to check that announcements we receive are valid and
 /// that all the endpoints we received are active.
-mod announcement;
+pub(crate) mod announcement;
 mod messages;
 pub mod models;
+pub mod scores;
 mod tester;
 
 pub(crate) use messages::{PeerManagementMessage, PeerManagementMessageSerializer};
@@ -69,6 +75,7 @@ impl PeerManagementHandler {
         initial_peers: InitialPeers,
         peer_id: PeerId,
         peer_db: SharedPeerDB,
+        peer_scores: SharedPeerScores,
         (sender_msg, receiver_msg): (
             MassaSender<PeerMessageTuple>,
             MassaReceiver<PeerMessageTuple>,
@@ -84,6 +91,7 @@ impl PeerManagementHandler {
         config: &ProtocolConfig,
     ) -> Self {
         let message_serializer = PeerManagementMessageSerializer::new();
+        let peer_categories = target_out_connections.clone();
 
         let ((test_sender, test_receiver), testers) = Tester::run(
             config,
@@ -99,6 +107,7 @@ impl PeerManagementHandler {
         .spawn({
             let peer_db = peer_db.clone();
             let ticker = tick(Duration::from_secs(10));
+            let dump_ticker = tick(config.peers_file_dump_interval.to_duration());
             let config = config.clone();
             let message_serializer = MessagesSerializer::new()
                 .with_peer_management_message_serializer(PeerManagementMessageSerializer::new());
@@ -111,6 +120,16 @@ impl PeerManagementHandler {
                 loop {
                     select! {
                         recv(ticker) -> _ => {
+                            let (to_ban, to_unban) = peer_scores.write().update_bans();
+                            for peer_id in to_ban {
+                                info!("Temporarily banning peer {} because of its low score", peer_id);
+                                active_connections.shutdown_connection(&peer_id);
+                                peer_db.write().score_ban_peer(&peer_id);
+                            }
+                            for peer_id in to_unban {
+                                peer_db.write().score_unban_peer(&peer_id);
+                            }
+
                             let peers_to_send = peer_db.read().get_rand_peers_to_send(100);
                             if peers_to_send.is_empty() {
                                 continue;
@@ -125,6 +144,15 @@ impl PeerManagementHandler {
                                }
                             }
                         }
+                        recv(dump_ticker) -> _ => {
+                            let mut peer_db_write = peer_db.write();
+                            if let Err(err) = peer_db_write.prune(config.peers_max_age) {
+                                warn!("error pruning the known peers: {}", err);
+                            }
+                            if let Err(err) = peer_db_write.save(&config.peers_file, &peer_categories) {
+                                warn!("error saving the known peers: {}", err);
+                            }
+                        }
                         recv(receiver_cmd) -> cmd => {
                             receiver_cmd.inc_metrics();
                             // internal command
@@ -140,6 +168,7 @@ impl PeerManagementHandler {
                             },
                              Ok(PeerManagementCmd::Unban(peer_ids)) => {
                                 for peer_id in peer_ids {
+                                    peer_scores.write().forgive(&peer_id);
                                     peer_db.write().unban_peer(&peer_id);
                                 }
                             },
@@ -156,10 +185,19 @@ impl PeerManagementHandler {
                                     warn!("error sending bootstrap peers: {:?}", err);
                                 }
                              },
+                             Ok(PeerManagementCmd::GetPeerScores { responder }) => {
+                                let scores = peer_scores.read().get_scores();
+                                if let Err(err) = responder.try_send(scores) {
+                                    warn!("error sending peer scores: {:?}", err);
+                                }
+                             },
                              Ok(PeerManagementCmd::Stop) => {
                                 while let Ok(_msg) = test_receiver.try_recv() {
                                     // nothing to do just clean the channel
                                 }
+                                if let Err(err) = peer_db.read().save(&config.peers_file, &peer_categories) {
+                                    warn!("error saving the known peers: {}", err);
+                                }
                                 return;
                              },
                             Err(e) => {
@@ -265,6 +303,10 @@ pub struct MassaHandshake {
     peer_id_serializer: PeerIdSerializer,
     peer_id_deserializer: PeerIdDeserializer,
     message_handlers: MessagesHandler,
+    /// Codec negotiated with each peer, used to compress the messages sent to it
+    peer_compressions: SharedPeerCompressions,
+    /// Peers that negotiated the compact block relay, the other ones are sent block headers
+    compact_block_peers: SharedCompactBlockPeers,
 }
 
 impl MassaHandshake {
@@ -272,9 +314,13 @@ impl MassaHandshake {
         peer_db: SharedPeerDB,
         config: ProtocolConfig,
         message_handlers: MessagesHandler,
+        peer_compressions: SharedPeerCompressions,
+        compact_block_peers: SharedCompactBlockPeers,
     ) -> Self {
         Self {
             peer_db,
+            peer_compressions,
+            compact_block_peers,
             announcement_serializer: AnnouncementSerializer::new(),
             announcement_deserializer: AnnouncementDeserializer::new(
                 AnnouncementDeserializerArgs {
@@ -333,6 +379,10 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     Some(format!("Failed to serialize announcement: {}", err)),
                 )
             })?;
+        // codec we accept, ignored by the peers that don't support compression
+        bytes.push(u8::from(self.config.compression));
+        // whether we relay compact blocks, ignored by the peers that don't support them
+        bytes.push(u8::from(self.config.compact_block_relay));
         endpoint.send::<PeerId>(&bytes)?;
         let received = endpoint.receive::<PeerId>()?;
         if received.len() < 32 {
@@ -359,6 +409,8 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
             }
         }
 
+        let mut peer_compression = ProtocolCompression::None;
+        let mut peer_compact_blocks = false;
         let res = {
             {
                 let mut peer_db_write = self.peer_db.write();
@@ -391,7 +443,7 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
             )?;
             match id {
                 0 => {
-                    let (_, announcement) = self
+                    let (rest, announcement) = self
                         .announcement_deserializer
                         .deserialize::<DeserializeError>(
                             received.get(1..).ok_or(PeerNetError::HandshakeError.error(
@@ -412,6 +464,19 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                         return Err(PeerNetError::HandshakeError
                             .error("Massa Handshake", Some("Invalid signature".to_string())));
                     }
+                    // peers that don't announce a codec don't support compression
+                    let their_compression = rest
+                        .first()
+                        .and_then(|codec| ProtocolCompression::try_from(*codec).ok())
+                        .unwrap_or(ProtocolCompression::None);
+                    if their_compression == ProtocolCompression::Zstd
+                        && self.config.compression == ProtocolCompression::Zstd
+                    {
+                        peer_compression = ProtocolCompression::Zstd;
+                    }
+                    // compact blocks are sent only if both sides relay them
+                    peer_compact_blocks =
+                        self.config.compact_block_relay && rest.get(1) == Some(&1);
                     let message = PeerManagementMessage::NewPeerConnected((
                         peer_id.clone(),
                         announcement.clone().listeners,
@@ -499,6 +564,18 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
             match &res {
                 Ok((peer_id, Some(announcement))) => {
                     info!("Peer connected: {:?}", peer_id);
+                    self.peer_compressions
+                        .write()
+                        .insert(peer_id.clone(), peer_compression);
+                    if peer_compact_blocks {
+                        self.compact_block_peers.write().insert(peer_id.clone());
+                    } else {
+                        self.compact_block_peers.write().remove(peer_id);
+                    }
+                    self.message_handlers
+                        .connection_stats
+                        .write()
+                        .insert(peer_id.clone(), ConnectionStats::started_now());
                     //TODO: Hacky organize better when multiple ip/listeners
                     if !announcement.listeners.is_empty() {
                         peer_db_write
@@ -515,15 +592,14 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                             info.last_announce = announcement.clone();
                             info.state = PeerState::Trusted;
                         })
-                        .or_insert(PeerInfo {
-                            last_announce: announcement.clone(),
-                            state: PeerState::Trusted,
-                        });
+                        .or_insert_with(|| PeerInfo::new(announcement.clone(), PeerState::Trusted))
+                        .record_success();
                 }
                 Ok((_peer_id, None)) => {
                     peer_db_write.peers.entry(peer_id).and_modify(|info| {
                         //TODO: Add the peerdb but for now impossible as we don't have announcement and we need one to place in peerdb
                         info.state = PeerState::HandshakeFailed;
+                        info.record_failure();
                     });
                     return Err(PeerNetError::HandshakeError.error(
                         "Massa Handshake",
@@ -534,6 +610,7 @@ impl InitConnectionHandler<PeerId, Context, MessagesHandler> for MassaHandshake
                     peer_db_write.peers.entry(peer_id).and_modify(|info| {
                         //TODO: Add the peerdb but for now impossible as we don't have announcement and we need one to place in peerdb
                         info.state = PeerState::HandshakeFailed;
+                        info.record_failure();
                     });
                 }
             }
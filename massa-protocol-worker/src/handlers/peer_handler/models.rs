@@ -1,16 +1,22 @@
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::{BootstrapPeers, PeerId, ProtocolError};
+use massa_protocol_exports::{BootstrapPeers, PeerData, PeerId, PeerScore, ProtocolError};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use massa_time::MassaTime;
 use parking_lot::RwLock;
 use peernet::transports::TransportType;
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
+use std::net::IpAddr;
+use std::path::Path;
 use std::time::Duration;
 use std::{collections::HashMap, net::SocketAddr, sync::Arc};
-use tracing::log::info;
+use tracing::log::{info, warn};
 
-use super::announcement::Announcement;
+use super::announcement::{
+    Announcement, AnnouncementDeserializer, AnnouncementDeserializerArgs, AnnouncementSerializer,
+};
 
 const THREE_DAYS_MS: u64 = 3 * 24 * 60 * 60 * 1_000_000;
 
@@ -23,6 +29,8 @@ pub struct PeerDB {
     pub index_by_newest: BTreeSet<(Reverse<u64>, PeerId)>,
     /// Tested addresses used to avoid testing the same address too often. //TODO: Need to be pruned
     pub tested_addresses: HashMap<SocketAddr, MassaTime>,
+    /// Peers banned temporarily because of their low score, the other banned peers were banned explicitly
+    pub score_bans: HashSet<PeerId>,
 }
 
 pub type SharedPeerDB = Arc<RwLock<PeerDB>>;
@@ -33,6 +41,48 @@ pub type PeerMessageTuple = (PeerId, Vec<u8>);
 pub struct PeerInfo {
     pub last_announce: Announcement,
     pub state: PeerState,
+    /// Last time a handshake with the peer succeeded
+    pub last_seen: Option<MassaTime>,
+    /// Number of successful handshakes with the peer
+    pub successes: u64,
+    /// Number of failed handshakes with the peer
+    pub failures: u64,
+}
+
+impl PeerInfo {
+    pub fn new(last_announce: Announcement, state: PeerState) -> Self {
+        PeerInfo {
+            last_announce,
+            state,
+            last_seen: None,
+            successes: 0,
+            failures: 0,
+        }
+    }
+
+    /// Note a successful handshake with the peer
+    pub fn record_success(&mut self) {
+        self.successes = self.successes.saturating_add(1);
+        self.last_seen = MassaTime::now().ok();
+    }
+
+    /// Note a failed handshake with the peer
+    pub fn record_failure(&mut self) {
+        self.failures = self.failures.saturating_add(1);
+    }
+}
+
+/// Known peer as saved in the peers file
+#[derive(Serialize, Deserialize)]
+struct SavedPeer {
+    /// Last announcement of the peer, serialized with its signature
+    announcement: Vec<u8>,
+    banned: bool,
+    last_seen: Option<MassaTime>,
+    successes: u64,
+    failures: u64,
+    /// Category of the peer IP, if any
+    category: Option<String>,
 }
 
 #[warn(dead_code)]
@@ -51,6 +101,9 @@ pub enum PeerManagementCmd {
     GetBootstrapPeers {
         responder: MassaSender<BootstrapPeers>,
     },
+    GetPeerScores {
+        responder: MassaSender<HashMap<PeerId, PeerScore>>,
+    },
     Stop,
 }
 
@@ -68,9 +121,28 @@ impl PeerDB {
         } else {
             info!("Tried to ban unknown peer: {:?}", peer_id);
         };
+        // an explicit ban outlasts the score ban
+        self.score_bans.remove(peer_id);
+    }
+
+    /// Ban a peer because of its low score, unless it is already banned
+    pub fn score_ban_peer(&mut self, peer_id: &PeerId) {
+        if let Some(peer) = self.peers.get_mut(peer_id) && peer.state != PeerState::Banned {
+            peer.state = PeerState::Banned;
+            self.score_bans.insert(peer_id.clone());
+            info!("Temporarily banned peer: {:?}", peer_id);
+        }
+    }
+
+    /// End the ban of a peer banned because of its low score, the explicit bans are kept
+    pub fn score_unban_peer(&mut self, peer_id: &PeerId) {
+        if self.score_bans.remove(peer_id) {
+            self.unban_peer(peer_id);
+        }
     }
 
     pub fn unban_peer(&mut self, peer_id: &PeerId) {
+        self.score_bans.remove(peer_id);
         if self.peers.contains_key(peer_id) {
             self.peers.remove(peer_id);
             info!("Unbanned peer: {:?}", peer_id);
@@ -149,8 +221,183 @@ impl PeerDB {
             .count() as u64
     }
 
-    // Flush PeerDB to disk ?
-    fn _flush(&self) -> Result<(), ProtocolError> {
-        unimplemented!()
+    /// Forget the peers not seen for `max_age`, except the banned ones, and the old tested addresses
+    pub fn prune(&mut self, max_age: MassaTime) -> Result<(), ProtocolError> {
+        let min_time = MassaTime::now()?.saturating_sub(max_age);
+        let old_peers: Vec<PeerId> = self
+            .peers
+            .iter()
+            .filter(|(_, info)| {
+                info.state != PeerState::Banned
+                    && info.state != PeerState::InHandshake
+                    && info
+                        .last_seen
+                        .unwrap_or_else(|| MassaTime::from_millis(info.last_announce.timestamp))
+                        < min_time
+            })
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect();
+        for peer_id in &old_peers {
+            self.peers.remove(peer_id);
+        }
+        self.index_by_newest
+            .retain(|(_, peer_id)| self.peers.contains_key(peer_id));
+        self.tested_addresses
+            .retain(|_, tested_time| *tested_time >= min_time);
+        Ok(())
+    }
+
+    /// Save the known peers to `path`, with the category of their IP among `categories`
+    pub fn save(
+        &self,
+        path: &Path,
+        categories: &HashMap<String, (Vec<IpAddr>, usize)>,
+    ) -> Result<(), ProtocolError> {
+        let announcement_serializer = AnnouncementSerializer::new();
+        let mut saved_peers = HashMap::with_capacity(self.peers.len());
+        for (peer_id, info) in &self.peers {
+            if info.state == PeerState::InHandshake {
+                continue;
+            }
+            let mut announcement = Vec::new();
+            announcement_serializer
+                .serialize(&info.last_announce, &mut announcement)
+                .map_err(|err| ProtocolError::GeneralProtocolError(err.to_string()))?;
+            //TODO: Adapt for multiple listeners
+            let category = info.last_announce.listeners.keys().next().and_then(|addr| {
+                let ip = addr.ip().to_canonical();
+                categories
+                    .iter()
+                    .find(|(_, (ips, _))| ips.contains(&ip))
+                    .map(|(name, _)| name.clone())
+            });
+            saved_peers.insert(
+                peer_id.clone(),
+                SavedPeer {
+                    announcement,
+                    // the score bans are temporary and not persisted
+                    banned: info.state == PeerState::Banned && !self.score_bans.contains(peer_id),
+                    last_seen: info.last_seen,
+                    successes: info.successes,
+                    failures: info.failures,
+                    category,
+                },
+            );
+        }
+        // write to a temporary file first to never leave a truncated file behind
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec(&saved_peers)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Load the peers saved at `path`, skipping the ones not seen for `max_age` and the ones with an invalid announcement.
+    /// A file that can't be read or parsed gives an empty database: a corrupt file is moved aside
+    /// with the `corrupt` extension, so that the next save doesn't overwrite it.
+    ///
+    /// Peers seen before are trusted again, so that we reconnect to them without testing them first.
+    /// Also returns the listeners and categories of the peers saved with a category.
+    pub fn load(
+        path: &Path,
+        max_age: MassaTime,
+        max_listeners: u64,
+    ) -> Result<(PeerDB, HashMap<PeerId, PeerData>), ProtocolError> {
+        let mut peer_db = PeerDB::default();
+        let mut categorized_peers = HashMap::new();
+        if !path.exists() {
+            return Ok((peer_db, categorized_peers));
+        }
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                warn!(
+                    "could not read the saved peers at {}, starting without them: {}",
+                    path.display(),
+                    err
+                );
+                return Ok((peer_db, categorized_peers));
+            }
+        };
+        let saved_peers: HashMap<PeerId, SavedPeer> = match serde_json::from_slice(&bytes) {
+            Ok(saved_peers) => saved_peers,
+            Err(err) => {
+                let corrupt_path = path.with_extension("corrupt");
+                warn!(
+                    "corrupt saved peers at {}, starting without them and moving the file to {}: {}",
+                    path.display(),
+                    corrupt_path.display(),
+                    err
+                );
+                if let Err(err) = std::fs::rename(path, &corrupt_path) {
+                    warn!(
+                        "could not move the corrupt saved peers to {}: {}",
+                        corrupt_path.display(),
+                        err
+                    );
+                }
+                return Ok((peer_db, categorized_peers));
+            }
+        };
+        let announcement_deserializer =
+            AnnouncementDeserializer::new(AnnouncementDeserializerArgs { max_listeners });
+        for (peer_id, saved_peer) in saved_peers {
+            let announcement = match announcement_deserializer
+                .deserialize::<DeserializeError>(&saved_peer.announcement)
+            {
+                Ok((rest, announcement)) if rest.is_empty() => announcement,
+                _ => {
+                    warn!("invalid saved announcement of peer {}", peer_id);
+                    continue;
+                }
+            };
+            if peer_id
+                .verify_signature(&announcement.hash, &announcement.signature)
+                .is_err()
+            {
+                warn!("invalid saved announcement signature of peer {}", peer_id);
+                continue;
+            }
+            let state = if saved_peer.banned {
+                PeerState::Banned
+            } else if saved_peer.last_seen.is_some() {
+                PeerState::Trusted
+            } else {
+                PeerState::HandshakeFailed
+            };
+            if state == PeerState::Trusted && !announcement.listeners.is_empty() {
+                peer_db
+                    .index_by_newest
+                    .insert((Reverse(announcement.timestamp), peer_id.clone()));
+            }
+            if let Some(category) = saved_peer.category {
+                categorized_peers.insert(
+                    peer_id.clone(),
+                    PeerData {
+                        listeners: announcement.listeners.clone(),
+                        category,
+                    },
+                );
+            }
+            peer_db.peers.insert(
+                peer_id,
+                PeerInfo {
+                    last_announce: announcement,
+                    state,
+                    last_seen: saved_peer.last_seen,
+                    successes: saved_peer.successes,
+                    failures: saved_peer.failures,
+                },
+            );
+        }
+        peer_db.prune(max_age)?;
+        categorized_peers.retain(|peer_id, _| peer_db.peers.contains_key(peer_id));
+        Ok((peer_db, categorized_peers))
+    }
+
+    /// Whether we know peers we connected to before
+    pub fn has_trusted_peers(&self) -> bool {
+        self.peers
+            .values()
+            .any(|info| info.state == PeerState::Trusted)
     }
 }
@@ -0,0 +1,219 @@
+//! Reputation of the peers.
+//!
+//! Each peer gets a score from what it sends us: delivered blocks (worth more when delivered quickly)
+//! and new operations increase it, timeouts and invalid data decrease it.
+//! Scores decay towards 0 over time so that old behaviors are progressively forgotten.
+//! Peers with a better score are preferred when connecting and asking for blocks,
+//! and peers whose score falls under a threshold are banned temporarily.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use massa_protocol_exports::{PeerId, PeerScore, ProtocolConfig};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+
+/// Score of a block delivered instantly, halved for every `BLOCK_LATENCY_REFERENCE` of latency
+const BLOCK_SCORE: f64 = 10.0;
+/// Latency halving the score of a delivered block
+const BLOCK_LATENCY_REFERENCE: Duration = Duration::from_secs(1);
+/// Score of each new operation
+const OPERATION_SCORE: f64 = 0.1;
+/// Max score earned by a single batch of operations
+const MAX_OPERATIONS_SCORE: f64 = 5.0;
+/// Score of a request not answered in time
+const TIMEOUT_SCORE: f64 = -5.0;
+/// Score of invalid data
+const INVALID_DATA_SCORE: f64 = -50.0;
+/// Weight of the last delivered block in the average latency
+const LATENCY_SMOOTHING: f64 = 0.2;
+/// Scores closer to 0 than this are forgotten
+const NEGLIGIBLE_SCORE: f64 = 0.01;
+
+/// Something a peer did that changes its score
+#[derive(Debug, Clone, Copy)]
+pub enum PeerScoreEvent {
+    /// The peer delivered a block, `latency` after we asked it (None if it pushed the block to us)
+    Block { latency: Option<Duration> },
+    /// The peer sent us this number of new operations
+    Operations(usize),
+    /// The peer didn't answer a block request in time
+    Timeout,
+    /// The peer sent us invalid data
+    InvalidData,
+}
+
+struct PeerScoreState {
+    /// Score at `updated`
+    score: f64,
+    updated: Instant,
+    average_latency: Option<Duration>,
+    useful_blocks: u64,
+    useful_operations: u64,
+    timeouts: u64,
+    invalid_data: u64,
+    banned_until: Option<Instant>,
+}
+
+impl PeerScoreState {
+    fn new(now: Instant) -> Self {
+        PeerScoreState {
+            score: 0.0,
+            updated: now,
+            average_latency: None,
+            useful_blocks: 0,
+            useful_operations: 0,
+            timeouts: 0,
+            invalid_data: 0,
+            banned_until: None,
+        }
+    }
+
+    /// Score decayed until `now`
+    fn score_at(&self, now: Instant, half_life: Duration) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.score * 0.5f64.powf(elapsed / half_life.as_secs_f64().max(f64::EPSILON))
+    }
+}
+
+/// Reputation scores of the peers
+pub struct PeerScores {
+    scores: HashMap<PeerId, PeerScoreState>,
+    half_life: Duration,
+    ban_threshold: f64,
+    ban_duration: Duration,
+}
+
+pub type SharedPeerScores = Arc<RwLock<PeerScores>>;
+
+impl PeerScores {
+    pub fn new(config: &ProtocolConfig) -> Self {
+        PeerScores {
+            scores: HashMap::new(),
+            half_life: config.peer_score_half_life.to_duration(),
+            ban_threshold: config.peer_score_ban_threshold,
+            ban_duration: config.peer_score_ban_duration.to_duration(),
+        }
+    }
+
+    /// Updates the score of `peer_id` with `event`
+    pub fn record(&mut self, peer_id: &PeerId, event: PeerScoreEvent) {
+        let now = Instant::now();
+        let half_life = self.half_life;
+        let state = self
+            .scores
+            .entry(peer_id.clone())
+            .or_insert_with(|| PeerScoreState::new(now));
+        let delta = match event {
+            PeerScoreEvent::Block { latency } => {
+                state.useful_blocks = state.useful_blocks.saturating_add(1);
+                match latency {
+                    Some(latency) => {
+                        state.average_latency = Some(match state.average_latency {
+                            Some(average) => average
+                                .mul_f64(1.0 - LATENCY_SMOOTHING)
+                                .saturating_add(latency.mul_f64(LATENCY_SMOOTHING)),
+                            None => latency,
+                        });
+                        BLOCK_SCORE
+                            * 0.5f64
+                                .powf(latency.as_secs_f64() / BLOCK_LATENCY_REFERENCE.as_secs_f64())
+                    }
+                    None => BLOCK_SCORE,
+                }
+            }
+            PeerScoreEvent::Operations(count) => {
+                state.useful_operations = state.useful_operations.saturating_add(count as u64);
+                (count as f64 * OPERATION_SCORE).min(MAX_OPERATIONS_SCORE)
+            }
+            PeerScoreEvent::Timeout => {
+                state.timeouts = state.timeouts.saturating_add(1);
+                TIMEOUT_SCORE
+            }
+            PeerScoreEvent::InvalidData => {
+                state.invalid_data = state.invalid_data.saturating_add(1);
+                INVALID_DATA_SCORE
+            }
+        };
+        state.score = state.score_at(now, half_life) + delta;
+        state.updated = now;
+    }
+
+    /// Current score of `peer_id`, 0 if unknown
+    pub fn score(&self, peer_id: &PeerId) -> f64 {
+        self.scores
+            .get(peer_id)
+            .map_or(0.0, |state| state.score_at(Instant::now(), self.half_life))
+    }
+
+    /// Starts and ends the temporary bans, and forgets the negligible scores.
+    ///
+    /// Returns the peers to ban, and the peers whose ban ended.
+    pub fn update_bans(&mut self) -> (Vec<PeerId>, Vec<PeerId>) {
+        let now = Instant::now();
+        let mut to_ban = Vec::new();
+        let mut to_unban = Vec::new();
+        for (peer_id, state) in self.scores.iter_mut() {
+            let score = state.score_at(now, self.half_life);
+            match state.banned_until {
+                Some(banned_until) if banned_until <= now => {
+                    // start again from a neutral score
+                    state.banned_until = None;
+                    state.score = 0.0;
+                    state.updated = now;
+                    to_unban.push(peer_id.clone());
+                }
+                None if score < self.ban_threshold => {
+                    state.banned_until = Some(now + self.ban_duration);
+                    to_ban.push(peer_id.clone());
+                }
+                _ => {}
+            }
+        }
+        let half_life = self.half_life;
+        self.scores.retain(|_, state| {
+            state.banned_until.is_some() || state.score_at(now, half_life).abs() > NEGLIGIBLE_SCORE
+        });
+        (to_ban, to_unban)
+    }
+
+    /// Resets the score of a peer unbanned by hand
+    pub fn forgive(&mut self, peer_id: &PeerId) {
+        self.scores.remove(peer_id);
+    }
+
+    /// Scores of all the peers
+    pub fn get_scores(&self) -> HashMap<PeerId, PeerScore> {
+        let now = Instant::now();
+        let now_time = MassaTime::now().ok();
+        self.scores
+            .iter()
+            .map(|(peer_id, state)| {
+                let banned_until = state.banned_until.and_then(|banned_until| {
+                    now_time?
+                        .checked_add(MassaTime::from_millis(
+                            banned_until.saturating_duration_since(now).as_millis() as u64,
+                        ))
+                        .ok()
+                });
+                (
+                    peer_id.clone(),
+                    PeerScore {
+                        score: state.score_at(now, self.half_life),
+                        average_latency: state
+                            .average_latency
+                            .map(|latency| MassaTime::from_millis(latency.as_millis() as u64)),
+                        useful_blocks: state.useful_blocks,
+                        useful_operations: state.useful_operations,
+                        timeouts: state.timeouts,
+                        invalid_data: state.invalid_data,
+                        banned_until,
+                    },
+                )
+            })
+            .collect()
+    }
+}
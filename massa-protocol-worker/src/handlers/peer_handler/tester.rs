@@ -192,10 +192,10 @@ impl Tester {
                                     }
                                     info.state = super::PeerState::Trusted;
                                 })
-                                .or_insert(PeerInfo {
-                                    last_announce: announcement,
-                                    state: super::PeerState::Trusted,
-                                });
+                                .or_insert_with(|| {
+                                    PeerInfo::new(announcement, super::PeerState::Trusted)
+                                })
+                                .record_success();
                         }
                         Ok(peer_id.clone())
                     }
@@ -223,6 +223,7 @@ impl Tester {
                 let mut peer_db_write = peer_db.write();
                 peer_db_write.peers.entry(peer_id).and_modify(|info| {
                     info.state = super::PeerState::HandshakeFailed;
+                    info.record_failure();
                 });
             }
             if let Err(e) = socket.shutdown(std::net::Shutdown::Both) {
@@ -1,9 +1,17 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+    sync::Arc,
+};
+
 use massa_channel::sender::MassaSender;
-use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{PeerId, ProtocolCompression};
 use massa_serialization::{
     DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
+use massa_time::MassaTime;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use parking_lot::RwLock;
 use peernet::{
     error::{PeerNetError, PeerNetResult},
     messages::{
@@ -26,6 +34,9 @@ pub enum Message {
     Endorsement(EndorsementMessage),
     Operation(OperationMessage),
     PeerManagement(Box<PeerManagementMessage>),
+    /// Another message, serialized and compressed with a codec negotiated with the peer.
+    /// Holds the codec id followed by the compressed bytes.
+    Compressed(Vec<u8>),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -35,6 +46,7 @@ pub enum MessageTypeId {
     Endorsement = 1,
     Operation = 2,
     PeerManagement = 3,
+    Compressed = 4,
 }
 
 impl From<&Message> for MessageTypeId {
@@ -44,6 +56,7 @@ impl From<&Message> for MessageTypeId {
             Message::Endorsement(_) => MessageTypeId::Endorsement,
             Message::Operation(_) => MessageTypeId::Operation,
             Message::PeerManagement(_) => MessageTypeId::PeerManagement,
+            Message::Compressed(_) => MessageTypeId::Compressed,
         }
     }
 }
@@ -212,10 +225,162 @@ impl PeerNetMessagesSerializer<Message> for MessagesSerializer {
                     ))
                 }
             }
+            Message::Compressed(data) => {
+                buffer.extend(data);
+                Ok(())
+            }
         }
     }
 }
 
+/// Traffic on the connection with a peer
+#[derive(Debug, Clone)]
+pub struct ConnectionStats {
+    pub connected_since: Option<MassaTime>,
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+    pub last_message_time: Option<MassaTime>,
+}
+
+impl ConnectionStats {
+    /// Stats of a connection established now
+    pub fn started_now() -> Self {
+        ConnectionStats {
+            connected_since: MassaTime::now().ok(),
+            bytes_received: 0,
+            bytes_sent: 0,
+            last_message_time: None,
+        }
+    }
+}
+
+/// Traffic on the connection with each connected peer
+pub type SharedConnectionStats = Arc<RwLock<HashMap<PeerId, ConnectionStats>>>;
+
+/// zstd level of the compressed messages
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// Codec negotiated with each peer during the handshake
+pub type SharedPeerCompressions = Arc<RwLock<HashMap<PeerId, ProtocolCompression>>>;
+
+/// Peers that negotiated the compact block relay during the handshake
+pub type SharedCompactBlockPeers = Arc<RwLock<HashSet<PeerId>>>;
+
+/// Replaces a compact block by its header, for the peers that did not negotiate the compact block relay:
+/// they ask for the operation list of the block as for any other header
+pub fn without_compact_block(message: Message) -> Message {
+    match message {
+        Message::Block(block_message) => match *block_message {
+            BlockMessage::CompactBlock(compact_block) => {
+                Message::Block(Box::new(BlockMessage::BlockHeader(compact_block.header)))
+            }
+            block_message => Message::Block(Box::new(block_message)),
+        },
+        message => message,
+    }
+}
+
+/// Compression of the messages sent to the peers that negotiated it during the handshake.
+///
+/// Only the messages carrying operations or block bodies are compressed:
+/// the other ones are small and barely compressible.
+#[derive(Clone)]
+pub struct MessagesCompression {
+    /// Codec negotiated with each connected peer
+    pub peers: SharedPeerCompressions,
+    /// Messages smaller than this size in bytes are sent uncompressed
+    pub min_message_size: usize,
+}
+
+impl MessagesCompression {
+    /// Compresses `message` if it is worth it and `peer_id` negotiated a codec
+    pub fn compress(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+    ) -> PeerNetResult<Message> {
+        let is_target = match &message {
+            Message::Operation(message) => matches!(message, OperationMessage::Operations(_)),
+            Message::Block(message) => matches!(**message, BlockMessage::ReplyForBlocks(_)),
+            _ => false,
+        };
+        let codec = self
+            .peers
+            .read()
+            .get(peer_id)
+            .copied()
+            .unwrap_or(ProtocolCompression::None);
+        if !is_target || codec == ProtocolCompression::None {
+            return Ok(message);
+        }
+
+        let mut bytes = Vec::new();
+        message_serializer.serialize(&message, &mut bytes)?;
+        if bytes.len() < self.min_message_size {
+            return Ok(message);
+        }
+        let compressed = zstd::bulk::compress(&bytes, ZSTD_COMPRESSION_LEVEL).map_err(|err| {
+            PeerNetError::HandlerError.error(
+                "MessagesCompression",
+                Some(format!("Failed to compress message: {}", err)),
+            )
+        })?;
+        if compressed.len() >= bytes.len() {
+            return Ok(message);
+        }
+        let mut data = Vec::with_capacity(compressed.len() + 1);
+        data.push(u8::from(codec));
+        data.extend(compressed);
+        Ok(Message::Compressed(data))
+    }
+}
+
+/// Decompresses the payload of a `Message::Compressed`, bounded by `max_message_size` like the received messages
+fn decompress_message(
+    data: &[u8],
+    accepted_codec: ProtocolCompression,
+    max_message_size: usize,
+) -> PeerNetResult<Vec<u8>> {
+    let (codec, compressed) = data.split_first().ok_or_else(|| {
+        PeerNetError::HandlerError.error(
+            "MessagesHandler",
+            Some(String::from("Empty compressed message")),
+        )
+    })?;
+    match ProtocolCompression::try_from(*codec) {
+        Ok(ProtocolCompression::Zstd) if accepted_codec == ProtocolCompression::Zstd => {
+            let mut decompressed = Vec::new();
+            zstd::stream::read::Decoder::new(compressed)
+                .and_then(|decoder| {
+                    decoder
+                        .take(max_message_size as u64 + 1)
+                        .read_to_end(&mut decompressed)
+                })
+                .map_err(|err| {
+                    PeerNetError::HandlerError.error(
+                        "MessagesHandler",
+                        Some(format!("Failed to decompress message: {}", err)),
+                    )
+                })?;
+            if decompressed.len() > max_message_size {
+                return Err(PeerNetError::HandlerError.error(
+                    "MessagesHandler",
+                    Some(String::from("Decompressed message is too large")),
+                ));
+            }
+            Ok(decompressed)
+        }
+        _ => Err(PeerNetError::HandlerError.error(
+            "MessagesHandler",
+            Some(format!(
+                "Message compressed with unaccepted codec {}",
+                codec
+            )),
+        )),
+    }
+}
+
 #[derive(Clone)]
 pub struct MessagesHandler {
     pub id_deserializer: U64VarIntDeserializer,
@@ -223,10 +388,30 @@ pub struct MessagesHandler {
     pub sender_endorsements: MassaSender<PeerMessageTuple>,
     pub sender_operations: MassaSender<PeerMessageTuple>,
     pub sender_peers: MassaSender<PeerMessageTuple>,
+    /// Codec of the compressed messages we accept
+    pub compression: ProtocolCompression,
+    pub max_message_size: usize,
+    /// Traffic received from each peer is noted here
+    pub connection_stats: SharedConnectionStats,
 }
 
 impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
     fn handle(&self, data: &[u8], peer_id: &PeerId) -> PeerNetResult<()> {
+        {
+            let mut connection_stats = self.connection_stats.write();
+            let stats = connection_stats
+                .entry(peer_id.clone())
+                .or_insert_with(ConnectionStats::started_now);
+            stats.bytes_received = stats.bytes_received.saturating_add(data.len() as u64);
+            stats.last_message_time = MassaTime::now().ok();
+        }
+        self.handle_message(data, peer_id)
+    }
+}
+
+impl MessagesHandler {
+    /// Dispatches a received message to the handler of its type
+    fn handle_message(&self, data: &[u8], peer_id: &PeerId) -> PeerNetResult<()> {
         let (data, raw_id) = self
             .id_deserializer
             .deserialize::<DeserializeError>(data)
@@ -279,6 +464,27 @@ impl PeerNetMessagesHandler<PeerId> for MessagesHandler {
                         Some(format!("Failed to send block message to channel: {}", err)),
                     )
                 }),
+            MessageTypeId::Compressed => {
+                let decompressed =
+                    decompress_message(data, self.compression, self.max_message_size)?;
+                // a compressed message can't hold another compressed message
+                let (_, inner_id) = self
+                    .id_deserializer
+                    .deserialize::<DeserializeError>(&decompressed)
+                    .map_err(|err| {
+                        PeerNetError::HandlerError.error(
+                            "MessagesHandler",
+                            Some(format!("Failed to deserialize id: {}", err)),
+                        )
+                    })?;
+                if inner_id == u64::from(MessageTypeId::Compressed) {
+                    return Err(PeerNetError::HandlerError.error(
+                        "MessagesHandler",
+                        Some(String::from("Nested compressed message")),
+                    ));
+                }
+                self.handle_message(&decompressed, peer_id)
+            }
         }
     }
 }
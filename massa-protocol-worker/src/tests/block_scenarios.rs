@@ -3,8 +3,10 @@
 use std::collections::HashSet;
 use std::time::Duration;
 
-use crate::handlers::block_handler::{AskForBlocksInfo, BlockInfoReply, BlockMessage};
-use crate::messages::Message;
+use crate::handlers::block_handler::{
+    short_operation_id, AskForBlocksInfo, BlockInfoReply, BlockMessage, CompactBlock,
+};
+use crate::messages::{without_compact_block, Message};
 
 use super::context::{protocol_test, protocol_test_with_storage};
 use super::tools::{assert_block_info_sent_to_node, assert_hash_asked_to_node};
@@ -191,6 +193,521 @@ fn test_full_ask_block_workflow() {
     )
 }
 
+#[test]
+#[serial]
+fn test_compact_block_workflow() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Create a block with an operation we already know and one we don't.
+            let op_1 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_2 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_thread = op_1
+                .content_creator_address
+                .get_thread(protocol_config.thread_count);
+            let block = tools::create_block_with_operations(
+                &node_a_keypair,
+                Slot::new(1, op_thread),
+                vec![op_1.clone(), op_2.clone()],
+            );
+            storage.store_operations(vec![op_1]);
+            //end setup
+
+            //3. Send the compact block from node a
+            let salt = 42;
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::CompactBlock(CompactBlock {
+                        header: block.content.header.clone(),
+                        salt,
+                        short_operation_ids: block
+                            .content
+                            .operations
+                            .iter()
+                            .map(|id| short_operation_id(salt, id))
+                            .collect(),
+                    }))),
+                )
+                .unwrap();
+
+            //4. Assert that we register the block header to the consensus
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlockHeader {
+                            header: _,
+                            block_id,
+                        } => {
+                            assert_eq!(block_id, block.id);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+
+            //5. Send a wishlist that ask for the block
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![(block.id, Some(block.content.header.clone()))]
+                        .into_iter()
+                        .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+
+            //6. Assert that we only asked node a for the operation we don't know
+            let msg = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node A didn't receive the ask for operations message");
+            match msg {
+                Message::Block(message) => {
+                    if let BlockMessage::AskForBlocks(asked) = *message {
+                        assert_eq!(asked.len(), 1);
+                        assert_eq!(asked[0].0, block.id);
+                        assert_eq!(asked[0].1, AskForBlocksInfo::OperationsAt(vec![1]));
+                    } else {
+                        panic!("Node A didn't receive the ask for operations message");
+                    }
+                }
+                _ => panic!("Node A didn't receive the ask for operations message"),
+            }
+
+            //7. Node A answer with the missing operation
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::ReplyForBlocks(vec![(
+                        block.id,
+                        BlockInfoReply::Operations(vec![op_2]),
+                    )]))),
+                )
+                .unwrap();
+
+            //8. Assert that we send the rebuilt block to consensus
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlock {
+                            slot,
+                            block_id,
+                            block_storage,
+                            created: _,
+                        } => {
+                            assert_eq!(slot, block.content.header.content.slot);
+                            assert_eq!(block_id, block.id);
+                            let received_block =
+                                block_storage.read_blocks().get(&block_id).cloned().unwrap();
+                            assert_eq!(received_block.content.operations, block.content.operations);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+#[serial]
+fn test_compact_block_operations_asked_to_legacy_peer() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              mut consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node with the compact block relay and a legacy one
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let node_b_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+            let (node_b_peer_id, node_b) = network_controller.create_fake_legacy_connection(
+                PeerId::from_public_key(node_b_keypair.get_public_key()),
+            );
+
+            //2. Create a block with an operation we already know and one we don't.
+            let op_1 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_2 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_thread = op_1
+                .content_creator_address
+                .get_thread(protocol_config.thread_count);
+            let block = tools::create_block_with_operations(
+                &node_a_keypair,
+                Slot::new(1, op_thread),
+                vec![op_1.clone(), op_2.clone()],
+            );
+            storage.store_operations(vec![op_1.clone()]);
+            //end setup
+
+            //3. Send the compact block from node a
+            let salt = 42;
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::CompactBlock(CompactBlock {
+                        header: block.content.header.clone(),
+                        salt,
+                        short_operation_ids: block
+                            .content
+                            .operations
+                            .iter()
+                            .map(|id| short_operation_id(salt, id))
+                            .collect(),
+                    }))),
+                )
+                .unwrap();
+
+            //4. Assert that we register the block header to the consensus
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlockHeader {
+                            header: _,
+                            block_id,
+                        } => {
+                            assert_eq!(block_id, block.id);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+
+            //5. Send a wishlist that ask for the block
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![(block.id, Some(block.content.header.clone()))]
+                        .into_iter()
+                        .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+
+            //6. Assert that node a is asked for the operation we don't know
+            let msg = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node A didn't receive the ask for operations message");
+            match msg {
+                Message::Block(message) => {
+                    if let BlockMessage::AskForBlocks(asked) = *message {
+                        assert_eq!(asked.len(), 1);
+                        assert_eq!(asked[0].0, block.id);
+                        assert_eq!(asked[0].1, AskForBlocksInfo::OperationsAt(vec![1]));
+                    } else {
+                        panic!("Node A didn't receive the ask for operations message");
+                    }
+                }
+                _ => panic!("Node A didn't receive the ask for operations message"),
+            }
+
+            //7. Node A doesn't answer: assert that legacy node b is asked for the operation list
+            let msg = node_b
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node B didn't receive the ask for infos message");
+            match msg {
+                Message::Block(message) => {
+                    if let BlockMessage::AskForBlocks(asked) = *message {
+                        assert_eq!(asked.len(), 1);
+                        assert_eq!(asked[0].0, block.id);
+                        assert_eq!(asked[0].1, AskForBlocksInfo::Info);
+                    } else {
+                        panic!("Node B didn't receive the ask for infos message");
+                    }
+                }
+                _ => panic!("Node B didn't receive the ask for infos message"),
+            }
+
+            //8. Node B answer with the infos
+            network_controller
+                .send_from_peer(
+                    &node_b_peer_id,
+                    Message::Block(Box::new(BlockMessage::ReplyForBlocks(vec![(
+                        block.id,
+                        BlockInfoReply::Info(vec![op_1.id, op_2.id]),
+                    )]))),
+                )
+                .unwrap();
+
+            //9. Assert that we only asked node b for the operation we don't know
+            let msg = node_b
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node B didn't receive the ask for operations message");
+            match msg {
+                Message::Block(message) => {
+                    if let BlockMessage::AskForBlocks(asked) = *message {
+                        assert_eq!(asked.len(), 1);
+                        assert_eq!(asked[0].0, block.id);
+                        assert_eq!(asked[0].1, AskForBlocksInfo::Operations(vec![op_2.id]));
+                    } else {
+                        panic!("Node B didn't receive the ask for operations message");
+                    }
+                }
+                _ => panic!("Node B didn't receive the ask for operations message"),
+            }
+
+            //10. Node B answer with the missing operation
+            network_controller
+                .send_from_peer(
+                    &node_b_peer_id,
+                    Message::Block(Box::new(BlockMessage::ReplyForBlocks(vec![(
+                        block.id,
+                        BlockInfoReply::Operations(vec![op_2]),
+                    )]))),
+                )
+                .unwrap();
+
+            //11. Assert that we send the block to consensus
+            loop {
+                match consensus_event_receiver.wait_command(
+                    MassaTime::from_millis(100),
+                    |command| match command {
+                        MockConsensusControllerMessage::RegisterBlock {
+                            slot,
+                            block_id,
+                            block_storage,
+                            created: _,
+                        } => {
+                            assert_eq!(slot, block.content.header.content.slot);
+                            assert_eq!(block_id, block.id);
+                            let received_block =
+                                block_storage.read_blocks().get(&block_id).cloned().unwrap();
+                            assert_eq!(received_block.content.operations, block.content.operations);
+                            Some(())
+                        }
+                        _evt => None,
+                    },
+                ) {
+                    Some(()) => {
+                        break;
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
+#[test]
+fn test_compact_block_sent_as_header_without_compact_relay() {
+    let keypair = KeyPair::generate(0).unwrap();
+    let operation = tools::create_operation_with_expire_period(&keypair, 5);
+    let block = tools::create_block_with_operations(&keypair, Slot::new(1, 0), vec![operation]);
+    let message = Message::Block(Box::new(BlockMessage::CompactBlock(CompactBlock {
+        header: block.content.header.clone(),
+        salt: 42,
+        short_operation_ids: block
+            .content
+            .operations
+            .iter()
+            .map(|id| short_operation_id(42, id))
+            .collect(),
+    })));
+
+    match without_compact_block(message) {
+        Message::Block(message) => match *message {
+            BlockMessage::BlockHeader(header) => assert_eq!(header.id, block.id),
+            _ => panic!("the compact block should be sent as a header"),
+        },
+        _ => panic!("the compact block should be sent as a header"),
+    }
+    // the other messages are sent unchanged
+    assert!(matches!(
+        without_compact_block(Message::Block(Box::new(BlockMessage::AskForBlocks(
+            vec![]
+        )))),
+        Message::Block(message) if matches!(*message, BlockMessage::AskForBlocks(_))
+    ));
+}
+
+#[test]
+#[serial]
+fn test_compact_block_unrequested_operations() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test_with_storage(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver,
+              mut storage| {
+            //1. Create a node
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+
+            //2. Create a block with an operation we already know and one we don't.
+            let op_1 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_2 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_3 = tools::create_operation_with_expire_period(&node_a_keypair, 5);
+            let op_thread = op_1
+                .content_creator_address
+                .get_thread(protocol_config.thread_count);
+            let block = tools::create_block_with_operations(
+                &node_a_keypair,
+                Slot::new(1, op_thread),
+                vec![op_1.clone(), op_2],
+            );
+            storage.store_operations(vec![op_1]);
+            //end setup
+
+            //3. Send the compact block from node a and ask for the block
+            let salt = 42;
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::CompactBlock(CompactBlock {
+                        header: block.content.header.clone(),
+                        salt,
+                        short_operation_ids: block
+                            .content
+                            .operations
+                            .iter()
+                            .map(|id| short_operation_id(salt, id))
+                            .collect(),
+                    }))),
+                )
+                .unwrap();
+            protocol_controller
+                .send_wishlist_delta(
+                    vec![(block.id, Some(block.content.header.clone()))]
+                        .into_iter()
+                        .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .unwrap();
+            let msg = node_a
+                .recv_timeout(Duration::from_millis(1500))
+                .expect("Node A didn't receive the ask for operations message");
+            match msg {
+                Message::Block(message) => {
+                    if let BlockMessage::AskForBlocks(asked) = *message {
+                        assert_eq!(asked[0].1, AskForBlocksInfo::OperationsAt(vec![1]));
+                    } else {
+                        panic!("Node A didn't receive the ask for operations message");
+                    }
+                }
+                _ => panic!("Node A didn't receive the ask for operations message"),
+            }
+
+            //4. Node A answers with an operation that is not in the block
+            network_controller
+                .send_from_peer(
+                    &node_a_peer_id,
+                    Message::Block(Box::new(BlockMessage::ReplyForBlocks(vec![(
+                        block.id,
+                        BlockInfoReply::Operations(vec![op_3]),
+                    )]))),
+                )
+                .unwrap();
+
+            //5. Check that node connection is closed (node should be banned)
+            std::thread::sleep(std::time::Duration::from_millis(1000));
+            assert_eq!(
+                network_controller
+                    .get_connections()
+                    .get_peer_ids_connected()
+                    .len(),
+                0
+            );
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
+
 #[test]
 #[serial]
 fn test_empty_block() {
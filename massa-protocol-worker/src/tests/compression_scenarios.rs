@@ -0,0 +1,71 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::{collections::HashMap, ops::Bound::Included, sync::Arc};
+
+use massa_channel::MassaChannel;
+use massa_protocol_exports::{test_exports::tools, PeerId, ProtocolCompression};
+use massa_serialization::U64VarIntDeserializer;
+use massa_signature::KeyPair;
+use parking_lot::RwLock;
+use peernet::messages::{MessagesHandler as _, MessagesSerializer as _};
+
+use crate::{
+    handlers::operation_handler::{OperationMessage, OperationMessageSerializer},
+    messages::{Message, MessagesCompression, MessagesHandler, MessagesSerializer},
+};
+
+#[test]
+fn test_compressed_operations_are_handled_like_uncompressed_ones() {
+    let (sender_operations, receiver_operations) =
+        MassaChannel::new("operations".to_string(), None);
+    let (sender_endorsements, _receiver_endorsements) =
+        MassaChannel::new("endorsements".to_string(), None);
+    let (sender_blocks, _receiver_blocks) = MassaChannel::new("blocks".to_string(), None);
+    let (sender_peers, _receiver_peers) = MassaChannel::new("peers".to_string(), None);
+    let mut handler = MessagesHandler {
+        sender_blocks,
+        sender_endorsements,
+        sender_operations,
+        sender_peers,
+        id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        compression: ProtocolCompression::Zstd,
+        max_message_size: 1048576000,
+        connection_stats: Default::default(),
+    };
+    let serializer = MessagesSerializer::new()
+        .with_operation_message_serializer(OperationMessageSerializer::new());
+
+    let peer_id = PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key());
+    let keypair = KeyPair::generate(0).unwrap();
+    let operations = (0..50)
+        .map(|_| tools::create_operation_with_expire_period(&keypair, 1))
+        .collect();
+    let message = Message::Operation(OperationMessage::Operations(operations));
+    let mut uncompressed = Vec::new();
+    serializer.serialize(&message, &mut uncompressed).unwrap();
+
+    let compression = MessagesCompression {
+        peers: Arc::new(RwLock::new(HashMap::from([(
+            peer_id.clone(),
+            ProtocolCompression::Zstd,
+        )]))),
+        min_message_size: 1024,
+    };
+    let compressed = compression
+        .compress(&peer_id, &serializer, message)
+        .unwrap();
+    assert!(matches!(compressed, Message::Compressed(_)));
+    let mut bytes = Vec::new();
+    serializer.serialize(&compressed, &mut bytes).unwrap();
+    assert!(bytes.len() < uncompressed.len());
+
+    // the operations handler receives the same bytes as without compression
+    handler.handle(&bytes, &peer_id).unwrap();
+    let (sender, received) = receiver_operations.try_recv().unwrap();
+    assert_eq!(sender, peer_id);
+    assert_eq!(received, uncompressed[1..]);
+
+    // compressed messages are refused when compression is disabled
+    handler.compression = ProtocolCompression::None;
+    assert!(handler.handle(&bytes, &peer_id).is_err());
+}
@@ -0,0 +1,88 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::time::Duration;
+
+use massa_protocol_exports::PeerId;
+use massa_protocol_exports::{test_exports::tools, ProtocolConfig};
+use massa_signature::KeyPair;
+use peernet::messages::MessagesSerializer as _;
+use serial_test::serial;
+
+use crate::{
+    handlers::operation_handler::{OperationMessage, OperationMessageSerializer},
+    messages::{Message, MessagesSerializer},
+};
+
+use super::context::protocol_test;
+
+#[test]
+#[serial]
+fn test_protocol_reports_connected_peers() {
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_panic(info);
+        std::process::exit(1);
+    }));
+
+    let mut protocol_config = ProtocolConfig::default();
+    protocol_config.thread_count = 2;
+    protocol_config.initial_peers = "./src/tests/empty_initial_peers.json".to_string().into();
+    protocol_test(
+        &protocol_config,
+        move |mut network_controller,
+              protocol_controller,
+              protocol_manager,
+              consensus_event_receiver,
+              pool_event_receiver,
+              selector_event_receiver| {
+            //1. Create 2 nodes
+            let node_a_keypair = KeyPair::generate(0).unwrap();
+            let node_b_keypair = KeyPair::generate(0).unwrap();
+            let (node_a_peer_id, _node_a) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_a_keypair.get_public_key()));
+            let (node_b_peer_id, _node_b) = network_controller
+                .create_fake_connection(PeerId::from_public_key(node_b_keypair.get_public_key()));
+
+            //2. Node A sends us an operation
+            let operation = tools::create_operation_with_expire_period(&node_a_keypair, 1);
+            let message = Message::Operation(OperationMessage::Operations(vec![operation]));
+            let mut data = Vec::new();
+            MessagesSerializer::new()
+                .with_operation_message_serializer(OperationMessageSerializer::new())
+                .serialize(&message, &mut data)
+                .unwrap();
+            network_controller
+                .send_from_peer(&node_a_peer_id, message)
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(1000));
+
+            //3. Both nodes are reported, with the traffic and the operation received from node A
+            let connected_peers = protocol_controller.get_connected_peers().unwrap();
+            assert_eq!(connected_peers.len(), 2);
+            let node_a_info = connected_peers.get(&node_a_peer_id).unwrap();
+            assert!(node_a_info.is_outgoing);
+            assert_eq!(node_a_info.bytes_received, data.len() as u64);
+            assert!(node_a_info.connected_since.is_some());
+            assert!(node_a_info.last_message_time.is_some());
+            assert_eq!(node_a_info.known_operations, 1);
+            let node_b_info = connected_peers.get(&node_b_peer_id).unwrap();
+            assert_eq!(node_b_info.bytes_received, 0);
+            assert_eq!(node_b_info.last_message_time, None);
+            assert_eq!(node_b_info.known_operations, 0);
+
+            //4. Disconnected nodes are not reported anymore
+            network_controller.remove_fake_connection(&node_b_peer_id);
+            let connected_peers = protocol_controller.get_connected_peers().unwrap();
+            assert_eq!(connected_peers.len(), 1);
+            assert!(connected_peers.contains_key(&node_a_peer_id));
+            (
+                network_controller,
+                protocol_controller,
+                protocol_manager,
+                consensus_event_receiver,
+                pool_event_receiver,
+                selector_event_receiver,
+            )
+        },
+    )
+}
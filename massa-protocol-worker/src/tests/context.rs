@@ -95,6 +95,9 @@ pub fn start_protocol_controller_with_mock_network(
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        compression: config.compression,
+        max_message_size: config.max_message_size,
+        connection_stats: Default::default(),
     };
 
     let (controller, channels) = create_protocol_controller(config.clone());
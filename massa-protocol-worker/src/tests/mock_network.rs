@@ -19,18 +19,21 @@ use crate::{
         operation_handler::OperationMessageSerializer,
         peer_handler::PeerManagementMessageSerializer,
     },
-    messages::{Message, MessagesHandler, MessagesSerializer},
+    messages::{without_compact_block, Message, MessagesHandler, MessagesSerializer},
     wrap_network::{ActiveConnectionsTrait, NetworkController},
 };
 
 pub struct MockActiveConnections {
     pub connections: HashMap<PeerId, MassaSender<Message>>,
+    /// Peers that negotiated the compact block relay
+    pub compact_block_peers: HashSet<PeerId>,
 }
 
 impl MockActiveConnections {
     pub fn new() -> Self {
         Self {
             connections: HashMap::new(),
+            compact_block_peers: HashSet::new(),
         }
     }
 }
@@ -82,8 +85,13 @@ impl ActiveConnectionsTrait for SharedMockActiveConnections {
         message: Message,
         _high_priority: bool,
     ) -> Result<(), massa_protocol_exports::ProtocolError> {
-        let _ = self
-            .read()
+        let connections = self.read();
+        let message = if connections.compact_block_peers.contains(peer_id) {
+            message
+        } else {
+            without_compact_block(message)
+        };
+        let _ = connections
             .connections
             .get(peer_id)
             .unwrap()
@@ -92,8 +100,16 @@ impl ActiveConnectionsTrait for SharedMockActiveConnections {
     }
 
     fn shutdown_connection(&mut self, peer_id: &PeerId) {
-        self.write().connections.remove(peer_id);
+        let mut connections = self.write();
+        connections.connections.remove(peer_id);
+        connections.compact_block_peers.remove(peer_id);
+    }
+
+    fn has_compact_blocks(&self, peer_id: &PeerId) -> bool {
+        self.read().compact_block_peers.contains(peer_id)
     }
+
+    fn forget_closed_connections(&self, _unconnected_peers: &mut HashSet<PeerId>) {}
 }
 
 pub struct MockNetworkController {
@@ -131,7 +147,21 @@ impl MockNetworkController {
 }
 
 impl MockNetworkController {
+    /// Simulate a peer connecting to us, with the compact block relay negotiated
     pub fn create_fake_connection(&mut self, peer_id: PeerId) -> (PeerId, MassaReceiver<Message>) {
+        let (peer_id, receiver) = self.create_fake_legacy_connection(peer_id);
+        self.connections
+            .write()
+            .compact_block_peers
+            .insert(peer_id.clone());
+        (peer_id, receiver)
+    }
+
+    /// Simulate a peer connecting to us, without the compact block relay
+    pub fn create_fake_legacy_connection(
+        &mut self,
+        peer_id: PeerId,
+    ) -> (PeerId, MassaReceiver<Message>) {
         let (sender, receiver) = MassaChannel::new("create_fake_connection".to_string(), None);
         self.connections
             .write()
@@ -141,7 +171,9 @@ impl MockNetworkController {
     }
 
     pub fn remove_fake_connection(&mut self, peer_id: &PeerId) {
-        self.connections.write().connections.remove(peer_id);
+        let mut connections = self.connections.write();
+        connections.connections.remove(peer_id);
+        connections.compact_block_peers.remove(peer_id);
     }
 
     /// Simulate a peer that send a message to us
@@ -17,11 +17,15 @@ use crate::{create_protocol_controller, start_protocol_controller};
 mod ban_nodes_scenarios;
 mod block_scenarios;
 mod cache_scenarios;
+mod compression_scenarios;
+mod connected_peers_scenarios;
 mod context;
 mod endorsements_scenarios;
 mod in_block_operations_scenarios;
 mod mock_network;
 mod operations_scenarios;
+mod peer_db_scenarios;
+mod peer_scores_scenarios;
 mod tools;
 
 #[test]
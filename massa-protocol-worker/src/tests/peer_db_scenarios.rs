@@ -0,0 +1,185 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use massa_protocol_exports::PeerId;
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use peernet::transports::TransportType;
+use tempfile::NamedTempFile;
+
+use crate::handlers::peer_handler::{
+    announcement::Announcement,
+    models::{PeerDB, PeerInfo, PeerState},
+};
+
+fn create_peer(addr: &str) -> (PeerId, Announcement) {
+    let keypair = KeyPair::generate(0).unwrap();
+    let addr: SocketAddr = addr.parse().unwrap();
+    let announcement = Announcement::new(
+        HashMap::from([(addr, TransportType::Tcp)]),
+        Some(addr.ip()),
+        &keypair,
+    )
+    .unwrap();
+    (
+        PeerId::from_public_key(keypair.get_public_key()),
+        announcement,
+    )
+}
+
+#[test]
+fn test_peer_db_is_reloaded_from_file() {
+    let mut peer_db = PeerDB::default();
+
+    // peer we connected to
+    let (good_peer, announcement) = create_peer("82.245.123.77:31244");
+    let mut info = PeerInfo::new(announcement, PeerState::Trusted);
+    info.record_success();
+    info.record_failure();
+    peer_db.peers.insert(good_peer.clone(), info);
+
+    // peer not seen for a long time
+    let (old_peer, announcement) = create_peer("82.245.123.78:31244");
+    let mut info = PeerInfo::new(announcement, PeerState::Trusted);
+    info.last_seen = Some(MassaTime::from_millis(1));
+    peer_db.peers.insert(old_peer.clone(), info);
+
+    // banned peer, kept whatever its age
+    let (banned_peer, announcement) = create_peer("82.245.123.79:31244");
+    let mut info = PeerInfo::new(announcement, PeerState::Banned);
+    info.last_seen = Some(MassaTime::from_millis(1));
+    peer_db.peers.insert(banned_peer.clone(), info);
+
+    // peer banned because of its score, not banned anymore after a restart
+    let (score_banned_peer, announcement) = create_peer("82.245.123.80:31244");
+    let mut info = PeerInfo::new(announcement, PeerState::Trusted);
+    info.record_success();
+    peer_db.peers.insert(score_banned_peer.clone(), info);
+    peer_db.score_ban_peer(&score_banned_peer);
+
+    let peers_file = NamedTempFile::new().unwrap();
+    let categories = HashMap::from([(
+        "Bootstrap".to_string(),
+        (vec!["82.245.123.77".parse().unwrap()], 1),
+    )]);
+    peer_db.save(peers_file.path(), &categories).unwrap();
+
+    let (loaded_peer_db, categorized_peers) =
+        PeerDB::load(peers_file.path(), MassaTime::from_millis(3_600_000), 100).unwrap();
+    assert_eq!(loaded_peer_db.peers.len(), 3);
+    assert!(!loaded_peer_db.peers.contains_key(&old_peer));
+    assert!(loaded_peer_db.has_trusted_peers());
+
+    let good_peer_info = loaded_peer_db.peers.get(&good_peer).unwrap();
+    assert_eq!(good_peer_info.state, PeerState::Trusted);
+    assert_eq!(good_peer_info.successes, 1);
+    assert_eq!(good_peer_info.failures, 1);
+    assert!(good_peer_info.last_seen.is_some());
+    assert_eq!(
+        good_peer_info.last_announce,
+        peer_db.peers.get(&good_peer).unwrap().last_announce
+    );
+    assert_eq!(
+        loaded_peer_db.peers.get(&banned_peer).unwrap().state,
+        PeerState::Banned
+    );
+    assert_eq!(
+        loaded_peer_db.peers.get(&score_banned_peer).unwrap().state,
+        PeerState::Trusted
+    );
+
+    // only the trusted peers are reconnected to
+    assert_eq!(loaded_peer_db.index_by_newest.len(), 2);
+    assert!(loaded_peer_db
+        .index_by_newest
+        .iter()
+        .all(|(_, peer_id)| peer_id == &good_peer || peer_id == &score_banned_peer));
+
+    assert_eq!(categorized_peers.len(), 1);
+    assert_eq!(
+        categorized_peers.get(&good_peer).unwrap().category,
+        "Bootstrap"
+    );
+}
+
+#[test]
+fn test_score_bans_dont_lift_explicit_bans() {
+    let mut peer_db = PeerDB::default();
+    let peers: Vec<PeerId> = [
+        "82.245.123.77:31244",
+        "82.245.123.78:31244",
+        "82.245.123.79:31244",
+    ]
+    .iter()
+    .map(|addr| {
+        let (peer_id, announcement) = create_peer(addr);
+        peer_db.peers.insert(
+            peer_id.clone(),
+            PeerInfo::new(announcement, PeerState::Trusted),
+        );
+        peer_id
+    })
+    .collect();
+
+    // banned explicitly, then because of its score: the end of the score ban keeps it banned
+    peer_db.ban_peer(&peers[0]);
+    peer_db.score_ban_peer(&peers[0]);
+    peer_db.score_unban_peer(&peers[0]);
+    assert_eq!(
+        peer_db.peers.get(&peers[0]).unwrap().state,
+        PeerState::Banned
+    );
+
+    // banned because of its score, then explicitly: the explicit ban takes over
+    peer_db.score_ban_peer(&peers[1]);
+    assert!(peer_db.score_bans.contains(&peers[1]));
+    peer_db.ban_peer(&peers[1]);
+    peer_db.score_unban_peer(&peers[1]);
+    assert_eq!(
+        peer_db.peers.get(&peers[1]).unwrap().state,
+        PeerState::Banned
+    );
+
+    // only banned because of its score: unbanned at the end of the score ban
+    peer_db.score_ban_peer(&peers[2]);
+    assert_eq!(
+        peer_db.peers.get(&peers[2]).unwrap().state,
+        PeerState::Banned
+    );
+    peer_db.score_unban_peer(&peers[2]);
+    assert!(!peer_db.peers.contains_key(&peers[2]));
+    assert!(peer_db.score_bans.is_empty());
+}
+
+#[test]
+fn test_missing_peers_file_gives_empty_peer_db() {
+    let peers_file = NamedTempFile::new().unwrap().path().to_path_buf();
+    let (peer_db, categorized_peers) =
+        PeerDB::load(&peers_file, MassaTime::from_millis(3_600_000), 100).unwrap();
+    assert!(peer_db.peers.is_empty());
+    assert!(!peer_db.has_trusted_peers());
+    assert!(categorized_peers.is_empty());
+}
+
+#[test]
+fn test_corrupt_peers_file_gives_empty_peer_db() {
+    let peers_dir = tempfile::tempdir().unwrap();
+    let peers_file = peers_dir.path().join("peers.json");
+    std::fs::write(&peers_file, b"{\"truncated").unwrap();
+
+    let (peer_db, categorized_peers) =
+        PeerDB::load(&peers_file, MassaTime::from_millis(3_600_000), 100).unwrap();
+    assert!(peer_db.peers.is_empty());
+    assert!(categorized_peers.is_empty());
+
+    // the corrupt file is kept aside, and the next save starts a new file
+    assert!(!peers_file.exists());
+    assert_eq!(
+        std::fs::read(peers_file.with_extension("corrupt")).unwrap(),
+        b"{\"truncated"
+    );
+    peer_db.save(&peers_file, &HashMap::new()).unwrap();
+    let (peer_db, _) = PeerDB::load(&peers_file, MassaTime::from_millis(3_600_000), 100).unwrap();
+    assert!(peer_db.peers.is_empty());
+}
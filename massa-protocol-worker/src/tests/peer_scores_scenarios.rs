@@ -0,0 +1,103 @@
+// Copyright (c) 2023 MASSA LABS <info@massa.net>
+
+use std::time::Duration;
+
+use massa_protocol_exports::{PeerId, ProtocolConfig};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+
+use crate::handlers::peer_handler::scores::{PeerScoreEvent, PeerScores};
+
+fn random_peer_id() -> PeerId {
+    PeerId::from_public_key(KeyPair::generate(0).unwrap().get_public_key())
+}
+
+#[test]
+fn test_useful_peers_are_scored_above_faulty_ones() {
+    let peer_scores_config = ProtocolConfig::default();
+    let mut scores = PeerScores::new(&peer_scores_config);
+    let fast_peer = random_peer_id();
+    let slow_peer = random_peer_id();
+    let faulty_peer = random_peer_id();
+
+    scores.record(
+        &fast_peer,
+        PeerScoreEvent::Block {
+            latency: Some(Duration::from_millis(100)),
+        },
+    );
+    scores.record(&fast_peer, PeerScoreEvent::Operations(10));
+    scores.record(
+        &slow_peer,
+        PeerScoreEvent::Block {
+            latency: Some(Duration::from_secs(3)),
+        },
+    );
+    scores.record(&faulty_peer, PeerScoreEvent::Timeout);
+
+    assert!(scores.score(&fast_peer) > scores.score(&slow_peer));
+    assert!(scores.score(&slow_peer) > 0.0);
+    assert!(scores.score(&faulty_peer) < 0.0);
+    assert_eq!(scores.score(&random_peer_id()), 0.0);
+
+    let all_scores = scores.get_scores();
+    let fast_peer_score = all_scores.get(&fast_peer).unwrap();
+    assert_eq!(fast_peer_score.useful_blocks, 1);
+    assert_eq!(fast_peer_score.useful_operations, 10);
+    assert_eq!(
+        fast_peer_score.average_latency,
+        Some(MassaTime::from_millis(100))
+    );
+    assert_eq!(all_scores.get(&faulty_peer).unwrap().timeouts, 1);
+}
+
+#[test]
+fn test_peers_under_threshold_are_banned_temporarily() {
+    let peer_scores_config = ProtocolConfig {
+        peer_score_ban_duration: MassaTime::from_millis(100),
+        ..ProtocolConfig::default()
+    };
+    let mut scores = PeerScores::new(&peer_scores_config);
+    let good_peer = random_peer_id();
+    let bad_peer = random_peer_id();
+
+    scores.record(&good_peer, PeerScoreEvent::Block { latency: None });
+    scores.record(&bad_peer, PeerScoreEvent::InvalidData);
+    let (to_ban, to_unban) = scores.update_bans();
+    assert!(to_ban.is_empty() && to_unban.is_empty());
+
+    scores.record(&bad_peer, PeerScoreEvent::InvalidData);
+    scores.record(&bad_peer, PeerScoreEvent::InvalidData);
+    let (to_ban, to_unban) = scores.update_bans();
+    assert_eq!(to_ban, vec![bad_peer.clone()]);
+    assert!(to_unban.is_empty());
+    assert!(scores
+        .get_scores()
+        .get(&bad_peer)
+        .unwrap()
+        .banned_until
+        .is_some());
+
+    // already banned: not banned again
+    let (to_ban, _) = scores.update_bans();
+    assert!(to_ban.is_empty());
+
+    std::thread::sleep(Duration::from_millis(150));
+    let (to_ban, to_unban) = scores.update_bans();
+    assert!(to_ban.is_empty());
+    assert_eq!(to_unban, vec![bad_peer.clone()]);
+    assert_eq!(scores.score(&bad_peer), 0.0);
+}
+
+#[test]
+fn test_forgiven_peer_score_is_reset() {
+    let peer_scores_config = ProtocolConfig::default();
+    let mut scores = PeerScores::new(&peer_scores_config);
+    let peer_id = random_peer_id();
+
+    scores.record(&peer_id, PeerScoreEvent::InvalidData);
+    assert!(scores.score(&peer_id) < 0.0);
+    scores.forgive(&peer_id);
+    assert_eq!(scores.score(&peer_id), 0.0);
+    assert!(scores.get_scores().is_empty());
+}
@@ -48,7 +48,9 @@ use crate::{
         },
     },
     manager::ProtocolManagerImpl,
-    messages::MessagesHandler,
+    messages::{
+        MessagesCompression, MessagesHandler, SharedCompactBlockPeers, SharedPeerCompressions,
+    },
     wrap_network::NetworkControllerImpl,
 };
 
@@ -187,7 +189,13 @@ pub fn start_protocol_controller(
     massa_metrics: MassaMetrics,
 ) -> Result<(Box<dyn ProtocolManager>, KeyPair, NodeId), ProtocolError> {
     debug!("starting protocol controller");
-    let peer_db = Arc::new(RwLock::new(PeerDB::default()));
+    // reload the peers known before the restart
+    let (peer_db, saved_peers_infos) = PeerDB::load(
+        &config.peers_file,
+        config.peers_max_age,
+        config.max_size_listeners_per_peer,
+    )?;
+    let peer_db = Arc::new(RwLock::new(peer_db));
 
     let (sender_operations, receiver_operations) = MassaChannel::new(
         "sender_operations".to_string(),
@@ -213,7 +221,12 @@ pub fn start_protocol_controller(
         sender_operations: sender_operations.clone(),
         sender_peers: sender_peers.clone(),
         id_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        compression: config.compression,
+        max_message_size: config.max_message_size,
+        connection_stats: Default::default(),
     };
+    let peer_compressions: SharedPeerCompressions = Default::default();
+    let compact_block_peers: SharedCompactBlockPeers = Default::default();
 
     // try to read node keypair from file, otherwise generate it & write to file. Then derive nodeId
     let keypair = if std::path::Path::is_file(&config.keypair_file) {
@@ -239,7 +252,13 @@ pub fn start_protocol_controller(
     };
 
     let mut peernet_config = PeerNetConfiguration::default(
-        MassaHandshake::new(peer_db.clone(), config.clone(), message_handlers.clone()),
+        MassaHandshake::new(
+            peer_db.clone(),
+            config.clone(),
+            message_handlers.clone(),
+            peer_compressions.clone(),
+            compact_block_peers.clone(),
+        ),
         message_handlers.clone(),
         Context {
             our_keypair: keypair.clone(),
@@ -250,24 +269,24 @@ pub fn start_protocol_controller(
         &std::fs::read_to_string(&config.initial_peers)?,
     )?;
 
+    // the seed list is kept even when we know peers we connected to before:
+    // the saved peers are candidates right away, the seeds are tested and are a fallback if the saved peers are gone
+    let seed_peers = initial_peers_infos
+        .iter()
+        .map(|(peer_id, data)| (peer_id.clone(), data.listeners.clone()));
     let initial_peers = if let Some(bootstrap_peers) = bootstrap_peers {
         //TODO: Remove when we will be able to test the bootstrap peer even if someone else found them full
-        bootstrap_peers
-            .0
-            .into_iter()
-            .chain(
-                initial_peers_infos
-                    .iter()
-                    .map(|(peer_id, data)| (peer_id.clone(), data.listeners.clone())),
-            )
-            .collect()
+        bootstrap_peers.0.into_iter().chain(seed_peers).collect()
     } else {
-        initial_peers_infos
-            .iter()
-            .map(|(peer_id, data)| (peer_id.clone(), data.listeners.clone()))
-            .collect()
+        seed_peers.collect()
     };
 
+    // categories of the initial peers, completed with the ones of the saved peers
+    let categorized_peers_infos: HashMap<PeerId, PeerData> = saved_peers_infos
+        .into_iter()
+        .chain(initial_peers_infos)
+        .collect();
+
     let peernet_categories = config
         .peers_categories
         .iter()
@@ -275,7 +294,7 @@ pub fn start_protocol_controller(
             (
                 category_name.clone(),
                 (
-                    initial_peers_infos
+                    categorized_peers_infos
                         .iter()
                         .filter_map(|info| {
                             if info.1.category == *category_name {
@@ -314,9 +333,15 @@ pub fn start_protocol_controller(
     };
     peernet_config.max_in_connections = config.max_in_connections;
 
-    let network_controller = Box::new(NetworkControllerImpl::new(PeerNetManager::new(
-        peernet_config,
-    )));
+    let network_controller = Box::new(NetworkControllerImpl::new(
+        PeerNetManager::new(peernet_config),
+        MessagesCompression {
+            peers: peer_compressions,
+            min_message_size: config.compression_min_message_size,
+        },
+        compact_block_peers,
+        message_handlers.connection_stats.clone(),
+    ));
 
     let connectivity_thread_handle = start_connectivity_thread(
         PeerId::from_public_key(keypair.get_public_key()),
@@ -340,7 +365,7 @@ pub fn start_protocol_controller(
                 (
                     category_name.clone(),
                     (
-                        initial_peers_infos
+                        categorized_peers_infos
                             .iter()
                             .filter_map(|info| {
                                 if info.1.category == *category_name {
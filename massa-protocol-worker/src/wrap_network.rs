@@ -1,10 +1,13 @@
 use std::{
     collections::{HashMap, HashSet},
     net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 use massa_protocol_exports::{PeerId, ProtocolError};
 use peernet::{
+    error::PeerNetResult,
+    messages::MessagesSerializer as PeerNetMessagesSerializer,
     network_manager::{PeerNetManager, SharedActiveConnections},
     peer::PeerConnectionType,
     transports::TransportType,
@@ -13,7 +16,10 @@ use peernet::{
 use crate::{
     context::Context,
     handlers::peer_handler::MassaHandshake,
-    messages::{Message, MessagesHandler, MessagesSerializer},
+    messages::{
+        without_compact_block, Message, MessagesCompression, MessagesHandler, MessagesSerializer,
+        SharedCompactBlockPeers, SharedConnectionStats,
+    },
 };
 
 pub trait ActiveConnectionsTrait: Send + Sync {
@@ -32,6 +38,11 @@ pub trait ActiveConnectionsTrait: Send + Sync {
     fn get_nb_out_connections(&self) -> usize;
     fn get_nb_in_connections(&self) -> usize;
     fn shutdown_connection(&mut self, peer_id: &PeerId);
+    /// Whether the peer negotiated the compact block relay during the handshake
+    fn has_compact_blocks(&self, peer_id: &PeerId) -> bool;
+    /// Forgets the state kept for the peers whose connection closed.
+    /// `unconnected_peers` holds the peers found unconnected by the previous call.
+    fn forget_closed_connections(&self, unconnected_peers: &mut HashSet<PeerId>);
 }
 
 impl Clone for Box<dyn ActiveConnectionsTrait> {
@@ -40,6 +51,26 @@ impl Clone for Box<dyn ActiveConnectionsTrait> {
     }
 }
 
+/// Sends `message` to `peer_id`, serialized by `message_serializer` when it is queued
+fn send_to_connection<MS: PeerNetMessagesSerializer<Message>>(
+    connections: &SharedActiveConnections<PeerId>,
+    peer_id: &PeerId,
+    message_serializer: &MS,
+    message: Message,
+    high_priority: bool,
+) -> Result<(), ProtocolError> {
+    if let Some(connection) = connections.read().connections.get(peer_id) {
+        connection
+            .send_channels
+            .try_send(message_serializer, message, high_priority)
+            .map_err(|err| ProtocolError::SendError(err.to_string()))
+    } else {
+        Err(ProtocolError::SendError(
+            "Peer isn't connected anymore".to_string(),
+        ))
+    }
+}
+
 impl ActiveConnectionsTrait for SharedActiveConnections<PeerId> {
     fn send_to_peer(
         &self,
@@ -48,16 +79,7 @@ impl ActiveConnectionsTrait for SharedActiveConnections<PeerId> {
         message: Message,
         high_priority: bool,
     ) -> Result<(), ProtocolError> {
-        if let Some(connection) = self.read().connections.get(peer_id) {
-            connection
-                .send_channels
-                .try_send(message_serializer, message, high_priority)
-                .map_err(|err| ProtocolError::SendError(err.to_string()))
-        } else {
-            Err(ProtocolError::SendError(
-                "Peer isn't connected anymore".to_string(),
-            ))
-        }
+        send_to_connection(self, peer_id, message_serializer, message, high_priority)
     }
 
     fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
@@ -100,6 +122,134 @@ impl ActiveConnectionsTrait for SharedActiveConnections<PeerId> {
             connection.shutdown();
         }
     }
+
+    fn has_compact_blocks(&self, _peer_id: &PeerId) -> bool {
+        false
+    }
+
+    fn forget_closed_connections(&self, _unconnected_peers: &mut HashSet<PeerId>) {
+        // no state is kept for the peers
+    }
+}
+
+/// Serializer counting the bytes of the messages it serializes
+struct CountingSerializer<'a> {
+    inner: &'a MessagesSerializer,
+    bytes: AtomicUsize,
+}
+
+impl PeerNetMessagesSerializer<Message> for CountingSerializer<'_> {
+    fn serialize(&self, message: &Message, buffer: &mut Vec<u8>) -> PeerNetResult<()> {
+        let start = buffer.len();
+        self.inner.serialize(message, buffer)?;
+        self.bytes
+            .fetch_add(buffer.len().saturating_sub(start), Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Active connections compressing the messages sent to the peers that negotiated it,
+/// sending compact blocks only to the peers that negotiated them,
+/// and counting the bytes sent to each peer
+#[derive(Clone)]
+pub struct CompressedActiveConnections {
+    connections: SharedActiveConnections<PeerId>,
+    compression: MessagesCompression,
+    compact_block_peers: SharedCompactBlockPeers,
+    connection_stats: SharedConnectionStats,
+}
+
+impl ActiveConnectionsTrait for CompressedActiveConnections {
+    fn send_to_peer(
+        &self,
+        peer_id: &PeerId,
+        message_serializer: &MessagesSerializer,
+        message: Message,
+        high_priority: bool,
+    ) -> Result<(), ProtocolError> {
+        let message = if self.compact_block_peers.read().contains(peer_id) {
+            message
+        } else {
+            without_compact_block(message)
+        };
+        let message = self
+            .compression
+            .compress(peer_id, message_serializer, message)
+            .map_err(|err| ProtocolError::SendError(err.to_string()))?;
+        // the bytes sent are counted when the message is serialized to be queued
+        let counting_serializer = CountingSerializer {
+            inner: message_serializer,
+            bytes: AtomicUsize::new(0),
+        };
+        send_to_connection(
+            &self.connections,
+            peer_id,
+            &counting_serializer,
+            message,
+            high_priority,
+        )?;
+        if let Some(stats) = self.connection_stats.write().get_mut(peer_id) {
+            stats.bytes_sent = stats
+                .bytes_sent
+                .saturating_add(counting_serializer.bytes.into_inner() as u64);
+        }
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn ActiveConnectionsTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_peer_ids_connected(&self) -> HashSet<PeerId> {
+        self.connections.get_peer_ids_connected()
+    }
+
+    fn get_peers_connected(
+        &self,
+    ) -> HashMap<PeerId, (SocketAddr, PeerConnectionType, Option<String>)> {
+        self.connections.get_peers_connected()
+    }
+
+    fn get_nb_out_connections(&self) -> usize {
+        self.connections.get_nb_out_connections()
+    }
+
+    fn get_nb_in_connections(&self) -> usize {
+        self.connections.get_nb_in_connections()
+    }
+
+    fn shutdown_connection(&mut self, peer_id: &PeerId) {
+        self.compression.peers.write().remove(peer_id);
+        self.compact_block_peers.write().remove(peer_id);
+        self.connection_stats.write().remove(peer_id);
+        self.connections.shutdown_connection(peer_id);
+    }
+
+    fn has_compact_blocks(&self, peer_id: &PeerId) -> bool {
+        self.compact_block_peers.read().contains(peer_id)
+    }
+
+    fn forget_closed_connections(&self, unconnected_peers: &mut HashSet<PeerId>) {
+        let peers_connected = self.connections.get_peer_ids_connected();
+        let mut compressions = self.compression.peers.write();
+        let mut compact_block_peers = self.compact_block_peers.write();
+        let mut connection_stats = self.connection_stats.write();
+
+        // the handshake records a peer before its connection is registered:
+        // a peer is only forgotten if it was already unconnected at the previous call
+        unconnected_peers.retain(|peer_id| !peers_connected.contains(peer_id));
+        compressions.retain(|peer_id, _| !unconnected_peers.contains(peer_id));
+        compact_block_peers.retain(|peer_id| !unconnected_peers.contains(peer_id));
+        connection_stats.retain(|peer_id, _| !unconnected_peers.contains(peer_id));
+
+        *unconnected_peers = compressions
+            .keys()
+            .chain(compact_block_peers.iter())
+            .chain(connection_stats.keys())
+            .filter(|peer_id| !peers_connected.contains(*peer_id))
+            .cloned()
+            .collect();
+    }
 }
 
 pub trait NetworkController: Send + Sync {
@@ -123,19 +273,35 @@ pub trait NetworkController: Send + Sync {
 
 pub struct NetworkControllerImpl {
     peernet_manager: PeerNetManager<PeerId, Context, MassaHandshake, MessagesHandler>,
+    compression: MessagesCompression,
+    compact_block_peers: SharedCompactBlockPeers,
+    connection_stats: SharedConnectionStats,
 }
 
 impl NetworkControllerImpl {
     pub fn new(
         peernet_manager: PeerNetManager<PeerId, Context, MassaHandshake, MessagesHandler>,
+        compression: MessagesCompression,
+        compact_block_peers: SharedCompactBlockPeers,
+        connection_stats: SharedConnectionStats,
     ) -> Self {
-        Self { peernet_manager }
+        Self {
+            peernet_manager,
+            compression,
+            compact_block_peers,
+            connection_stats,
+        }
     }
 }
 
 impl NetworkController for NetworkControllerImpl {
     fn get_active_connections(&self) -> Box<dyn ActiveConnectionsTrait> {
-        Box::new(self.peernet_manager.active_connections.clone())
+        Box::new(CompressedActiveConnections {
+            connections: self.peernet_manager.active_connections.clone(),
+            compression: self.compression.clone(),
+            compact_block_peers: self.compact_block_peers.clone(),
+            connection_stats: self.connection_stats.clone(),
+        })
     }
 
     fn start_listener(
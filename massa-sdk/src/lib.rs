@@ -14,16 +14,24 @@ use jsonrpsee::ws_client::{HeaderMap, HeaderValue, WsClient, WsClientBuilder};
 use jsonrpsee::{core::RpcResult, http_client::HttpClientBuilder};
 use jsonrpsee_http_client as _;
 use jsonrpsee_ws_client as _;
-use massa_api_exports::page::PagedVecV2;
+use massa_api_exports::page::{
+    AddressHistoryPage, AddressHistoryPageRequest, EventPage, EventPageRequest, PagedVecV2,
+};
 use massa_api_exports::ApiRequest;
 use massa_api_exports::{
     address::AddressInfo,
+    async_message::AsyncMessageInfo,
     block::{BlockInfo, BlockSummary},
     datastore::{DatastoreEntryInput, DatastoreEntryOutput},
     endorsement::EndorsementInfo,
-    execution::{ExecuteReadOnlyResponse, ReadOnlyBytecodeExecution, ReadOnlyCall},
+    execution::{
+        ExecuteReadOnlyResponse, GasEstimationRequest, GasEstimationResponse,
+        ReadOnlyBytecodeExecution, ReadOnlyCall, SlotDeterminismCheckInfo,
+    },
     node::NodeStatus,
-    operation::{OperationInfo, OperationInput},
+    operation::{OperationInfo, OperationInput, OperationReceipt, OperationSimulation},
+    rolls::DrawInputs,
+    state_proof::{StateProofInput, StateProofOutput},
     TimeInterval,
 };
 use massa_models::secure_share::SecureShare;
@@ -35,14 +43,17 @@ use massa_models::{
     clique::Clique,
     composite::PubkeySig,
     endorsement::EndorsementId,
-    execution::EventFilter,
+    execution::{AsyncMessageFilter, EventFilter},
     node::NodeId,
     operation::{Operation, OperationId},
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
+    slot::Slot,
     version::Version,
 };
 use massa_proto_rs::massa::api::v1::massa_service_client::MassaServiceClient;
+use massa_protocol_exports::{ConnectedPeerInfo, PeerScore};
+use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 use thiserror::Error;
@@ -201,6 +212,53 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns the reputation scores of the peers known by the node
+    pub async fn node_peers_scores(&self) -> RpcResult<BTreeMap<NodeId, PeerScore>> {
+        self.http_client
+            .request("node_peers_scores", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Returns the peers the node is connected to, with the live state of their connection
+    pub async fn node_get_peers(&self) -> RpcResult<BTreeMap<NodeId, ConnectedPeerInfo>> {
+        self.http_client
+            .request("node_get_peers", rpc_params![])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Try to connect to the peer listening at the given address
+    /// No confirmation to expect.
+    pub async fn node_connect_peer(&self, addr: SocketAddr) -> RpcResult<()> {
+        self.http_client
+            .request("node_connect_peer", rpc_params![addr])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Close the connection with the given node id, without banning it
+    /// No confirmation to expect.
+    pub async fn node_disconnect_peer(&self, id: NodeId) -> RpcResult<()> {
+        self.http_client
+            .request("node_disconnect_peer", rpc_params![id])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Check the determinism of an upcoming final slot by re-executing it once it is finalized
+    /// and comparing its state changes with the finalized ones. Returns once the slot is finalized.
+    pub async fn execution_check_slot_determinism(
+        &self,
+        slot: Slot,
+        trace: bool,
+    ) -> RpcResult<SlotDeterminismCheckInfo> {
+        self.http_client
+            .request("execution_check_slot_determinism", rpc_params![slot, trace])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns node peers whitelist IP address(es).
     pub async fn node_peers_whitelist(&self) -> RpcResult<Vec<IpAddr>> {
         self.http_client
@@ -313,6 +371,14 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns the inputs of the selector draws of the given cycles.
+    pub async fn get_draw_inputs(&self, cycles: Vec<u64>) -> RpcResult<Vec<DrawInputs>> {
+        self.http_client
+            .request("get_draw_inputs", rpc_params![cycles])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns operation(s) information associated to a given list of operation(s) ID(s).
     pub async fn get_operations(
         &self,
@@ -324,6 +390,17 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Returns the execution receipts of a given list of operation(s) ID(s).
+    pub async fn get_operation_receipts(
+        &self,
+        operation_ids: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationReceipt>> {
+        self.http_client
+            .request("get_operation_receipts", rpc_params![operation_ids])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Returns endorsement(s) information associated to a given list of endorsement(s) ID(s)
     pub async fn get_endorsements(
         &self,
@@ -354,6 +431,44 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get a page of the events emitted by smart contracts matching a filter
+    pub async fn get_filtered_sc_output_event_page(
+        &self,
+        filter: EventFilter,
+        page_request: EventPageRequest,
+    ) -> RpcResult<EventPage> {
+        self.http_client
+            .request(
+                "get_filtered_sc_output_event_page",
+                rpc_params![filter, page_request],
+            )
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Get the pending asynchronous messages matching a filter
+    pub async fn get_async_messages(
+        &self,
+        filter: AsyncMessageFilter,
+    ) -> RpcResult<Vec<AsyncMessageInfo>> {
+        self.http_client
+            .request("get_async_messages", rpc_params![filter])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Get a page of the final operations sent or received by an address, most recent first
+    pub async fn get_address_history(
+        &self,
+        address: Address,
+        page_request: AddressHistoryPageRequest,
+    ) -> RpcResult<AddressHistoryPage> {
+        self.http_client
+            .request("get_address_history", rpc_params![address, page_request])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
     pub(crate) async fn _get_graph_interval(
@@ -374,6 +489,19 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Gets the balances and datastore keys of addresses right after a past final slot.
+    /// Only available on nodes in archive mode.
+    pub async fn get_addresses_at(
+        &self,
+        addresses: Vec<Address>,
+        slot: Slot,
+    ) -> RpcResult<Vec<AddressInfo>> {
+        self.http_client
+            .request("get_addresses", rpc_params![addresses, slot])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -385,6 +513,30 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Get datastore entries right after a past final slot.
+    /// Only available on nodes in archive mode.
+    pub async fn get_datastore_entries_at(
+        &self,
+        input: Vec<DatastoreEntryInput>,
+        slot: Slot,
+    ) -> RpcResult<Vec<DatastoreEntryOutput>> {
+        self.http_client
+            .request("get_datastore_entries", rpc_params![input, slot])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// Get final state entries along with their proofs against the final state hash
+    pub async fn get_state_proof(
+        &self,
+        input: Vec<StateProofInput>,
+    ) -> RpcResult<StateProofOutput> {
+        self.http_client
+            .request("get_state_proof", rpc_params![input])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
@@ -398,6 +550,33 @@ impl RpcClient {
             .map_err(|e| to_error_obj(e.to_string()))
     }
 
+    /// Simulates the execution of signed operations without sending them.
+    pub async fn simulate_operations(
+        &self,
+        operations: Vec<OperationInput>,
+    ) -> RpcResult<Vec<OperationSimulation>> {
+        self.http_client
+            .request("simulate_operations", rpc_params![operations])
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))
+    }
+
+    /// estimate the `max_gas` of a smart contract operation
+    pub async fn estimate_gas(
+        &self,
+        request: GasEstimationRequest,
+    ) -> RpcResult<GasEstimationResponse> {
+        self.http_client
+            .request::<Vec<GasEstimationResponse>, Vec<Vec<GasEstimationRequest>>>(
+                "estimate_gas",
+                vec![vec![request]],
+            )
+            .await
+            .map_err(|e| to_error_obj(e.to_string()))?
+            .pop()
+            .ok_or_else(|| to_error_obj("missing return value on estimate_gas".to_owned()))
+    }
+
     /// execute read only bytecode
     pub async fn execute_read_only_bytecode(
         &self,
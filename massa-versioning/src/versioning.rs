@@ -2074,6 +2074,8 @@ mod test {
             max_history_length: 100,
             max_new_elements: 100,
             thread_count: THREAD_COUNT,
+            enable_archive: false,
+            max_archive_periods: 1000,
         };
         let db = Arc::new(RwLock::new(MassaDB::new(db_config)));
 